@@ -0,0 +1,50 @@
+/* C interface of proc_memory, exported by the shared library of the proc_memory_ffi crate:
+ *   cargo build --release -p proc_memory_ffi
+ * A panic inside the library never unwinds into the caller, the function returns its failure value instead.
+ */
+#ifndef PROC_MEMORY_H
+#define PROC_MEMORY_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Handle to an opened process */
+typedef struct Proc Proc;
+
+/* Open the process with specified id, NULL on failure. Release it with proc_close() */
+Proc *proc_open(uint32_t pid);
+
+/* Open the process with specified title or executable name, NULL on failure. Release it with proc_close() */
+Proc *proc_open_name(const char *name);
+
+/* Release a handle, NULL is ignored */
+void proc_close(Proc *proc);
+
+/* Process id of the handle, -1 for NULL */
+int64_t proc_pid(const Proc *proc);
+
+/* Read 'len' bytes at 'address' into 'buffer', stopping at the first unreadable byte. Returns the read count */
+size_t proc_read(const Proc *proc, uintptr_t address, uint8_t *buffer, size_t len);
+
+/* Write 'len' bytes of 'data' at 'address', stopping at the first unwritable byte. Returns the written count */
+size_t proc_write(const Proc *proc, uintptr_t address, const uint8_t *data, size_t len);
+
+/* Scan the readable memory for 'signature', hex bytes with ?? wildcards like "48 8B 05 ?? ?? ?? ??".
+ * Stores up to 'capacity' match addresses in 'matches' and returns the total number of matches,
+ * -1 if the signature is invalid */
+int64_t proc_scan(const Proc *proc, const char *signature, uintptr_t *matches, size_t capacity);
+
+/* Evaluate an address expression or pointer chain like "[game.exe+0x1000]+0x20" or "game.exe+0x1000 -> 0x20",
+ * storing the address in 'address'. Returns false if it is invalid or a pointer can't be read */
+bool proc_eval_address(const Proc *proc, const char *expr, uintptr_t *address);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,204 @@
+//! Derive macros for the proc_memory crate.
+//!
+//! `#[derive(RemoteStruct)]` generates a `proc_memory::RemoteStruct` implementation that reads every field of
+//! a struct from its offset inside another process. Fields annotated with `#[offset(0x30)]` are read from that
+//! offset, other fields are read right after the previous one, at the next offset aligned for their type.
+
+use proc_macro::{Delimiter, TokenStream, TokenTree};
+
+struct Field {
+    name: String,
+    ty: String,
+    offset: Option<String>,
+}
+
+#[proc_macro_derive(RemoteStruct, attributes(offset))]
+pub fn derive_remote_struct(input: TokenStream) -> TokenStream {
+    match expand(input) {
+        Ok(output) => output.parse().unwrap(),
+        Err(message) => format!("compile_error!({:?});", message).parse().unwrap(),
+    }
+}
+
+fn expand(input: TokenStream) -> Result<String, String> {
+    let mut tokens = input.into_iter().peekable();
+    let mut name = None;
+
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Ident(ident) if ident.to_string() == "struct" => {
+                name = Some(match tokens.next() {
+                    Some(TokenTree::Ident(ident)) => ident.to_string(),
+                    _ => return Err("expected struct name".to_string()),
+                });
+                break;
+            }
+            TokenTree::Ident(ident)
+                if ident.to_string() == "enum" || ident.to_string() == "union" =>
+            {
+                return Err("RemoteStruct can only be derived for structs".to_string());
+            }
+            _ => {}
+        }
+    }
+    let name = name.ok_or_else(|| "expected a struct".to_string())?;
+
+    let body = match tokens.next() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => group.stream(),
+        Some(TokenTree::Punct(punct)) if punct.as_char() == '<' => {
+            return Err("RemoteStruct does not support generic structs".to_string());
+        }
+        _ => return Err("RemoteStruct requires a struct with named fields".to_string()),
+    };
+
+    let fields = parse_fields(body)?;
+    Ok(generate(&name, &fields))
+}
+
+fn parse_fields(body: TokenStream) -> Result<Vec<Field>, String> {
+    let mut fields = Vec::new();
+    let mut tokens = body.into_iter().peekable();
+
+    loop {
+        let mut offset = None;
+
+        //Attributes and visibility
+        loop {
+            match tokens.peek() {
+                Some(TokenTree::Punct(punct)) if punct.as_char() == '#' => {
+                    tokens.next();
+                    if let Some(TokenTree::Group(group)) = tokens.next() {
+                        if let Some(value) = parse_offset_attribute(group.stream())? {
+                            offset = Some(value);
+                        }
+                    }
+                }
+                Some(TokenTree::Ident(ident)) if ident.to_string() == "pub" => {
+                    tokens.next();
+                    if let Some(TokenTree::Group(group)) = tokens.peek() {
+                        if group.delimiter() == Delimiter::Parenthesis {
+                            tokens.next();
+                        }
+                    }
+                }
+                _ => break,
+            }
+        }
+
+        let name = match tokens.next() {
+            Some(TokenTree::Ident(ident)) => ident.to_string(),
+            None => break,
+            _ => return Err("expected field name".to_string()),
+        };
+        match tokens.next() {
+            Some(TokenTree::Punct(punct)) if punct.as_char() == ':' => {}
+            _ => return Err(format!("expected ':' after field '{}'", name)),
+        }
+
+        //Type tokens up to the next top-level comma
+        let mut ty = TokenStream::new();
+        let mut depth = 0i32;
+        while let Some(token) = tokens.peek() {
+            if let TokenTree::Punct(punct) = token {
+                match punct.as_char() {
+                    ',' if depth == 0 => break,
+                    '<' => depth += 1,
+                    '>' => depth -= 1,
+                    _ => {}
+                }
+            }
+            ty.extend(std::iter::once(tokens.next().unwrap()));
+        }
+        tokens.next();
+
+        fields.push(Field {
+            name,
+            ty: ty.to_string(),
+            offset,
+        });
+    }
+
+    Ok(fields)
+}
+
+fn parse_offset_attribute(attribute: TokenStream) -> Result<Option<String>, String> {
+    let mut tokens = attribute.into_iter();
+    match tokens.next() {
+        Some(TokenTree::Ident(ident)) if ident.to_string() == "offset" => {}
+        _ => return Ok(None),
+    }
+    match tokens.next() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+            let value = group.stream().to_string();
+            if value.is_empty() {
+                return Err("#[offset] requires a value, e.g. #[offset(0x30)]".to_string());
+            }
+            Ok(Some(value))
+        }
+        _ => Err("#[offset] requires a value, e.g. #[offset(0x30)]".to_string()),
+    }
+}
+
+fn generate(name: &str, fields: &[Field]) -> String {
+    //Explicit offsets are taken as is, implicit ones follow the previous field rounded up to the field alignment
+    let offsets: Vec<String> = fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| match (&field.offset, index) {
+            (Some(offset), _) => format!("offsets[{}] = ({}) as usize;", index, offset),
+            (None, 0) => String::new(),
+            (None, _) => format!(
+                "offsets[{index}] = {{
+                    let end = offsets[{previous}] + ::std::mem::size_of::<{previous_ty}>();
+                    let align = ::std::mem::align_of::<{ty}>();
+                    (end + align - 1) & !(align - 1)
+                }};",
+                index = index,
+                previous = index - 1,
+                previous_ty = fields[index - 1].ty,
+                ty = field.ty,
+            ),
+        })
+        .collect();
+
+    let reads: Vec<String> = fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            format!(
+                "{}: proc.read::<{}>(base + offsets[{}].1)?,",
+                field.name, field.ty, index
+            )
+        })
+        .collect();
+    let table: Vec<String> = fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| format!("({:?}, OFFSETS[{}]),", field.name, index))
+        .collect();
+
+    format!(
+        "impl ::proc_memory::RemoteStruct for {name} {{
+            fn read_from<P: ::proc_memory::ProcT + ?Sized>(proc: &P, base: usize) -> ::std::option::Option<Self> {{
+                let offsets = <Self as ::proc_memory::RemoteStruct>::field_offsets();
+                ::std::option::Option::Some({name} {{ {reads} }})
+            }}
+
+            fn field_offsets() -> &'static [(&'static str, usize)] {{
+                #[allow(unused_mut)]
+                const OFFSETS: [usize; {count}] = {{
+                    let mut offsets = [0usize; {count}];
+                    {offsets}
+                    offsets
+                }};
+                const TABLE: [(&str, usize); {count}] = [{table}];
+                &TABLE
+            }}
+        }}",
+        name = name,
+        count = fields.len(),
+        offsets = offsets.join(" "),
+        reads = reads.join(" "),
+        table = table.join(" "),
+    )
+}
@@ -0,0 +1,4 @@
+//! Shared library exporting the C interface declared in include/proc_memory.h, so that crates depending on
+//! proc_memory don't build a cdylib.
+
+pub use proc_memory::ffi::*;
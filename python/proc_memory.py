@@ -0,0 +1,149 @@
+"""Python bindings of proc_memory, on top of the C interface of the proc_memory_ffi shared library.
+
+Build the library with `cargo build --release -p proc_memory_ffi`, then point PROC_MEMORY_LIB at it or keep it
+next to this file:
+
+    from proc_memory import Proc
+
+    with Proc.open(1234) as proc:
+        health = proc.eval_address("[game.exe+0x1000]+0x20")
+        print(proc.read_i32(health))
+        for address in proc.scan("48 8B 05 ?? ?? ?? ??"):
+            print(hex(address))
+"""
+
+import ctypes
+import os
+import struct
+import sys
+
+_NAMES = {"win32": "proc_memory_ffi.dll", "darwin": "libproc_memory_ffi.dylib"}
+# Matches scan() makes room for before the first call, it only scans again when there are more
+_SCAN_CAPACITY = 4096
+
+
+def _load():
+    path = os.environ.get("PROC_MEMORY_LIB")
+    if path is None:
+        name = _NAMES.get(sys.platform, "libproc_memory_ffi.so")
+        path = os.path.join(os.path.dirname(os.path.abspath(__file__)), name)
+    lib = ctypes.CDLL(path)
+    handle = ctypes.c_void_p
+    lib.proc_open.argtypes = [ctypes.c_uint32]
+    lib.proc_open.restype = handle
+    lib.proc_open_name.argtypes = [ctypes.c_char_p]
+    lib.proc_open_name.restype = handle
+    lib.proc_close.argtypes = [handle]
+    lib.proc_close.restype = None
+    lib.proc_pid.argtypes = [handle]
+    lib.proc_pid.restype = ctypes.c_int64
+    lib.proc_read.argtypes = [handle, ctypes.c_size_t, ctypes.c_void_p, ctypes.c_size_t]
+    lib.proc_read.restype = ctypes.c_size_t
+    lib.proc_write.argtypes = [handle, ctypes.c_size_t, ctypes.c_char_p, ctypes.c_size_t]
+    lib.proc_write.restype = ctypes.c_size_t
+    lib.proc_scan.argtypes = [handle, ctypes.c_char_p, ctypes.POINTER(ctypes.c_size_t), ctypes.c_size_t]
+    lib.proc_scan.restype = ctypes.c_int64
+    lib.proc_eval_address.argtypes = [handle, ctypes.c_char_p, ctypes.POINTER(ctypes.c_size_t)]
+    lib.proc_eval_address.restype = ctypes.c_bool
+    return lib
+
+
+_lib = _load()
+
+
+class Proc:
+    """Opened process, closed by close() or at the end of a with block."""
+
+    def __init__(self, handle):
+        self._handle = handle
+
+    @classmethod
+    def open(cls, pid):
+        """Open the process with specified id, raises OSError on failure."""
+        handle = _lib.proc_open(pid)
+        if not handle:
+            raise OSError("can't open process {}".format(pid))
+        return cls(handle)
+
+    @classmethod
+    def open_name(cls, name):
+        """Open the process with specified title or executable name, raises OSError on failure."""
+        handle = _lib.proc_open_name(name.encode())
+        if not handle:
+            raise OSError("can't find process {!r}".format(name))
+        return cls(handle)
+
+    def close(self):
+        if self._handle:
+            _lib.proc_close(self._handle)
+            self._handle = None
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *_):
+        self.close()
+
+    def __del__(self):
+        self.close()
+
+    @property
+    def pid(self):
+        return _lib.proc_pid(self._handle)
+
+    def read_partial(self, address, size):
+        """Bytes at 'address' up to the first unreadable one."""
+        buffer = ctypes.create_string_buffer(size)
+        count = _lib.proc_read(self._handle, address, buffer, size)
+        return buffer.raw[:count]
+
+    def read(self, address, size):
+        """'size' bytes at 'address', None if any of them can't be read."""
+        data = self.read_partial(address, size)
+        return data if len(data) == size else None
+
+    def write(self, address, data):
+        """Write 'data' at 'address', returns True if all of it was written."""
+        return _lib.proc_write(self._handle, address, bytes(data), len(data)) == len(data)
+
+    def _read_struct(self, address, fmt):
+        data = self.read(address, struct.calcsize(fmt))
+        return None if data is None else struct.unpack(fmt, data)[0]
+
+    def read_i32(self, address):
+        return self._read_struct(address, "<i")
+
+    def read_u32(self, address):
+        return self._read_struct(address, "<I")
+
+    def read_i64(self, address):
+        return self._read_struct(address, "<q")
+
+    def read_u64(self, address):
+        return self._read_struct(address, "<Q")
+
+    def read_f32(self, address):
+        return self._read_struct(address, "<f")
+
+    def read_f64(self, address):
+        return self._read_struct(address, "<d")
+
+    def scan(self, signature):
+        """Addresses of every match of 'signature', hex bytes with ?? wildcards like "48 8B 05 ?? ?? ?? ??"."""
+        encoded = signature.encode()
+        capacity = _SCAN_CAPACITY
+        while True:
+            matches = (ctypes.c_size_t * capacity)()
+            count = _lib.proc_scan(self._handle, encoded, matches, capacity)
+            if count < 0:
+                raise ValueError("invalid signature {!r}".format(signature))
+            if count <= capacity:
+                return list(matches[:count])
+            capacity = count
+
+    def eval_address(self, expr):
+        """Resolve an address expression or pointer chain like "game.exe+0x1000 -> 0x20", None if it fails."""
+        address = ctypes.c_size_t()
+        if not _lib.proc_eval_address(self._handle, expr.encode(), ctypes.byref(address)):
+            return None
+        return address.value
@@ -0,0 +1,155 @@
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll, Waker};
+
+use crate::{Pattern, Pod, Proc, ProcT, ScanOptions, ScanResult};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+///Sender of the shared pool of threads running blocking process calls
+fn pool() -> &'static Mutex<Sender<Job>> {
+    static POOL: OnceLock<Mutex<Sender<Job>>> = OnceLock::new();
+    POOL.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = std::thread::available_parallelism().map_or(4, |count| count.get());
+        for index in 0..workers {
+            let receiver = Arc::clone(&receiver);
+            std::thread::Builder::new()
+                .name(format!("proc_memory-blocking-{}", index))
+                .spawn(move || loop {
+                    let job = match receiver.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => return,
+                    };
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => return,
+                    }
+                })
+                .expect("failed to spawn proc_memory worker thread");
+        }
+        Mutex::new(sender)
+    })
+}
+
+struct Shared<T> {
+    ///Panic payload if the call panicked, resumed by the awaiting task
+    result: Option<std::thread::Result<T>>,
+    waker: Option<Waker>,
+}
+
+///Future resolving to the result of a blocking call running on the worker pool
+pub struct Blocking<T> {
+    shared: Arc<Mutex<Shared<T>>>,
+}
+
+impl<T: Send + 'static> Blocking<T> {
+    fn spawn(f: impl FnOnce() -> T + Send + 'static) -> Blocking<T> {
+        let shared = Arc::new(Mutex::new(Shared {
+            result: None,
+            waker: None,
+        }));
+        let job_shared = Arc::clone(&shared);
+        let job: Job = Box::new(move || {
+            //A panic is handed to the future instead of killing the worker
+            let result = panic::catch_unwind(AssertUnwindSafe(f));
+            let mut shared = job_shared.lock().unwrap();
+            shared.result = Some(result);
+            if let Some(waker) = shared.waker.take() {
+                waker.wake();
+            }
+        });
+        pool()
+            .lock()
+            .unwrap()
+            .send(job)
+            .expect("proc_memory worker pool stopped");
+        Blocking { shared }
+    }
+}
+
+impl<T> Future for Blocking<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut shared = self.shared.lock().unwrap();
+        match shared.result.take() {
+            Some(Ok(result)) => Poll::Ready(result),
+            Some(Err(payload)) => {
+                drop(shared);
+                panic::resume_unwind(payload)
+            }
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+///Handle to a process whose operations run on a pool of worker threads and can be awaited.
+///The futures don't depend on a particular async runtime
+pub struct AsyncProc<P: ProcT + Send + Sync + 'static = Proc> {
+    proc: Arc<P>,
+}
+
+impl<P: ProcT + Send + Sync + 'static> Clone for AsyncProc<P> {
+    fn clone(&self) -> Self {
+        AsyncProc {
+            proc: Arc::clone(&self.proc),
+        }
+    }
+}
+
+impl<P: ProcT + Send + Sync + 'static> AsyncProc<P> {
+    pub fn new(proc: P) -> AsyncProc<P> {
+        AsyncProc {
+            proc: Arc::new(proc),
+        }
+    }
+
+    ///The wrapped process, for calls that are cheap enough to make directly
+    pub fn proc(&self) -> &P {
+        &self.proc
+    }
+
+    ///Run 'f' with the process on the worker pool, a panic in 'f' is resumed by the task awaiting the result
+    pub fn run<T: Send + 'static>(&self, f: impl FnOnce(&P) -> T + Send + 'static) -> Blocking<T> {
+        let proc = Arc::clone(&self.proc);
+        Blocking::spawn(move || f(&proc))
+    }
+
+    ///Read a value of type T from specified memory address
+    pub fn read<T: Pod + Send>(&self, proc_address: usize) -> Blocking<Option<T>> {
+        self.run(move |proc| proc.read::<T>(proc_address))
+    }
+
+    ///Read 'len' bytes from specified memory address
+    pub fn read_bytes(&self, proc_address: usize, len: usize) -> Blocking<Option<Vec<u8>>> {
+        self.run(move |proc| proc.read_vec_uninit::<u8>(proc_address, len))
+    }
+
+    ///Write the value of T to the specified address
+    pub fn write<T: Pod + Send>(&self, proc_address: usize, data: T) -> Blocking<(bool, usize)> {
+        self.run(move |proc| proc.write(proc_address, &data))
+    }
+
+    ///Write the bytes of 'data' to the specified address
+    pub fn write_bytes(&self, proc_address: usize, data: Vec<u8>) -> Blocking<(bool, usize)> {
+        self.run(move |proc| proc.write_bytes(proc_address, &data))
+    }
+
+    ///Addresses of every match of 'pattern' in the readable regions of the process
+    pub fn scan(&self, pattern: Pattern) -> Blocking<Vec<usize>> {
+        self.run(move |proc| proc.scan(&pattern))
+    }
+
+    ///Scan with the specified options, cancel the scan through its token rather than dropping the future
+    pub fn scan_with(&self, pattern: Pattern, options: ScanOptions) -> Blocking<ScanResult> {
+        self.run(move |proc| proc.scan_with(&pattern, &options))
+    }
+}
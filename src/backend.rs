@@ -0,0 +1,370 @@
+use std::mem::MaybeUninit;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::{uninit, Module, Pod, ProcT, Protection, Region, RegionInfo, Thread};
+
+///Memory access of a target process, wrapped in a BackendProc to get the whole ProcT API on top of it.
+///Proc is the BackendProc of the backend of the current platform, ProcLinux or ProcWindows. Custom backends, like
+///a kernel driver, a network agent or an emulator, only have to provide read_raw(), write_raw() and regions(),
+///the operations they leave out fail
+///
+///```
+///use proc_memory::{BackendProc, MemoryBackend, ProcT, Protection, Region};
+///
+///struct Rom(Vec<u8>);
+///
+///impl MemoryBackend for Rom {
+///    fn read_raw(&self, address: usize, buffer: &mut [u8]) -> usize {
+///        let data = self.0.get(address..).unwrap_or(&[]);
+///        let count = data.len().min(buffer.len());
+///        buffer[..count].copy_from_slice(&data[..count]);
+///        count
+///    }
+///
+///    fn write_raw(&self, _address: usize, _data: &[u8]) -> usize {
+///        0
+///    }
+///
+///    fn regions(&self) -> Vec<Region> {
+///        vec![Region { base: 0, size: self.0.len(), protection: Protection::READ }]
+///    }
+///}
+///
+///let rom = BackendProc::new(Rom(vec![0xDE, 0xAD, 0xBE, 0xEF]));
+///assert_eq!(rom.read_be::<u32>(0), Some(0xDEADBEEF));
+///```
+pub trait MemoryBackend {
+    ///Read into 'buffer' from 'address', stopping at the first unreadable byte. Returns the number of bytes read
+    fn read_raw(&self, address: usize, buffer: &mut [u8]) -> usize;
+
+    ///Read into the uninitialized 'buffer' from 'address', stopping at the first unreadable byte. Returns the number
+    ///of bytes read. Zeroes 'buffer' for read_raw() by default, backends that never read from it skip that
+    fn read_uninit(&self, address: usize, buffer: &mut [MaybeUninit<u8>]) -> usize {
+        buffer.fill(MaybeUninit::new(0));
+        let buffer = unsafe { &mut *(buffer as *mut [MaybeUninit<u8>] as *mut [u8]) };
+        self.read_raw(address, buffer)
+    }
+
+    ///Write 'data' at 'address', stopping at the first unwritable byte. Returns the number of bytes written
+    fn write_raw(&self, address: usize, data: &[u8]) -> usize;
+
+    ///Mapped memory regions, sorted by address
+    fn regions(&self) -> Vec<Region>;
+
+    ///Loaded modules, none by default
+    fn modules(&self) -> Vec<Module> {
+        Vec::new()
+    }
+
+    ///Threads of the target, none by default
+    fn threads(&self) -> Vec<Thread> {
+        Vec::new()
+    }
+
+    ///Name of the target, None by default
+    fn name(&self) -> Option<String> {
+        None
+    }
+
+    ///Process id of the target, zero by default
+    fn pid(&self) -> isize {
+        0
+    }
+
+    ///Pointer width of the target, the one of the current process by default
+    fn is_64bit(&self) -> bool {
+        cfg!(target_pointer_width = "64")
+    }
+
+    ///Returns 'true' while the target can still be read, always by default
+    fn is_alive(&self) -> bool {
+        true
+    }
+
+    ///Open the target named 'name', None by default
+    fn get(_name: &str) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    ///Read many (address, len) ranges at once, one read_raw() per range by default
+    fn read_batch(&self, requests: &[(usize, usize)]) -> Vec<Option<Vec<u8>>> {
+        requests
+            .iter()
+            .map(|(address, len)| {
+                let mut data = vec![0u8; *len];
+                (self.read_raw(*address, &mut data) == *len).then_some(data)
+            })
+            .collect()
+    }
+
+    ///Region containing 'address' with the file mapped in it, found in regions() without a file by default
+    fn query(&self, address: usize) -> Option<RegionInfo> {
+        self.regions()
+            .into_iter()
+            .find(|region| region.contains(address))
+            .map(RegionInfo::from)
+    }
+
+    ///Regions with the files mapped in them, regions() without files by default
+    fn region_infos(&self) -> Vec<RegionInfo> {
+        self.regions().into_iter().map(RegionInfo::from).collect()
+    }
+
+    ///Change the protection of the pages covering 'len' bytes at 'address', unsupported by default
+    fn protect(&self, _address: usize, _len: usize, _protection: Protection) -> Option<Protection> {
+        None
+    }
+
+    ///Allocate 'len' bytes in the target, unsupported by default
+    fn alloc(&self, _len: usize, _protection: Protection) -> Option<usize> {
+        None
+    }
+
+    ///Release memory returned by alloc(), unsupported by default
+    fn free(&self, _address: usize, _len: usize) -> bool {
+        false
+    }
+
+    ///Start a thread running 'start(param)', unsupported by default
+    fn create_thread(&self, _start: usize, _param: usize) -> Option<u32> {
+        None
+    }
+
+    ///Run 'start(param)' and wait for its result, unsupported by default
+    fn create_thread_and_wait(&self, _start: usize, _param: usize) -> Option<u32> {
+        None
+    }
+
+    ///Write a crash-style dump of the target to 'path', unsupported by default
+    #[cfg(feature = "minidump")]
+    fn write_minidump(
+        &self,
+        _path: &std::path::Path,
+        _level: crate::DumpLevel,
+    ) -> std::io::Result<()> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "dumps are not supported by this backend",
+        ))
+    }
+
+    ///Freeze the target, unsupported by default
+    fn suspend(&self) -> bool {
+        false
+    }
+
+    ///Continue a target frozen with suspend(), unsupported by default
+    fn resume(&self) -> bool {
+        false
+    }
+
+    ///Command line of the target, None by default
+    fn command_line(&self) -> Option<String> {
+        None
+    }
+
+    ///Environment variables of the target, None by default
+    fn environment(&self) -> Option<Vec<(String, String)>> {
+        None
+    }
+
+    ///Path of the executable of the target, None by default
+    fn exe_path(&self) -> Option<PathBuf> {
+        None
+    }
+
+    ///Time the target was started at, None by default
+    fn start_time(&self) -> Option<SystemTime> {
+        None
+    }
+
+    ///Id of the process that created the target, None by default
+    fn parent_pid(&self) -> Option<isize> {
+        None
+    }
+}
+
+///Process whose memory is accessed through a MemoryBackend.
+///Reads, writes, scans, pointer chains and every other ProcT helper go through the backend, operations the
+///backend doesn't implement (allocation, protection, remote threads, suspending) fail
+#[derive(Clone, Debug, Default)]
+pub struct BackendProc<B: MemoryBackend> {
+    backend: B,
+}
+
+impl<B: MemoryBackend> BackendProc<B> {
+    pub fn new(backend: B) -> BackendProc<B> {
+        BackendProc { backend }
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
+
+    pub fn into_inner(self) -> B {
+        self.backend
+    }
+
+    ///Fill 'buffer' completely or fail
+    fn read_into(&self, proc_address: usize, buffer: &mut [MaybeUninit<u8>]) -> bool {
+        self.backend.read_uninit(proc_address, buffer) == buffer.len()
+    }
+}
+
+impl<B: MemoryBackend> From<B> for BackendProc<B> {
+    fn from(backend: B) -> Self {
+        BackendProc::new(backend)
+    }
+}
+
+impl<B: MemoryBackend> ProcT for BackendProc<B> {
+    ///Open the target through MemoryBackend::get(), custom backends are wrapped with BackendProc::new() instead
+    fn get(proc_name: &str) -> Option<Self> {
+        B::get(proc_name).map(BackendProc::new)
+    }
+
+    fn read<T: Pod>(&self, proc_address: usize) -> Option<T> {
+        uninit::read_value(|buffer| self.read_into(proc_address, buffer))
+    }
+
+    fn read_valid<T: Pod>(&self, proc_address: usize, validator: impl Fn(&T) -> bool) -> Option<T> {
+        self.read::<T>(proc_address)
+            .filter(|value| validator(value))
+    }
+
+    fn read_vec<T: Pod>(
+        &self,
+        proc_address: usize,
+        len: usize,
+        _default_provider: impl Fn() -> T,
+    ) -> Option<Vec<T>> {
+        uninit::read_values(len, |buffer| self.read_into(proc_address, buffer))
+    }
+
+    fn read_vec_uninit<T: Pod>(&self, proc_address: usize, len: usize) -> Option<Vec<T>> {
+        uninit::read_values(len, |buffer| self.read_into(proc_address, buffer))
+    }
+
+    fn read_partial(&self, proc_address: usize, len: usize) -> (Vec<u8>, usize) {
+        let data = uninit::read_bytes(len, |buffer| self.backend.read_uninit(proc_address, buffer));
+        let count = data.len();
+        (data, count)
+    }
+
+    fn read_batch(&self, requests: &[(usize, usize)]) -> Vec<Option<Vec<u8>>> {
+        self.backend.read_batch(requests)
+    }
+
+    fn write<T>(&self, proc_address: usize, data: &T) -> (bool, usize) {
+        let bytes = unsafe {
+            std::slice::from_raw_parts(data as *const T as *const u8, std::mem::size_of::<T>())
+        };
+        self.write_bytes(proc_address, bytes)
+    }
+
+    fn write_bytes(&self, proc_address: usize, data: &[u8]) -> (bool, usize) {
+        let count = self.backend.write_raw(proc_address, data).min(data.len());
+        (count == data.len(), count)
+    }
+
+    fn modules(&self) -> Vec<Module> {
+        self.backend.modules()
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        self.backend.regions()
+    }
+
+    fn query(&self, address: usize) -> Option<RegionInfo> {
+        self.backend.query(address)
+    }
+
+    fn region_infos(&self) -> Vec<RegionInfo> {
+        self.backend.region_infos()
+    }
+
+    fn threads(&self) -> Vec<Thread> {
+        self.backend.threads()
+    }
+
+    fn is_alive(&self) -> bool {
+        self.backend.is_alive()
+    }
+
+    fn protect(&self, address: usize, len: usize, protection: Protection) -> Option<Protection> {
+        self.backend.protect(address, len, protection)
+    }
+
+    fn alloc(&self, len: usize, protection: Protection) -> Option<usize> {
+        self.backend.alloc(len, protection)
+    }
+
+    fn free(&self, address: usize, len: usize) -> bool {
+        self.backend.free(address, len)
+    }
+
+    fn create_thread(&self, start: usize, param: usize) -> Option<u32> {
+        self.backend.create_thread(start, param)
+    }
+
+    fn create_thread_and_wait(&self, start: usize, param: usize) -> Option<u32> {
+        self.backend.create_thread_and_wait(start, param)
+    }
+
+    #[cfg(feature = "minidump")]
+    fn write_minidump(
+        &self,
+        path: &std::path::Path,
+        level: crate::DumpLevel,
+    ) -> std::io::Result<()> {
+        self.backend.write_minidump(path, level)
+    }
+
+    fn suspend(&self) -> bool {
+        self.backend.suspend()
+    }
+
+    fn resume(&self) -> bool {
+        self.backend.resume()
+    }
+
+    fn command_line(&self) -> Option<String> {
+        self.backend.command_line()
+    }
+
+    fn environment(&self) -> Option<Vec<(String, String)>> {
+        self.backend.environment()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.backend.name()
+    }
+
+    fn exe_path(&self) -> Option<PathBuf> {
+        self.backend.exe_path()
+    }
+
+    fn start_time(&self) -> Option<SystemTime> {
+        self.backend.start_time()
+    }
+
+    fn parent_pid(&self) -> Option<isize> {
+        self.backend.parent_pid()
+    }
+
+    fn is_64bit(&self) -> bool {
+        self.backend.is_64bit()
+    }
+
+    fn pid(&self) -> isize {
+        self.backend.pid()
+    }
+}
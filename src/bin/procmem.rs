@@ -0,0 +1,115 @@
+//! Command line access to the memory of other processes, built with the "cli" feature.
+//!
+//! Processes are given by pid or by name, addresses are address expressions like `[game.exe+0x1000]+0x20`.
+
+use std::net::TcpListener;
+use std::path::Path;
+use std::process::exit;
+
+use proc_memory::{parse_hex_bytes, serve_agent, Pattern, Proc, ProcT};
+
+const USAGE: &str = "usage: procmem <command> <process> [arguments]
+
+commands:
+  read <process> <address> <len>         hexdump of len bytes
+  write <process> <address> <hex bytes>  write bytes, e.g. \"90 90\"
+  scan <process> <signature>             addresses matching a signature, e.g. \"48 8B 05 ?? ?? ?? ??\"
+  regions <process>                      mapped memory regions
+  modules <process>                      loaded modules
+  dump <process> <address> <len> <file>  write len bytes to a file, unreadable pages as zeros
+  agent <process> <host:port>            serve the process to RemoteProc clients, unauthenticated
+
+<process> is a pid or a process name, <address> an address expression, <len> decimal or 0x hex";
+
+fn fail(message: &str) -> ! {
+    eprintln!("procmem: {}", message);
+    exit(1)
+}
+
+fn open(process: &str) -> Proc {
+    let proc = match process.parse::<u32>() {
+        Ok(pid) => Proc::from_pid(pid),
+        Err(_) => Proc::get(process),
+    };
+    proc.unwrap_or_else(|| fail(&format!("can't open process '{}'", process)))
+}
+
+fn address(proc: &Proc, expr: &str) -> usize {
+    proc.eval_address(expr)
+        .unwrap_or_else(|| fail(&format!("can't resolve address '{}'", expr)))
+}
+
+fn length(text: &str) -> usize {
+    let parsed = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => text.parse(),
+    };
+    parsed.unwrap_or_else(|_| fail(&format!("invalid length '{}'", text)))
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let (command, process, rest) = match args.as_slice() {
+        [command, process, rest @ ..] => (*command, *process, rest),
+        _ => fail(USAGE),
+    };
+
+    let proc = open(process);
+    match (command, rest) {
+        ("read", [expr, len]) => {
+            print!("{}", proc.hexdump(address(&proc, expr), length(len)));
+        }
+        ("write", [expr, data]) => {
+            let data =
+                parse_hex_bytes(data).unwrap_or_else(|| fail(&format!("invalid bytes '{}'", data)));
+            let (ok, count) = proc.write_bytes(address(&proc, expr), &data);
+            if !ok {
+                fail(&format!("wrote {} of {} bytes", count, data.len()));
+            }
+        }
+        ("scan", [signature]) => {
+            let pattern = Pattern::parse(signature)
+                .unwrap_or_else(|| fail(&format!("invalid signature '{}'", signature)));
+            for address in proc.scan(&pattern) {
+                println!("{:#X}", address);
+            }
+        }
+        ("regions", []) => {
+            for region in proc.region_infos() {
+                println!(
+                    "{:#014X}-{:#014X} {} {}",
+                    region.base,
+                    region.end(),
+                    region.protection,
+                    region.file.as_deref().unwrap_or("")
+                );
+            }
+        }
+        ("modules", []) => {
+            for module in proc.modules() {
+                println!(
+                    "{:#014X} {:#10X} {} {}",
+                    module.base, module.size, module.name, module.path
+                );
+            }
+        }
+        ("dump", [expr, len, file]) => {
+            let gaps = proc
+                .dump_range(address(&proc, expr), length(len), Path::new(file))
+                .unwrap_or_else(|error| fail(&format!("can't write '{}': {}", file, error)));
+            for gap in gaps {
+                eprintln!("unreadable: {:#X}-{:#X}", gap.start, gap.end);
+            }
+        }
+        ("agent", [address]) => {
+            let listener = TcpListener::bind(address)
+                .unwrap_or_else(|error| fail(&format!("can't listen on '{}': {}", address, error)));
+            eprintln!("serving {} on {}", process, address);
+            if let Err(error) = serve_agent(&proc, &listener) {
+                fail(&format!("agent stopped: {}", error));
+            }
+        }
+        _ => fail(USAGE),
+    }
+}
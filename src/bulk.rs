@@ -0,0 +1,64 @@
+use std::ops::Range;
+
+use crate::{ProcT, PAGE_SIZE};
+
+///Result of a page-aware bulk read
+#[derive(Clone, Debug, Default)]
+pub struct BulkRead {
+    ///Address the read started at
+    pub address: usize,
+    ///Read bytes, unreadable pages are filled with the sentinel byte
+    pub data: Vec<u8>,
+    ///Address ranges that could not be read
+    pub gaps: Vec<Range<usize>>,
+}
+
+impl BulkRead {
+    ///Returns 'true' if every requested byte was read
+    pub fn is_complete(&self) -> bool {
+        self.gaps.is_empty()
+    }
+
+    ///Returns 'true' if the byte at the specified address was read from the process
+    pub fn is_readable(&self, address: usize) -> bool {
+        address >= self.address
+            && address < self.address + self.data.len()
+            && !self.gaps.iter().any(|gap| gap.contains(&address))
+    }
+}
+
+pub(crate) fn read_bulk<P: ProcT + ?Sized>(
+    proc: &P,
+    proc_address: usize,
+    len: usize,
+    fill: u8,
+) -> BulkRead {
+    let mut result = BulkRead {
+        address: proc_address,
+        data: Vec::with_capacity(len),
+        gaps: Vec::new(),
+    };
+
+    let end = proc_address + len;
+    let mut current = proc_address;
+    while current < end {
+        let page_end = ((current / PAGE_SIZE) + 1) * PAGE_SIZE;
+        let chunk = page_end.min(end) - current;
+
+        let (bytes, count) = proc.read_partial(current, chunk);
+        result.data.extend_from_slice(&bytes[..count]);
+
+        if count < chunk {
+            //Skip the remainder of the page
+            result.data.resize(result.data.len() + chunk - count, fill);
+            let gap_start = current + count;
+            match result.gaps.last_mut() {
+                Some(last) if last.end == gap_start => last.end = current + chunk,
+                _ => result.gaps.push(gap_start..current + chunk),
+            }
+        }
+        current += chunk;
+    }
+
+    result
+}
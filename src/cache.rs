@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::mem::MaybeUninit;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::{uninit, Module, Pod, ProcT, Protection, Region, RegionInfo, Thread, PAGE_SIZE};
+
+///Cached copy of a page, holding the bytes up to the first unreadable one
+#[derive(Debug)]
+struct Page {
+    data: Vec<u8>,
+    fetched: Instant,
+}
+
+///Process whose reads are served from a page cache.
+///The first read of a page fetches it whole, later reads of the same page don't reach the target until the
+///cached copy expires or is invalidated. Writes go straight to the target and drop the pages they touch
+///
+///```
+///use proc_memory::{CachedProc, MockProc, ProcT};
+///
+///let proc = MockProc::new();
+///proc.map_value(0x1000, &1u32);
+///let cached = CachedProc::new(&proc);
+///assert_eq!(cached.read::<u32>(0x1000), Some(1));
+///
+///proc.write(0x1000, &2u32);
+///assert_eq!(cached.read::<u32>(0x1000), Some(1));
+///cached.invalidate();
+///assert_eq!(cached.read::<u32>(0x1000), Some(2));
+///```
+#[derive(Debug)]
+pub struct CachedProc<'a, P: ProcT + ?Sized> {
+    proc: &'a P,
+    ttl: Option<Duration>,
+    pages: Mutex<HashMap<usize, Page>>,
+}
+
+impl<'a, P: ProcT + ?Sized> CachedProc<'a, P> {
+    ///Cache the reads of 'proc', pages are kept until invalidate() is called
+    pub fn new(proc: &'a P) -> CachedProc<'a, P> {
+        CachedProc {
+            proc,
+            ttl: None,
+            pages: Mutex::new(HashMap::new()),
+        }
+    }
+
+    ///Fetch pages again once they are older than 'ttl', e.g. a frame time for overlays
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    ///The process the reads are forwarded to
+    pub fn proc(&self) -> &'a P {
+        self.proc
+    }
+
+    ///Drop every cached page
+    pub fn invalidate(&self) {
+        self.pages.lock().unwrap().clear();
+    }
+
+    ///Drop the cached pages overlapping 'len' bytes at 'address'
+    pub fn invalidate_range(&self, address: usize, len: usize) {
+        let first = address / PAGE_SIZE * PAGE_SIZE;
+        let end = address.saturating_add(len);
+        self.pages
+            .lock()
+            .unwrap()
+            .retain(|base, _| *base < first || *base >= end);
+    }
+
+    ///Number of pages currently cached
+    pub fn cached_pages(&self) -> usize {
+        self.pages.lock().unwrap().len()
+    }
+
+    ///Copy cached bytes starting at 'address' into 'buffer', fetching missing pages. Returns the copied count
+    fn copy_from(&self, address: usize, buffer: &mut [MaybeUninit<u8>]) -> usize {
+        let mut pages = self.pages.lock().unwrap();
+        let mut done = 0;
+        while done < buffer.len() {
+            let current = address + done;
+            let base = current / PAGE_SIZE * PAGE_SIZE;
+            let expired = match (pages.get(&base), self.ttl) {
+                (Some(page), Some(ttl)) => page.fetched.elapsed() > ttl,
+                (Some(_), None) => false,
+                (None, _) => true,
+            };
+            if expired {
+                let (data, _) = self.proc.read_partial(base, PAGE_SIZE);
+                pages.insert(
+                    base,
+                    Page {
+                        data,
+                        fetched: Instant::now(),
+                    },
+                );
+            }
+
+            let page = &pages[&base];
+            let offset = current - base;
+            if offset >= page.data.len() {
+                break;
+            }
+            let count = (page.data.len() - offset).min(buffer.len() - done);
+            uninit::copy_to(&mut buffer[done..], &page.data[offset..offset + count]);
+            done += count;
+            if offset + count < PAGE_SIZE && done < buffer.len() {
+                //The rest of the page is unreadable
+                break;
+            }
+        }
+        done
+    }
+
+    fn read_into(&self, proc_address: usize, buffer: &mut [MaybeUninit<u8>]) -> bool {
+        self.copy_from(proc_address, buffer) == buffer.len()
+    }
+}
+
+impl<'a, P: ProcT + ?Sized> ProcT for CachedProc<'a, P> {
+    ///Always None, caches are built with CachedProc::new()
+    fn get(_proc_name: &str) -> Option<Self> {
+        None
+    }
+
+    fn read<T: Pod>(&self, proc_address: usize) -> Option<T> {
+        uninit::read_value(|buffer| self.read_into(proc_address, buffer))
+    }
+
+    fn read_valid<T: Pod>(&self, proc_address: usize, validator: impl Fn(&T) -> bool) -> Option<T> {
+        self.read::<T>(proc_address)
+            .filter(|value| validator(value))
+    }
+
+    fn read_vec<T: Pod>(
+        &self,
+        proc_address: usize,
+        len: usize,
+        _default_provider: impl Fn() -> T,
+    ) -> Option<Vec<T>> {
+        uninit::read_values(len, |buffer| self.read_into(proc_address, buffer))
+    }
+
+    fn read_vec_uninit<T: Pod>(&self, proc_address: usize, len: usize) -> Option<Vec<T>> {
+        uninit::read_values(len, |buffer| self.read_into(proc_address, buffer))
+    }
+
+    fn read_partial(&self, proc_address: usize, len: usize) -> (Vec<u8>, usize) {
+        let data = uninit::read_bytes(len, |buffer| self.copy_from(proc_address, buffer));
+        let count = data.len();
+        (data, count)
+    }
+
+    fn write<T>(&self, proc_address: usize, data: &T) -> (bool, usize) {
+        let bytes = unsafe {
+            std::slice::from_raw_parts(data as *const T as *const u8, std::mem::size_of::<T>())
+        };
+        self.write_bytes(proc_address, bytes)
+    }
+
+    fn write_bytes(&self, proc_address: usize, data: &[u8]) -> (bool, usize) {
+        self.invalidate_range(proc_address, data.len());
+        self.proc.write_bytes(proc_address, data)
+    }
+
+    fn modules(&self) -> Vec<Module> {
+        self.proc.modules()
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        self.proc.regions()
+    }
+
+    fn query(&self, address: usize) -> Option<RegionInfo> {
+        self.proc.query(address)
+    }
+
+    fn region_infos(&self) -> Vec<RegionInfo> {
+        self.proc.region_infos()
+    }
+
+    fn threads(&self) -> Vec<Thread> {
+        self.proc.threads()
+    }
+
+    fn is_alive(&self) -> bool {
+        self.proc.is_alive()
+    }
+
+    fn protect(&self, address: usize, len: usize, protection: Protection) -> Option<Protection> {
+        self.invalidate_range(address, len);
+        self.proc.protect(address, len, protection)
+    }
+
+    fn alloc(&self, len: usize, protection: Protection) -> Option<usize> {
+        let address = self.proc.alloc(len, protection)?;
+        self.invalidate_range(address, len);
+        Some(address)
+    }
+
+    fn free(&self, address: usize, len: usize) -> bool {
+        self.invalidate_range(address, len);
+        self.proc.free(address, len)
+    }
+
+    fn create_thread(&self, start: usize, param: usize) -> Option<u32> {
+        self.proc.create_thread(start, param)
+    }
+
+    fn create_thread_and_wait(&self, start: usize, param: usize) -> Option<u32> {
+        self.proc.create_thread_and_wait(start, param)
+    }
+
+    #[cfg(feature = "minidump")]
+    fn write_minidump(
+        &self,
+        path: &std::path::Path,
+        level: crate::DumpLevel,
+    ) -> std::io::Result<()> {
+        self.proc.write_minidump(path, level)
+    }
+
+    fn suspend(&self) -> bool {
+        self.proc.suspend()
+    }
+
+    fn resume(&self) -> bool {
+        self.proc.resume()
+    }
+
+    fn command_line(&self) -> Option<String> {
+        self.proc.command_line()
+    }
+
+    fn environment(&self) -> Option<Vec<(String, String)>> {
+        self.proc.environment()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.proc.name()
+    }
+
+    fn exe_path(&self) -> Option<PathBuf> {
+        self.proc.exe_path()
+    }
+
+    fn start_time(&self) -> Option<SystemTime> {
+        self.proc.start_time()
+    }
+
+    fn parent_pid(&self) -> Option<isize> {
+        self.proc.parent_pid()
+    }
+
+    fn is_64bit(&self) -> bool {
+        self.proc.is_64bit()
+    }
+
+    fn pid(&self) -> isize {
+        self.proc.pid()
+    }
+}
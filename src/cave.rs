@@ -0,0 +1,59 @@
+use std::ops::Range;
+
+use crate::{elf, Module, ProcT};
+
+///Run of padding bytes inside executable code that can hold a patch
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CodeCave {
+    ///Address of the first padding byte
+    pub address: usize,
+    ///Number of consecutive padding bytes
+    pub size: usize,
+    ///The padding byte, 0x00 or 0xCC
+    pub fill: u8,
+}
+
+const PADDING_BYTES: [u8; 2] = [0x00, 0xCC];
+
+///Executable PE sections, or executable ELF segments for modules that aren't PE images
+fn executable_ranges<P: ProcT + ?Sized>(proc: &P, module: &Module) -> Vec<Range<usize>> {
+    if let Some(headers) = module.pe_headers(proc) {
+        return headers
+            .sections
+            .iter()
+            .filter(|section| section.is_executable())
+            .map(|section| section.address..section.address + section.size)
+            .collect();
+    }
+    elf::executable_ranges(proc, module).unwrap_or_default()
+}
+
+pub(crate) fn find_code_caves<P: ProcT + ?Sized>(
+    proc: &P,
+    module: &Module,
+    min_size: usize,
+) -> Vec<CodeCave> {
+    let mut caves = Vec::new();
+    for range in executable_ranges(proc, module) {
+        //Unreadable pages are filled with a byte that never counts as padding
+        let code = proc.read_bulk(range.start, range.len(), 0xFF);
+
+        let mut start = 0;
+        while start < code.data.len() {
+            let fill = code.data[start];
+            let len = code.data[start..]
+                .iter()
+                .take_while(|byte| **byte == fill)
+                .count();
+            if PADDING_BYTES.contains(&fill) && len >= min_size.max(1) {
+                caves.push(CodeCave {
+                    address: range.start + start,
+                    size: len,
+                    fill,
+                });
+            }
+            start += len;
+        }
+    }
+    caves
+}
@@ -0,0 +1,225 @@
+use crate::{invalid_data, AddressExpr, OffsetTable};
+
+///Element of an XML document, only what cheat tables use: names, text and children. Attributes are ignored
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    text: String,
+    children: Vec<Element>,
+}
+
+impl Element {
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> + 'a {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    ///Trimmed text of the child named 'name', None if missing or empty
+    fn child_text(&self, name: &str) -> Option<&str> {
+        let text = self.child(name)?.text.trim();
+        if text.is_empty() {
+            return None;
+        }
+        Some(text)
+    }
+}
+
+///Replace the predefined XML entities and character references of 'text'
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = match rest.find(';') {
+            Some(end) => end,
+            None => break,
+        };
+        let entity = &rest[1..end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(|decimal| decimal.parse()))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+        match c {
+            Some(c) => {
+                unescaped.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+///Parse an XML document into a nameless element holding the root
+fn parse_xml(text: &str) -> std::io::Result<Element> {
+    let mut stack = vec![Element::default()];
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        stack.last_mut().unwrap().text += &unescape(&rest[..start]);
+        rest = &rest[start..];
+
+        let skip_to = |rest: &str, end: &str| {
+            rest.find(end)
+                .map(|index| index + end.len())
+                .ok_or_else(|| invalid_data(format!("missing '{}'", end)))
+        };
+        if rest.starts_with("<?") {
+            rest = &rest[skip_to(rest, "?>")?..];
+        } else if rest.starts_with("<!--") {
+            rest = &rest[skip_to(rest, "-->")?..];
+        } else if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = skip_to(cdata, "]]>")?;
+            stack.last_mut().unwrap().text += &cdata[..end - 3];
+            rest = &cdata[end..];
+        } else if rest.starts_with("<!") {
+            rest = &rest[skip_to(rest, ">")?..];
+        } else if let Some(closing) = rest.strip_prefix("</") {
+            let end = skip_to(closing, ">")?;
+            let name = closing[..end - 1].trim();
+            let element = stack.pop().unwrap();
+            if stack.is_empty() || element.name != name {
+                return Err(invalid_data(format!("unexpected '</{}>'", name)));
+            }
+            stack.last_mut().unwrap().children.push(element);
+            rest = &closing[end..];
+        } else {
+            //Attribute values can contain '>', only stop outside of quotes
+            let mut quote = None;
+            let end = rest
+                .char_indices()
+                .find(|(_, c)| match quote {
+                    Some(open) if *c == open => {
+                        quote = None;
+                        false
+                    }
+                    Some(_) => false,
+                    None if *c == '"' || *c == '\'' => {
+                        quote = Some(*c);
+                        false
+                    }
+                    None => *c == '>',
+                })
+                .map(|(index, _)| index)
+                .ok_or_else(|| invalid_data("unterminated tag"))?;
+            let tag = &rest[1..end];
+            let name = tag
+                .split(|c: char| c.is_whitespace() || c == '/')
+                .next()
+                .unwrap_or_default();
+            let element = Element {
+                name: name.to_string(),
+                ..Element::default()
+            };
+            if tag.ends_with('/') {
+                stack.last_mut().unwrap().children.push(element);
+            } else {
+                stack.push(element);
+            }
+            rest = &rest[end + 1..];
+        }
+    }
+    if stack.len() != 1 {
+        return Err(invalid_data(format!(
+            "unclosed '<{}>'",
+            stack.last().unwrap().name
+        )));
+    }
+    Ok(stack.pop().unwrap())
+}
+
+///Replace the names of 'expr' that are user defined symbols of 'table' with their address
+fn substitute(expr: AddressExpr, table: &OffsetTable) -> AddressExpr {
+    let boxed = |expr: Box<AddressExpr>| Box::new(substitute(*expr, table));
+    match expr {
+        AddressExpr::Module(name) => match table.get(&name) {
+            Some(symbol) => symbol.clone(),
+            None => AddressExpr::Module(name),
+        },
+        AddressExpr::Deref(inner) => AddressExpr::Deref(boxed(inner)),
+        AddressExpr::Add(left, right) => AddressExpr::Add(boxed(left), boxed(right)),
+        AddressExpr::Sub(left, right) => AddressExpr::Sub(boxed(left), boxed(right)),
+        AddressExpr::Mul(left, right) => AddressExpr::Mul(boxed(left), boxed(right)),
+        AddressExpr::Number(value) => AddressExpr::Number(value),
+    }
+}
+
+///Address of a cheat entry: its base address followed by its pointer offsets. Cheat tables list the offsets
+///starting from the last one applied
+fn entry_address(entry: &Element, symbols: &OffsetTable) -> Option<AddressExpr> {
+    let mut address = substitute(AddressExpr::parse(entry.child_text("Address")?)?, symbols);
+    if let Some(offsets) = entry.child("Offsets") {
+        let offsets: Vec<&Element> = offsets.children("Offset").collect();
+        for offset in offsets.iter().rev() {
+            let offset = substitute(AddressExpr::parse(offset.text.trim())?, symbols);
+            address = AddressExpr::Add(
+                Box::new(AddressExpr::Deref(Box::new(address))),
+                Box::new(offset),
+            );
+        }
+    }
+    Some(address)
+}
+
+///Add the entries of a CheatEntries element and of its groups, named by description with the group path as prefix
+fn add_entries(table: &mut OffsetTable, symbols: &OffsetTable, entries: &Element, prefix: &str) {
+    for entry in entries.children("CheatEntry") {
+        let description = entry
+            .child_text("Description")
+            .map(|text| text.trim_matches('"').trim())
+            .unwrap_or_default();
+        let id = entry.child_text("ID").unwrap_or_default();
+        let mut name = format!("{}{}", prefix, description);
+        if description.is_empty() || table.get(&name).is_some() {
+            name = format!("{} ({})", name, id);
+        }
+
+        if let Some(address) = entry_address(entry, symbols) {
+            table.insert(&name, address);
+        }
+        if let Some(children) = entry.child("CheatEntries") {
+            add_entries(table, symbols, children, &format!("{}/", name));
+        }
+    }
+}
+
+///Named addresses of a Cheat Engine table: the user defined symbols, then the cheat entries
+pub(crate) fn parse(text: &str) -> std::io::Result<OffsetTable> {
+    let document = parse_xml(text)?;
+    let root = document
+        .child("CheatTable")
+        .ok_or_else(|| invalid_data("not a Cheat Engine table"))?;
+
+    let mut symbols = OffsetTable::new();
+    if let Some(entries) = root.child("UserdefinedSymbols") {
+        for symbol in entries.children("SymbolEntry") {
+            let address = symbol.child_text("Address").and_then(AddressExpr::parse);
+            if let (Some(name), Some(address)) = (symbol.child_text("Name"), address) {
+                let address = substitute(address, &symbols);
+                symbols.insert(name, address);
+            }
+        }
+    }
+
+    let mut table = symbols.clone();
+    if let Some(entries) = root.child("CheatEntries") {
+        add_entries(&mut table, &symbols, entries, "");
+    }
+    Ok(table)
+}
@@ -0,0 +1,68 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::ProcT;
+
+///Reason a verified write failed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WriteError {
+    ///Only the first 'written' bytes reached the process
+    Partial { written: usize },
+    ///The written range could not be read back
+    ReadBack,
+    ///The memory read back differs from the written bytes starting at 'offset'
+    Mismatch {
+        offset: usize,
+        expected: Vec<u8>,
+        found: Vec<u8>,
+    },
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriteError::Partial { written } => write!(f, "only {} bytes were written", written),
+            WriteError::ReadBack => write!(f, "written memory could not be read back"),
+            WriteError::Mismatch {
+                offset,
+                expected,
+                found,
+            } => write!(
+                f,
+                "memory differs at offset {:#X}: expected {:02X?}, found {:02X?}",
+                offset, expected, found
+            ),
+        }
+    }
+}
+
+impl Error for WriteError {}
+
+pub(crate) fn write_bytes_checked<P: ProcT + ?Sized>(
+    proc: &P,
+    proc_address: usize,
+    data: &[u8],
+) -> Result<(), WriteError> {
+    let (ok, written) = proc.write_bytes(proc_address, data);
+    if !ok || written < data.len() {
+        return Err(WriteError::Partial { written });
+    }
+
+    let found = proc
+        .read_vec_uninit::<u8>(proc_address, data.len())
+        .ok_or(WriteError::ReadBack)?;
+    match data.iter().zip(&found).position(|(a, b)| a != b) {
+        None => Ok(()),
+        Some(offset) => {
+            //Report the differing run rather than the whole buffer
+            let end = (offset..data.len())
+                .find(|index| data[*index] == found[*index])
+                .unwrap_or(data.len());
+            Err(WriteError::Mismatch {
+                offset,
+                expected: data[offset..end].to_vec(),
+                found: found[offset..end].to_vec(),
+            })
+        }
+    }
+}
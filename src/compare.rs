@@ -0,0 +1,87 @@
+use crate::ProcT;
+
+///Bytes compared per read
+const CHUNK_SIZE: usize = 0x10_0000;
+
+///Run of bytes that differ between two processes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Difference {
+    ///Offset of the first differing byte from the start of the compared ranges
+    pub offset: usize,
+    ///Bytes of the first process
+    pub a: Vec<u8>,
+    ///Bytes of the second process
+    pub b: Vec<u8>,
+}
+
+impl Difference {
+    ///Number of differing bytes
+    pub fn len(&self) -> usize {
+        self.a.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.a.is_empty()
+    }
+}
+
+///Diff the module named 'module_name' between two processes, e.g. a patched and a clean instance.
+///Offsets are relative to the module bases and only the bytes both modules share are compared, absolute pointers
+///differ when the bases do. Returns None if either process doesn't have the module loaded
+pub fn compare<A: ProcT + ?Sized, B: ProcT + ?Sized>(
+    proc_a: &A,
+    proc_b: &B,
+    module_name: &str,
+) -> Option<Vec<Difference>> {
+    let module_a = proc_a.module(module_name)?;
+    let module_b = proc_b.module(module_name)?;
+    let len = module_a.size.min(module_b.size);
+    Some(compare_ranges(
+        proc_a,
+        module_a.base,
+        proc_b,
+        module_b.base,
+        len,
+    ))
+}
+
+///Diff 'len' bytes at 'address_a' in the first process with the ones at 'address_b' in the second.
+///Bytes that can't be read in either process are skipped
+pub fn compare_ranges<A: ProcT + ?Sized, B: ProcT + ?Sized>(
+    proc_a: &A,
+    address_a: usize,
+    proc_b: &B,
+    address_b: usize,
+    len: usize,
+) -> Vec<Difference> {
+    let mut differences: Vec<Difference> = Vec::new();
+    let mut offset = 0;
+    while offset < len {
+        let chunk_len = CHUNK_SIZE.min(len - offset);
+        let chunk_a = proc_a.read_bulk(address_a + offset, chunk_len, 0);
+        let chunk_b = proc_b.read_bulk(address_b + offset, chunk_len, 0);
+
+        for i in 0..chunk_len {
+            let (byte_a, byte_b) = (chunk_a.data[i], chunk_b.data[i]);
+            if byte_a == byte_b
+                || !chunk_a.is_readable(address_a + offset + i)
+                || !chunk_b.is_readable(address_b + offset + i)
+            {
+                continue;
+            }
+            match differences.last_mut() {
+                Some(last) if last.offset + last.len() == offset + i => {
+                    last.a.push(byte_a);
+                    last.b.push(byte_b);
+                }
+                _ => differences.push(Difference {
+                    offset: offset + i,
+                    a: vec![byte_a],
+                    b: vec![byte_b],
+                }),
+            }
+        }
+        offset += chunk_len;
+    }
+    differences
+}
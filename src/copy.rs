@@ -0,0 +1,27 @@
+use crate::ProcT;
+
+///Bytes staged in the current process per transfer
+const CHUNK_SIZE: usize = 0x10000;
+
+///Copy 'len' bytes at 'src_address' in 'src_proc' to 'dst_address' in 'dst_proc', one chunk at a time so the
+///whole range is never held in memory. Stops at the first byte that can't be read or written.
+///Returns 'true' if everything was copied and the number of bytes copied
+pub fn copy_between<S: ProcT + ?Sized, D: ProcT + ?Sized>(
+    src_proc: &S,
+    src_address: usize,
+    dst_proc: &D,
+    dst_address: usize,
+    len: usize,
+) -> (bool, usize) {
+    let mut copied = 0;
+    while copied < len {
+        let chunk_len = CHUNK_SIZE.min(len - copied);
+        let (data, read) = src_proc.read_partial(src_address + copied, chunk_len);
+        let (_, written) = dst_proc.write_bytes(dst_address + copied, &data[..read]);
+        copied += written;
+        if read != chunk_len || written != read {
+            return (false, copied);
+        }
+    }
+    (true, copied)
+}
@@ -0,0 +1,131 @@
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use crate::{Proc, ProcT};
+
+///Cursor over the memory of another process, positions are relative to the address it was created at
+pub struct RemoteCursor<'a, P: ProcT + ?Sized = Proc> {
+    proc: &'a P,
+    address: usize,
+    len: Option<usize>,
+    position: u64,
+}
+
+impl<'a, P: ProcT + ?Sized> RemoteCursor<'a, P> {
+    ///Cursor starting at 'address', without an end
+    pub fn new(proc: &'a P, address: usize) -> Self {
+        RemoteCursor {
+            proc,
+            address,
+            len: None,
+            position: 0,
+        }
+    }
+
+    ///Cursor over the 'len' bytes starting at 'address', reads stop at the end like at the end of a file
+    pub fn with_len(proc: &'a P, address: usize, len: usize) -> Self {
+        RemoteCursor {
+            proc,
+            address,
+            len: Some(len),
+            position: 0,
+        }
+    }
+
+    ///Address of position 0
+    pub fn start_address(&self) -> usize {
+        self.address
+    }
+
+    ///Address the next read or write uses
+    pub fn address(&self) -> usize {
+        self.address.wrapping_add(self.position as usize)
+    }
+
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    ///Number of bytes left before the end, None for unbounded cursors
+    fn remaining(&self) -> Option<usize> {
+        self.len
+            .map(|len| len.saturating_sub(self.position as usize))
+    }
+}
+
+impl<'a, P: ProcT + ?Sized> Read for RemoteCursor<'a, P> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let wanted = match self.remaining() {
+            Some(remaining) => buf.len().min(remaining),
+            None => buf.len(),
+        };
+        if wanted == 0 {
+            return Ok(0);
+        }
+
+        let (bytes, count) = self.proc.read_partial(self.address(), wanted);
+        if count == 0 {
+            return Err(io::Error::other(format!(
+                "address {:#X} is not readable",
+                self.address()
+            )));
+        }
+        buf[..count].copy_from_slice(&bytes[..count]);
+        self.position += count as u64;
+        Ok(count)
+    }
+}
+
+impl<'a, P: ProcT + ?Sized> Write for RemoteCursor<'a, P> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let wanted = match self.remaining() {
+            Some(remaining) => buf.len().min(remaining),
+            None => buf.len(),
+        };
+        if wanted == 0 {
+            return Ok(0);
+        }
+
+        let (_, count) = self.proc.write_bytes(self.address(), &buf[..wanted]);
+        if count == 0 {
+            return Err(io::Error::other(format!(
+                "address {:#X} is not writable",
+                self.address()
+            )));
+        }
+        self.position += count as u64;
+        Ok(count)
+    }
+
+    ///Writes go straight to the process, there is nothing to flush
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a, P: ProcT + ?Sized> Seek for RemoteCursor<'a, P> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(offset) => match self.len {
+                Some(len) => (len as u64).checked_add_signed(offset),
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        "cursor has no end to seek from",
+                    ))
+                }
+            },
+        };
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
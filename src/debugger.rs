@@ -0,0 +1,831 @@
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+use crate::{Module, ProcT};
+
+///Exception raised by a thread of a debugged process, a signal on Linux
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DebugException {
+    pub thread: u32,
+    ///Exception code on Windows, e.g. 0xC0000005 for an access violation, signal number on Linux
+    pub code: u32,
+    ///Instruction pointer of the thread: the faulting instruction for access violations, the following one after
+    ///traps
+    pub address: usize,
+    ///Address whose access faulted, for access violations on Windows and SIGSEGV or SIGBUS on Linux
+    pub fault_address: Option<usize>,
+    ///Exception information on Windows, e.g. the kind of access and its address for an access violation. Empty on
+    ///Linux
+    pub parameters: Vec<usize>,
+}
+
+impl DebugException {
+    ///Returns 'true' for the exceptions raised by single steps and hardware breakpoints. On Linux they are SIGTRAP,
+    ///which int3 breakpoints raise too
+    pub fn is_single_step(&self) -> bool {
+        platform::is_single_step(self.code)
+    }
+}
+
+///Event of a debugged process. The thread it comes from stays stopped until the event is continued
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DebugEvent {
+    ///Thread found when attaching or created since, new threads didn't run yet
+    ThreadCreated {
+        thread: u32,
+    },
+    ThreadExited {
+        thread: u32,
+        exit_code: u32,
+    },
+    ///Module found when attaching or loaded since. Linux doesn't report loads, its module list is polled instead
+    ModuleLoaded(Module),
+    ModuleUnloaded(Module),
+    Exception(DebugException),
+    ///The process exited, no event follows
+    ProcessExited {
+        exit_code: u32,
+    },
+}
+
+impl DebugEvent {
+    ///Thread the event comes from, None for module and process events
+    pub fn thread(&self) -> Option<u32> {
+        match self {
+            DebugEvent::ThreadCreated { thread } | DebugEvent::ThreadExited { thread, .. } => {
+                Some(*thread)
+            }
+            DebugEvent::Exception(exception) => Some(exception.thread),
+            _ => None,
+        }
+    }
+}
+
+///Debugger attached to a process, reporting the creation and exit of its threads, the modules it loads and
+///unloads and the exceptions it raises. Detaches when dropped.
+///On Linux every thread is traced with ptrace, the debugger only works from the thread that attached it
+pub struct Debugger<'a, P: ProcT + ?Sized> {
+    proc: &'a P,
+    ///Modules reported as loaded and not unloaded since
+    modules: Vec<Module>,
+    ///Events found before they were waited for
+    queue: VecDeque<DebugEvent>,
+    platform: platform::State,
+    ///ptrace requests only work from the tracing thread
+    _thread_bound: PhantomData<*const ()>,
+}
+
+impl<'a, P: ProcT + ?Sized> Debugger<'a, P> {
+    ///Attach to 'proc', None if it can't be debugged, e.g. because another debugger is attached. The first events
+    ///report the threads and modules already there
+    pub fn attach(proc: &'a P) -> Option<Debugger<'a, P>> {
+        let mut debugger = Debugger {
+            proc,
+            modules: Vec::new(),
+            queue: VecDeque::new(),
+            platform: platform::State::attach(proc)?,
+            _thread_bound: PhantomData,
+        };
+        debugger.attached();
+        Some(debugger)
+    }
+
+    ///Wait up to 'timeout' for the next event, None if none came or the process is gone. The previous event is
+    ///continued first if it wasn't already, passing its exception on to the process
+    pub fn wait(&mut self, timeout: Duration) -> Option<DebugEvent> {
+        self.resume(false, false);
+        self.next_event(Instant::now() + timeout)
+    }
+
+    ///Let the thread stopped by the last event run again. Its exception, if any, is passed on to the process unless
+    ///'handled'
+    pub fn continue_event(&mut self, handled: bool) {
+        self.resume(handled, false);
+    }
+
+    ///Let the thread stopped by the last event run a single instruction, it then raises an exception for which
+    ///DebugException::is_single_step() is 'true'
+    pub fn step_event(&mut self, handled: bool) {
+        self.resume(handled, true);
+    }
+
+    ///Handle the events of the next 'duration', or until the process exits. 'on_event' returns 'true' for the
+    ///exceptions it handled, the others are passed on to the process
+    pub fn run(&mut self, duration: Duration, mut on_event: impl FnMut(&DebugEvent) -> bool) {
+        let deadline = Instant::now() + duration;
+        while let Some(event) = self.wait(deadline.saturating_duration_since(Instant::now())) {
+            let handled = on_event(&event);
+            self.continue_event(handled);
+        }
+    }
+
+    ///Stop every thread until the debugger detaches. wait() then only returns the events raised before they
+    ///stopped, and None once there are none left. Handling them before detaching keeps the exceptions caused by
+    ///the debugger, like hits of removed breakpoints, from reaching the process
+    pub fn stop(&mut self) {
+        self.stop_threads();
+    }
+
+    ///Ids of the threads known to the debugger
+    pub fn threads(&self) -> Vec<u32> {
+        self.thread_ids()
+    }
+
+    ///Thread stopped by the last event, None once the event is continued
+    pub fn stopped_thread(&self) -> Option<u32> {
+        self.current_thread()
+    }
+
+    pub(crate) fn proc(&self) -> &'a P {
+        self.proc
+    }
+}
+
+impl<P: ProcT + ?Sized> Drop for Debugger<'_, P> {
+    fn drop(&mut self) {
+        self.detach();
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::time::{Duration, Instant};
+
+    use libc::{c_void, pid_t};
+
+    use super::{DebugEvent, DebugException, Debugger};
+    use crate::ProcT;
+
+    const PTRACE_EVENT_CLONE: i32 = 3;
+
+    ///Interval between two looks at the module list, ptrace doesn't report module loads
+    const MODULE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+    pub(super) fn is_single_step(code: u32) -> bool {
+        code == libc::SIGTRAP as u32
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Status {
+        Running,
+        ///Created, its first stop wasn't seen yet
+        New,
+        ///Stopped, with the signal it gets when it continues
+        Stopped(i32),
+    }
+
+    pub(super) struct State {
+        pid: pid_t,
+        threads: Vec<(pid_t, Status)>,
+        ///Thread stopped by the last event
+        current: Option<pid_t>,
+        ///Debugger::stop() was called, the threads stay stopped
+        stopped: bool,
+        modules_checked: Instant,
+    }
+
+    fn wait(tid: pid_t) -> (pid_t, i32) {
+        let mut status = 0;
+        let result = unsafe { libc::waitpid(tid, &mut status, libc::__WALL) };
+        (result, status)
+    }
+
+    fn interrupt(tid: pid_t) -> bool {
+        let null = std::ptr::null_mut::<c_void>();
+        unsafe { libc::ptrace(libc::PTRACE_INTERRUPT, tid, null, null) == 0 }
+    }
+
+    ///Signal a thread got stopped for, zero for the other stops
+    fn stop_signal(status: i32) -> i32 {
+        if status >> 16 == 0 {
+            return libc::WSTOPSIG(status);
+        }
+        0
+    }
+
+    fn exception(tid: pid_t, signal: i32) -> DebugException {
+        let null = std::ptr::null_mut::<c_void>();
+        let address = unsafe {
+            let mut regs: libc::user_regs_struct = std::mem::zeroed();
+            libc::ptrace(
+                libc::PTRACE_GETREGS,
+                tid,
+                null,
+                &mut regs as *mut libc::user_regs_struct,
+            );
+            regs.rip as usize
+        };
+        let fault_address = match signal {
+            libc::SIGSEGV | libc::SIGBUS => unsafe {
+                let mut info: libc::siginfo_t = std::mem::zeroed();
+                let result = libc::ptrace(
+                    libc::PTRACE_GETSIGINFO,
+                    tid,
+                    null,
+                    &mut info as *mut libc::siginfo_t,
+                );
+                if result == 0 {
+                    Some(info.si_addr() as usize)
+                } else {
+                    None
+                }
+            },
+            _ => None,
+        };
+        DebugException {
+            thread: tid as u32,
+            code: signal as u32,
+            address,
+            fault_address,
+            parameters: Vec::new(),
+        }
+    }
+
+    impl State {
+        ///Seize and stop every thread of 'proc', the threads they create are traced too
+        pub(super) fn attach<P: ProcT + ?Sized>(proc: &P) -> Option<State> {
+            let null = std::ptr::null_mut::<c_void>();
+            let options = libc::PTRACE_O_TRACECLONE as usize as *mut c_void;
+            let mut threads = Vec::new();
+            for thread in proc.threads() {
+                let tid = thread.id as pid_t;
+                if unsafe { libc::ptrace(libc::PTRACE_SEIZE, tid, null, options) } != 0 {
+                    continue;
+                }
+                let (result, status) = if interrupt(tid) { wait(tid) } else { (-1, 0) };
+                if result == tid && libc::WIFSTOPPED(status) {
+                    threads.push((tid, Status::Stopped(stop_signal(status))));
+                } else {
+                    unsafe {
+                        libc::ptrace(libc::PTRACE_DETACH, tid, null, null);
+                    }
+                }
+            }
+            if threads.is_empty() {
+                return None;
+            }
+            Some(State {
+                pid: proc.pid() as pid_t,
+                threads,
+                current: None,
+                stopped: false,
+                modules_checked: Instant::now(),
+            })
+        }
+
+        fn status(&mut self, tid: pid_t) -> Option<&mut Status> {
+            self.threads
+                .iter_mut()
+                .find(|(thread, _)| *thread == tid)
+                .map(|(_, status)| status)
+        }
+
+        fn resume_thread(&mut self, tid: pid_t, step: bool) {
+            if let Some(status) = self.status(tid) {
+                if let Status::Stopped(signal) = *status {
+                    let request = if step {
+                        libc::PTRACE_SINGLESTEP
+                    } else {
+                        libc::PTRACE_CONT
+                    };
+                    let null = std::ptr::null_mut::<c_void>();
+                    unsafe {
+                        libc::ptrace(request, tid, null, signal as usize as *mut c_void);
+                    }
+                    *status = Status::Running;
+                }
+            }
+        }
+    }
+
+    impl<P: ProcT + ?Sized> Debugger<'_, P> {
+        pub(super) fn attached(&mut self) {
+            for (tid, _) in &self.platform.threads {
+                self.queue.push_back(DebugEvent::ThreadCreated {
+                    thread: *tid as u32,
+                });
+            }
+            self.update_modules();
+        }
+
+        fn update_modules(&mut self) {
+            self.platform.modules_checked = Instant::now();
+            let modules = self.proc.modules();
+            if modules.is_empty() {
+                return;
+            }
+            for module in &self.modules {
+                if !modules.contains(module) {
+                    self.queue
+                        .push_back(DebugEvent::ModuleUnloaded(module.clone()));
+                }
+            }
+            for module in &modules {
+                if !self.modules.contains(module) {
+                    self.queue
+                        .push_back(DebugEvent::ModuleLoaded(module.clone()));
+                }
+            }
+            self.modules = modules;
+        }
+
+        ///Record what waitpid() returned for 'tid', the event it reports if any. The thread stays stopped
+        fn update(&mut self, tid: pid_t, result: pid_t, status: i32) -> Option<DebugEvent> {
+            let index = self
+                .platform
+                .threads
+                .iter()
+                .position(|(thread, _)| *thread == tid)?;
+            if result != tid || !libc::WIFSTOPPED(status) {
+                self.platform.threads.swap_remove(index);
+                let exit_code = if libc::WIFSIGNALED(status) {
+                    128 + libc::WTERMSIG(status)
+                } else {
+                    libc::WEXITSTATUS(status)
+                } as u32;
+                if tid == self.platform.pid {
+                    self.platform.threads.clear();
+                    return Some(DebugEvent::ProcessExited { exit_code });
+                }
+                return Some(DebugEvent::ThreadExited {
+                    thread: tid as u32,
+                    exit_code,
+                });
+            }
+
+            if self.platform.threads[index].1 == Status::New {
+                self.platform.threads[index].1 = Status::Stopped(0);
+                return Some(DebugEvent::ThreadCreated { thread: tid as u32 });
+            }
+            let signal = stop_signal(status);
+            self.platform.threads[index].1 = Status::Stopped(signal);
+            match status >> 16 {
+                PTRACE_EVENT_CLONE => {
+                    let null = std::ptr::null_mut::<c_void>();
+                    let mut new_tid: libc::c_ulong = 0;
+                    let message = &mut new_tid as *mut libc::c_ulong as *mut c_void;
+                    if unsafe { libc::ptrace(libc::PTRACE_GETEVENTMSG, tid, null, message) } == 0 {
+                        self.platform.threads.push((new_tid as pid_t, Status::New));
+                    }
+                    None
+                }
+                0 => Some(DebugEvent::Exception(exception(tid, signal))),
+                _ => None,
+            }
+        }
+
+        pub(super) fn next_event(&mut self, deadline: Instant) -> Option<DebugEvent> {
+            loop {
+                if let Some(event) = self.queue.pop_front() {
+                    let stopped = event.thread().map(|thread| thread as pid_t).filter(|tid| {
+                        matches!(self.platform.status(*tid), Some(Status::Stopped(_)))
+                    });
+                    self.platform.current = stopped;
+                    return Some(event);
+                }
+                if self.platform.stopped || self.platform.threads.is_empty() {
+                    return None;
+                }
+                if self.platform.modules_checked.elapsed() >= MODULE_POLL_INTERVAL {
+                    self.update_modules();
+                    continue;
+                }
+
+                let mut idle = true;
+                for index in (0..self.platform.threads.len()).rev() {
+                    let (tid, status) = self.platform.threads[index];
+                    if let Status::Stopped(_) = status {
+                        continue;
+                    }
+                    let mut wait_status = 0;
+                    let result = unsafe {
+                        libc::waitpid(tid, &mut wait_status, libc::WNOHANG | libc::__WALL)
+                    };
+                    if result == 0 {
+                        continue;
+                    }
+                    idle = false;
+                    match self.update(tid, result, wait_status) {
+                        Some(event) => {
+                            self.queue.push_back(event);
+                            break;
+                        }
+                        None => self.platform.resume_thread(tid, false),
+                    }
+                }
+                if self.queue.is_empty() && Instant::now() >= deadline {
+                    return None;
+                }
+                if idle {
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+            }
+        }
+
+        pub(super) fn resume(&mut self, handled: bool, step: bool) {
+            let tid = match self.platform.current.take() {
+                Some(tid) => tid,
+                None => return,
+            };
+            if handled {
+                if let Some(status) = self.platform.status(tid) {
+                    *status = Status::Stopped(0);
+                }
+            }
+            if !self.platform.stopped {
+                self.platform.resume_thread(tid, step);
+            } else if step {
+                //Keep the other threads stopped, the step is over before returning
+                self.platform.resume_thread(tid, true);
+                let (result, status) = wait(tid);
+                if let Some(event) = self.update(tid, result, status) {
+                    self.queue.push_back(event);
+                }
+            }
+        }
+
+        pub(super) fn stop_threads(&mut self) {
+            self.platform.stopped = true;
+            let mut index = 0;
+            while index < self.platform.threads.len() {
+                let (tid, status) = self.platform.threads[index];
+                if let Status::Stopped(_) = status {
+                    index += 1;
+                    continue;
+                }
+                let (result, wait_status) = if interrupt(tid) { wait(tid) } else { (-1, 0) };
+                if let Some(event) = self.update(tid, result, wait_status) {
+                    self.queue.push_back(event);
+                }
+                //An exited thread was replaced by the last one
+                if self.platform.threads.get(index).map(|(thread, _)| *thread) == Some(tid) {
+                    index += 1;
+                }
+            }
+        }
+
+        pub(super) fn thread_ids(&self) -> Vec<u32> {
+            self.platform
+                .threads
+                .iter()
+                .map(|(tid, _)| *tid as u32)
+                .collect()
+        }
+
+        pub(super) fn current_thread(&self) -> Option<u32> {
+            self.platform.current.map(|tid| tid as u32)
+        }
+
+        pub(super) fn detach(&mut self) {
+            self.platform.current = None;
+            self.stop_threads();
+            let null = std::ptr::null_mut::<c_void>();
+            for (tid, status) in self.platform.threads.drain(..) {
+                let signal = match status {
+                    Status::Stopped(signal) => signal,
+                    _ => 0,
+                };
+                unsafe {
+                    libc::ptrace(
+                        libc::PTRACE_DETACH,
+                        tid,
+                        null,
+                        signal as usize as *mut c_void,
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::time::Instant;
+
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::System::Diagnostics::Debug::{GetThreadContext, SetThreadContext, CONTEXT};
+    use windows::Win32::System::Threading::{
+        OpenThread, ResumeThread, SuspendThread, THREAD_GET_CONTEXT, THREAD_SET_CONTEXT,
+        THREAD_SUSPEND_RESUME,
+    };
+
+    use super::{DebugEvent, DebugException, Debugger};
+    use crate::{Module, ProcT};
+
+    ///DEBUG_EVENT, the union is read as an array of pointer sized fields
+    #[repr(C)]
+    struct RawDebugEvent {
+        code: u32,
+        process_id: u32,
+        thread_id: u32,
+        info: [usize; 20],
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn DebugActiveProcess(process_id: u32) -> i32;
+        fn DebugActiveProcessStop(process_id: u32) -> i32;
+        fn DebugSetProcessKillOnExit(kill_on_exit: i32) -> i32;
+        fn WaitForDebugEvent(event: *mut RawDebugEvent, milliseconds: u32) -> i32;
+        fn ContinueDebugEvent(process_id: u32, thread_id: u32, status: u32) -> i32;
+        fn GetFinalPathNameByHandleW(file: isize, path: *mut u16, len: u32, flags: u32) -> u32;
+    }
+
+    const EXCEPTION_DEBUG_EVENT: u32 = 1;
+    const CREATE_THREAD_DEBUG_EVENT: u32 = 2;
+    const CREATE_PROCESS_DEBUG_EVENT: u32 = 3;
+    const EXIT_THREAD_DEBUG_EVENT: u32 = 4;
+    const EXIT_PROCESS_DEBUG_EVENT: u32 = 5;
+    const LOAD_DLL_DEBUG_EVENT: u32 = 6;
+    const UNLOAD_DLL_DEBUG_EVENT: u32 = 7;
+
+    const DBG_CONTINUE: u32 = 0x0001_0002;
+    const DBG_EXCEPTION_NOT_HANDLED: u32 = 0x8001_0001;
+
+    const STATUS_BREAKPOINT: u32 = 0x8000_0003;
+    const STATUS_SINGLE_STEP: u32 = 0x8000_0004;
+    const STATUS_ACCESS_VIOLATION: u32 = 0xC000_0005;
+    const STATUS_IN_PAGE_ERROR: u32 = 0xC000_0006;
+    const STATUS_WX86_SINGLE_STEP: u32 = 0x4000_001E;
+    const STATUS_WX86_BREAKPOINT: u32 = 0x4000_001F;
+
+    ///CONTEXT_AMD64 | CONTEXT_CONTROL
+    const CONTEXT_AMD64_CONTROL: u32 = 0x0010_0001;
+    ///Trap flag, single steps the thread
+    const EFLAGS_TF: u32 = 0x100;
+
+    pub(super) fn is_single_step(code: u32) -> bool {
+        code == STATUS_SINGLE_STEP || code == STATUS_WX86_SINGLE_STEP
+    }
+
+    pub(super) struct State {
+        pid: u32,
+        ///The thread DebugActiveProcess() creates in the target breaks once attached
+        attach_breakpoint: bool,
+        alive: bool,
+        threads: Vec<u32>,
+        ///Thread of the event waiting to be continued, and whether the event is an exception
+        current: Option<(u32, bool)>,
+        ///Threads whose trap flag step_event() set, until they step
+        stepping: Vec<u32>,
+        ///Threads suspended by Debugger::stop()
+        suspended: Option<Vec<HANDLE>>,
+    }
+
+    impl State {
+        pub(super) fn attach<P: ProcT + ?Sized>(proc: &P) -> Option<State> {
+            let pid = proc.pid() as u32;
+            unsafe {
+                if DebugActiveProcess(pid) == 0 {
+                    return None;
+                }
+                DebugSetProcessKillOnExit(0);
+            }
+            Some(State {
+                pid,
+                attach_breakpoint: true,
+                alive: true,
+                threads: Vec::new(),
+                current: None,
+                stepping: Vec::new(),
+                suspended: None,
+            })
+        }
+    }
+
+    ///Change the flags of a stopped thread
+    fn update_flags(thread_id: u32, update: impl FnOnce(&mut u32)) -> bool {
+        unsafe {
+            let thread = OpenThread(THREAD_GET_CONTEXT | THREAD_SET_CONTEXT, None, thread_id);
+            if thread == HANDLE(0) {
+                return false;
+            }
+            let mut context: CONTEXT = std::mem::zeroed();
+            context.ContextFlags = CONTEXT_AMD64_CONTROL;
+            let updated = GetThreadContext(thread, &mut context).as_bool() && {
+                update(&mut context.EFlags);
+                SetThreadContext(thread, &context).as_bool()
+            };
+            CloseHandle(thread);
+            updated
+        }
+    }
+
+    ///Module mapped from 'file' at 'base'
+    fn module_at<P: ProcT + ?Sized>(proc: &P, file: usize, base: usize) -> Module {
+        let mut buffer = [0u16; 1024];
+        let mut len = 0;
+        if file != 0 {
+            len = unsafe {
+                GetFinalPathNameByHandleW(
+                    file as isize,
+                    buffer.as_mut_ptr(),
+                    buffer.len() as u32,
+                    0,
+                )
+            } as usize;
+        }
+        //A length beyond the buffer is the size it should have had
+        if len > buffer.len() {
+            len = 0;
+        }
+        let path = String::from_utf16_lossy(&buffer[..len]);
+        let path = path.strip_prefix(r"\\?\").unwrap_or(&path).to_string();
+        let mut module = Module {
+            name: path.rsplit('\\').next().unwrap_or_default().to_string(),
+            path,
+            base,
+            size: 0,
+        };
+        module.size = module
+            .pe_headers(proc)
+            .map_or(0, |headers| headers.image_size);
+        module
+    }
+
+    impl<P: ProcT + ?Sized> Debugger<'_, P> {
+        pub(super) fn attached(&mut self) {}
+
+        fn translate(&mut self, event: &RawDebugEvent) -> Option<DebugEvent> {
+            let close = |handle: usize| unsafe {
+                if handle != 0 {
+                    CloseHandle(HANDLE(handle as isize));
+                }
+            };
+            let thread = event.thread_id;
+            match event.code {
+                CREATE_PROCESS_DEBUG_EVENT => {
+                    let module = module_at(self.proc, event.info[0], event.info[3]);
+                    close(event.info[0]);
+                    self.modules.push(module.clone());
+                    self.queue.push_back(DebugEvent::ModuleLoaded(module));
+                    self.platform.threads.push(thread);
+                    Some(DebugEvent::ThreadCreated { thread })
+                }
+                CREATE_THREAD_DEBUG_EVENT => {
+                    self.platform.threads.push(thread);
+                    Some(DebugEvent::ThreadCreated { thread })
+                }
+                EXIT_THREAD_DEBUG_EVENT => {
+                    self.platform.threads.retain(|known| *known != thread);
+                    self.platform
+                        .stepping
+                        .retain(|stepping| *stepping != thread);
+                    Some(DebugEvent::ThreadExited {
+                        thread,
+                        exit_code: event.info[0] as u32,
+                    })
+                }
+                EXIT_PROCESS_DEBUG_EVENT => {
+                    self.platform.alive = false;
+                    Some(DebugEvent::ProcessExited {
+                        exit_code: event.info[0] as u32,
+                    })
+                }
+                LOAD_DLL_DEBUG_EVENT => {
+                    let module = module_at(self.proc, event.info[0], event.info[1]);
+                    close(event.info[0]);
+                    self.modules.push(module.clone());
+                    Some(DebugEvent::ModuleLoaded(module))
+                }
+                UNLOAD_DLL_DEBUG_EVENT => {
+                    let base = event.info[0];
+                    let module = match self.modules.iter().position(|module| module.base == base) {
+                        Some(index) => self.modules.remove(index),
+                        None => Module {
+                            base,
+                            ..Module::default()
+                        },
+                    };
+                    Some(DebugEvent::ModuleUnloaded(module))
+                }
+                EXCEPTION_DEBUG_EVENT => {
+                    let code = event.info[0] as u32;
+                    let breakpoint = code == STATUS_BREAKPOINT || code == STATUS_WX86_BREAKPOINT;
+                    if breakpoint && self.platform.attach_breakpoint {
+                        self.platform.attach_breakpoint = false;
+                        return None;
+                    }
+                    if is_single_step(code) {
+                        self.platform
+                            .stepping
+                            .retain(|stepping| *stepping != thread);
+                    }
+                    let count = (event.info[3] as u32 as usize).min(15);
+                    let parameters = event.info[4..4 + count].to_vec();
+                    let fault_address = match code {
+                        STATUS_ACCESS_VIOLATION | STATUS_IN_PAGE_ERROR => {
+                            parameters.get(1).copied()
+                        }
+                        _ => None,
+                    };
+                    Some(DebugEvent::Exception(DebugException {
+                        thread,
+                        code,
+                        address: event.info[2],
+                        fault_address,
+                        parameters,
+                    }))
+                }
+                _ => None,
+            }
+        }
+
+        pub(super) fn next_event(&mut self, deadline: Instant) -> Option<DebugEvent> {
+            loop {
+                if let Some(event) = self.queue.pop_front() {
+                    return Some(event);
+                }
+                if !self.platform.alive {
+                    return None;
+                }
+                let timeout = match self.platform.suspended {
+                    Some(_) => 0,
+                    None => deadline
+                        .saturating_duration_since(Instant::now())
+                        .as_millis()
+                        .min(u32::MAX as u128 - 1) as u32,
+                };
+                let mut event: RawDebugEvent = unsafe { std::mem::zeroed() };
+                if unsafe { WaitForDebugEvent(&mut event, timeout) } == 0 {
+                    if timeout == 0 {
+                        return None;
+                    }
+                    continue;
+                }
+                let exception = event.code == EXCEPTION_DEBUG_EVENT;
+                self.platform.current = Some((event.thread_id, exception));
+                match self.translate(&event) {
+                    Some(event) => return Some(event),
+                    None => self.resume(true, false),
+                }
+            }
+        }
+
+        pub(super) fn resume(&mut self, handled: bool, step: bool) {
+            let (thread, exception) = match self.platform.current.take() {
+                Some(current) => current,
+                None => return,
+            };
+            if step && update_flags(thread, |flags| *flags |= EFLAGS_TF) {
+                self.platform.stepping.push(thread);
+            }
+            let status = if exception && !handled {
+                DBG_EXCEPTION_NOT_HANDLED
+            } else {
+                DBG_CONTINUE
+            };
+            unsafe {
+                ContinueDebugEvent(self.platform.pid, thread, status);
+            }
+        }
+
+        pub(super) fn stop_threads(&mut self) {
+            if self.platform.suspended.is_some() {
+                return;
+            }
+            let mut suspended = Vec::new();
+            for thread in &self.platform.threads {
+                unsafe {
+                    let handle = OpenThread(THREAD_SUSPEND_RESUME, None, *thread);
+                    if handle == HANDLE(0) {
+                        continue;
+                    }
+                    if SuspendThread(handle) == u32::MAX {
+                        CloseHandle(handle);
+                        continue;
+                    }
+                    suspended.push(handle);
+                }
+            }
+            self.platform.suspended = Some(suspended);
+        }
+
+        pub(super) fn thread_ids(&self) -> Vec<u32> {
+            self.platform.threads.clone()
+        }
+
+        pub(super) fn current_thread(&self) -> Option<u32> {
+            self.platform.current.map(|(thread, _)| thread)
+        }
+
+        pub(super) fn detach(&mut self) {
+            self.resume(false, false);
+            self.stop_threads();
+            //A trap flag left set would raise a single step exception without a debugger to handle it
+            for thread in std::mem::take(&mut self.platform.stepping) {
+                update_flags(thread, |flags| *flags &= !EFLAGS_TF);
+            }
+            unsafe {
+                if self.platform.alive {
+                    DebugActiveProcessStop(self.platform.pid);
+                }
+                for handle in self.platform.suspended.take().unwrap_or_default() {
+                    ResumeThread(handle);
+                    CloseHandle(handle);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,1328 @@
+use std::convert::TryInto;
+use std::fmt;
+
+use crate::{ProcT, RebasedAddress};
+
+///Longest valid x86 instruction
+pub(crate) const MAX_INSTRUCTION_LEN: usize = 15;
+
+const REGS64: [&str; 16] = [
+    "rax", "rcx", "rdx", "rbx", "rsp", "rbp", "rsi", "rdi", "r8", "r9", "r10", "r11", "r12", "r13",
+    "r14", "r15",
+];
+const REGS32: [&str; 16] = [
+    "eax", "ecx", "edx", "ebx", "esp", "ebp", "esi", "edi", "r8d", "r9d", "r10d", "r11d", "r12d",
+    "r13d", "r14d", "r15d",
+];
+const REGS16: [&str; 16] = [
+    "ax", "cx", "dx", "bx", "sp", "bp", "si", "di", "r8w", "r9w", "r10w", "r11w", "r12w", "r13w",
+    "r14w", "r15w",
+];
+const REGS8_REX: [&str; 16] = [
+    "al", "cl", "dl", "bl", "spl", "bpl", "sil", "dil", "r8b", "r9b", "r10b", "r11b", "r12b",
+    "r13b", "r14b", "r15b",
+];
+const REGS8: [&str; 8] = ["al", "cl", "dl", "bl", "ah", "ch", "dh", "bh"];
+const XMM: [&str; 16] = [
+    "xmm0", "xmm1", "xmm2", "xmm3", "xmm4", "xmm5", "xmm6", "xmm7", "xmm8", "xmm9", "xmm10",
+    "xmm11", "xmm12", "xmm13", "xmm14", "xmm15",
+];
+const YMM: [&str; 16] = [
+    "ymm0", "ymm1", "ymm2", "ymm3", "ymm4", "ymm5", "ymm6", "ymm7", "ymm8", "ymm9", "ymm10",
+    "ymm11", "ymm12", "ymm13", "ymm14", "ymm15",
+];
+const SEGMENTS: [&str; 8] = ["es", "cs", "ss", "ds", "fs", "gs", "?", "?"];
+const CONDITIONS: [&str; 16] = [
+    "o", "no", "b", "ae", "e", "ne", "be", "a", "s", "ns", "p", "np", "l", "ge", "le", "g",
+];
+const ALU: [&str; 8] = ["add", "or", "adc", "sbb", "and", "sub", "xor", "cmp"];
+const SHIFTS: [&str; 8] = ["rol", "ror", "rcl", "rcr", "shl", "shr", "sal", "sar"];
+
+///Instruction decoded from the memory of a process
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    pub address: usize,
+    pub bytes: Vec<u8>,
+    ///Mnemonic with its prefixes, like 'mov' or 'lock cmpxchg'. '(bad)' for bytes that couldn't be decoded
+    pub mnemonic: String,
+    ///Operands in Intel syntax
+    pub operands: Vec<String>,
+    ///Branch target or address of a RIP-relative operand
+    pub target: Option<usize>,
+    ///Module relative address of the instruction, None when no loaded module contains it
+    pub location: Option<RebasedAddress>,
+    ///Module relative 'target'
+    pub target_location: Option<RebasedAddress>,
+}
+
+impl Instruction {
+    ///Size of the instruction in bytes
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    ///Address of the next instruction
+    pub fn end(&self) -> usize {
+        self.address + self.len()
+    }
+
+    ///Mnemonic and operands, like 'mov rax, qword ptr [rcx+0x10]'
+    pub fn text(&self) -> String {
+        if self.operands.is_empty() {
+            return self.mnemonic.clone();
+        }
+        format!("{} {}", self.mnemonic, self.operands.join(", "))
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "{}+{:#X}", location.module, location.rva)?,
+            None => write!(f, "{:#X}", self.address)?,
+        }
+        let bytes: Vec<String> = self
+            .bytes
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect();
+        write!(f, "  {:<30}  {}", bytes.join(" "), self.text())?;
+        if let Some(location) = &self.target_location {
+            write!(f, "  ; {}+{:#X}", location.module, location.rva)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Memory {
+    base: Option<u8>,
+    index: Option<u8>,
+    scale: u8,
+    disp: i64,
+    ///Displacement is relative to the next instruction
+    rip: bool,
+    ///Size of the address registers
+    address_size: u8,
+    segment: Option<u8>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Rm {
+    Reg(u8),
+    Mem(Memory),
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ModRm {
+    ///Middle field without the REX extension, the opcode extension of group instructions
+    digit: u8,
+    ///Middle field as a register number
+    reg: u8,
+    rm: Rm,
+}
+
+#[derive(Clone, Debug)]
+enum Operand {
+    Reg(&'static str),
+    ///Memory of the given size in bytes, 0 for lea and other operands that are only an address
+    Mem(Memory, u8),
+    ///Immediate of the given size in bytes
+    Imm(i64, u8),
+    ///Branch target relative to the next instruction
+    Rel(i64),
+    ///Absolute address of a far pointer or moffs operand
+    Abs(u64),
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    mode64: bool,
+    operand_size_prefix: bool,
+    address_size_prefix: bool,
+    ///Last F2 or F3 prefix
+    rep: Option<u8>,
+    lock: bool,
+    segment: Option<u8>,
+    ///REX prefix, 0 if there is none
+    rex: u8,
+}
+
+impl<'a> Decoder<'a> {
+    fn byte(&mut self) -> Option<u8> {
+        if self.position >= MAX_INSTRUCTION_LEN {
+            return None;
+        }
+        let byte = *self.bytes.get(self.position)?;
+        self.position += 1;
+        Some(byte)
+    }
+
+    ///Sign extended immediate of 'size' bytes
+    fn imm(&mut self, size: u8) -> Option<i64> {
+        let start = self.position;
+        for _ in 0..size {
+            self.byte()?;
+        }
+        let bytes = &self.bytes[start..self.position];
+        Some(match size {
+            1 => bytes[0] as i8 as i64,
+            2 => i16::from_le_bytes(bytes.try_into().ok()?) as i64,
+            4 => i32::from_le_bytes(bytes.try_into().ok()?) as i64,
+            _ => i64::from_le_bytes(bytes.try_into().ok()?),
+        })
+    }
+
+    fn rex_w(&self) -> bool {
+        self.rex & 8 != 0
+    }
+
+    ///Operand size of instructions defaulting to 32 bits
+    fn operand_size(&self) -> u8 {
+        if self.rex_w() {
+            8
+        } else if self.operand_size_prefix {
+            2
+        } else {
+            4
+        }
+    }
+
+    ///Operand size of pushes, pops and near branches, 64 bits in long mode
+    fn stack_size(&self) -> u8 {
+        match (self.mode64, self.operand_size_prefix) {
+            (_, true) => 2,
+            (true, false) => 8,
+            (false, false) => 4,
+        }
+    }
+
+    ///Size of the immediate of 'size' operands, 64 bit operations take a sign extended 32 bit immediate
+    fn imm_size(size: u8) -> u8 {
+        size.min(4)
+    }
+
+    fn address_size(&self) -> u8 {
+        match (self.mode64, self.address_size_prefix) {
+            (true, false) => 8,
+            (true, true) | (false, false) => 4,
+            (false, true) => 2,
+        }
+    }
+
+    fn reg(&self, number: u8, size: u8) -> &'static str {
+        let number = number as usize;
+        match size {
+            1 if self.rex != 0 => REGS8_REX[number],
+            1 => REGS8[number & 7],
+            2 => REGS16[number],
+            4 => REGS32[number],
+            8 => REGS64[number],
+            32 => YMM[number],
+            _ => XMM[number],
+        }
+    }
+
+    fn modrm(&mut self) -> Option<ModRm> {
+        let byte = self.byte()?;
+        let md = byte >> 6;
+        let digit = (byte >> 3) & 7;
+        let reg = digit | ((self.rex & 4) << 1);
+        let rm = byte & 7;
+        if md == 3 {
+            return Some(ModRm {
+                digit,
+                reg,
+                rm: Rm::Reg(rm | ((self.rex & 1) << 3)),
+            });
+        }
+
+        let address_size = self.address_size();
+        if address_size == 2 {
+            //16 bit addressing isn't supported
+            return None;
+        }
+        let mut memory = Memory {
+            base: None,
+            index: None,
+            scale: 1,
+            disp: 0,
+            rip: false,
+            address_size,
+            segment: self.segment,
+        };
+        if rm == 4 {
+            let sib = self.byte()?;
+            let index = ((sib >> 3) & 7) | ((self.rex & 2) << 2);
+            let base = sib & 7;
+            if index != 4 {
+                memory.index = Some(index);
+                memory.scale = 1 << (sib >> 6);
+            }
+            if base == 5 && md == 0 {
+                memory.disp = self.imm(4)?;
+            } else {
+                memory.base = Some(base | ((self.rex & 1) << 3));
+            }
+        } else if rm == 5 && md == 0 {
+            memory.disp = self.imm(4)?;
+            memory.rip = self.mode64;
+        } else {
+            memory.base = Some(rm | ((self.rex & 1) << 3));
+        }
+        match md {
+            1 => memory.disp += self.imm(1)?,
+            2 => memory.disp += self.imm(4)?,
+            _ => {}
+        }
+        Some(ModRm {
+            digit,
+            reg,
+            rm: Rm::Mem(memory),
+        })
+    }
+
+    ///r/m operand of 'size' bytes
+    fn rm(&self, modrm: &ModRm, size: u8) -> Operand {
+        match modrm.rm {
+            Rm::Reg(number) => Operand::Reg(self.reg(number, size)),
+            Rm::Mem(memory) => Operand::Mem(memory, size),
+        }
+    }
+
+    ///Register operand of the middle field
+    fn gr(&self, modrm: &ModRm, size: u8) -> Operand {
+        Operand::Reg(self.reg(modrm.reg, size))
+    }
+
+    ///SSE instruction with its operand forms chosen by the mandatory prefix: none, 66, F3 and F2
+    fn sse(&mut self, names: [&str; 4], store: bool) -> Option<(String, Vec<Operand>)> {
+        let (name, size) = match (self.rep, self.operand_size_prefix) {
+            (Some(0xF3), _) => (names[2], 4),
+            (Some(0xF2), _) => (names[3], 8),
+            (None, true) => (names[1], 16),
+            _ => (names[0], 16),
+        };
+        if name.is_empty() {
+            return None;
+        }
+        let modrm = self.modrm()?;
+        let xmm = self.gr(&modrm, 16);
+        let rm = self.rm(&modrm, size);
+        let operands = if store { vec![rm, xmm] } else { vec![xmm, rm] };
+        Some((name.to_string(), operands))
+    }
+
+    fn one_byte(&mut self, opcode: u8) -> Option<(String, Vec<Operand>)> {
+        let size = self.operand_size();
+        let name = |text: &str| text.to_string();
+        Some(match opcode {
+            0x00..=0x3F if opcode & 7 < 6 => {
+                let mnemonic = name(ALU[(opcode >> 3) as usize]);
+                match opcode & 7 {
+                    0 | 2 => {
+                        let modrm = self.modrm()?;
+                        let (rm, reg) = (self.rm(&modrm, 1), self.gr(&modrm, 1));
+                        (
+                            mnemonic,
+                            if opcode & 2 == 0 {
+                                vec![rm, reg]
+                            } else {
+                                vec![reg, rm]
+                            },
+                        )
+                    }
+                    1 | 3 => {
+                        let modrm = self.modrm()?;
+                        let (rm, reg) = (self.rm(&modrm, size), self.gr(&modrm, size));
+                        (
+                            mnemonic,
+                            if opcode & 2 == 0 {
+                                vec![rm, reg]
+                            } else {
+                                vec![reg, rm]
+                            },
+                        )
+                    }
+                    4 => (
+                        mnemonic,
+                        vec![Operand::Reg("al"), Operand::Imm(self.imm(1)?, 1)],
+                    ),
+                    _ => (
+                        mnemonic,
+                        vec![
+                            Operand::Reg(self.reg(0, size)),
+                            Operand::Imm(self.imm(Self::imm_size(size))?, size),
+                        ],
+                    ),
+                }
+            }
+            0x06 | 0x0E | 0x16 | 0x1E if !self.mode64 => (
+                name("push"),
+                vec![Operand::Reg(SEGMENTS[(opcode >> 3) as usize])],
+            ),
+            0x07 | 0x17 | 0x1F if !self.mode64 => (
+                name("pop"),
+                vec![Operand::Reg(SEGMENTS[(opcode >> 3) as usize])],
+            ),
+            0x27 | 0x2F | 0x37 | 0x3F if !self.mode64 => (
+                name(["daa", "das", "aaa", "aas"][((opcode >> 3) & 3) as usize]),
+                vec![],
+            ),
+            0x40..=0x4F if !self.mode64 => {
+                let mnemonic = if opcode < 0x48 { "inc" } else { "dec" };
+                (
+                    name(mnemonic),
+                    vec![Operand::Reg(self.reg(opcode & 7, size))],
+                )
+            }
+            0x50..=0x5F => {
+                let mnemonic = if opcode < 0x58 { "push" } else { "pop" };
+                let number = (opcode & 7) | ((self.rex & 1) << 3);
+                (
+                    name(mnemonic),
+                    vec![Operand::Reg(self.reg(number, self.stack_size()))],
+                )
+            }
+            0x60 if !self.mode64 => (name("pushad"), vec![]),
+            0x61 if !self.mode64 => (name("popad"), vec![]),
+            0x63 if self.mode64 => {
+                let modrm = self.modrm()?;
+                (
+                    name("movsxd"),
+                    vec![self.gr(&modrm, size), self.rm(&modrm, 4)],
+                )
+            }
+            0x63 => {
+                let modrm = self.modrm()?;
+                (name("arpl"), vec![self.rm(&modrm, 2), self.gr(&modrm, 2)])
+            }
+            0x68 => (
+                name("push"),
+                vec![Operand::Imm(
+                    self.imm(Self::imm_size(self.stack_size()))?,
+                    self.stack_size(),
+                )],
+            ),
+            0x6A => (
+                name("push"),
+                vec![Operand::Imm(self.imm(1)?, self.stack_size())],
+            ),
+            0x69 | 0x6B => {
+                let modrm = self.modrm()?;
+                let imm_size = if opcode == 0x69 {
+                    Self::imm_size(size)
+                } else {
+                    1
+                };
+                (
+                    name("imul"),
+                    vec![
+                        self.gr(&modrm, size),
+                        self.rm(&modrm, size),
+                        Operand::Imm(self.imm(imm_size)?, size),
+                    ],
+                )
+            }
+            0x6C => (name("insb"), vec![]),
+            0x6D => (name(if size == 2 { "insw" } else { "insd" }), vec![]),
+            0x6E => (name("outsb"), vec![]),
+            0x6F => (name(if size == 2 { "outsw" } else { "outsd" }), vec![]),
+            0x70..=0x7F => (
+                format!("j{}", CONDITIONS[(opcode & 15) as usize]),
+                vec![Operand::Rel(self.imm(1)?)],
+            ),
+            0x80..=0x83 => {
+                if opcode == 0x82 && self.mode64 {
+                    return None;
+                }
+                let modrm = self.modrm()?;
+                let operand_size = if opcode & 1 == 0 { 1 } else { size };
+                let imm_size = if opcode == 0x81 {
+                    Self::imm_size(size)
+                } else {
+                    1
+                };
+                (
+                    name(ALU[modrm.digit as usize]),
+                    vec![
+                        self.rm(&modrm, operand_size),
+                        Operand::Imm(self.imm(imm_size)?, operand_size),
+                    ],
+                )
+            }
+            0x84..=0x8B => {
+                let mnemonic = match opcode {
+                    0x84 | 0x85 => "test",
+                    0x86 | 0x87 => "xchg",
+                    _ => "mov",
+                };
+                let modrm = self.modrm()?;
+                let operand_size = if opcode & 1 == 0 { 1 } else { size };
+                let (rm, reg) = (self.rm(&modrm, operand_size), self.gr(&modrm, operand_size));
+                let operands = if opcode >= 0x8A {
+                    vec![reg, rm]
+                } else {
+                    vec![rm, reg]
+                };
+                (name(mnemonic), operands)
+            }
+            0x8C | 0x8E => {
+                let modrm = self.modrm()?;
+                let segment = Operand::Reg(SEGMENTS[modrm.digit as usize]);
+                //Segment registers are moved to and from memory as words, general registers use the operand size
+                let rm_size = match modrm.rm {
+                    Rm::Reg(_) => size,
+                    Rm::Mem(_) => 2,
+                };
+                let rm = self.rm(&modrm, rm_size);
+                (
+                    name("mov"),
+                    if opcode == 0x8C {
+                        vec![rm, segment]
+                    } else {
+                        vec![segment, rm]
+                    },
+                )
+            }
+            0x8D => {
+                let modrm = self.modrm()?;
+                if let Rm::Reg(_) = modrm.rm {
+                    return None;
+                }
+                (name("lea"), vec![self.gr(&modrm, size), self.rm(&modrm, 0)])
+            }
+            0x8F => {
+                let modrm = self.modrm()?;
+                (name("pop"), vec![self.rm(&modrm, self.stack_size())])
+            }
+            0x90 if self.rex & 1 == 0 => (
+                name(if self.rep == Some(0xF3) {
+                    "pause"
+                } else {
+                    "nop"
+                }),
+                vec![],
+            ),
+            0x90..=0x97 => {
+                let number = (opcode & 7) | ((self.rex & 1) << 3);
+                (
+                    name("xchg"),
+                    vec![
+                        Operand::Reg(self.reg(number, size)),
+                        Operand::Reg(self.reg(0, size)),
+                    ],
+                )
+            }
+            0x98 => (name(["cbw", "cwde", "cdqe"][(size / 4) as usize]), vec![]),
+            0x99 => (name(["cwd", "cdq", "cqo"][(size / 4) as usize]), vec![]),
+            0x9A | 0xEA if !self.mode64 => {
+                let offset = self.imm(Self::imm_size(size))? as u32 as u64;
+                let segment = self.imm(2)? as u16 as u64;
+                let mnemonic = if opcode == 0x9A {
+                    "call far"
+                } else {
+                    "jmp far"
+                };
+                (
+                    name(mnemonic),
+                    vec![Operand::Abs(segment), Operand::Abs(offset)],
+                )
+            }
+            0x9B => (name("wait"), vec![]),
+            0x9C => (name(if self.mode64 { "pushfq" } else { "pushfd" }), vec![]),
+            0x9D => (name(if self.mode64 { "popfq" } else { "popfd" }), vec![]),
+            0x9E => (name("sahf"), vec![]),
+            0x9F => (name("lahf"), vec![]),
+            0xA0..=0xA3 => {
+                let address = self.imm(self.address_size())? as u64;
+                let operand_size = if opcode & 1 == 0 { 1 } else { size };
+                let accumulator = Operand::Reg(self.reg(0, operand_size));
+                let memory = Operand::Abs(address);
+                (
+                    name("mov"),
+                    if opcode < 0xA2 {
+                        vec![accumulator, memory]
+                    } else {
+                        vec![memory, accumulator]
+                    },
+                )
+            }
+            0xA4..=0xA7 | 0xAA..=0xAF => {
+                let base =
+                    ["movs", "cmps", "", "stos", "lods", "scas"][((opcode - 0xA4) / 2) as usize];
+                let suffix = if opcode & 1 == 0 {
+                    "b"
+                } else {
+                    ["", "", "w", "", "d", "", "", "", "q"][size as usize]
+                };
+                let prefix = match self.rep {
+                    Some(0xF3) if matches!(opcode, 0xA6 | 0xA7 | 0xAE | 0xAF) => "repe ",
+                    Some(0xF3) => "rep ",
+                    Some(_) => "repne ",
+                    None => "",
+                };
+                self.rep = None;
+                (format!("{}{}{}", prefix, base, suffix), vec![])
+            }
+            0xA8 => (
+                name("test"),
+                vec![Operand::Reg("al"), Operand::Imm(self.imm(1)?, 1)],
+            ),
+            0xA9 => (
+                name("test"),
+                vec![
+                    Operand::Reg(self.reg(0, size)),
+                    Operand::Imm(self.imm(Self::imm_size(size))?, size),
+                ],
+            ),
+            0xB0..=0xB7 => {
+                let number = (opcode & 7) | ((self.rex & 1) << 3);
+                (
+                    name("mov"),
+                    vec![
+                        Operand::Reg(self.reg(number, 1)),
+                        Operand::Imm(self.imm(1)?, 1),
+                    ],
+                )
+            }
+            0xB8..=0xBF => {
+                let number = (opcode & 7) | ((self.rex & 1) << 3);
+                (
+                    name("mov"),
+                    vec![
+                        Operand::Reg(self.reg(number, size)),
+                        Operand::Imm(self.imm(size)?, size),
+                    ],
+                )
+            }
+            0xC0 | 0xC1 | 0xD0..=0xD3 => {
+                let modrm = self.modrm()?;
+                let operand_size = if opcode & 1 == 0 { 1 } else { size };
+                let count = match opcode {
+                    0xC0 | 0xC1 => Operand::Imm(self.imm(1)?, 1),
+                    0xD0 | 0xD1 => Operand::Imm(1, 1),
+                    _ => Operand::Reg("cl"),
+                };
+                (
+                    name(SHIFTS[modrm.digit as usize]),
+                    vec![self.rm(&modrm, operand_size), count],
+                )
+            }
+            0xC2 => (name("ret"), vec![Operand::Imm(self.imm(2)?, 2)]),
+            0xC3 => (name("ret"), vec![]),
+            0xC6 | 0xC7 => {
+                let modrm = self.modrm()?;
+                match (modrm.digit, modrm.rm) {
+                    (0, _) => {}
+                    (7, Rm::Reg(0)) if opcode == 0xC6 => {
+                        return Some((name("xabort"), vec![Operand::Imm(self.imm(1)?, 1)]))
+                    }
+                    (7, Rm::Reg(0)) => {
+                        let offset = self.imm(Self::imm_size(size))?;
+                        return Some((name("xbegin"), vec![Operand::Rel(offset)]));
+                    }
+                    _ => return None,
+                }
+                let operand_size = if opcode == 0xC6 { 1 } else { size };
+                (
+                    name("mov"),
+                    vec![
+                        self.rm(&modrm, operand_size),
+                        Operand::Imm(self.imm(Self::imm_size(operand_size))?, operand_size),
+                    ],
+                )
+            }
+            0xC8 => {
+                let frame = self.imm(2)?;
+                (
+                    name("enter"),
+                    vec![Operand::Imm(frame, 2), Operand::Imm(self.imm(1)?, 1)],
+                )
+            }
+            0xC9 => (name("leave"), vec![]),
+            0xCA => (name("retf"), vec![Operand::Imm(self.imm(2)?, 2)]),
+            0xCB => (name("retf"), vec![]),
+            0xCC => (name("int3"), vec![]),
+            0xCD => (name("int"), vec![Operand::Imm(self.imm(1)?, 1)]),
+            0xCE if !self.mode64 => (name("into"), vec![]),
+            0xCF => (name(if self.rex_w() { "iretq" } else { "iretd" }), vec![]),
+            0xD4 | 0xD5 if !self.mode64 => (
+                name(if opcode == 0xD4 { "aam" } else { "aad" }),
+                vec![Operand::Imm(self.imm(1)?, 1)],
+            ),
+            0xD7 => (name("xlatb"), vec![]),
+            0xD8..=0xDF => {
+                //x87 instructions are only decoded for their length
+                let modrm = self.modrm()?;
+                let operands = match modrm.rm {
+                    Rm::Mem(memory) => vec![Operand::Mem(memory, 0)],
+                    Rm::Reg(_) => vec![],
+                };
+                (format!("(x87 {:02X})", opcode), operands)
+            }
+            0xE0..=0xE3 => {
+                let mnemonic = match opcode {
+                    0xE0 => "loopne",
+                    0xE1 => "loope",
+                    0xE2 => "loop",
+                    _ if self.mode64 => "jrcxz",
+                    _ => "jecxz",
+                };
+                (name(mnemonic), vec![Operand::Rel(self.imm(1)?)])
+            }
+            0xE4 | 0xE5 => {
+                let accumulator = Operand::Reg(self.reg(0, if opcode == 0xE4 { 1 } else { 4 }));
+                (name("in"), vec![accumulator, Operand::Imm(self.imm(1)?, 1)])
+            }
+            0xE6 | 0xE7 => {
+                let accumulator = Operand::Reg(self.reg(0, if opcode == 0xE6 { 1 } else { 4 }));
+                (
+                    name("out"),
+                    vec![Operand::Imm(self.imm(1)?, 1), accumulator],
+                )
+            }
+            0xE8 => (name("call"), vec![Operand::Rel(self.imm(4)?)]),
+            0xE9 => (name("jmp"), vec![Operand::Rel(self.imm(4)?)]),
+            0xEB => (name("jmp"), vec![Operand::Rel(self.imm(1)?)]),
+            0xEC => (name("in"), vec![Operand::Reg("al"), Operand::Reg("dx")]),
+            0xED => (name("in"), vec![Operand::Reg("eax"), Operand::Reg("dx")]),
+            0xEE => (name("out"), vec![Operand::Reg("dx"), Operand::Reg("al")]),
+            0xEF => (name("out"), vec![Operand::Reg("dx"), Operand::Reg("eax")]),
+            0xF1 => (name("int1"), vec![]),
+            0xF4 => (name("hlt"), vec![]),
+            0xF5 => (name("cmc"), vec![]),
+            0xF6 | 0xF7 => {
+                let modrm = self.modrm()?;
+                let operand_size = if opcode == 0xF6 { 1 } else { size };
+                let rm = self.rm(&modrm, operand_size);
+                match modrm.digit {
+                    0 | 1 => (
+                        name("test"),
+                        vec![
+                            rm,
+                            Operand::Imm(self.imm(Self::imm_size(operand_size))?, operand_size),
+                        ],
+                    ),
+                    digit => (
+                        name(["", "", "not", "neg", "mul", "imul", "div", "idiv"][digit as usize]),
+                        vec![rm],
+                    ),
+                }
+            }
+            0xF8 => (name("clc"), vec![]),
+            0xF9 => (name("stc"), vec![]),
+            0xFA => (name("cli"), vec![]),
+            0xFB => (name("sti"), vec![]),
+            0xFC => (name("cld"), vec![]),
+            0xFD => (name("std"), vec![]),
+            0xFE => {
+                let modrm = self.modrm()?;
+                match modrm.digit {
+                    0 => (name("inc"), vec![self.rm(&modrm, 1)]),
+                    1 => (name("dec"), vec![self.rm(&modrm, 1)]),
+                    _ => return None,
+                }
+            }
+            0xFF => {
+                let modrm = self.modrm()?;
+                match modrm.digit {
+                    0 => (name("inc"), vec![self.rm(&modrm, size)]),
+                    1 => (name("dec"), vec![self.rm(&modrm, size)]),
+                    2 => (name("call"), vec![self.rm(&modrm, self.stack_size())]),
+                    3 | 5 if matches!(modrm.rm, Rm::Reg(_)) => return None,
+                    3 => (name("call far"), vec![self.rm(&modrm, 0)]),
+                    4 => (name("jmp"), vec![self.rm(&modrm, self.stack_size())]),
+                    5 => (name("jmp far"), vec![self.rm(&modrm, 0)]),
+                    6 => (name("push"), vec![self.rm(&modrm, self.stack_size())]),
+                    _ => return None,
+                }
+            }
+            0x0F => return self.two_byte(),
+            _ => return None,
+        })
+    }
+
+    fn two_byte(&mut self) -> Option<(String, Vec<Operand>)> {
+        let opcode = self.byte()?;
+        let size = self.operand_size();
+        let name = |text: &str| text.to_string();
+        Some(match opcode {
+            0x05 => (name("syscall"), vec![]),
+            0x0B => (name("ud2"), vec![]),
+            0x31 => (name("rdtsc"), vec![]),
+            0xA2 => (name("cpuid"), vec![]),
+            0x01 => {
+                let modrm = self.modrm()?;
+                let mnemonic = match modrm.rm {
+                    Rm::Mem(memory) => {
+                        let names = ["sgdt", "sidt", "lgdt", "lidt", "smsw", "", "lmsw", "invlpg"];
+                        let mnemonic = names[modrm.digit as usize];
+                        if mnemonic.is_empty() {
+                            return None;
+                        }
+                        return Some((name(mnemonic), vec![Operand::Mem(memory, 0)]));
+                    }
+                    Rm::Reg(number) => match (modrm.digit, number & 7) {
+                        (2, 0) => "xgetbv",
+                        (2, 1) => "xsetbv",
+                        (2, 5) => "xend",
+                        (2, 6) => "xtest",
+                        (5, 6) => "rdpkru",
+                        (5, 7) => "wrpkru",
+                        (7, 0) => "swapgs",
+                        (7, 1) => "rdtscp",
+                        _ => {
+                            return Some((
+                                format!("(0F 01 {:02X})", self.bytes[self.position - 1]),
+                                vec![],
+                            ))
+                        }
+                    },
+                };
+                (name(mnemonic), vec![])
+            }
+            0x0D | 0x18..=0x1F => {
+                let modrm = self.modrm()?;
+                (name("nop"), vec![self.rm(&modrm, size)])
+            }
+            0x10 | 0x11 => self.sse(["movups", "movupd", "movss", "movsd"], opcode == 0x11)?,
+            0x28 | 0x29 => self.sse(["movaps", "movapd", "", ""], opcode == 0x29)?,
+            0x2A => {
+                let mnemonic = match self.rep {
+                    Some(0xF3) => "cvtsi2ss",
+                    Some(0xF2) => "cvtsi2sd",
+                    _ => return None,
+                };
+                let modrm = self.modrm()?;
+                (
+                    name(mnemonic),
+                    vec![
+                        self.gr(&modrm, 16),
+                        self.rm(&modrm, if self.rex_w() { 8 } else { 4 }),
+                    ],
+                )
+            }
+            0x2C | 0x2D => {
+                let truncate = if opcode == 0x2C { "t" } else { "" };
+                let (suffix, operand_size) = match self.rep {
+                    Some(0xF3) => ("ss2si", 4),
+                    Some(0xF2) => ("sd2si", 8),
+                    _ => return None,
+                };
+                let modrm = self.modrm()?;
+                let register_size = if self.rex_w() { 8 } else { 4 };
+                (
+                    format!("cvt{}{}", truncate, suffix),
+                    vec![
+                        self.gr(&modrm, register_size),
+                        self.rm(&modrm, operand_size),
+                    ],
+                )
+            }
+            0x2E => self.sse(["ucomiss", "ucomisd", "", ""], false)?,
+            0x2F => self.sse(["comiss", "comisd", "", ""], false)?,
+            0x40..=0x4F => {
+                let modrm = self.modrm()?;
+                (
+                    format!("cmov{}", CONDITIONS[(opcode & 15) as usize]),
+                    vec![self.gr(&modrm, size), self.rm(&modrm, size)],
+                )
+            }
+            0x51 => self.sse(["sqrtps", "sqrtpd", "sqrtss", "sqrtsd"], false)?,
+            0x54 => self.sse(["andps", "andpd", "", ""], false)?,
+            0x55 => self.sse(["andnps", "andnpd", "", ""], false)?,
+            0x56 => self.sse(["orps", "orpd", "", ""], false)?,
+            0x57 => self.sse(["xorps", "xorpd", "", ""], false)?,
+            0x58 => self.sse(["addps", "addpd", "addss", "addsd"], false)?,
+            0x59 => self.sse(["mulps", "mulpd", "mulss", "mulsd"], false)?,
+            0x5A => self.sse(["cvtps2pd", "cvtpd2ps", "cvtss2sd", "cvtsd2ss"], false)?,
+            0x5C => self.sse(["subps", "subpd", "subss", "subsd"], false)?,
+            0x5D => self.sse(["minps", "minpd", "minss", "minsd"], false)?,
+            0x5E => self.sse(["divps", "divpd", "divss", "divsd"], false)?,
+            0x5F => self.sse(["maxps", "maxpd", "maxss", "maxsd"], false)?,
+            0x6E | 0x7E if self.operand_size_prefix && self.rep.is_none() => {
+                let modrm = self.modrm()?;
+                let operand_size = if self.rex_w() { 8 } else { 4 };
+                let mnemonic = if self.rex_w() { "movq" } else { "movd" };
+                let (xmm, rm) = (self.gr(&modrm, 16), self.rm(&modrm, operand_size));
+                (
+                    name(mnemonic),
+                    if opcode == 0x6E {
+                        vec![xmm, rm]
+                    } else {
+                        vec![rm, xmm]
+                    },
+                )
+            }
+            0x7E if self.rep == Some(0xF3) => {
+                let modrm = self.modrm()?;
+                (name("movq"), vec![self.gr(&modrm, 16), self.rm(&modrm, 8)])
+            }
+            0xD6 if self.operand_size_prefix => {
+                let modrm = self.modrm()?;
+                (name("movq"), vec![self.rm(&modrm, 8), self.gr(&modrm, 16)])
+            }
+            0x6F | 0x7F => {
+                let mnemonic = match (self.rep, self.operand_size_prefix) {
+                    (Some(0xF3), _) => "movdqu",
+                    (None, true) => "movdqa",
+                    _ => return None,
+                };
+                self.rep = None;
+                let modrm = self.modrm()?;
+                let (xmm, rm) = (self.gr(&modrm, 16), self.rm(&modrm, 16));
+                (
+                    name(mnemonic),
+                    if opcode == 0x6F {
+                        vec![xmm, rm]
+                    } else {
+                        vec![rm, xmm]
+                    },
+                )
+            }
+            0xEF if self.operand_size_prefix => {
+                let modrm = self.modrm()?;
+                (name("pxor"), vec![self.gr(&modrm, 16), self.rm(&modrm, 16)])
+            }
+            0x80..=0x8F => (
+                format!("j{}", CONDITIONS[(opcode & 15) as usize]),
+                vec![Operand::Rel(self.imm(4)?)],
+            ),
+            0x90..=0x9F => {
+                let modrm = self.modrm()?;
+                (
+                    format!("set{}", CONDITIONS[(opcode & 15) as usize]),
+                    vec![self.rm(&modrm, 1)],
+                )
+            }
+            0xA3 | 0xAB | 0xB3 | 0xBB => {
+                let modrm = self.modrm()?;
+                let mnemonic = ["bt", "bts", "btr", "btc"][((opcode >> 3) & 3) as usize];
+                (
+                    name(mnemonic),
+                    vec![self.rm(&modrm, size), self.gr(&modrm, size)],
+                )
+            }
+            0xA4 | 0xA5 | 0xAC | 0xAD => {
+                let modrm = self.modrm()?;
+                let mnemonic = if opcode < 0xA8 { "shld" } else { "shrd" };
+                let count = if opcode & 1 == 0 {
+                    Operand::Imm(self.imm(1)?, 1)
+                } else {
+                    Operand::Reg("cl")
+                };
+                (
+                    name(mnemonic),
+                    vec![self.rm(&modrm, size), self.gr(&modrm, size), count],
+                )
+            }
+            0xAF => {
+                let modrm = self.modrm()?;
+                (
+                    name("imul"),
+                    vec![self.gr(&modrm, size), self.rm(&modrm, size)],
+                )
+            }
+            0xB0 | 0xB1 | 0xC0 | 0xC1 => {
+                let modrm = self.modrm()?;
+                let operand_size = if opcode & 1 == 0 { 1 } else { size };
+                let mnemonic = if opcode < 0xC0 { "cmpxchg" } else { "xadd" };
+                (
+                    name(mnemonic),
+                    vec![self.rm(&modrm, operand_size), self.gr(&modrm, operand_size)],
+                )
+            }
+            0xB6 | 0xB7 | 0xBE | 0xBF => {
+                let modrm = self.modrm()?;
+                let mnemonic = if opcode < 0xB8 { "movzx" } else { "movsx" };
+                let source_size = if opcode & 1 == 0 { 1 } else { 2 };
+                (
+                    name(mnemonic),
+                    vec![self.gr(&modrm, size), self.rm(&modrm, source_size)],
+                )
+            }
+            0xB8 if self.rep == Some(0xF3) => {
+                let modrm = self.modrm()?;
+                (
+                    name("popcnt"),
+                    vec![self.gr(&modrm, size), self.rm(&modrm, size)],
+                )
+            }
+            0xBA => {
+                let modrm = self.modrm()?;
+                if modrm.digit < 4 {
+                    return None;
+                }
+                let mnemonic = ["bt", "bts", "btr", "btc"][(modrm.digit - 4) as usize];
+                (
+                    name(mnemonic),
+                    vec![self.rm(&modrm, size), Operand::Imm(self.imm(1)?, 1)],
+                )
+            }
+            0xBC | 0xBD => {
+                let modrm = self.modrm()?;
+                let mnemonic = match (opcode, self.rep) {
+                    (0xBC, Some(0xF3)) => "tzcnt",
+                    (0xBD, Some(0xF3)) => "lzcnt",
+                    (0xBC, _) => "bsf",
+                    _ => "bsr",
+                };
+                (
+                    name(mnemonic),
+                    vec![self.gr(&modrm, size), self.rm(&modrm, size)],
+                )
+            }
+            0xC8..=0xCF => {
+                let number = (opcode & 7) | ((self.rex & 1) << 3);
+                (name("bswap"), vec![Operand::Reg(self.reg(number, size))])
+            }
+            _ => {
+                //Unknown instructions are only decoded for their length
+                let has_modrm =
+                    !matches!(opcode, 0x00..=0x0F | 0x30..=0x37 | 0x77 | 0xA0..=0xA2 | 0xA8..=0xAA);
+                let mut operands = Vec::new();
+                match opcode {
+                    0x38 => {
+                        let opcode = self.byte()?;
+                        self.modrm()?;
+                        return Some((format!("(0F 38 {:02X})", opcode), operands));
+                    }
+                    0x3A => {
+                        let opcode = self.byte()?;
+                        self.modrm()?;
+                        self.imm(1)?;
+                        return Some((format!("(0F 3A {:02X})", opcode), operands));
+                    }
+                    _ if has_modrm => {
+                        let modrm = self.modrm()?;
+                        if let Rm::Mem(memory) = modrm.rm {
+                            operands.push(Operand::Mem(memory, 0));
+                        }
+                        if matches!(opcode, 0x70..=0x73 | 0xC2 | 0xC4..=0xC6) {
+                            self.imm(1)?;
+                        }
+                    }
+                    0x00..=0x0F => return None,
+                    _ => {}
+                }
+                (format!("(0F {:02X})", opcode), operands)
+            }
+        })
+    }
+
+    ///AVX instruction with a VEX (C4, C5) or EVEX (62) prefix. Only common VEX instructions are named, the others
+    ///are decoded for their length
+    fn vex(&mut self, prefix: u8) -> Option<(String, Vec<Operand>)> {
+        //R, X, B and vvvv are stored inverted
+        let (map, payload, vector_length) = match prefix {
+            0xC5 => {
+                let byte = self.byte()?;
+                self.rex = 0x40 | ((!byte >> 5) & 4);
+                (1, byte, (byte >> 2) & 1)
+            }
+            0xC4 => {
+                let (first, second) = (self.byte()?, self.byte()?);
+                self.rex = 0x40 | ((!first >> 5) & 7) | ((second >> 4) & 8);
+                (first & 0x1F, second, (second >> 2) & 1)
+            }
+            _ => {
+                let (first, second, third) = (self.byte()?, self.byte()?, self.byte()?);
+                self.rex = 0x40 | ((!first >> 5) & 7) | ((second >> 4) & 8);
+                (first & 7, second, (third >> 5) & 3)
+            }
+        };
+        if map == 0 || map > 3 && prefix != 0x62 {
+            return None;
+        }
+        let pp = payload & 3;
+        let vvvv = (!payload >> 3) & if self.mode64 { 0xF } else { 7 };
+        let opcode = self.byte()?;
+        let size = 16 << vector_length;
+
+        let named = if prefix == 0xC5 || prefix == 0xC4 && map == 1 {
+            match (opcode, pp) {
+                (0x77, 0) => Some(if vector_length == 0 {
+                    "vzeroupper"
+                } else {
+                    "vzeroall"
+                }),
+                (0x10 | 0x11, _) => Some(["vmovups", "vmovupd", "vmovss", "vmovsd"][pp as usize]),
+                (0x28 | 0x29, 0) => Some("vmovaps"),
+                (0x28 | 0x29, 1) => Some("vmovapd"),
+                (0x6F | 0x7F, 1) => Some("vmovdqa"),
+                (0x6F | 0x7F, 2) => Some("vmovdqu"),
+                (0x57, 0) => Some("vxorps"),
+                (0x57, 1) => Some("vxorpd"),
+                (0x74, 1) => Some("vpcmpeqb"),
+                (0x75, 1) => Some("vpcmpeqw"),
+                (0x76, 1) => Some("vpcmpeqd"),
+                (0xD7, 1) => Some("vpmovmskb"),
+                (0xDA, 1) => Some("vpminub"),
+                (0xDB, 1) => Some("vpand"),
+                (0xDF, 1) => Some("vpandn"),
+                (0xEB, 1) => Some("vpor"),
+                (0xEF, 1) => Some("vpxor"),
+                (0xF8, 1) => Some("vpsubb"),
+                (0xFC, 1) => Some("vpaddb"),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        if let Some(mnemonic) = named {
+            if opcode == 0x77 {
+                return Some((mnemonic.to_string(), vec![]));
+            }
+            let modrm = self.modrm()?;
+            let reg = self.gr(&modrm, size);
+            let operands = match opcode {
+                0x10 | 0x11 if pp >= 2 => {
+                    let scalar = if pp == 2 { 4 } else { 8 };
+                    match modrm.rm {
+                        Rm::Reg(_) => {
+                            let (reg, rm) = (self.gr(&modrm, 16), self.rm(&modrm, 16));
+                            let source = Operand::Reg(self.reg(vvvv, 16));
+                            if opcode == 0x10 {
+                                vec![reg, source, rm]
+                            } else {
+                                vec![rm, source, reg]
+                            }
+                        }
+                        Rm::Mem(_) if opcode == 0x10 => {
+                            vec![self.gr(&modrm, 16), self.rm(&modrm, scalar)]
+                        }
+                        Rm::Mem(_) => vec![self.rm(&modrm, scalar), self.gr(&modrm, 16)],
+                    }
+                }
+                0x10 | 0x28 | 0x6F => vec![reg, self.rm(&modrm, size)],
+                0x11 | 0x29 | 0x7F => vec![self.rm(&modrm, size), reg],
+                0xD7 => vec![self.gr(&modrm, 4), self.rm(&modrm, size)],
+                _ => vec![
+                    reg,
+                    Operand::Reg(self.reg(vvvv, size)),
+                    self.rm(&modrm, size),
+                ],
+            };
+            return Some((mnemonic.to_string(), operands));
+        }
+
+        let mut operands = Vec::new();
+        if prefix == 0x62 || map != 1 || opcode != 0x77 {
+            if let Rm::Mem(memory) = self.modrm()?.rm {
+                operands.push(Operand::Mem(memory, 0));
+            }
+        }
+        if map == 3 || map == 1 && matches!(opcode, 0x70..=0x73 | 0xC2 | 0xC4..=0xC6) {
+            self.imm(1)?;
+        }
+        let kind = if prefix == 0x62 { "evex" } else { "vex" };
+        let map = match map {
+            1 => "0F",
+            2 => "0F 38",
+            3 => "0F 3A",
+            5 => "MAP5",
+            6 => "MAP6",
+            _ => return None,
+        };
+        Some((format!("({} {} {:02X})", kind, map, opcode), operands))
+    }
+}
+
+fn hex(value: u64) -> String {
+    format!("{:#x}", value)
+}
+
+fn format_operand(operand: &Operand, end: usize, target: &mut Option<usize>) -> String {
+    match operand {
+        Operand::Reg(name) => name.to_string(),
+        Operand::Imm(value, size) => {
+            let mask = match size {
+                1 => 0xFF,
+                2 => 0xFFFF,
+                4 => 0xFFFF_FFFF,
+                _ => u64::MAX,
+            };
+            hex(*value as u64 & mask)
+        }
+        Operand::Rel(offset) => {
+            let address = end.wrapping_add(*offset as usize);
+            *target = Some(address);
+            hex(address as u64)
+        }
+        Operand::Abs(address) => hex(*address),
+        Operand::Mem(memory, size) => {
+            let prefix = match size {
+                0 => "",
+                1 => "byte ptr ",
+                2 => "word ptr ",
+                4 => "dword ptr ",
+                8 => "qword ptr ",
+                16 => "xmmword ptr ",
+                32 => "ymmword ptr ",
+                _ => "zmmword ptr ",
+            };
+            let segment = memory
+                .segment
+                .map(|segment| format!("{}:", SEGMENTS[segment as usize]))
+                .unwrap_or_default();
+            let registers = if memory.address_size == 8 {
+                REGS64
+            } else {
+                REGS32
+            };
+            let mut inner = String::new();
+            if memory.rip {
+                let address = end.wrapping_add(memory.disp as usize);
+                *target = Some(address);
+                inner += &hex(address as u64);
+            } else {
+                if let Some(base) = memory.base {
+                    inner += registers[base as usize];
+                }
+                if let Some(index) = memory.index {
+                    if !inner.is_empty() {
+                        inner += "+";
+                    }
+                    inner += registers[index as usize];
+                    if memory.scale > 1 {
+                        inner += &format!("*{}", memory.scale);
+                    }
+                }
+                if inner.is_empty() {
+                    inner += &hex(memory.disp as u32 as u64);
+                } else if memory.disp < 0 {
+                    inner += &format!("-{}", hex(memory.disp.unsigned_abs()));
+                } else if memory.disp > 0 {
+                    inner += &format!("+{}", hex(memory.disp as u64));
+                }
+            }
+            format!("{}{}[{}]", prefix, segment, inner)
+        }
+    }
+}
+
+///Decode the instruction at the start of 'bytes', located at 'address' in a 64 bit process if 'is_64bit' is set.
+///Bytes that don't form a known instruction decode as a one byte '(bad)' instruction. Returns None if 'bytes' ends
+///before the instruction does. Module locations are left empty
+///
+///```
+///let instruction = proc_memory::decode(&[0x48, 0x8B, 0x05, 0x10, 0, 0, 0], 0x1000, true).unwrap();
+///assert_eq!(instruction.text(), "mov rax, qword ptr [0x1017]");
+///assert_eq!(instruction.target, Some(0x1017));
+///```
+pub fn decode(bytes: &[u8], address: usize, is_64bit: bool) -> Option<Instruction> {
+    let mut decoder = Decoder {
+        bytes,
+        position: 0,
+        mode64: is_64bit,
+        operand_size_prefix: false,
+        address_size_prefix: false,
+        rep: None,
+        lock: false,
+        segment: None,
+        rex: 0,
+    };
+
+    let mut opcode = decoder.byte()?;
+    loop {
+        match opcode {
+            0x66 => decoder.operand_size_prefix = true,
+            0x67 => decoder.address_size_prefix = true,
+            0xF0 => decoder.lock = true,
+            0xF2 | 0xF3 => decoder.rep = Some(opcode),
+            0x26 | 0x2E | 0x36 | 0x3E => decoder.segment = Some((opcode >> 3) & 3),
+            0x64 | 0x65 => decoder.segment = Some(opcode - 0x60),
+            _ => break,
+        }
+        opcode = decoder.byte()?;
+    }
+    if is_64bit && (0x40..=0x4F).contains(&opcode) {
+        decoder.rex = opcode;
+        opcode = decoder.byte()?;
+    }
+
+    let bad = || Instruction {
+        address,
+        bytes: bytes[..1].to_vec(),
+        mnemonic: "(bad)".to_string(),
+        operands: Vec::new(),
+        target: None,
+        location: None,
+        target_location: None,
+    };
+    //C4, C5 and 62 are VEX and EVEX prefixes in long mode, and outside of it when followed by a register ModRM
+    let is_vex = matches!(opcode, 0xC4 | 0xC5 | 0x62)
+        && decoder.rex == 0
+        && (is_64bit
+            || bytes
+                .get(decoder.position)
+                .is_some_and(|next| *next >= 0xC0));
+    let decoded = if is_vex {
+        decoder.vex(opcode)
+    } else {
+        decoder.one_byte(opcode)
+    };
+    let (mnemonic, operands) = match decoded {
+        Some(decoded) => decoded,
+        None if bytes.len() >= MAX_INSTRUCTION_LEN || decoder.position < bytes.len() => {
+            return Some(bad())
+        }
+        None => return None,
+    };
+
+    let end = address.wrapping_add(decoder.position);
+    let mut target = None;
+    let operands = operands
+        .iter()
+        .map(|operand| format_operand(operand, end, &mut target))
+        .collect();
+    let mut prefixes = String::new();
+    if decoder.lock {
+        prefixes += "lock ";
+    }
+    Some(Instruction {
+        address,
+        bytes: bytes[..decoder.position].to_vec(),
+        mnemonic: prefixes + &mnemonic,
+        operands,
+        target,
+        location: None,
+        target_location: None,
+    })
+}
+
+///Decode 'count' instructions at 'address' in 'proc', stopping early at unreadable memory
+pub(crate) fn disassemble<P: ProcT + ?Sized>(
+    proc: &P,
+    address: usize,
+    count: usize,
+) -> Vec<Instruction> {
+    let (data, _) = proc.read_partial(address, count.saturating_mul(MAX_INSTRUCTION_LEN));
+    let modules = proc.modules();
+    let locate = |address: usize| {
+        modules
+            .iter()
+            .find(|module| module.contains(address))
+            .map(|module| RebasedAddress::new(&module.name, address - module.base))
+    };
+
+    let mut instructions = Vec::new();
+    let mut offset = 0;
+    while instructions.len() < count {
+        let mut instruction = match decode(&data[offset..], address + offset, proc.is_64bit()) {
+            Some(instruction) => instruction,
+            None => break,
+        };
+        instruction.location = locate(instruction.address);
+        instruction.target_location = instruction.target.and_then(locate);
+        offset += instruction.len();
+        instructions.push(instruction);
+    }
+    instructions
+}
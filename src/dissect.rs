@@ -0,0 +1,211 @@
+use std::convert::TryInto;
+use std::fmt;
+
+use crate::{ProcT, RebasedAddress};
+
+///Minimum characters for a run of UTF-16 units to be taken as text
+const MIN_TEXT_CHARS: usize = 4;
+
+///Guessed type of a field
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldKind {
+    ///Pointer into a loaded module, like a vtable or a global
+    ModulePointer(RebasedAddress),
+    ///Pointer into other mapped memory, usually the heap
+    HeapPointer(usize),
+    Double(f64),
+    Float(f32),
+    ///32 bit integer of small magnitude, like a counter or an enum
+    SmallInt(i32),
+    ///UTF-16 text stored inline, only Latin characters are recognized
+    Utf16Text(String),
+    Zero,
+    ///Bytes that don't look like anything in particular
+    Unknown(Vec<u8>),
+}
+
+impl fmt::Display for FieldKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldKind::ModulePointer(location) => {
+                write!(f, "ptr    {}+{:#X}", location.module, location.rva)
+            }
+            FieldKind::HeapPointer(address) => write!(f, "ptr    {:#X}", address),
+            FieldKind::Double(value) => write!(f, "f64    {}", value),
+            FieldKind::Float(value) => write!(f, "f32    {}", value),
+            FieldKind::SmallInt(value) => write!(f, "i32    {}", value),
+            FieldKind::Utf16Text(text) => write!(f, "wchar  {:?}", text),
+            FieldKind::Zero => write!(f, "zero"),
+            FieldKind::Unknown(bytes) => {
+                write!(f, "?     ")?;
+                for byte in bytes {
+                    write!(f, " {:02X}", byte)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+///Field of a dissected structure
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+    ///Offset from the start of the structure
+    pub offset: usize,
+    pub size: usize,
+    pub kind: FieldKind,
+}
+
+///Annotated layout of an unknown structure, a starting point for reversing it. The fields are guesses: every
+///pointer sized slot is classified on its own, a slot of two 32 bit values is split in two fields
+///
+///```
+///use proc_memory::{Dissection, FieldKind, MockProc, ProcT};
+///
+///let proc = MockProc::new();
+///proc.map(0x1000, vec![0u8; 0x20]);
+///proc.write(0x1000, &0x1010u64);
+///proc.write(0x1008, &1.5f32);
+///proc.write(0x100C, &42i32);
+///let dissection = Dissection::read(&proc, 0x1000, 0x10).unwrap();
+///let kinds: Vec<&FieldKind> = dissection.fields.iter().map(|field| &field.kind).collect();
+///assert_eq!(kinds, [&FieldKind::HeapPointer(0x1010), &FieldKind::Float(1.5), &FieldKind::SmallInt(42)]);
+///```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dissection {
+    pub address: usize,
+    pub fields: Vec<Field>,
+}
+
+impl Dissection {
+    ///Read 'len' bytes at 'address' and guess the type of their fields, None if they can't be read
+    pub fn read<P: ProcT + ?Sized>(proc: &P, address: usize, len: usize) -> Option<Dissection> {
+        let data = proc.read_vec_uninit::<u8>(address, len)?;
+        let pointer_size = proc.pointer_size();
+        let modules = proc.modules();
+        let mut regions = proc.regions();
+        regions.sort_by_key(|region| region.base);
+        let classify_pointer = |value: usize| {
+            if let Some(module) = modules.iter().find(|module| module.contains(value)) {
+                return Some(FieldKind::ModulePointer(RebasedAddress::new(
+                    &module.name,
+                    value - module.base,
+                )));
+            }
+            let index = regions.partition_point(|region| region.base <= value);
+            if index > 0 && regions[index - 1].contains(value) {
+                return Some(FieldKind::HeapPointer(value));
+            }
+            None
+        };
+
+        let mut fields = Vec::new();
+        let mut offset = 0;
+        while offset < data.len() {
+            let slot = &data[offset..data.len().min(offset + pointer_size)];
+            if let Some(size) = text_len(&data[offset..]) {
+                let units: Vec<u16> = data[offset..offset + size]
+                    .chunks_exact(2)
+                    .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                    .take_while(|unit| *unit != 0)
+                    .collect();
+                fields.push(Field {
+                    offset,
+                    size,
+                    kind: FieldKind::Utf16Text(String::from_utf16_lossy(&units)),
+                });
+                offset += size;
+                continue;
+            }
+
+            if slot.len() == pointer_size {
+                let value = match pointer_size {
+                    4 => u32::from_le_bytes(slot.try_into().unwrap()) as usize,
+                    _ => u64::from_le_bytes(slot.try_into().unwrap()) as usize,
+                };
+                let kind = if value == 0 {
+                    Some(FieldKind::Zero)
+                } else {
+                    classify_pointer(value)
+                };
+                if let Some(kind) = kind {
+                    fields.push(Field {
+                        offset,
+                        size: pointer_size,
+                        kind,
+                    });
+                    offset += pointer_size;
+                    continue;
+                }
+            }
+            if slot.len() == 8 {
+                let value = f64::from_le_bytes(slot.try_into().unwrap());
+                if is_plausible(value, 1e-6, 1e9) {
+                    fields.push(Field {
+                        offset,
+                        size: 8,
+                        kind: FieldKind::Double(value),
+                    });
+                    offset += 8;
+                    continue;
+                }
+            }
+
+            for half in slot.chunks(4) {
+                fields.push(Field {
+                    offset,
+                    size: half.len(),
+                    kind: classify_u32(half),
+                });
+                offset += half.len();
+            }
+        }
+        Some(Dissection { address, fields })
+    }
+}
+
+impl fmt::Display for Dissection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for field in &self.fields {
+            writeln!(f, "+{:#06X}  {}", field.offset, field.kind)?;
+        }
+        Ok(())
+    }
+}
+
+///Returns 'true' for normal numbers whose magnitude lies between 'min' and 'max'
+fn is_plausible(value: f64, min: f64, max: f64) -> bool {
+    value.is_normal() && (min..=max).contains(&value.abs())
+}
+
+fn classify_u32(bytes: &[u8]) -> FieldKind {
+    if bytes.len() != 4 {
+        return FieldKind::Unknown(bytes.to_vec());
+    }
+    let value = i32::from_le_bytes(bytes.try_into().unwrap());
+    let float = f32::from_le_bytes(bytes.try_into().unwrap());
+    if value == 0 {
+        FieldKind::Zero
+    } else if value.unsigned_abs() < 0x10_0000 {
+        FieldKind::SmallInt(value)
+    } else if is_plausible(float as f64, 1e-4, 1e7) {
+        FieldKind::Float(float)
+    } else {
+        FieldKind::Unknown(bytes.to_vec())
+    }
+}
+
+///Size in bytes, terminator and padding to 4 bytes included, of the UTF-16 text 'data' starts with. None if it
+///doesn't start with at least MIN_TEXT_CHARS printable characters
+fn text_len(data: &[u8]) -> Option<usize> {
+    let chars = data
+        .chunks_exact(2)
+        .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+        .take_while(|unit| (0x20..0x7F).contains(unit) || (0xA0..0x180).contains(unit))
+        .count();
+    if chars < MIN_TEXT_CHARS {
+        return None;
+    }
+    let terminated = (chars + 1) * 2;
+    Some(terminated.next_multiple_of(4).min(data.len()))
+}
@@ -0,0 +1,78 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::ops::Range;
+use std::path::Path;
+
+use crate::{ProcT, Progress};
+
+///Bytes read from the process per write to the file
+const CHUNK_SIZE: usize = 0x10_0000;
+
+///Stream 'len' bytes at 'proc_address' into the file at 'path', unreadable pages are written as zeros.
+///Returns the address ranges that could not be read
+pub(crate) fn dump_range<P: ProcT + ?Sized>(
+    proc: &P,
+    proc_address: usize,
+    len: usize,
+    path: &Path,
+    progress: Option<&dyn Fn(&Progress)>,
+) -> io::Result<Vec<Range<usize>>> {
+    let mut file = BufWriter::new(File::create(path)?);
+    let gaps = dump_to(proc, proc_address, len, &mut file, progress)?;
+    file.flush()?;
+    Ok(gaps)
+}
+
+///dump_range() compressing the file as an LZ4 frame
+#[cfg(feature = "lz4")]
+pub(crate) fn dump_range_lz4<P: ProcT + ?Sized>(
+    proc: &P,
+    proc_address: usize,
+    len: usize,
+    path: &Path,
+) -> io::Result<Vec<Range<usize>>> {
+    let mut file = crate::Lz4Writer::new(BufWriter::new(File::create(path)?))?;
+    let gaps = dump_to(proc, proc_address, len, &mut file, None)?;
+    file.finish()?;
+    Ok(gaps)
+}
+
+fn dump_to<P: ProcT + ?Sized>(
+    proc: &P,
+    proc_address: usize,
+    len: usize,
+    file: &mut impl Write,
+    progress: Option<&dyn Fn(&Progress)>,
+) -> io::Result<Vec<Range<usize>>> {
+    let mut gaps: Vec<Range<usize>> = Vec::new();
+    let mut state = Progress {
+        bytes_total: len,
+        regions_total: 1,
+        ..Progress::default()
+    };
+
+    let end = proc_address + len;
+    let mut current = proc_address;
+    while current < end {
+        let chunk_len = CHUNK_SIZE.min(end - current);
+        let chunk = proc.read_bulk(current, chunk_len, 0);
+        file.write_all(&chunk.data)?;
+
+        for gap in chunk.gaps {
+            match gaps.last_mut() {
+                Some(last) if last.end == gap.start => last.end = gap.end,
+                _ => gaps.push(gap),
+            }
+        }
+
+        current += chunk_len;
+        state.bytes_done += chunk_len;
+        if current == end {
+            state.regions_done = 1;
+        }
+        if let Some(progress) = progress {
+            progress(&state);
+        }
+    }
+    Ok(gaps)
+}
@@ -0,0 +1,509 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::mem::MaybeUninit;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::elf::{self, ElfHeader};
+use crate::pe::{u16_at, u32_at, u64_at};
+use crate::{invalid_data, uninit, Module, Pod, ProcT, Protection, Region, Thread, ThreadState};
+
+const MINIDUMP_SIGNATURE: &[u8; 4] = b"MDMP";
+const THREAD_LIST_STREAM: u32 = 3;
+const MODULE_LIST_STREAM: u32 = 4;
+const MEMORY_LIST_STREAM: u32 = 5;
+const SYSTEM_INFO_STREAM: u32 = 7;
+const MEMORY_64_LIST_STREAM: u32 = 9;
+const MISC_INFO_STREAM: u32 = 15;
+const MEMORY_INFO_LIST_STREAM: u32 = 16;
+const MEM_COMMIT: u32 = 0x1000;
+
+const ET_CORE: u16 = 4;
+const NT_PRSTATUS: u32 = 1;
+const NT_FILE: u32 = 0x46494C45;
+
+///Memory range of the dumped process stored in the file
+#[derive(Clone, Debug)]
+struct Segment {
+    address: usize,
+    size: usize,
+    file_offset: u64,
+    ///Bytes of the range present in the file, the rest was not captured
+    file_size: usize,
+    protection: Protection,
+}
+
+impl Segment {
+    fn contains(&self, address: usize) -> bool {
+        address >= self.address && address - self.address < self.file_size
+    }
+}
+
+///A minidump or ELF core file opened as a read-only process.
+///Reads, scans and pointer chains work like on a live process, writes and anything that needs one fail
+#[derive(Debug)]
+pub struct DumpFile {
+    file: Mutex<File>,
+    ///Sorted by address
+    segments: Vec<Segment>,
+    modules: Vec<Module>,
+    threads: Vec<Thread>,
+    pid: isize,
+    parent_pid: Option<isize>,
+    is_64bit: bool,
+}
+
+///Read 'len' bytes at 'offset', checking the length against the file first so corrupted sizes can't allocate
+fn read_at(file: &mut File, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+    let file_len = file.metadata()?.len();
+    if offset.saturating_add(len as u64) > file_len {
+        return Err(invalid_data("dump is truncated"));
+    }
+    let mut bytes = vec![0u8; len];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn file_name(path: &str) -> String {
+    path.rsplit(['/', '\\']).next().unwrap_or(path).to_string()
+}
+
+impl DumpFile {
+    ///Open the minidump or ELF core file at 'path', the format is detected from its signature
+    pub fn open(path: impl AsRef<Path>) -> io::Result<DumpFile> {
+        let mut file = File::open(path)?;
+        let mut signature = [0u8; 4];
+        file.read_exact(&mut signature)?;
+        match &signature {
+            MINIDUMP_SIGNATURE => DumpFile::open_minidump(file),
+            b"\x7FELF" => DumpFile::open_core(file),
+            _ => Err(invalid_data("not a minidump or ELF core file")),
+        }
+    }
+
+    fn open_minidump(mut file: File) -> io::Result<DumpFile> {
+        let header = read_at(&mut file, 0, 32)?;
+        let stream_count = u32_at(&header, 8).unwrap_or(0) as usize;
+        let directory_rva = u32_at(&header, 12).unwrap_or(0) as u64;
+        let directory = read_at(&mut file, directory_rva, stream_count * 12)?;
+
+        let mut segments = Vec::new();
+        let mut modules = Vec::new();
+        let mut thread_ids = Vec::new();
+        let mut memory_info: Vec<(usize, usize, Protection)> = Vec::new();
+        let mut pid = 0;
+        let mut is_64bit = cfg!(target_pointer_width = "64");
+
+        for entry in directory.chunks_exact(12) {
+            let kind = u32_at(entry, 0).unwrap_or(0);
+            let size = u32_at(entry, 4).unwrap_or(0) as usize;
+            let rva = u32_at(entry, 8).unwrap_or(0) as u64;
+            let truncated = || invalid_data("truncated minidump stream");
+            match kind {
+                THREAD_LIST_STREAM => {
+                    let stream = read_at(&mut file, rva, size)?;
+                    let count = u32_at(&stream, 0).ok_or_else(truncated)? as usize;
+                    for index in 0..count {
+                        thread_ids.push(u32_at(&stream, 4 + index * 48).ok_or_else(truncated)?);
+                    }
+                }
+                MODULE_LIST_STREAM => {
+                    let stream = read_at(&mut file, rva, size)?;
+                    let count = u32_at(&stream, 0).ok_or_else(truncated)? as usize;
+                    for index in 0..count {
+                        let entry = 4 + index * 108;
+                        let base = u64_at(&stream, entry).ok_or_else(truncated)? as usize;
+                        let size = u32_at(&stream, entry + 8).ok_or_else(truncated)? as usize;
+                        let name_rva = u32_at(&stream, entry + 20).ok_or_else(truncated)? as u64;
+
+                        //MINIDUMP_STRING: length in bytes followed by UTF-16 characters
+                        let name_len = u32_at(&read_at(&mut file, name_rva, 4)?, 0).unwrap_or(0);
+                        let name = read_at(&mut file, name_rva + 4, name_len as usize)?;
+                        let wide: Vec<u16> = name
+                            .chunks_exact(2)
+                            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                            .collect();
+                        let path = String::from_utf16_lossy(&wide);
+                        modules.push(Module {
+                            name: file_name(&path),
+                            path,
+                            base,
+                            size,
+                        });
+                    }
+                }
+                MEMORY_LIST_STREAM => {
+                    let stream = read_at(&mut file, rva, size)?;
+                    let count = u32_at(&stream, 0).ok_or_else(truncated)? as usize;
+                    for index in 0..count {
+                        let descriptor = 4 + index * 16;
+                        let data_size = u32_at(&stream, descriptor + 8).ok_or_else(truncated)?;
+                        segments.push(Segment {
+                            address: u64_at(&stream, descriptor).ok_or_else(truncated)? as usize,
+                            size: data_size as usize,
+                            file_offset: u32_at(&stream, descriptor + 12).ok_or_else(truncated)?
+                                as u64,
+                            file_size: data_size as usize,
+                            protection: Protection::READ,
+                        });
+                    }
+                }
+                MEMORY_64_LIST_STREAM => {
+                    //Ranges are stored back to back starting at BaseRva
+                    let stream = read_at(&mut file, rva, size)?;
+                    let count = u64_at(&stream, 0).ok_or_else(truncated)? as usize;
+                    let mut file_offset = u64_at(&stream, 8).ok_or_else(truncated)?;
+                    for index in 0..count {
+                        let descriptor = 16 + index * 16;
+                        let data_size = u64_at(&stream, descriptor + 8).ok_or_else(truncated)?;
+                        segments.push(Segment {
+                            address: u64_at(&stream, descriptor).ok_or_else(truncated)? as usize,
+                            size: data_size as usize,
+                            file_offset,
+                            file_size: data_size as usize,
+                            protection: Protection::READ,
+                        });
+                        file_offset += data_size;
+                    }
+                }
+                SYSTEM_INFO_STREAM => {
+                    let stream = read_at(&mut file, rva, size)?;
+                    //PROCESSOR_ARCHITECTURE_AMD64 and PROCESSOR_ARCHITECTURE_ARM64
+                    is_64bit = matches!(u16_at(&stream, 0), Some(9) | Some(12));
+                }
+                MISC_INFO_STREAM => {
+                    let stream = read_at(&mut file, rva, size)?;
+                    //MINIDUMP_MISC1_PROCESS_ID
+                    if u32_at(&stream, 4).unwrap_or(0) & 1 != 0 {
+                        pid = u32_at(&stream, 8).unwrap_or(0) as isize;
+                    }
+                }
+                MEMORY_INFO_LIST_STREAM => {
+                    let stream = read_at(&mut file, rva, size)?;
+                    let header_size = u32_at(&stream, 0).ok_or_else(truncated)? as usize;
+                    let entry_size = u32_at(&stream, 4).ok_or_else(truncated)? as usize;
+                    let count = u64_at(&stream, 8).ok_or_else(truncated)? as usize;
+                    for index in 0..count {
+                        let entry = header_size + index * entry_size;
+                        if u32_at(&stream, entry + 32) != Some(MEM_COMMIT) {
+                            continue;
+                        }
+                        memory_info.push((
+                            u64_at(&stream, entry).ok_or_else(truncated)? as usize,
+                            u64_at(&stream, entry + 24).ok_or_else(truncated)? as usize,
+                            Protection::from_windows(u32_at(&stream, entry + 36).unwrap_or(0)),
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for segment in &mut segments {
+            if let Some((_, _, protection)) = memory_info
+                .iter()
+                .find(|(base, size, _)| segment.address >= *base && segment.address - base < *size)
+            {
+                segment.protection = *protection;
+            }
+        }
+
+        let threads = thread_ids
+            .into_iter()
+            .map(|id| Thread {
+                id,
+                process_id: pid as u32,
+                start_address: None,
+                state: ThreadState::Stopped,
+            })
+            .collect();
+        Ok(DumpFile::new(
+            file, segments, modules, threads, pid, None, is_64bit,
+        ))
+    }
+
+    fn open_core(mut file: File) -> io::Result<DumpFile> {
+        let ident = read_at(&mut file, 0, 64)?;
+        let headers_len =
+            ElfHeader::headers_len(&ident).ok_or_else(|| invalid_data("invalid ELF header"))?;
+        let header = ElfHeader::parse(&read_at(&mut file, 0, headers_len)?)
+            .ok_or_else(|| invalid_data("invalid ELF header"))?;
+        if header.kind != ET_CORE {
+            return Err(invalid_data("ELF file is not a core dump"));
+        }
+        let is_64bit = header.is_64bit;
+        let word_size = if is_64bit { 8 } else { 4 };
+
+        let mut segments = Vec::new();
+        let mut modules: Vec<Module> = Vec::new();
+        let mut thread_ids = Vec::new();
+        let mut parent_pid = None;
+        for segment in &header.segments {
+            match segment.kind {
+                elf::PT_LOAD => segments.push(Segment {
+                    address: segment.address,
+                    size: segment.mem_size,
+                    file_offset: segment.offset as u64,
+                    file_size: segment.file_size.min(segment.mem_size),
+                    protection: Protection::new(
+                        segment.flags & elf::PF_R != 0,
+                        segment.flags & elf::PF_W != 0,
+                        segment.flags & elf::PF_X != 0,
+                    ),
+                }),
+                elf::PT_NOTE => {
+                    let notes = read_at(&mut file, segment.offset as u64, segment.file_size)?;
+                    let mut offset = 0;
+                    while let (Some(name_size), Some(desc_size), Some(kind)) = (
+                        u32_at(&notes, offset),
+                        u32_at(&notes, offset + 4),
+                        u32_at(&notes, offset + 8),
+                    ) {
+                        let desc_start = offset + 12 + (name_size as usize).next_multiple_of(4);
+                        let desc = notes
+                            .get(desc_start..desc_start + desc_size as usize)
+                            .ok_or_else(|| invalid_data("truncated core file note"))?;
+                        offset = desc_start + (desc_size as usize).next_multiple_of(4);
+
+                        match kind {
+                            NT_PRSTATUS => {
+                                //pr_pid holds the thread id, followed by pr_ppid
+                                let pid_offset = if is_64bit { 32 } else { 24 };
+                                if let Some(tid) = u32_at(desc, pid_offset) {
+                                    thread_ids.push(tid);
+                                }
+                                if parent_pid.is_none() {
+                                    parent_pid =
+                                        u32_at(desc, pid_offset + 4).map(|ppid| ppid as isize);
+                                }
+                            }
+                            NT_FILE => {
+                                let count = elf::word_at(desc, 0, is_64bit).unwrap_or(0);
+                                let mut names = desc
+                                    .get(word_size * (2 + 3 * count)..)
+                                    .unwrap_or(&[])
+                                    .split(|c| *c == 0);
+                                for index in 0..count {
+                                    let entry = word_size * (2 + 3 * index);
+                                    let (Some(start), Some(end), Some(path)) = (
+                                        elf::word_at(desc, entry, is_64bit),
+                                        elf::word_at(desc, entry + word_size, is_64bit),
+                                        names.next(),
+                                    ) else {
+                                        break;
+                                    };
+                                    let path = String::from_utf8_lossy(path).into_owned();
+                                    if let Some(module) =
+                                        modules.iter_mut().find(|module| module.path == path)
+                                    {
+                                        let module_end = (module.base + module.size).max(end);
+                                        module.base = module.base.min(start);
+                                        module.size = module_end - module.base;
+                                    } else {
+                                        modules.push(Module {
+                                            name: file_name(&path),
+                                            path,
+                                            base: start,
+                                            size: end - start,
+                                        });
+                                    }
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        //The first NT_PRSTATUS note belongs to the main (or crashing) thread
+        let pid = thread_ids.first().copied().unwrap_or(0) as isize;
+        let threads = thread_ids
+            .into_iter()
+            .map(|id| Thread {
+                id,
+                process_id: pid as u32,
+                start_address: None,
+                state: ThreadState::Stopped,
+            })
+            .collect();
+        Ok(DumpFile::new(
+            file, segments, modules, threads, pid, parent_pid, is_64bit,
+        ))
+    }
+
+    fn new(
+        file: File,
+        mut segments: Vec<Segment>,
+        mut modules: Vec<Module>,
+        threads: Vec<Thread>,
+        pid: isize,
+        parent_pid: Option<isize>,
+        is_64bit: bool,
+    ) -> DumpFile {
+        segments.sort_by_key(|segment| segment.address);
+        modules.retain(|module| module.size > 0);
+        DumpFile {
+            file: Mutex::new(file),
+            segments,
+            modules,
+            threads,
+            pid,
+            parent_pid,
+            is_64bit,
+        }
+    }
+
+    fn segment(&self, address: usize) -> Option<&Segment> {
+        let index = self
+            .segments
+            .partition_point(|segment| segment.address <= address);
+        self.segments[..index]
+            .iter()
+            .rev()
+            .find(|segment| segment.contains(address))
+    }
+
+    ///Read exactly 'buffer.len()' bytes of dumped memory
+    fn read_into(&self, proc_address: usize, buffer: &mut [MaybeUninit<u8>]) -> bool {
+        let (bytes, count) = self.read_partial(proc_address, buffer.len());
+        if count != buffer.len() {
+            return false;
+        }
+        uninit::copy_to(buffer, &bytes);
+        true
+    }
+}
+
+impl ProcT for DumpFile {
+    ///Dumps are not running processes, open them with DumpFile::open() instead
+    fn get(_proc_name: &str) -> Option<Self> {
+        None
+    }
+
+    fn read<T: Pod>(&self, proc_address: usize) -> Option<T> {
+        uninit::read_value(|buffer| self.read_into(proc_address, buffer))
+    }
+
+    fn read_valid<T: Pod>(&self, proc_address: usize, validator: impl Fn(&T) -> bool) -> Option<T> {
+        self.read::<T>(proc_address)
+            .filter(|value| validator(value))
+    }
+
+    fn read_vec<T: Pod>(
+        &self,
+        proc_address: usize,
+        len: usize,
+        _default_provider: impl Fn() -> T,
+    ) -> Option<Vec<T>> {
+        uninit::read_values(len, |buffer| self.read_into(proc_address, buffer))
+    }
+
+    fn read_vec_uninit<T: Pod>(&self, proc_address: usize, len: usize) -> Option<Vec<T>> {
+        uninit::read_values(len, |buffer| self.read_into(proc_address, buffer))
+    }
+
+    fn read_partial(&self, proc_address: usize, len: usize) -> (Vec<u8>, usize) {
+        let mut data = vec![0u8; len];
+        let mut done = 0;
+        if let Ok(mut file) = self.file.lock() {
+            while done < len {
+                let address = match proc_address.checked_add(done) {
+                    Some(address) => address,
+                    None => break,
+                };
+                let segment = match self.segment(address) {
+                    Some(segment) => segment,
+                    None => break,
+                };
+                let offset = address - segment.address;
+                let count = (segment.file_size - offset).min(len - done);
+                let read = file
+                    .seek(SeekFrom::Start(segment.file_offset + offset as u64))
+                    .and_then(|_| file.read_exact(&mut data[done..done + count]));
+                if read.is_err() {
+                    break;
+                }
+                done += count;
+            }
+        }
+        data.truncate(done);
+        (data, done)
+    }
+
+    fn write<T>(&self, _proc_address: usize, _data: &T) -> (bool, usize) {
+        (false, 0)
+    }
+
+    fn write_bytes(&self, _proc_address: usize, _data: &[u8]) -> (bool, usize) {
+        (false, 0)
+    }
+
+    fn modules(&self) -> Vec<Module> {
+        self.modules.clone()
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        self.segments
+            .iter()
+            .map(|segment| Region {
+                base: segment.address,
+                size: segment.size,
+                protection: segment.protection,
+            })
+            .collect()
+    }
+
+    fn threads(&self) -> Vec<Thread> {
+        self.threads.clone()
+    }
+
+    fn is_alive(&self) -> bool {
+        false
+    }
+
+    fn protect(&self, _address: usize, _len: usize, _protection: Protection) -> Option<Protection> {
+        None
+    }
+
+    fn alloc(&self, _len: usize, _protection: Protection) -> Option<usize> {
+        None
+    }
+
+    fn free(&self, _address: usize, _len: usize) -> bool {
+        false
+    }
+
+    fn create_thread(&self, _start: usize, _param: usize) -> Option<u32> {
+        None
+    }
+
+    fn create_thread_and_wait(&self, _start: usize, _param: usize) -> Option<u32> {
+        None
+    }
+
+    ///Name of the first module, which is the executable
+    fn name(&self) -> Option<String> {
+        self.modules.first().map(|module| module.name.clone())
+    }
+
+    fn exe_path(&self) -> Option<PathBuf> {
+        self.modules
+            .first()
+            .map(|module| PathBuf::from(&module.path))
+    }
+
+    fn parent_pid(&self) -> Option<isize> {
+        self.parent_pid
+    }
+
+    fn is_64bit(&self) -> bool {
+        self.is_64bit
+    }
+
+    fn pid(&self) -> isize {
+        self.pid
+    }
+}
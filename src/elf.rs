@@ -0,0 +1,277 @@
+use std::collections::HashSet;
+use std::ops::Range;
+
+use crate::pe::{u16_at, u32_at, u64_at};
+use crate::{Module, ProcT};
+
+///Symbol defined by an ELF module
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Symbol {
+    ///Symbol name
+    pub name: String,
+    ///Absolute address of the symbol in the process
+    pub address: usize,
+    ///Size in bytes of the symbol, zero if unknown
+    pub size: usize,
+    ///'true' for functions, 'false' for data and other symbol types
+    pub is_function: bool,
+}
+
+const SHT_SYMTAB: u32 = 2;
+const SHT_DYNSYM: u32 = 11;
+pub(crate) const PT_LOAD: u32 = 1;
+pub(crate) const PF_X: u32 = 1;
+pub(crate) const PF_W: u32 = 2;
+pub(crate) const PF_R: u32 = 4;
+pub(crate) const PT_NOTE: u32 = 4;
+
+struct SectionHeader {
+    kind: u32,
+    offset: usize,
+    size: usize,
+    link: usize,
+}
+
+pub(crate) fn word_at(bytes: &[u8], offset: usize, is_64bit: bool) -> Option<usize> {
+    if is_64bit {
+        u64_at(bytes, offset).map(|value| value as usize)
+    } else {
+        u32_at(bytes, offset).map(|value| value as usize)
+    }
+}
+
+///Reads the module file, through the process root first so modules of containerized processes are found
+fn read_module_file<P: ProcT + ?Sized>(proc: &P, module: &Module) -> Option<Vec<u8>> {
+    std::fs::read(format!("/proc/{}/root{}", proc.pid(), module.path))
+        .or_else(|_| std::fs::read(&module.path))
+        .ok()
+}
+
+pub(crate) struct ProgramHeader {
+    pub(crate) kind: u32,
+    pub(crate) flags: u32,
+    pub(crate) offset: usize,
+    pub(crate) address: usize,
+    pub(crate) file_size: usize,
+    pub(crate) mem_size: usize,
+}
+
+pub(crate) struct ElfHeader {
+    pub(crate) is_64bit: bool,
+    ///ET_* file type, 4 for core files
+    pub(crate) kind: u16,
+    pub(crate) segments: Vec<ProgramHeader>,
+    sh_offset: usize,
+    sh_size: usize,
+    sh_count: usize,
+}
+
+impl ElfHeader {
+    ///Bytes from the start of the file needed by parse() to read the program headers
+    pub(crate) fn headers_len(file: &[u8]) -> Option<usize> {
+        let (ph_offset, ph_size, ph_count) = match file.get(4)? {
+            1 => (
+                word_at(file, 0x1C, false)?,
+                u16_at(file, 0x2A)?,
+                u16_at(file, 0x2C)?,
+            ),
+            2 => (
+                word_at(file, 0x20, true)?,
+                u16_at(file, 0x36)?,
+                u16_at(file, 0x38)?,
+            ),
+            _ => return None,
+        };
+        ph_offset.checked_add(ph_size as usize * ph_count as usize)
+    }
+
+    pub(crate) fn parse(file: &[u8]) -> Option<ElfHeader> {
+        if file.get(..4)? != b"\x7FELF" || *file.get(5)? != 1 {
+            //Not an ELF file or not little endian
+            return None;
+        }
+        let is_64bit = match file.get(4)? {
+            1 => false,
+            2 => true,
+            _ => return None,
+        };
+
+        let (ph_offset, sh_offset, ph_size, ph_count, sh_size, sh_count) = if is_64bit {
+            (
+                word_at(file, 0x20, true)?,
+                word_at(file, 0x28, true)?,
+                u16_at(file, 0x36)? as usize,
+                u16_at(file, 0x38)? as usize,
+                u16_at(file, 0x3A)? as usize,
+                u16_at(file, 0x3C)? as usize,
+            )
+        } else {
+            (
+                word_at(file, 0x1C, false)?,
+                word_at(file, 0x20, false)?,
+                u16_at(file, 0x2A)? as usize,
+                u16_at(file, 0x2C)? as usize,
+                u16_at(file, 0x2E)? as usize,
+                u16_at(file, 0x30)? as usize,
+            )
+        };
+
+        let segments = (0..ph_count)
+            .map(|index| {
+                //Offsets come from the file, a corrupted one must not overflow
+                let header = file.get(index.checked_mul(ph_size)?.checked_add(ph_offset)?..)?;
+                Some(if is_64bit {
+                    ProgramHeader {
+                        kind: u32_at(header, 0)?,
+                        flags: u32_at(header, 4)?,
+                        offset: word_at(header, 8, true)?,
+                        address: word_at(header, 16, true)?,
+                        file_size: word_at(header, 32, true)?,
+                        mem_size: word_at(header, 40, true)?,
+                    }
+                } else {
+                    ProgramHeader {
+                        kind: u32_at(header, 0)?,
+                        flags: u32_at(header, 24)?,
+                        offset: word_at(header, 4, false)?,
+                        address: word_at(header, 8, false)?,
+                        file_size: word_at(header, 16, false)?,
+                        mem_size: word_at(header, 20, false)?,
+                    }
+                })
+            })
+            .collect::<Option<Vec<ProgramHeader>>>()?;
+
+        Some(ElfHeader {
+            is_64bit,
+            kind: u16_at(file, 0x10)?,
+            segments,
+            sh_offset,
+            sh_size,
+            sh_count,
+        })
+    }
+
+    ///Difference between the load address and the addresses linked in the file
+    fn load_bias(&self, module: &Module) -> Option<usize> {
+        let lowest_address = self
+            .segments
+            .iter()
+            .filter(|segment| segment.kind == PT_LOAD)
+            .map(|segment| segment.address)
+            .min()?;
+        Some(
+            module
+                .base
+                .wrapping_sub(lowest_address & !(crate::PAGE_SIZE - 1)),
+        )
+    }
+}
+
+///Address ranges of the loaded segments that are mapped executable
+pub(crate) fn executable_ranges<P: ProcT + ?Sized>(
+    proc: &P,
+    module: &Module,
+) -> Option<Vec<Range<usize>>> {
+    let file = read_module_file(proc, module)?;
+    let header = ElfHeader::parse(&file)?;
+    let load_bias = header.load_bias(module)?;
+    Some(
+        header
+            .segments
+            .iter()
+            .filter(|segment| segment.kind == PT_LOAD && segment.flags & PF_X != 0)
+            .map(|segment| {
+                let start = load_bias.wrapping_add(segment.address);
+                start..start + segment.mem_size
+            })
+            .collect(),
+    )
+}
+
+pub(crate) fn read_symbols<P: ProcT + ?Sized>(proc: &P, module: &Module) -> Option<Vec<Symbol>> {
+    let file = read_module_file(proc, module)?;
+    let header = ElfHeader::parse(&file)?;
+    let load_bias = header.load_bias(module)?;
+    let is_64bit = header.is_64bit;
+
+    let sections = (0..header.sh_count)
+        .map(|index| {
+            let start = index
+                .checked_mul(header.sh_size)?
+                .checked_add(header.sh_offset)?;
+            let header = file.get(start..)?;
+            Some(if is_64bit {
+                SectionHeader {
+                    kind: u32_at(header, 4)?,
+                    offset: word_at(header, 0x18, true)?,
+                    size: word_at(header, 0x20, true)?,
+                    link: u32_at(header, 0x28)? as usize,
+                }
+            } else {
+                SectionHeader {
+                    kind: u32_at(header, 4)?,
+                    offset: word_at(header, 0x10, false)?,
+                    size: word_at(header, 0x14, false)?,
+                    link: u32_at(header, 0x18)? as usize,
+                }
+            })
+        })
+        .collect::<Option<Vec<SectionHeader>>>()?;
+
+    let entry_size = if is_64bit { 24 } else { 16 };
+    let mut symbols: Vec<Symbol> = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    for table in sections
+        .iter()
+        .filter(|section| section.kind == SHT_SYMTAB || section.kind == SHT_DYNSYM)
+    {
+        let strings = sections.get(table.link)?;
+        let strings = file.get(strings.offset..strings.offset.checked_add(strings.size)?)?;
+        let entries = file.get(table.offset..table.offset.checked_add(table.size)?)?;
+
+        for entry in entries.chunks_exact(entry_size) {
+            let (name, value, size, info, section_index) = if is_64bit {
+                (
+                    u32_at(entry, 0)? as usize,
+                    word_at(entry, 8, true)?,
+                    word_at(entry, 16, true)?,
+                    entry[4],
+                    u16_at(entry, 6)?,
+                )
+            } else {
+                (
+                    u32_at(entry, 0)? as usize,
+                    word_at(entry, 4, false)?,
+                    word_at(entry, 8, false)?,
+                    entry[12],
+                    u16_at(entry, 14)?,
+                )
+            };
+            //Skip undefined symbols, they are resolved from other modules
+            if section_index == 0 || name == 0 {
+                continue;
+            }
+
+            //Names past the string table or without a terminator come from a corrupted file
+            let name = match strings.get(name..) {
+                Some(rest) => match rest.iter().position(|c| *c == 0) {
+                    Some(end) => String::from_utf8_lossy(&rest[..end]).into_owned(),
+                    None => continue,
+                },
+                None => continue,
+            };
+            if !seen.insert(name.clone()) {
+                continue;
+            }
+            symbols.push(Symbol {
+                name,
+                address: load_bias.wrapping_add(value),
+                size,
+                is_function: info & 0xF == 2,
+            });
+        }
+    }
+
+    Some(symbols)
+}
@@ -0,0 +1,59 @@
+use crate::Pod;
+
+///Integer and float types whose byte order can be converted
+pub trait Endian: Pod {
+    ///Reverse the byte order of the value
+    fn swap_bytes(self) -> Self;
+
+    ///Convert a big endian value to the native byte order
+    fn from_be(value: Self) -> Self {
+        if cfg!(target_endian = "big") {
+            value
+        } else {
+            value.swap_bytes()
+        }
+    }
+
+    ///Convert a little endian value to the native byte order
+    fn from_le(value: Self) -> Self {
+        if cfg!(target_endian = "little") {
+            value
+        } else {
+            value.swap_bytes()
+        }
+    }
+
+    ///Convert a native value to big endian
+    fn to_be(self) -> Self {
+        Self::from_be(self)
+    }
+
+    ///Convert a native value to little endian
+    fn to_le(self) -> Self {
+        Self::from_le(self)
+    }
+}
+
+macro_rules! impl_endian_int {
+    ($($t:ty),*) => {
+        $(impl Endian for $t {
+            fn swap_bytes(self) -> Self {
+                <$t>::swap_bytes(self)
+            }
+        })*
+    };
+}
+
+impl_endian_int!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl Endian for f32 {
+    fn swap_bytes(self) -> Self {
+        f32::from_bits(self.to_bits().swap_bytes())
+    }
+}
+
+impl Endian for f64 {
+    fn swap_bytes(self) -> Self {
+        f64::from_bits(self.to_bits().swap_bytes())
+    }
+}
@@ -0,0 +1,389 @@
+use std::io::{self, Error, ErrorKind, Read, Write};
+
+use crate::{
+    Module, ProcT, Protection, RebasedAddress, RegionInfo, ScanSession, Sharing, ValueType,
+};
+
+///Scan hit with the context needed to make sense of it outside the tool, ready to be written as JSON or CSV
+///
+///```
+///use proc_memory::{write_hits_csv, MockProc, ProcT, ScanHit, ValueType};
+///
+///let proc = MockProc::new();
+///proc.map_value(0x1000, &100i32);
+///let hits = ScanHit::describe(&proc, &[0x1000], Some(ValueType::I32));
+///let mut csv = Vec::new();
+///write_hits_csv(&hits, &mut csv).unwrap();
+///assert_eq!(
+///    String::from_utf8(csv).unwrap().lines().nth(1),
+///    Some("0x1000,,,100,0x1000,4,rw-,,private")
+///);
+///```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScanHit {
+    pub address: usize,
+    ///Module relative address, None when no loaded module contains it
+    pub location: Option<RebasedAddress>,
+    ///Current value written in decimal, None without a value type or if it can't be read
+    pub value: Option<String>,
+    ///Region containing the address
+    pub region: Option<RegionInfo>,
+}
+
+impl ScanHit {
+    ///Describe the hits at 'addresses', reading their current value as 'value_type' if given
+    pub fn describe<P: ProcT + ?Sized>(
+        proc: &P,
+        addresses: &[usize],
+        value_type: Option<ValueType>,
+    ) -> Vec<ScanHit> {
+        let typed: Vec<(usize, Option<ValueType>)> = addresses
+            .iter()
+            .map(|address| (*address, value_type))
+            .collect();
+        describe_all(proc, &typed)
+    }
+
+    ///Describe the entries of 'session' with their current values
+    pub fn describe_session<P: ProcT + ?Sized>(proc: &P, session: &ScanSession) -> Vec<ScanHit> {
+        let typed: Vec<(usize, Option<ValueType>)> = session
+            .entries()
+            .iter()
+            .map(|entry| (entry.address, Some(entry.value_type)))
+            .collect();
+        describe_all(proc, &typed)
+    }
+}
+
+fn describe_all<P: ProcT + ?Sized>(proc: &P, hits: &[(usize, Option<ValueType>)]) -> Vec<ScanHit> {
+    let modules = proc.modules();
+    let mut regions = proc.region_infos();
+    regions.sort_by_key(|region| region.base);
+
+    hits.iter()
+        .map(|(address, value_type)| {
+            let address = *address;
+            let location = modules
+                .iter()
+                .find(|module| module.contains(address))
+                .map(|module| RebasedAddress::new(&module.name, address - module.base));
+            let value = value_type.and_then(|value_type| {
+                let bytes = proc.read_vec_uninit::<u8>(address, value_type.size())?;
+                value_type.format(&bytes)
+            });
+            let index = regions.partition_point(|region| region.base <= address);
+            let region = index
+                .checked_sub(1)
+                .map(|index| &regions[index])
+                .filter(|region| region.contains(address))
+                .cloned();
+            ScanHit {
+                address,
+                location,
+                value,
+                region,
+            }
+        })
+        .collect()
+}
+
+fn sharing_name(sharing: Sharing) -> &'static str {
+    match sharing {
+        Sharing::Private => "private",
+        Sharing::Shared => "shared",
+        Sharing::CopyOnWrite => "copy-on-write",
+    }
+}
+
+fn sharing_from_name(name: &str) -> Option<Sharing> {
+    match name {
+        "private" => Some(Sharing::Private),
+        "shared" => Some(Sharing::Shared),
+        "copy-on-write" => Some(Sharing::CopyOnWrite),
+        _ => None,
+    }
+}
+
+///Rights written by the Display impl of Protection, like "r-x"
+fn protection_from_name(name: &str) -> Option<Protection> {
+    match name.as_bytes() {
+        [read @ (b'r' | b'-'), write @ (b'w' | b'-'), execute @ (b'x' | b'-')] => Some(
+            Protection::new(*read == b'r', *write == b'w', *execute == b'x'),
+        ),
+        _ => None,
+    }
+}
+
+///Names of the exported fields, in order
+const COLUMNS: [&str; 9] = [
+    "address",
+    "module",
+    "offset",
+    "value",
+    "region_base",
+    "region_size",
+    "protection",
+    "file",
+    "sharing",
+];
+
+///Fields of 'hit' in the order of COLUMNS, numbers are hexadecimal except the region size
+fn fields(hit: &ScanHit) -> [Option<String>; 9] {
+    let location = hit.location.as_ref();
+    let region = hit.region.as_ref();
+    [
+        Some(format!("{:#x}", hit.address)),
+        location.map(|location| location.module.clone()),
+        location.map(|location| format!("{:#x}", location.rva)),
+        hit.value.clone(),
+        region.map(|region| format!("{:#x}", region.base)),
+        region.map(|region| region.size.to_string()),
+        region.map(|region| region.protection.to_string()),
+        region.and_then(|region| region.file.clone()),
+        region.map(|region| sharing_name(region.sharing).to_string()),
+    ]
+}
+
+///Write 'hits' as a JSON array of objects with string fields, missing fields are null
+pub fn write_hits_json(hits: &[ScanHit], mut out: impl Write) -> io::Result<()> {
+    fn string(text: &str) -> String {
+        let mut quoted = String::from("\"");
+        for c in text.chars() {
+            match c {
+                '"' => quoted.push_str("\\\""),
+                '\\' => quoted.push_str("\\\\"),
+                '\n' => quoted.push_str("\\n"),
+                '\t' => quoted.push_str("\\t"),
+                c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+                c => quoted.push(c),
+            }
+        }
+        quoted.push('"');
+        quoted
+    }
+
+    writeln!(out, "[")?;
+    for (index, hit) in hits.iter().enumerate() {
+        let object: Vec<String> = COLUMNS
+            .iter()
+            .zip(fields(hit))
+            .map(|(name, value)| {
+                let value = value.map_or_else(|| "null".to_string(), |value| string(&value));
+                format!("\"{}\": {}", name, value)
+            })
+            .collect();
+        let separator = if index + 1 < hits.len() { "," } else { "" };
+        writeln!(out, "  {{{}}}{}", object.join(", "), separator)?;
+    }
+    writeln!(out, "]")
+}
+
+///Write 'hits' as CSV with a header row, missing fields are left empty
+pub fn write_hits_csv(hits: &[ScanHit], out: impl Write) -> io::Result<()> {
+    let rows: Vec<Vec<Option<String>>> = hits.iter().map(|hit| fields(hit).to_vec()).collect();
+    write_csv(&COLUMNS, &rows, out)
+}
+
+///Read hits written by write_hits_csv(). Empty fields are read as missing, so an empty value or file name
+///comes back as None
+///
+///```
+///use proc_memory::{read_hits_csv, write_hits_csv, MockProc, ScanHit, ValueType};
+///
+///let proc = MockProc::new();
+///proc.map_value(0x1000, &100i32);
+///let hits = ScanHit::describe(&proc, &[0x1000], Some(ValueType::I32));
+///let mut csv = Vec::new();
+///write_hits_csv(&hits, &mut csv).unwrap();
+///assert_eq!(read_hits_csv(csv.as_slice()).unwrap(), hits);
+///```
+pub fn read_hits_csv(input: impl Read) -> io::Result<Vec<ScanHit>> {
+    read_csv(&COLUMNS, input, |row| {
+        let hex = |index: usize| row[index].as_deref().and_then(crate::offsets::parse_hex);
+        let location = match (&row[1], hex(2)) {
+            (Some(module), Some(rva)) => Some(RebasedAddress::new(module, rva)),
+            (None, None) => None,
+            _ => return None,
+        };
+        let region = match (hex(4), &row[5], &row[6], &row[8]) {
+            (Some(base), Some(size), Some(protection), Some(sharing)) => Some(RegionInfo {
+                base,
+                size: size.parse().ok()?,
+                protection: protection_from_name(protection)?,
+                file: row[7].clone(),
+                sharing: sharing_from_name(sharing)?,
+            }),
+            (None, None, None, None) => None,
+            _ => return None,
+        };
+        Some(ScanHit {
+            address: hex(0)?,
+            location,
+            value: row[3].clone(),
+            region,
+        })
+    })
+}
+
+const REGION_COLUMNS: [&str; 5] = ["base", "size", "protection", "file", "sharing"];
+
+///Write 'regions' as CSV with a header row, the base in hexadecimal and the size in decimal
+pub fn write_regions_csv(regions: &[RegionInfo], out: impl Write) -> io::Result<()> {
+    let rows: Vec<Vec<Option<String>>> = regions
+        .iter()
+        .map(|region| {
+            vec![
+                Some(format!("{:#x}", region.base)),
+                Some(region.size.to_string()),
+                Some(region.protection.to_string()),
+                region.file.clone(),
+                Some(sharing_name(region.sharing).to_string()),
+            ]
+        })
+        .collect();
+    write_csv(&REGION_COLUMNS, &rows, out)
+}
+
+///Read regions written by write_regions_csv()
+pub fn read_regions_csv(input: impl Read) -> io::Result<Vec<RegionInfo>> {
+    read_csv(&REGION_COLUMNS, input, |row| {
+        Some(RegionInfo {
+            base: row[0].as_deref().and_then(crate::offsets::parse_hex)?,
+            size: row[1].as_deref()?.parse().ok()?,
+            protection: protection_from_name(row[2].as_deref()?)?,
+            file: row[3].clone(),
+            sharing: sharing_from_name(row[4].as_deref()?)?,
+        })
+    })
+}
+
+const MODULE_COLUMNS: [&str; 4] = ["name", "path", "base", "size"];
+
+///Write 'modules' as CSV with a header row, the base in hexadecimal and the size in decimal
+pub fn write_modules_csv(modules: &[Module], out: impl Write) -> io::Result<()> {
+    let rows: Vec<Vec<Option<String>>> = modules
+        .iter()
+        .map(|module| {
+            vec![
+                Some(module.name.clone()),
+                Some(module.path.clone()),
+                Some(format!("{:#x}", module.base)),
+                Some(module.size.to_string()),
+            ]
+        })
+        .collect();
+    write_csv(&MODULE_COLUMNS, &rows, out)
+}
+
+///Read modules written by write_modules_csv()
+pub fn read_modules_csv(input: impl Read) -> io::Result<Vec<Module>> {
+    read_csv(&MODULE_COLUMNS, input, |row| {
+        Some(Module {
+            name: row[0].clone()?,
+            path: row[1].clone().unwrap_or_default(),
+            base: row[2].as_deref().and_then(crate::offsets::parse_hex)?,
+            size: row[3].as_deref()?.parse().ok()?,
+        })
+    })
+}
+
+fn write_csv(
+    columns: &[&str],
+    rows: &[Vec<Option<String>>],
+    mut out: impl Write,
+) -> io::Result<()> {
+    fn field(text: &str) -> String {
+        if text.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", text.replace('"', "\"\""))
+        } else {
+            text.to_string()
+        }
+    }
+
+    writeln!(out, "{}", columns.join(","))?;
+    for row in rows {
+        let row: Vec<String> = row
+            .iter()
+            .map(|value| value.as_deref().map_or_else(String::new, field))
+            .collect();
+        writeln!(out, "{}", row.join(","))?;
+    }
+    Ok(())
+}
+
+///Read CSV written by write_csv(), checking the header against 'columns' and turning each row into an item
+///with 'parse'. Empty fields are None
+fn read_csv<T>(
+    columns: &[&str],
+    mut input: impl Read,
+    parse: impl Fn(&[Option<String>]) -> Option<T>,
+) -> io::Result<Vec<T>> {
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+    let mut rows = split_csv(&text)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "unterminated quoted field"))?
+        .into_iter();
+
+    let header: Vec<String> = rows
+        .next()
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .collect();
+    if header != columns {
+        return Err(Error::new(ErrorKind::InvalidData, "unexpected CSV header"));
+    }
+    rows.enumerate()
+        .map(|(index, row)| {
+            if row.len() == columns.len() {
+                if let Some(item) = parse(&row) {
+                    return Ok(item);
+                }
+            }
+            Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid CSV row {}", index + 2),
+            ))
+        })
+        .collect()
+}
+
+///Rows of 'text' split into fields, quoted fields may contain commas, quotes and line breaks.
+///Blank lines are skipped, None if a quoted field isn't closed
+fn split_csv(text: &str) -> Option<Vec<Vec<Option<String>>>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut chars = text.chars().peekable();
+    let mut quoted = false;
+    let finish = |field: &mut String| Some(std::mem::take(field)).filter(|field| !field.is_empty());
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted || field.is_empty() => quoted = !quoted,
+            ',' if !quoted => row.push(finish(&mut field)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' if !quoted => {
+                row.push(finish(&mut field));
+                if row.len() > 1 || row[0].is_some() {
+                    rows.push(std::mem::take(&mut row));
+                } else {
+                    row.clear();
+                }
+            }
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return None;
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(finish(&mut field));
+        rows.push(row);
+    }
+    Some(rows)
+}
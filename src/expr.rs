@@ -0,0 +1,200 @@
+use std::fmt;
+
+use crate::offsets::parse_hex;
+use crate::{AddressChain, ProcT};
+
+///Address expression such as `[[module.dll+0x1000]+0x20]+0x8`.
+///
+///Brackets read the pointer stored at the inner address, module names resolve to the module base address and
+///numbers are hexadecimal, with or without '0x'. Module names that are valid hex numbers or contain operators
+///can be quoted: `"my-lib.so"+10`. Pointer chains written as `module+offset -> offset` are accepted as well.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AddressExpr {
+    ///Constant value
+    Number(usize),
+    ///Base address of a module
+    Module(String),
+    ///Pointer stored at the inner address
+    Deref(Box<AddressExpr>),
+    ///Sum of both values
+    Add(Box<AddressExpr>, Box<AddressExpr>),
+    ///Difference of both values
+    Sub(Box<AddressExpr>, Box<AddressExpr>),
+    ///Product of both values
+    Mul(Box<AddressExpr>, Box<AddressExpr>),
+}
+
+impl AddressExpr {
+    ///Parse an expression, returns None on syntax errors
+    pub fn parse(text: &str) -> Option<AddressExpr> {
+        if text.contains("->") {
+            return AddressChain::parse(text).map(AddressExpr::from);
+        }
+
+        let tokens = tokenize(text)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+        };
+        let expr = parser.expr()?;
+        if parser.position != parser.tokens.len() {
+            return None;
+        }
+        Some(expr)
+    }
+
+    ///Evaluate the expression against the live process, returns None if a module or pointer can't be read
+    pub fn eval<P: ProcT + ?Sized>(&self, proc: &P) -> Option<usize> {
+        self.eval_sized(proc, proc.pointer_size())
+    }
+
+    fn eval_sized<P: ProcT + ?Sized>(&self, proc: &P, pointer_size: usize) -> Option<usize> {
+        let eval = |expr: &AddressExpr| expr.eval_sized(proc, pointer_size);
+        match self {
+            AddressExpr::Number(value) => Some(*value),
+            AddressExpr::Module(name) => proc.module(name).map(|module| module.base),
+            AddressExpr::Deref(inner) => proc.read_ptr_sized(eval(inner)?, pointer_size),
+            AddressExpr::Add(left, right) => Some(eval(left)?.wrapping_add(eval(right)?)),
+            AddressExpr::Sub(left, right) => Some(eval(left)?.wrapping_sub(eval(right)?)),
+            AddressExpr::Mul(left, right) => Some(eval(left)?.wrapping_mul(eval(right)?)),
+        }
+    }
+}
+
+impl From<AddressChain> for AddressExpr {
+    fn from(chain: AddressChain) -> AddressExpr {
+        let mut expr = AddressExpr::Number(chain.base);
+        if let Some(module) = chain.module {
+            expr = AddressExpr::Add(Box::new(AddressExpr::Module(module)), Box::new(expr));
+        }
+        for offset in chain.offsets {
+            let deref = Box::new(AddressExpr::Deref(Box::new(expr)));
+            expr = if offset < 0 {
+                AddressExpr::Sub(deref, Box::new(AddressExpr::Number(offset.unsigned_abs())))
+            } else {
+                AddressExpr::Add(deref, Box::new(AddressExpr::Number(offset as usize)))
+            };
+        }
+        expr
+    }
+}
+
+impl fmt::Display for AddressExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddressExpr::Number(value) => write!(f, "{:#X}", value),
+            AddressExpr::Module(name) => write!(f, "\"{}\"", name),
+            AddressExpr::Deref(inner) => write!(f, "[{}]", inner),
+            AddressExpr::Add(left, right) => write!(f, "{}+{}", left, right),
+            AddressExpr::Sub(left, right) => write!(f, "{}-({})", left, right),
+            AddressExpr::Mul(left, right) => write!(f, "({})*({})", left, right),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Number(usize),
+    Name(String),
+    Symbol(char),
+}
+
+fn tokenize(text: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' | '\t' => {
+                chars.next();
+            }
+            '[' | ']' | '(' | ')' | '+' | '-' | '*' => {
+                tokens.push(Token::Symbol(c));
+                chars.next();
+            }
+            '"' => {
+                chars.next();
+                let name: String = chars.by_ref().take_while(|c| *c != '"').collect();
+                tokens.push(Token::Name(name));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '.' || c == '_' {
+                        word.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                if word.is_empty() {
+                    return None;
+                }
+                match parse_hex(&word) {
+                    Some(value) => tokens.push(Token::Number(value)),
+                    None => tokens.push(Token::Name(word)),
+                }
+            }
+        }
+    }
+
+    Some(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn next_symbol(&mut self, symbols: &[char]) -> Option<char> {
+        match self.tokens.get(self.position) {
+            Some(Token::Symbol(c)) if symbols.contains(c) => {
+                self.position += 1;
+                Some(*c)
+            }
+            _ => None,
+        }
+    }
+
+    fn expr(&mut self) -> Option<AddressExpr> {
+        let mut left = self.product()?;
+        while let Some(symbol) = self.next_symbol(&['+', '-']) {
+            let right = Box::new(self.product()?);
+            left = match symbol {
+                '+' => AddressExpr::Add(Box::new(left), right),
+                _ => AddressExpr::Sub(Box::new(left), right),
+            };
+        }
+        Some(left)
+    }
+
+    fn product(&mut self) -> Option<AddressExpr> {
+        let mut left = self.term()?;
+        while self.next_symbol(&['*']).is_some() {
+            left = AddressExpr::Mul(Box::new(left), Box::new(self.term()?));
+        }
+        Some(left)
+    }
+
+    fn term(&mut self) -> Option<AddressExpr> {
+        if self.next_symbol(&['[']).is_some() {
+            let inner = self.expr()?;
+            self.next_symbol(&[']'])?;
+            return Some(AddressExpr::Deref(Box::new(inner)));
+        }
+        if self.next_symbol(&['(']).is_some() {
+            let inner = self.expr()?;
+            self.next_symbol(&[')'])?;
+            return Some(inner);
+        }
+
+        let token = self.tokens.get(self.position)?.clone();
+        self.position += 1;
+        match token {
+            Token::Number(value) => Some(AddressExpr::Number(value)),
+            Token::Name(name) => Some(AddressExpr::Module(name)),
+            Token::Symbol(_) => None,
+        }
+    }
+}
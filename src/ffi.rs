@@ -0,0 +1,168 @@
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::{Pattern, Proc, ProcT};
+
+///Run 'f', returning 'failure' if it panics. Unwinding into C is undefined behavior and would abort the host
+fn guard<R>(failure: R, f: impl FnOnce() -> R) -> R {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(failure)
+}
+
+///Open the process with specified id, NULL on failure. Release it with proc_close()
+#[no_mangle]
+pub extern "C" fn proc_open(pid: u32) -> *mut Proc {
+    guard(std::ptr::null_mut(), || match Proc::from_pid(pid) {
+        Some(proc) => Box::into_raw(Box::new(proc)),
+        None => std::ptr::null_mut(),
+    })
+}
+
+///Open the process with specified title or executable name, NULL on failure. Release it with proc_close()
+///# Safety
+///'name' must be a NUL terminated string
+#[no_mangle]
+pub unsafe extern "C" fn proc_open_name(name: *const c_char) -> *mut Proc {
+    guard(std::ptr::null_mut(), || {
+        if name.is_null() {
+            return std::ptr::null_mut();
+        }
+        let name = match CStr::from_ptr(name).to_str() {
+            Ok(name) => name,
+            Err(_) => return std::ptr::null_mut(),
+        };
+        match Proc::get(name) {
+            Some(proc) => Box::into_raw(Box::new(proc)),
+            None => std::ptr::null_mut(),
+        }
+    })
+}
+
+///Release a handle returned by proc_open() or proc_open_name(), NULL is ignored
+///# Safety
+///'proc' must not be used afterwards
+#[no_mangle]
+pub unsafe extern "C" fn proc_close(proc: *mut Proc) {
+    guard((), || {
+        if !proc.is_null() {
+            drop(Box::from_raw(proc));
+        }
+    })
+}
+
+///Process id of the handle, -1 for NULL
+///# Safety
+///'proc' must be NULL or a handle that wasn't closed
+#[no_mangle]
+pub unsafe extern "C" fn proc_pid(proc: *const Proc) -> i64 {
+    guard(-1, || match proc.as_ref() {
+        Some(proc) => proc.pid() as i64,
+        None => -1,
+    })
+}
+
+///Read 'len' bytes at 'address' into 'buffer', stopping at the first unreadable byte. Returns the read count
+///# Safety
+///'proc' must be NULL or a handle that wasn't closed, 'buffer' must be valid for 'len' bytes
+#[no_mangle]
+pub unsafe extern "C" fn proc_read(
+    proc: *const Proc,
+    address: usize,
+    buffer: *mut u8,
+    len: usize,
+) -> usize {
+    guard(0, || {
+        let proc = match proc.as_ref() {
+            Some(proc) if !buffer.is_null() => proc,
+            _ => return 0,
+        };
+        let (data, count) = proc.read_partial(address, len);
+        let count = count.min(data.len()).min(len);
+        std::ptr::copy_nonoverlapping(data.as_ptr(), buffer, count);
+        count
+    })
+}
+
+///Write 'len' bytes of 'data' at 'address', stopping at the first unwritable byte. Returns the written count
+///# Safety
+///'proc' must be NULL or a handle that wasn't closed, 'data' must be valid for 'len' bytes
+#[no_mangle]
+pub unsafe extern "C" fn proc_write(
+    proc: *const Proc,
+    address: usize,
+    data: *const u8,
+    len: usize,
+) -> usize {
+    guard(0, || {
+        let proc = match proc.as_ref() {
+            Some(proc) if !data.is_null() => proc,
+            _ => return 0,
+        };
+        proc.write_bytes(address, std::slice::from_raw_parts(data, len))
+            .1
+    })
+}
+
+///Scan the readable memory for 'signature', hex bytes with '??' wildcards like "48 8B 05 ?? ?? ?? ??".
+///Stores up to 'capacity' match addresses in 'matches' and returns the total number of matches, -1 if the
+///signature is invalid
+///# Safety
+///'proc' must be NULL or a handle that wasn't closed, 'signature' a NUL terminated string and 'matches' valid for
+///'capacity' addresses
+#[no_mangle]
+pub unsafe extern "C" fn proc_scan(
+    proc: *const Proc,
+    signature: *const c_char,
+    matches: *mut usize,
+    capacity: usize,
+) -> i64 {
+    guard(-1, || {
+        let proc = match proc.as_ref() {
+            Some(proc) if !signature.is_null() => proc,
+            _ => return -1,
+        };
+        let pattern = match CStr::from_ptr(signature)
+            .to_str()
+            .ok()
+            .and_then(Pattern::parse)
+        {
+            Some(pattern) => pattern,
+            None => return -1,
+        };
+        let found = proc.scan(&pattern);
+        if !matches.is_null() {
+            let count = found.len().min(capacity);
+            std::ptr::copy_nonoverlapping(found.as_ptr(), matches, count);
+        }
+        found.len() as i64
+    })
+}
+
+///Evaluate an address expression or pointer chain like "[game.exe+0x1000]+0x20" or "game.exe+0x1000 -> 0x20",
+///storing the address in 'address'. Returns false if it is invalid or a pointer can't be read
+///# Safety
+///'proc' must be NULL or a handle that wasn't closed, 'expr' a NUL terminated string and 'address' writable
+#[no_mangle]
+pub unsafe extern "C" fn proc_eval_address(
+    proc: *const Proc,
+    expr: *const c_char,
+    address: *mut usize,
+) -> bool {
+    guard(false, || {
+        let proc = match proc.as_ref() {
+            Some(proc) if !expr.is_null() && !address.is_null() => proc,
+            _ => return false,
+        };
+        let resolved = CStr::from_ptr(expr)
+            .to_str()
+            .ok()
+            .and_then(|expr| proc.eval_address(expr));
+        match resolved {
+            Some(resolved) => {
+                *address = resolved;
+                true
+            }
+            None => false,
+        }
+    })
+}
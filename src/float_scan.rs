@@ -0,0 +1,109 @@
+use std::convert::TryInto;
+use std::marker::PhantomData;
+
+use crate::scan::Matcher;
+
+///Float value to search for. Live values are rarely bit for bit equal to the one shown on screen, so floats are
+///matched with a tolerance. NaN never matches
+///
+///```
+///use proc_memory::{FloatValue, MockProc, ProcT, ScanOptions};
+///
+///let proc = MockProc::new();
+///proc.map(0x1000, vec![0u8; 16]);
+///proc.write(0x1008, &99.97f32);
+///let options = ScanOptions::new().align(4);
+///
+///let near = FloatValue::Approx { value: 100.0, epsilon: 0.05 };
+///assert_eq!(proc.scan_f32(near, &options).matches, vec![0x1008]);
+///let rounded = FloatValue::Rounded { value: 100.0, decimals: 0 };
+///assert_eq!(proc.scan_f32(rounded, &options).matches, vec![0x1008]);
+///let range = FloatValue::Between { min: -1.0, max: 1.0 };
+///assert_eq!(proc.scan_f32(range, &options).matches, vec![0x1000, 0x1004, 0x100C]);
+///```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FloatValue {
+    ///At most 'epsilon' away from 'value'
+    Approx { value: f64, epsilon: f64 },
+    ///Equal to 'value' once both are rounded to 'decimals' decimal places, e.g. 2.5 matches 2.4999
+    Rounded { value: f64, decimals: u32 },
+    ///Between 'min' and 'max', both included
+    Between { min: f64, max: f64 },
+}
+
+impl FloatValue {
+    ///Returns 'true' if 'value' is accepted
+    pub fn matches(&self, value: f64) -> bool {
+        match *self {
+            FloatValue::Approx {
+                value: expected,
+                epsilon,
+            } => (value - expected).abs() <= epsilon,
+            FloatValue::Rounded {
+                value: expected,
+                decimals,
+            } => {
+                let scale = 10f64.powi(decimals as i32);
+                (value * scale).round() == (expected * scale).round()
+            }
+            FloatValue::Between { min, max } => min <= value && value <= max,
+        }
+    }
+}
+
+///Float type values are read as
+pub(crate) trait Float: Copy {
+    const SIZE: usize;
+
+    ///Value of the first SIZE bytes of 'bytes'
+    fn from_bytes(bytes: &[u8]) -> f64;
+}
+
+impl Float for f32 {
+    const SIZE: usize = 4;
+
+    fn from_bytes(bytes: &[u8]) -> f64 {
+        f32::from_ne_bytes(bytes[..4].try_into().unwrap()) as f64
+    }
+}
+
+impl Float for f64 {
+    const SIZE: usize = 8;
+
+    fn from_bytes(bytes: &[u8]) -> f64 {
+        f64::from_ne_bytes(bytes[..8].try_into().unwrap())
+    }
+}
+
+///Matches the F values accepted by a FloatValue
+pub(crate) struct FloatMatcher<F: Float> {
+    value: FloatValue,
+    float: PhantomData<F>,
+}
+
+impl<F: Float> FloatMatcher<F> {
+    pub(crate) fn new(value: FloatValue) -> FloatMatcher<F> {
+        FloatMatcher {
+            value,
+            float: PhantomData,
+        }
+    }
+}
+
+impl<F: Float> Matcher for FloatMatcher<F> {
+    fn len(&self) -> usize {
+        F::SIZE
+    }
+
+    fn find_all(&self, data: &[u8], limit: usize, address: usize, alignment: usize) -> Vec<usize> {
+        if data.len() < F::SIZE || limit == 0 {
+            return Vec::new();
+        }
+        let last_start = (data.len() - F::SIZE).min(limit - 1);
+        let first = (alignment - address % alignment) % alignment;
+        (first..=last_start)
+            .step_by(alignment)
+            .filter(|offset| self.value.matches(F::from_bytes(&data[*offset..])))
+            .collect()
+    }
+}
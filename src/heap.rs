@@ -0,0 +1,167 @@
+use std::ops::Range;
+
+use crate::ProcT;
+
+const SEGMENT_SIGNATURE: u32 = 0xFFEE_FFEE;
+const SEGMENT_HEAP_SIGNATURE: u32 = 0xDDEE_DDEE;
+const HEAP_ENTRY_SIZE: usize = 0x10;
+const HEAP_ENTRY_BUSY: u8 = 0x01;
+///Upper bound for the entries walked in one heap, larger counts are treated as a corrupted heap
+const MAX_ENTRIES: usize = 0x100_0000;
+
+///NT heap of a 64-bit Windows process, listed in the PEB heap array
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Heap {
+    ///Address of the _HEAP structure, which is also the heap handle
+    pub address: usize,
+    ///'true' for segment heaps (Windows 10+ opt-in), their blocks can't be walked
+    pub is_segment_heap: bool,
+}
+
+///Allocated block of a heap
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeapBlock {
+    ///Address of the heap owning the block
+    pub heap: usize,
+    ///Address of the user data, right after the block header
+    pub address: usize,
+    ///Usable size in bytes
+    pub size: usize,
+    ///'true' for large blocks allocated directly with VirtualAlloc
+    pub is_virtual: bool,
+}
+
+impl HeapBlock {
+    ///Addresses of the user data
+    pub fn range(&self) -> Range<usize> {
+        self.address..self.address + self.size
+    }
+}
+
+///Header fields of a _HEAP_ENTRY
+struct HeapEntry {
+    size: usize,
+    flags: u8,
+    unused_bytes: usize,
+}
+
+impl Heap {
+    ///Read the heap at 'address', telling NT heaps and segment heaps apart by their signature
+    pub fn read<P: ProcT + ?Sized>(proc: &P, address: usize) -> Option<Heap> {
+        let is_segment_heap = match proc.read::<u32>(address + 0x10)? {
+            SEGMENT_SIGNATURE => false,
+            SEGMENT_HEAP_SIGNATURE => true,
+            _ => return None,
+        };
+        Some(Heap {
+            address,
+            is_segment_heap,
+        })
+    }
+
+    ///Decode the _HEAP_ENTRY at 'address', headers are XORed with the heap Encoding when EncodeFlagMask is set
+    fn entry<P: ProcT + ?Sized>(
+        &self,
+        proc: &P,
+        address: usize,
+        encoding: Option<u64>,
+    ) -> Option<HeapEntry> {
+        let mut header = proc.read::<u64>(address + 8)?;
+        if let Some(encoding) = encoding {
+            header ^= encoding;
+        }
+        let bytes = header.to_le_bytes();
+        Some(HeapEntry {
+            size: u16::from_le_bytes([bytes[0], bytes[1]]) as usize * HEAP_ENTRY_SIZE,
+            flags: bytes[2],
+            unused_bytes: bytes[7] as usize,
+        })
+    }
+
+    ///Walk the allocated blocks of the heap. Blocks served by the low fragmentation heap show up as the
+    ///larger backend block holding them. Segment heaps return an empty list
+    pub fn blocks<P: ProcT + ?Sized>(&self, proc: &P) -> Vec<HeapBlock> {
+        let mut blocks = Vec::new();
+        if self.is_segment_heap {
+            return blocks;
+        }
+        let encoding = match proc.read::<u32>(self.address + 0x7C) {
+            Some(0) => None,
+            Some(_) => proc.read::<u64>(self.address + 0x88),
+            None => return blocks,
+        };
+        let regions = proc.regions();
+
+        //SegmentList, linked through _HEAP_SEGMENT.SegmentListEntry at 0x18
+        let head = self.address + 0x120;
+        let mut link = proc.read::<usize>(head).unwrap_or(head);
+        let mut walked = 0;
+        while link != head && link != 0 {
+            let segment = link - 0x18;
+            let (first, last) = match (
+                proc.read::<usize>(segment + 0x40),
+                proc.read::<usize>(segment + 0x48),
+            ) {
+                (Some(first), Some(last)) => (first, last),
+                _ => break,
+            };
+
+            let mut entry = first;
+            while entry < last && walked < MAX_ENTRIES {
+                walked += 1;
+                let header = match self.entry(proc, entry, encoding) {
+                    Some(header) if header.size > 0 => header,
+                    //Uncommitted range, continue at the next committed region of the segment
+                    _ => match regions
+                        .iter()
+                        .find(|region| region.base > entry && region.base < last)
+                    {
+                        Some(region) => {
+                            entry = region.base;
+                            continue;
+                        }
+                        None => break,
+                    },
+                };
+                if header.flags & HEAP_ENTRY_BUSY != 0 {
+                    let size = header
+                        .size
+                        .checked_sub(header.unused_bytes)
+                        .filter(|size| *size <= header.size - HEAP_ENTRY_SIZE)
+                        .unwrap_or(header.size - HEAP_ENTRY_SIZE);
+                    blocks.push(HeapBlock {
+                        heap: self.address,
+                        address: entry + HEAP_ENTRY_SIZE,
+                        size,
+                        is_virtual: false,
+                    });
+                }
+                entry += header.size;
+            }
+            link = match proc.read::<usize>(link) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+
+        //VirtualAllocdBlocks, each a _HEAP_VIRTUAL_ALLOC_ENTRY followed by the user data
+        let head = self.address + 0x110;
+        let mut link = proc.read::<usize>(head).unwrap_or(head);
+        while link != head && link != 0 && walked < MAX_ENTRIES {
+            walked += 1;
+            if let Some(commit_size) = proc.read::<usize>(link + 0x20) {
+                blocks.push(HeapBlock {
+                    heap: self.address,
+                    address: link + 0x40,
+                    size: commit_size.saturating_sub(0x40),
+                    is_virtual: true,
+                });
+            }
+            link = match proc.read::<usize>(link) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        blocks
+    }
+}
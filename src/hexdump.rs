@@ -0,0 +1,95 @@
+use std::fmt;
+
+use crate::BulkRead;
+
+///Formatted view of process memory, one line per 'width' bytes prefixed with the address.
+///Unreadable bytes are shown as '??'
+#[derive(Clone, Debug)]
+pub struct Hexdump {
+    memory: BulkRead,
+    width: usize,
+    ascii: bool,
+}
+
+impl Hexdump {
+    pub(crate) fn new(memory: BulkRead) -> Hexdump {
+        Hexdump {
+            memory,
+            width: 16,
+            ascii: true,
+        }
+    }
+
+    ///Bytes per line, 16 by default
+    pub fn width(mut self, width: usize) -> Hexdump {
+        self.width = width.max(1);
+        self
+    }
+
+    ///Show the printable ASCII column, enabled by default
+    pub fn ascii(mut self, ascii: bool) -> Hexdump {
+        self.ascii = ascii;
+        self
+    }
+
+    ///Address of the first byte
+    pub fn address(&self) -> usize {
+        self.memory.address
+    }
+
+    ///The dumped bytes and unreadable ranges
+    pub fn memory(&self) -> &BulkRead {
+        &self.memory
+    }
+}
+
+impl fmt::Display for Hexdump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let end = self.memory.address + self.memory.data.len();
+        let address_digits = if end > u32::MAX as usize { 16 } else { 8 };
+
+        for (index, line) in self.memory.data.chunks(self.width).enumerate() {
+            let line_address = self.memory.address + index * self.width;
+            write!(f, "{:0width$X} ", line_address, width = address_digits)?;
+
+            for column in 0..self.width {
+                match line.get(column) {
+                    Some(byte) if self.memory.is_readable(line_address + column) => {
+                        write!(f, " {:02X}", byte)?
+                    }
+                    Some(_) => write!(f, " ??")?,
+                    None => write!(f, "   ")?,
+                }
+            }
+
+            if self.ascii {
+                write!(f, "  |")?;
+                for (column, byte) in line.iter().enumerate() {
+                    let c = if !self.memory.is_readable(line_address + column) {
+                        '?'
+                    } else if byte.is_ascii_graphic() || *byte == b' ' {
+                        *byte as char
+                    } else {
+                        '.'
+                    };
+                    write!(f, "{}", c)?;
+                }
+                write!(f, "|")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+///Parse bytes written as pairs of hex digits, whitespace is ignored, e.g. "90 90" or "9090"
+pub fn parse_hex_bytes(text: &str) -> Option<Vec<u8>> {
+    let digits: String = text.split_whitespace().collect();
+    if !digits.is_ascii() || !digits.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&digits[index..index + 2], 16).ok())
+        .collect()
+}
@@ -0,0 +1,85 @@
+use std::path::Path;
+
+use crate::{Module, ProcT, Protection};
+
+///Load the library at 'path' into the process and find it in the module list afterwards
+pub(crate) fn inject_library<P: ProcT + ?Sized>(proc: &P, path: &Path) -> Option<Module> {
+    //The target resolves relative paths against its own working directory
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().ok()?.join(path)
+    };
+    //Symlinked libraries show up under the name of the file they point to
+    let resolved = std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+    let name = resolved.file_name()?.to_str()?;
+
+    load_library(proc, path.to_str()?)?;
+    proc.modules()
+        .into_iter()
+        .find(|module| module.is_named(name))
+}
+
+///Copy 'bytes' into a temporary allocation of the process for the duration of 'f'
+fn with_remote_bytes<P: ProcT + ?Sized, R>(
+    proc: &P,
+    bytes: &[u8],
+    f: impl FnOnce(usize) -> Option<R>,
+) -> Option<R> {
+    let remote = proc.alloc(bytes.len(), Protection::READ_WRITE)?;
+    let result = if proc.write_bytes(remote, bytes).0 {
+        f(remote)
+    } else {
+        None
+    };
+    proc.free(remote, bytes.len());
+    result
+}
+
+#[cfg(target_os = "windows")]
+fn load_library<P: ProcT + ?Sized>(proc: &P, path: &str) -> Option<()> {
+    let load_library = proc.module("kernel32.dll")?.export(proc, "LoadLibraryW")?;
+    //The exit code only holds the low half of the module handle, so success is checked through the module list
+    with_remote_bytes(
+        proc,
+        &crate::strings::encode_wstring(path, None),
+        |remote| proc.create_thread_and_wait(load_library, remote),
+    )
+    .map(|_| ())
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+fn load_library<P: ProcT + ?Sized>(proc: &P, path: &str) -> Option<()> {
+    let libc_module = proc
+        .modules()
+        .into_iter()
+        .find(|module| module.name.starts_with("libc.so") || module.name.starts_with("libc-"))?;
+    //glibc before 2.34 only exports the internal variant from libc itself
+    let dlopen = libc_module
+        .symbol(proc, "dlopen")
+        .or_else(|| libc_module.symbol(proc, "__libc_dlopen_mode"))?;
+
+    let handle = with_remote_bytes(
+        proc,
+        &crate::strings::encode_cstring(path, None),
+        |remote| {
+            crate::syscall::remote_call(
+                proc.pid() as libc::pid_t,
+                dlopen as u64,
+                &[remote as u64, libc::RTLD_NOW as u64],
+            )
+        },
+    )?;
+    if handle == 0 {
+        return None;
+    }
+    Some(())
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    all(target_os = "linux", target_arch = "x86_64")
+)))]
+fn load_library<P: ProcT + ?Sized>(_proc: &P, _path: &str) -> Option<()> {
+    None
+}
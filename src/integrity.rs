@@ -0,0 +1,135 @@
+use std::ops::Range;
+use std::time::Duration;
+
+use crate::{Module, ProcT, Region};
+
+///Bytes hashed per read
+const CHUNK_SIZE: usize = 0x10000;
+const FNV_OFFSET: u64 = 0xCBF2_9CE4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01B3;
+
+///FNV-1a hash of the memory in 'range', None if any of it can't be read
+pub(crate) fn hash_range<P: ProcT + ?Sized>(proc: &P, range: Range<usize>) -> Option<u64> {
+    let mut hash = FNV_OFFSET;
+    let mut address = range.start;
+    while address < range.end {
+        let len = (range.end - address).min(CHUNK_SIZE);
+        let (data, count) = proc.read_partial(address, len);
+        if count != len {
+            return None;
+        }
+        for byte in data {
+            hash = (hash ^ byte as u64).wrapping_mul(FNV_PRIME);
+        }
+        address += len;
+    }
+    Some(hash)
+}
+
+///Content change of a watched range
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntegrityChange {
+    ///Watched addresses
+    pub range: Range<usize>,
+    ///Hash of the previous check, None if the range wasn't readable
+    pub old: Option<u64>,
+    ///Hash of the current contents, None if the range isn't readable anymore
+    pub new: Option<u64>,
+}
+
+///Hashes a set of address ranges and reports which ones changed since the previous check.
+///Useful to notice when the target patches its own code, or unloads and reloads a module
+///
+///```
+///use proc_memory::{IntegrityMonitor, MockProc, ProcT};
+///
+///let proc = MockProc::new();
+///proc.map(0x1000, vec![0x90; 0x100]);
+///let mut monitor = IntegrityMonitor::new();
+///monitor.watch(&proc, 0x1000..0x1100);
+///assert!(monitor.check(&proc).is_empty());
+///
+///proc.write(0x1010, &0xCCu8);
+///assert_eq!(monitor.check(&proc)[0].range, 0x1000..0x1100);
+///```
+#[derive(Clone, Debug, Default)]
+pub struct IntegrityMonitor {
+    watched: Vec<(Range<usize>, Option<u64>)>,
+}
+
+impl IntegrityMonitor {
+    pub fn new() -> IntegrityMonitor {
+        IntegrityMonitor::default()
+    }
+
+    ///Start watching 'range', its current contents are the baseline. Returns 'true' if it could be read
+    pub fn watch<P: ProcT + ?Sized>(&mut self, proc: &P, range: Range<usize>) -> bool {
+        let hash = hash_range(proc, range.clone());
+        self.watched.push((range, hash));
+        hash.is_some()
+    }
+
+    ///Watch the whole 'region'
+    pub fn watch_region<P: ProcT + ?Sized>(&mut self, proc: &P, region: &Region) -> bool {
+        self.watch(proc, region.range())
+    }
+
+    ///Watch the executable regions of 'module', where code patches land
+    pub fn watch_module<P: ProcT + ?Sized>(&mut self, proc: &P, module: &Module) -> bool {
+        let end = module.base + module.size;
+        let mut readable = true;
+        for region in proc.regions() {
+            if region.protection.execute && region.base < end && region.end() > module.base {
+                readable &= self.watch(proc, region.base.max(module.base)..region.end().min(end));
+            }
+        }
+        readable
+    }
+
+    ///Stop watching 'range', returns 'true' if it was watched
+    pub fn unwatch(&mut self, range: &Range<usize>) -> bool {
+        let count = self.watched.len();
+        self.watched.retain(|(watched, _)| watched != range);
+        self.watched.len() != count
+    }
+
+    ///Watched ranges
+    pub fn ranges(&self) -> impl Iterator<Item = &Range<usize>> {
+        self.watched.iter().map(|(range, _)| range)
+    }
+
+    ///Hash every watched range again and return the ones that changed, which become the new baseline
+    pub fn check<P: ProcT + ?Sized>(&mut self, proc: &P) -> Vec<IntegrityChange> {
+        let mut changes = Vec::new();
+        for (range, hash) in &mut self.watched {
+            let new = hash_range(proc, range.clone());
+            if new != *hash {
+                changes.push(IntegrityChange {
+                    range: range.clone(),
+                    old: *hash,
+                    new,
+                });
+                *hash = new;
+            }
+        }
+        changes
+    }
+
+    ///Check the ranges every 'interval' and call 'on_change' with each change, until it returns 'false' or the
+    ///process exits. Blocks the calling thread
+    pub fn run<P: ProcT + ?Sized>(
+        &mut self,
+        proc: &P,
+        interval: Duration,
+        mut on_change: impl FnMut(&IntegrityChange) -> bool,
+    ) {
+        while proc.is_alive() {
+            for change in self.check(proc) {
+                if !on_change(&change) {
+                    return;
+                }
+            }
+            std::thread::sleep(interval);
+        }
+    }
+}
@@ -14,16 +14,21 @@
 //!```
 //!
 //!```no_run
-//!use proc_memory::ProcT;
+//!use proc_memory::{Pod, ProcT};
 //!
+//!#[repr(C)]
+//!#[derive(Clone, Copy)]
 //!struct TwoNum {
 //!    num1: u64,
 //!    num2: i64,
 //!}
 //!
+//!//Any bit pattern is a valid TwoNum
+//!unsafe impl Pod for TwoNum {}
+//!
 //!let proc = proc_memory::Proc::get("Other Proccess").unwrap();
 //!let two_num = proc.read::<TwoNum>(0x7FF49E8720A8).unwrap();
-//!println!("{} + {} = {}", two_num.num1, two_num.num2, two_num.num1 + two_num.num2);
+//!println!("{} + {} = {}", two_num.num1, two_num.num2, two_num.num1 as i64 + two_num.num2);
 //!```
 //!
 //!```no_run
@@ -34,191 +39,1696 @@
 //!println!("{} + {} = {}", vec[0], vec[1], vec[0] + vec[1]);
 //!```
 
+#[cfg(feature = "async")]
+mod async_proc;
+mod backend;
+mod bulk;
+mod cache;
+mod cave;
+mod cheat_table;
+mod checked;
+mod compare;
+mod copy;
+mod cursor;
+#[cfg(target_arch = "x86_64")]
+mod debugger;
+#[cfg(feature = "disasm")]
+mod disasm;
+mod dissect;
+mod dump;
+mod dump_file;
+mod elf;
+mod endian;
+mod export;
+mod expr;
+///C interface declared in include/proc_memory.h, handles are boxed Proc values released with proc_close()
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod float_scan;
+mod heap;
+mod hexdump;
+#[cfg(feature = "injection")]
+mod inject;
+mod integrity;
+mod log;
+#[cfg(feature = "lz4")]
+mod lz4;
+mod manager;
+mod memchr;
+mod memory_usage;
+#[cfg(feature = "minidump")]
+mod minidump;
+mod mock;
+mod module;
+mod offsets;
+mod patch;
+#[cfg(feature = "pdb")]
+mod pdb;
+mod pe;
+mod peb;
+mod pod;
+mod pointer_map;
+mod proc_read;
+mod proc_self;
+mod progress;
+mod protection;
+mod region;
+#[cfg(feature = "remote")]
+mod remote;
+mod remote_array;
+mod remote_ptr;
+mod remote_struct;
+mod retry;
+mod scan;
+mod scan_session;
+mod session;
+mod shared;
+mod snapshot;
+mod stl;
+mod string_scan;
+mod strings;
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+mod syscall;
+mod thread;
+mod throttle;
+#[cfg(target_arch = "x86_64")]
+mod trace;
+mod uninit;
+#[cfg(target_arch = "x86_64")]
+mod watch;
+
+#[cfg(feature = "async")]
+pub use async_proc::{AsyncProc, Blocking};
+pub use backend::{BackendProc, MemoryBackend};
+pub use bulk::BulkRead;
+pub use cache::CachedProc;
+pub use cave::CodeCave;
+pub use checked::WriteError;
+pub use compare::{compare, compare_ranges, Difference};
+pub use copy::copy_between;
+pub use cursor::RemoteCursor;
+#[cfg(target_arch = "x86_64")]
+pub use debugger::{DebugEvent, DebugException, Debugger};
+#[cfg(feature = "disasm")]
+pub use disasm::{decode, Instruction};
+pub use dissect::{Dissection, Field, FieldKind};
+pub use dump_file::DumpFile;
+pub use elf::Symbol;
+pub use endian::Endian;
+pub use export::{
+    read_hits_csv, read_modules_csv, read_regions_csv, write_hits_csv, write_hits_json,
+    write_modules_csv, write_regions_csv, ScanHit,
+};
+pub use expr::AddressExpr;
+pub use float_scan::FloatValue;
+pub use heap::{Heap, HeapBlock};
+pub use hexdump::{parse_hex_bytes, Hexdump};
 pub use implementation::*;
+pub use integrity::{IntegrityChange, IntegrityMonitor};
+pub use log::{clear_logger, set_logger, LogLevel, LogRecord};
+#[cfg(feature = "lz4")]
+pub use lz4::{Lz4Reader, Lz4Writer};
+pub use manager::{Manager, ManagerEvent, Tracked};
+pub use memory_usage::MemoryUsage;
+#[cfg(feature = "minidump")]
+pub use minidump::DumpLevel;
+pub use mock::MockProc;
+pub use module::Module;
+pub use offsets::{AddressChain, OffsetTable};
+pub use patch::{Patch, PatchSet};
+#[cfg(feature = "pdb")]
+pub use pdb::{PdbInfo, PdbSymbols};
+pub use pe::{Export, Import, PeHeaders, Section};
+pub use peb::{Peb, ProcessParameters, Teb};
+pub use pod::Pod;
+pub use pointer_map::PointerMap;
+pub use proc_read::ProcRead;
+pub use proc_self::ProcSelf;
+pub use progress::Progress;
+pub use protection::Protection;
+pub use region::{Region, RegionInfo, Sharing};
+#[cfg(feature = "remote")]
+pub use remote::{serve_agent, serve_agent_connection, RemoteBackend, RemoteProc};
+pub use remote_array::{RemoteArray, RemoteChunks};
+pub use remote_ptr::RemotePtr;
+pub use remote_struct::RemoteStruct;
+pub use retry::{RetryPolicy, RetryingProc};
+pub use scan::{CancelToken, Pattern, ScanOptions, ScanResult};
+pub use scan_session::{Refine, ScanEntry, ScanSession, ValueType};
+pub use session::{RebasedAddress, Session};
+pub use shared::SharedProc;
+pub use snapshot::Snapshot;
+
+#[cfg(feature = "derive")]
+pub use proc_memory_derive::RemoteStruct;
+pub use stl::{StdLayout, MAX_CONTAINER_BYTES};
+pub use string_scan::{StringEncoding, StringMatch, StringScanResult};
+#[cfg(target_arch = "x86_64")]
+pub use thread::{FloatRegisters, Registers, StackFrame};
+pub use thread::{Thread, ThreadState};
+pub use throttle::ThrottledProc;
+#[cfg(target_arch = "x86_64")]
+pub use trace::{Trace, TraceStep};
+#[cfg(target_arch = "x86_64")]
+pub use watch::{WatchAccess, WatchHit, WatchMethod, Watchpoint, MAX_WATCHPOINTS};
+
+///'InvalidData' error for malformed files and text formats
+pub(crate) fn invalid_data(message: impl Into<String>) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message.into())
+}
+
 pub trait ProcT {
     ///Get a handle to a process with specified title
-    fn get(proc_name: &str) -> Option<ProcWindows>;
+    fn get(proc_name: &str) -> Option<Self>
+    where
+        Self: Sized;
 
     ///Read a certain type T from specified memory address
-    fn read<T>(&self, proc_address: usize) -> Option<T>;
+    fn read<T: Pod>(&self, proc_address: usize) -> Option<T>;
+
+    ///Get a typed pointer to the specified memory address
+    fn ptr<T>(&self, proc_address: usize) -> RemotePtr<'_, T, Self>
+    where
+        Self: Sized,
+    {
+        RemotePtr::new(self, proc_address)
+    }
+
+    ///Get a typed array of 'len' elements starting at the specified memory address
+    fn array<T: Pod>(&self, proc_address: usize, len: usize) -> RemoteArray<'_, T, Self>
+    where
+        Self: Sized,
+    {
+        RemoteArray::new(self, proc_address, len)
+    }
+
+    ///Size in bytes of a pointer in the process
+    fn pointer_size(&self) -> usize {
+        if self.is_64bit() {
+            8
+        } else {
+            4
+        }
+    }
+
+    ///Read a pointer using the pointer width of the process
+    fn read_ptr(&self, proc_address: usize) -> Option<usize> {
+        self.read_ptr_sized(proc_address, self.pointer_size())
+    }
+
+    ///Read a pointer of 'pointer_size' bytes (4 or 8), avoids detecting the process bitness on every read
+    fn read_ptr_sized(&self, proc_address: usize, pointer_size: usize) -> Option<usize> {
+        if pointer_size == 4 {
+            self.read::<u32>(proc_address)
+                .map(|pointer| pointer as usize)
+        } else {
+            self.read::<u64>(proc_address)
+                .map(|pointer| pointer as usize)
+        }
+    }
 
     ///Read a certain type T from specified memory address and only return the value if 'validator' function returns 'true'
-    fn read_valid<T>(&self, proc_address: usize, validator: impl Fn(&T) -> bool) -> Option<T>;
+    fn read_valid<T: Pod>(&self, proc_address: usize, validator: impl Fn(&T) -> bool) -> Option<T>;
 
-    ///Read a vector of type T with specified 'len' number of elements, fills the array with value returned by 'default_provider'
-    fn read_vec<T>(
+    ///Read a vector of type T with specified 'len' number of elements straight into uninitialized memory.
+    ///'default_provider' is no longer called, it is kept so existing callers still compile
+    fn read_vec<T: Pod>(
         &self,
         proc_address: usize,
         len: usize,
         default_provider: impl Fn() -> T,
     ) -> Option<Vec<T>>;
 
+    ///Read a vector of type T with specified 'len' number of elements without initializing it first
+    fn read_vec_uninit<T: Pod>(&self, proc_address: usize, len: usize) -> Option<Vec<T>>;
+
+    ///Cursor implementing std::io::Read and Seek starting at the specified address
+    fn reader_at(&self, proc_address: usize) -> RemoteCursor<'_, Self> {
+        RemoteCursor::new(self, proc_address)
+    }
+
+    ///Cursor implementing std::io::Write and Seek starting at the specified address
+    fn writer_at(&self, proc_address: usize) -> RemoteCursor<'_, Self> {
+        RemoteCursor::new(self, proc_address)
+    }
+
+    ///Read a big endian value of type T from specified memory address
+    fn read_be<T: Endian>(&self, proc_address: usize) -> Option<T> {
+        self.read::<T>(proc_address).map(T::from_be)
+    }
+
+    ///Read a little endian value of type T from specified memory address
+    fn read_le<T: Endian>(&self, proc_address: usize) -> Option<T> {
+        self.read::<T>(proc_address).map(T::from_le)
+    }
+
+    ///Read up to 'len' bytes from specified memory address, stopping at the first unreadable byte.
+    ///Returns the bytes that were read and their count
+    fn read_partial(&self, proc_address: usize, len: usize) -> (Vec<u8>, usize);
+
+    ///Read many (address, len) ranges at once, each result is None if its range couldn't be read completely
+    fn read_batch(&self, requests: &[(usize, usize)]) -> Vec<Option<Vec<u8>>> {
+        requests
+            .iter()
+            .map(|(address, len)| self.read_vec_uninit::<u8>(*address, *len))
+            .collect()
+    }
+
+    ///Run 'f' with a view of the process whose reads share one page cache, dropped when 'f' returns.
+    ///Each page is read once, so every value read in the scope, like the fields sampled for one frame,
+    ///comes from the same copy of its page. Writes through the view go to the process
+    ///
+    ///```
+    ///use proc_memory::{MockProc, ProcT};
+    ///
+    ///let proc = MockProc::new();
+    ///proc.map_value(0x1000, &[10u32, 20]);
+    ///let sum = proc.cached(|view| {
+    ///    view.read::<u32>(0x1000).unwrap() + view.read::<u32>(0x1004).unwrap()
+    ///});
+    ///assert_eq!(sum, 30);
+    ///```
+    fn cached<R>(&self, f: impl FnOnce(&CachedProc<'_, Self>) -> R) -> R {
+        f(&CachedProc::new(self))
+    }
+
+    ///Read a value of type T at each of 'addresses' through a single read_batch() call
+    fn read_many<T: Pod>(&self, addresses: &[usize]) -> Vec<Option<T>> {
+        let size = std::mem::size_of::<T>();
+        let requests: Vec<(usize, usize)> =
+            addresses.iter().map(|address| (*address, size)).collect();
+        self.read_batch(&requests)
+            .into_iter()
+            .map(|bytes| {
+                bytes.map(|bytes| unsafe { std::ptr::read_unaligned(bytes.as_ptr() as *const T) })
+            })
+            .collect()
+    }
+
+    ///Read 'len' bytes from specified memory address one page at a time, unreadable pages are filled with 'fill'
+    ///and recorded as gaps instead of failing the whole read
+    fn read_bulk(&self, proc_address: usize, len: usize, fill: u8) -> BulkRead {
+        bulk::read_bulk(self, proc_address, len, fill)
+    }
+
+    ///Hexdump of 'len' bytes at the specified address, printable through Display
+    fn hexdump(&self, proc_address: usize, len: usize) -> Hexdump {
+        Hexdump::new(self.read_bulk(proc_address, len, 0))
+    }
+
+    ///Write 'len' bytes at the specified address to the file at 'path', unreadable pages are written as zeros.
+    ///Returns the address ranges that could not be read
+    fn dump_range(
+        &self,
+        proc_address: usize,
+        len: usize,
+        path: &std::path::Path,
+    ) -> std::io::Result<Vec<std::ops::Range<usize>>> {
+        dump::dump_range(self, proc_address, len, path, None)
+    }
+
+    ///dump_range() compressing the file as an LZ4 frame, read it back with Lz4Reader or the lz4 command line tool
+    #[cfg(feature = "lz4")]
+    fn dump_range_lz4(
+        &self,
+        proc_address: usize,
+        len: usize,
+        path: &std::path::Path,
+    ) -> std::io::Result<Vec<std::ops::Range<usize>>> {
+        dump::dump_range_lz4(self, proc_address, len, path)
+    }
+
+    ///dump_range() calling 'progress' after each chunk written
+    fn dump_range_with_progress(
+        &self,
+        proc_address: usize,
+        len: usize,
+        path: &std::path::Path,
+        progress: &dyn Fn(&Progress),
+    ) -> std::io::Result<Vec<std::ops::Range<usize>>> {
+        dump::dump_range(self, proc_address, len, path, Some(progress))
+    }
+
+    ///Read a null terminated UTF-8 string of at most 'max_len' bytes, returns None if the bytes are not valid UTF-8
+    fn read_cstring(&self, proc_address: usize, max_len: usize) -> Option<String> {
+        let bytes = strings::read_until_nul(self, proc_address, max_len, 1)?;
+        String::from_utf8(bytes).ok()
+    }
+
+    ///Read a null terminated string of at most 'max_len' bytes, replacing invalid UTF-8 sequences
+    fn read_cstring_lossy(&self, proc_address: usize, max_len: usize) -> Option<String> {
+        let bytes = strings::read_until_nul(self, proc_address, max_len, 1)?;
+        Some(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    ///Read a null terminated UTF-16 string of at most 'max_len' characters, invalid code units are replaced
+    fn read_wstring(&self, proc_address: usize, max_len: usize) -> Option<String> {
+        let bytes = strings::read_until_nul(self, proc_address, max_len, 2)?;
+        Some(stl::decode_wide(&bytes, 2))
+    }
+
+    ///Read a C++ std::string using the standard library layout of the current platform
+    fn read_std_string(&self, proc_address: usize) -> Option<String> {
+        self.read_std_string_with(proc_address, StdLayout::native())
+    }
+
+    ///Read a C++ std::string stored with the specified standard library layout
+    fn read_std_string_with(&self, proc_address: usize, layout: StdLayout) -> Option<String> {
+        let bytes = stl::read_string_bytes(self, proc_address, layout, 1)?;
+        Some(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    ///Read a C++ std::wstring using the standard library layout of the current platform
+    fn read_std_wstring(&self, proc_address: usize) -> Option<String> {
+        self.read_std_wstring_with(proc_address, StdLayout::native())
+    }
+
+    ///Read a C++ std::wstring stored with the specified standard library layout
+    fn read_std_wstring_with(&self, proc_address: usize, layout: StdLayout) -> Option<String> {
+        let bytes = stl::read_string_bytes(self, proc_address, layout, layout.wchar_size())?;
+        Some(stl::decode_wide(&bytes, layout.wchar_size()))
+    }
+
+    ///Read the elements of a C++ std::vector<T>, returns None if its pointers are inconsistent
+    fn read_std_vector<T: Pod>(&self, proc_address: usize) -> Option<Vec<T>> {
+        stl::read_vector(self, proc_address)
+    }
+
     ///Write the value of T to the specified address
     fn write<T>(&self, proc_address: usize, data: &T) -> (bool, usize);
 
+    ///Write the value of T to the specified address in big endian byte order
+    fn write_be<T: Endian>(&self, proc_address: usize, data: T) -> (bool, usize) {
+        self.write(proc_address, &data.to_be())
+    }
+
+    ///Write the value of T to the specified address in little endian byte order
+    fn write_le<T: Endian>(&self, proc_address: usize, data: T) -> (bool, usize) {
+        self.write(proc_address, &data.to_le())
+    }
+
+    ///Write the bytes of 'data' to the specified address
+    fn write_bytes(&self, proc_address: usize, data: &[u8]) -> (bool, usize);
+
+    ///Write the value of T, read it back and fail with the differing bytes if it didn't stick
+    fn write_checked<T: Pod>(&self, proc_address: usize, data: &T) -> Result<(), WriteError> {
+        let bytes = unsafe {
+            std::slice::from_raw_parts(data as *const T as *const u8, std::mem::size_of::<T>())
+        };
+        checked::write_bytes_checked(self, proc_address, bytes)
+    }
+
+    ///Write the bytes of 'data', read them back and fail with the differing bytes if they didn't stick
+    fn write_bytes_checked(&self, proc_address: usize, data: &[u8]) -> Result<(), WriteError> {
+        checked::write_bytes_checked(self, proc_address, data)
+    }
+
+    ///Write 's' as a null terminated UTF-8 string, truncated to fit 'max_len' bytes including the terminator
+    fn write_cstring(&self, proc_address: usize, s: &str, max_len: Option<usize>) -> (bool, usize) {
+        self.write_bytes(proc_address, &strings::encode_cstring(s, max_len))
+    }
+
+    ///Write 's' as a null terminated UTF-16 string, truncated to fit 'max_len' characters including the terminator
+    fn write_wstring(&self, proc_address: usize, s: &str, max_len: Option<usize>) -> (bool, usize) {
+        self.write_bytes(proc_address, &strings::encode_wstring(s, max_len))
+    }
+
+    ///List the modules loaded in the process
+    fn modules(&self) -> Vec<Module>;
+
+    ///List the committed memory regions of the process in address order
+    fn regions(&self) -> Vec<Region>;
+
+    ///Bounds, protection and mapped file of the region containing 'address', None if it isn't mapped
+    fn query(&self, address: usize) -> Option<RegionInfo> {
+        self.regions()
+            .into_iter()
+            .find(|region| region.contains(address))
+            .map(RegionInfo::from)
+    }
+
+    ///Committed memory regions in address order, each with the file mapped in it
+    fn region_infos(&self) -> Vec<RegionInfo> {
+        self.regions()
+            .into_iter()
+            .map(|region| match self.query(region.base) {
+                Some(info) => RegionInfo {
+                    file: info.file,
+                    sharing: info.sharing,
+                    ..RegionInfo::from(region)
+                },
+                None => RegionInfo::from(region),
+            })
+            .collect()
+    }
+
+    ///Addresses of every match of 'pattern' in the readable regions of the process
+    fn scan(&self, pattern: &Pattern) -> Vec<usize> {
+        self.scan_with(pattern, &ScanOptions::default()).matches
+    }
+
+    ///Scan the readable regions of the process for 'pattern', restricted by the protection and range filters of 'options'
+    fn scan_with(&self, pattern: &Pattern, options: &ScanOptions) -> ScanResult {
+        let regions = options.regions(self);
+        let _span = log::span(
+            "scan",
+            format_args!(
+                "{} bytes pattern over {} regions",
+                pattern.len(),
+                regions.len()
+            ),
+        );
+        scan::scan_regions(self, &regions, pattern, options)
+    }
+
+    ///Scan for f32 values accepted by 'value', e.g. FloatValue::Between { min: 99.0, max: 101.0 } for a health bar
+    ///showing 100
+    fn scan_f32(&self, value: FloatValue, options: &ScanOptions) -> ScanResult {
+        let matcher = float_scan::FloatMatcher::<f32>::new(value);
+        scan::scan_regions(self, &options.regions(self), &matcher, options)
+    }
+
+    ///Scan for f64 values accepted by 'value'
+    fn scan_f64(&self, value: FloatValue, options: &ScanOptions) -> ScanResult {
+        let matcher = float_scan::FloatMatcher::<f64>::new(value);
+        scan::scan_regions(self, &options.regions(self), &matcher, options)
+    }
+
+    ///Scan for 'text' encoded as UTF-8 and as little-endian UTF-16, ignoring case if 'ignore_case' is set
+    fn scan_string(
+        &self,
+        text: &str,
+        ignore_case: bool,
+        options: &ScanOptions,
+    ) -> StringScanResult {
+        string_scan::scan_string(self, text, ignore_case, options)
+    }
+
+    ///Addresses of every match of 'pattern' inside 'range'
+    fn scan_range(&self, range: std::ops::Range<usize>, pattern: &Pattern) -> Vec<usize> {
+        scan::scan_range(self, range, pattern, &ScanOptions::default()).matches
+    }
+
+    ///Decode 'count' instructions at 'address', annotated with module relative locations, e.g. to check what a patch
+    ///is about to overwrite. Stops early at unreadable memory
+    #[cfg(feature = "disasm")]
+    fn disassemble(&self, address: usize, count: usize) -> Vec<Instruction> {
+        disasm::disassemble(self, address, count)
+    }
+
+    ///Watch the accesses to 'watchpoints' by every thread during 'duration', finding out what reads or writes an
+    ///address. The process is debugged meanwhile, None if it can't be or if there are more than MAX_WATCHPOINTS
+    #[cfg(target_arch = "x86_64")]
+    fn find_accesses(
+        &self,
+        watchpoints: &[Watchpoint],
+        duration: std::time::Duration,
+    ) -> Option<Vec<WatchHit>> {
+        watch::find_accesses(self, watchpoints, duration, WatchMethod::DebugRegisters)
+    }
+
+    ///Watch the accesses to 'watchpoints' like find_accesses(), catching them with 'method'. WatchMethod::GuardPages
+    ///takes any number and size of watchpoints, for threads whose debug registers are unavailable
+    #[cfg(target_arch = "x86_64")]
+    fn find_accesses_with(
+        &self,
+        watchpoints: &[Watchpoint],
+        duration: std::time::Duration,
+        method: WatchMethod,
+    ) -> Option<Vec<WatchHit>> {
+        watch::find_accesses(self, watchpoints, duration, method)
+    }
+
+    ///Single step thread 'thread' for up to 'count' instructions, recording their addresses and, if 'registers' is
+    ///set, the registers each one changed. The process is debugged meanwhile, None if it can't be or if the thread
+    ///doesn't exist. Blocks while the thread waits in a system call
+    #[cfg(target_arch = "x86_64")]
+    fn trace_thread(&self, thread: u32, count: usize, registers: bool) -> Option<Trace> {
+        trace::trace_thread(self, thread, count, registers)
+    }
+
+    ///Find a loaded module by file name, ignoring ASCII case
+    fn module(&self, name: &str) -> Option<Module> {
+        self.modules()
+            .into_iter()
+            .find(|module| module.is_named(name))
+    }
+
+    ///Evaluate an address expression such as `[[module.dll+0x1000]+0x20]+0x8` against the process
+    fn eval_address(&self, expr: &str) -> Option<usize> {
+        AddressExpr::parse(expr)?.eval(self)
+    }
+
+    ///List the threads of the process
+    fn threads(&self) -> Vec<Thread>;
+
+    ///Resolve the address registered as 'name' in 'offsets'
+    fn resolve(&self, offsets: &OffsetTable, name: &str) -> Option<usize> {
+        offsets.resolve(self, name)
+    }
+
+    ///Returns 'true' while the process is still running
+    fn is_alive(&self) -> bool;
+
+    ///Change the protection of the pages covering 'len' bytes at 'address', returns the previous protection of the first page
+    fn protect(&self, address: usize, len: usize, protection: Protection) -> Option<Protection>;
+
+    ///Allocate 'len' bytes of zeroed memory in the process, rounded up to whole pages
+    fn alloc(&self, len: usize, protection: Protection) -> Option<usize>;
+
+    ///Release memory returned by alloc(), 'len' must be the allocated length.
+    ///Windows always releases the whole allocation
+    fn free(&self, address: usize, len: usize) -> bool;
+
+    ///Start a thread in the process running 'start(param)', returns its thread id.
+    ///On Linux the thread is created with a raw clone and shares the thread-local storage of the main thread
+    fn create_thread(&self, start: usize, param: usize) -> Option<u32>;
+
+    ///Run 'start(param)' in the process and wait for it to return, giving the thread exit code.
+    ///On Linux the main thread is borrowed for the call instead of creating a new thread
+    fn create_thread_and_wait(&self, start: usize, param: usize) -> Option<u32>;
+
+    ///Copy 'code' into new read/execute memory of the process and start a thread at it if 'execute' is set.
+    ///Release it with free(address, code.len()) once it is no longer running
+    fn inject_code(&self, code: &[u8], execute: bool) -> Option<RemotePtr<'_, (), Self>>
+    where
+        Self: Sized,
+    {
+        let address = self.alloc(code.len(), Protection::READ_WRITE)?;
+        let ready = self.write_bytes(address, code).0
+            && self
+                .protect(address, code.len(), Protection::READ_EXECUTE)
+                .is_some();
+        if !ready || (execute && self.create_thread(address, 0).is_none()) {
+            self.free(address, code.len());
+            return None;
+        }
+        Some(RemotePtr::new(self, address))
+    }
+
+    ///Load the library at 'path' into the process with LoadLibraryW (dlopen on Linux) and return the loaded module
+    #[cfg(feature = "injection")]
+    fn inject_library(&self, path: &std::path::Path) -> Option<Module> {
+        inject::inject_library(self, path)
+    }
+
+    ///Write a crash-style dump of the process to 'path': a minidump on Windows, an ELF core file on Linux.
+    ///Fails with ErrorKind::Unsupported unless the handle implements it
+    #[cfg(feature = "minidump")]
+    fn write_minidump(&self, _path: &std::path::Path, _level: DumpLevel) -> std::io::Result<()> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "dumps are not supported by this process handle",
+        ))
+    }
+
+    ///Freeze every thread of the process so several reads see a consistent state, 'false' unless the handle
+    ///implements it
+    fn suspend(&self) -> bool {
+        false
+    }
+
+    ///Continue a process frozen with suspend()
+    fn resume(&self) -> bool {
+        false
+    }
+
+    ///Command line the process was started with, None unless the handle implements it
+    fn command_line(&self) -> Option<String> {
+        None
+    }
+
+    ///Environment variables of the process as name/value pairs, None unless the handle implements it
+    fn environment(&self) -> Option<Vec<(String, String)>> {
+        None
+    }
+
+    ///Executable name of the process
+    fn name(&self) -> Option<String>;
+
+    ///Full path of the process executable, None unless the handle implements it
+    fn exe_path(&self) -> Option<std::path::PathBuf> {
+        None
+    }
+
+    ///Time the process was started at, None unless the handle implements it
+    fn start_time(&self) -> Option<std::time::SystemTime> {
+        None
+    }
+
+    ///Id of the process that created this one, None unless the handle implements it
+    fn parent_pid(&self) -> Option<isize> {
+        None
+    }
+
+    ///Returns 'true' if the process uses 64-bit pointers, falls back to the pointer width of the current process
+    ///if it can't be detected
+    fn is_64bit(&self) -> bool;
+
     ///Get the opened process id
     fn pid(&self) -> isize;
 }
+///Size of the memory pages used when splitting reads
+pub const PAGE_SIZE: usize = 0x1000;
+
+///Handle to a process of the current platform, the ProcWindows backend wrapped in a BackendProc.
+///It is Send and Sync: reads and writes are positioned, handles and clones can be used from several threads at
+///once. Wrap it in a SharedProc to share one attachment between threads
 #[cfg(target_os = "windows")]
-pub type Proc = ProcWindows;
+pub type Proc = BackendProc<ProcWindows>;
+///Handle to a process of the current platform, the ProcLinux backend wrapped in a BackendProc.
+///It is Send and Sync: reads and writes are positioned, handles and clones can be used from several threads at
+///once. Wrap it in a SharedProc to share one attachment between threads
 #[cfg(target_os = "linux")]
-pub type Proc = ProcLinux;
+pub type Proc = BackendProc<ProcLinux>;
+
+//Fails to build if a field makes the handles lose Send or Sync
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Proc>();
+    assert_send_sync::<SharedProc>();
+};
 #[cfg(target_os = "windows")]
 #[allow(clippy::needless_return)]
 pub mod implementation {
     use std::ffi::c_void;
+    use std::mem::MaybeUninit;
+    use std::ops::Range;
+    use std::path::PathBuf;
     use std::process::Output;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-    use windows::Win32::Foundation::{GetLastError, HANDLE, HWND, PWSTR};
+    use windows::Win32::Foundation::{CloseHandle, FILETIME, HANDLE, INVALID_HANDLE_VALUE, PWSTR};
     use windows::Win32::System::Diagnostics::Debug::{ReadProcessMemory, WriteProcessMemory};
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Module32FirstW, Module32NextW, Process32FirstW, Process32NextW,
+        Thread32First, Thread32Next, MODULEENTRY32W, PROCESSENTRY32W, TH32CS_SNAPMODULE,
+        TH32CS_SNAPMODULE32, TH32CS_SNAPPROCESS, TH32CS_SNAPTHREAD, THREADENTRY32,
+    };
     use windows::Win32::System::Threading::{
-        OpenProcess, PROCESS_ALL_ACCESS
+        GetCurrentProcess, GetExitCodeProcess, GetProcessId, GetProcessTimes, OpenProcess,
+        OpenThread, QueryFullProcessImageNameW, PROCESS_ALL_ACCESS, PROCESS_NAME_WIN32,
+        THREAD_QUERY_INFORMATION,
     };
+    #[cfg(feature = "window-lookup")]
     use windows::Win32::UI::WindowsAndMessaging::{FindWindowW, GetWindowThreadProcessId};
 
+    use crate::{
+        log, BackendProc, Heap, MemoryBackend, MemoryUsage, Module, Peb, Proc, Protection, Region,
+        RegionInfo, Sharing, Teb, Thread, ThreadState, PAGE_SIZE,
+    };
+
+    #[link(name = "ntdll")]
+    extern "system" {
+        fn NtQueryInformationProcess(
+            process: HANDLE,
+            class: u32,
+            information: *mut c_void,
+            length: u32,
+            return_length: *mut u32,
+        ) -> i32;
+        fn NtQueryInformationThread(
+            thread: HANDLE,
+            class: u32,
+            information: *mut c_void,
+            length: u32,
+            return_length: *mut u32,
+        ) -> i32;
+        fn NtQuerySystemInformation(
+            class: u32,
+            information: *mut c_void,
+            length: u32,
+            return_length: *mut u32,
+        ) -> i32;
+        fn NtSuspendProcess(process: HANDLE) -> i32;
+        fn NtResumeProcess(process: HANDLE) -> i32;
+    }
+
+    #[cfg(feature = "minidump")]
+    #[link(name = "dbghelp")]
+    extern "system" {
+        fn MiniDumpWriteDump(
+            process: HANDLE,
+            process_id: u32,
+            file: HANDLE,
+            dump_type: u32,
+            exception_param: *const c_void,
+            user_stream_param: *const c_void,
+            callback_param: *const c_void,
+        ) -> i32;
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn IsWow64Process2(
+            process: HANDLE,
+            process_machine: *mut u16,
+            native_machine: *mut u16,
+        ) -> i32;
+        fn VirtualProtectEx(
+            process: HANDLE,
+            address: *const c_void,
+            size: usize,
+            new_protect: u32,
+            old_protect: *mut u32,
+        ) -> i32;
+        fn VirtualAllocEx(
+            process: HANDLE,
+            address: *const c_void,
+            size: usize,
+            allocation_type: u32,
+            protect: u32,
+        ) -> *mut c_void;
+        fn CreateRemoteThread(
+            process: HANDLE,
+            attributes: *const c_void,
+            stack_size: usize,
+            start: *const c_void,
+            param: *const c_void,
+            flags: u32,
+            thread_id: *mut u32,
+        ) -> HANDLE;
+        fn WaitForSingleObject(handle: HANDLE, milliseconds: u32) -> u32;
+        fn GetExitCodeThread(thread: HANDLE, exit_code: *mut u32) -> i32;
+        fn VirtualQueryEx(
+            process: HANDLE,
+            address: *const c_void,
+            information: *mut MemoryBasicInformation,
+            length: usize,
+        ) -> usize;
+        fn VirtualFreeEx(process: HANDLE, address: *mut c_void, size: usize, free_type: u32)
+            -> i32;
+        fn K32GetMappedFileNameW(
+            process: HANDLE,
+            address: *const c_void,
+            file_name: *mut u16,
+            size: u32,
+        ) -> u32;
+        fn DuplicateHandle(
+            source_process: HANDLE,
+            source: HANDLE,
+            target_process: HANDLE,
+            target: *mut HANDLE,
+            access: u32,
+            inherit: i32,
+            options: u32,
+        ) -> i32;
+        fn GetFileType(file: HANDLE) -> u32;
+        fn K32GetProcessMemoryInfo(
+            process: HANDLE,
+            counters: *mut ProcessMemoryCounters,
+            size: u32,
+        ) -> i32;
+        fn GetFinalPathNameByHandleW(file: HANDLE, path: *mut u16, size: u32, flags: u32) -> u32;
+    }
+
+    ///PROCESS_MEMORY_COUNTERS_EX
+    #[repr(C)]
+    #[derive(Default)]
+    #[allow(dead_code)]
+    struct ProcessMemoryCounters {
+        size: u32,
+        page_fault_count: u32,
+        peak_working_set_size: usize,
+        working_set_size: usize,
+        quota_peak_paged_pool_usage: usize,
+        quota_paged_pool_usage: usize,
+        quota_peak_non_paged_pool_usage: usize,
+        quota_non_paged_pool_usage: usize,
+        pagefile_usage: usize,
+        peak_pagefile_usage: usize,
+        private_usage: usize,
+    }
+
+    ///SYSTEM_HANDLE_TABLE_ENTRY_INFO_EX
+    #[repr(C)]
+    #[allow(dead_code)]
+    struct SystemHandleEntry {
+        object: usize,
+        process_id: usize,
+        handle: usize,
+        granted_access: u32,
+        creator_back_trace_index: u16,
+        object_type_index: u16,
+        attributes: u32,
+        reserved: u32,
+    }
+
+    ///MEMORY_BASIC_INFORMATION
+    #[repr(C)]
+    #[derive(Default)]
+    #[allow(dead_code)]
+    struct MemoryBasicInformation {
+        base_address: usize,
+        allocation_base: usize,
+        allocation_protect: u32,
+        #[cfg(target_pointer_width = "64")]
+        partition_id: u16,
+        region_size: usize,
+        state: u32,
+        protect: u32,
+        kind: u32,
+    }
+
     #[derive(Clone, Copy, Debug, Default)]
     pub struct ProcWindows {
         win_handle: HANDLE,
     }
 
+    ///Pid in the first row of 'tasklist /FO CSV /NH', 0 if no process matched ("INFO: No tasks are running...")
     fn parse_tlist_output(plist: Output) -> u32 {
-        let plist = plist;
-        let stdout = String::from_utf8(plist.stdout);
+        let stdout = match String::from_utf8(plist.stdout) {
+            Ok(stdout) => stdout,
+            Err(_) => return 0,
+        };
+        let first = stdout.lines().next().unwrap_or_default();
+        return match first.split(',').nth(1) {
+            Some(pid) => pid.trim_matches('"').parse().unwrap_or(0),
+            None => 0,
+        };
+    }
+
+    fn wide_to_string(wide: &[u16]) -> String {
+        let len = wide.iter().position(|c| *c == 0).unwrap_or(wide.len());
+        return String::from_utf16_lossy(&wide[..len]);
+    }
+
+    impl ProcWindows {
+        ///Read into 'buffer' from 'address' until it is full or a page can't be read, returns the read count
+        fn read_at(&self, address: usize, buffer: &mut [MaybeUninit<u8>]) -> usize {
+            let len = buffer.len();
+            unsafe {
+                let mut read_bytes = 0;
+                let result = ReadProcessMemory(
+                    self.win_handle,
+                    address as *const c_void,
+                    buffer.as_mut_ptr() as *mut c_void,
+                    len,
+                    &mut read_bytes,
+                );
+                if result.as_bool() && read_bytes == len {
+                    return len;
+                }
 
-        if stdout.is_err() {
-            return 0;
+                //The whole read failed, retry page by page until the first unreadable page
+                let mut done = 0;
+                while done < len {
+                    let page_end = (address + done + PAGE_SIZE) & !(PAGE_SIZE - 1);
+                    let chunk = (page_end - (address + done)).min(len - done);
+                    let mut chunk_read = 0;
+
+                    let result = ReadProcessMemory(
+                        self.win_handle,
+                        (address + done) as *const c_void,
+                        buffer.as_mut_ptr().add(done) as *mut c_void,
+                        chunk,
+                        &mut chunk_read,
+                    );
+                    done += chunk_read;
+                    if !result.as_bool() || chunk_read != chunk {
+                        log::debug(
+                            "read",
+                            format_args!(
+                                "{} bytes at {:#X} stopped after {}: {}",
+                                len,
+                                address,
+                                done,
+                                std::io::Error::last_os_error()
+                            ),
+                        );
+                        break;
+                    }
+                }
+                return done;
+            }
+        }
+
+        ///Committed region containing 'address', as returned by VirtualQueryEx
+        fn query_memory(&self, address: usize) -> Option<MemoryBasicInformation> {
+            const MEM_COMMIT: u32 = 0x1000;
+
+            let mut info = MemoryBasicInformation::default();
+            let size = unsafe {
+                VirtualQueryEx(
+                    self.win_handle,
+                    address as *const c_void,
+                    &mut info,
+                    std::mem::size_of::<MemoryBasicInformation>(),
+                )
+            };
+            if size == 0 || info.state != MEM_COMMIT {
+                return None;
+            }
+            return Some(info);
+        }
+
+        ///Address range and protection of the committed pages containing 'address'
+        fn region(&self, address: usize) -> Option<(Range<usize>, Protection)> {
+            const PAGE_GUARD: u32 = 0x100;
+
+            let info = self.query_memory(address)?;
+            let protection = if info.protect & PAGE_GUARD != 0 {
+                Protection::NONE
+            } else {
+                Protection::from_windows(info.protect)
+            };
+            return Some((
+                info.base_address..info.base_address + info.region_size,
+                protection,
+            ));
+        }
+
+        ///Sharing of a region, images are never written back to their file so they count as copy-on-write
+        fn sharing(info: &MemoryBasicInformation) -> Sharing {
+            const MEM_PRIVATE: u32 = 0x20000;
+            const MEM_IMAGE: u32 = 0x100_0000;
+            const PAGE_WRITECOPY: u32 = 0x08;
+            const PAGE_EXECUTE_WRITECOPY: u32 = 0x80;
+
+            if info.kind == MEM_PRIVATE {
+                return Sharing::Private;
+            }
+            let protect = info.protect & 0xFF;
+            if info.kind == MEM_IMAGE
+                || protect == PAGE_WRITECOPY
+                || protect == PAGE_EXECUTE_WRITECOPY
+            {
+                return Sharing::CopyOnWrite;
+            }
+            return Sharing::Shared;
+        }
+
+        ///Device path of the file mapped at 'address', like \\Device\\HarddiskVolume3\\Windows\\System32\\ntdll.dll
+        fn mapped_file(&self, address: usize) -> Option<String> {
+            let mut file_name = [0u16; 1024];
+            let len = unsafe {
+                K32GetMappedFileNameW(
+                    self.win_handle,
+                    address as *const c_void,
+                    file_name.as_mut_ptr(),
+                    file_name.len() as u32,
+                )
+            };
+            if len == 0 {
+                return None;
+            }
+            return Some(wide_to_string(&file_name[..len as usize]));
+        }
+
+        ///PROCESS_BASIC_INFORMATION: exit status, PEB address, affinity, priority, pid, parent pid
+        fn basic_information(&self) -> Option<[usize; 6]> {
+            unsafe {
+                let mut info = [0usize; 6];
+                let status = NtQueryInformationProcess(
+                    self.win_handle,
+                    0,
+                    info.as_mut_ptr() as *mut c_void,
+                    std::mem::size_of_val(&info) as u32,
+                    std::ptr::null_mut(),
+                );
+                if status < 0 {
+                    return None;
+                }
+                return Some(info);
+            }
+        }
+
+        ///Handle and id of a new thread running 'start(param)', the caller closes the handle
+        fn start_thread(&self, start: usize, param: usize) -> Option<(HANDLE, u32)> {
+            unsafe {
+                let mut id = 0;
+                let thread = CreateRemoteThread(
+                    self.win_handle,
+                    std::ptr::null(),
+                    0,
+                    start as *const c_void,
+                    param as *const c_void,
+                    0,
+                    &mut id,
+                );
+                if thread == HANDLE(0) {
+                    return None;
+                }
+                return Some((thread, id));
+            }
+        }
+
+        ///Win32 start address of the thread with specified id
+        fn thread_start_address(thread_id: u32) -> Option<usize> {
+            unsafe {
+                let thread = OpenThread(THREAD_QUERY_INFORMATION, None, thread_id);
+                if thread == HANDLE(0) {
+                    return None;
+                }
+
+                //ThreadQuerySetWin32StartAddress
+                let mut start = 0usize;
+                let status = NtQueryInformationThread(
+                    thread,
+                    9,
+                    std::ptr::addr_of_mut!(start) as *mut c_void,
+                    std::mem::size_of::<usize>() as u32,
+                    std::ptr::null_mut(),
+                );
+                CloseHandle(thread);
+                if status < 0 {
+                    return None;
+                }
+                return Some(start);
+            }
+        }
+    }
+
+    impl BackendProc<ProcWindows> {
+        ///Open the process with the specified id
+        pub fn from_pid(pid: u32) -> Option<Proc> {
+            unsafe {
+                let handle = OpenProcess(PROCESS_ALL_ACCESS, None, pid);
+                if handle == HANDLE(0) {
+                    log::debug(
+                        "attach",
+                        format_args!(
+                            "opening pid {} failed: {}",
+                            pid,
+                            std::io::Error::last_os_error()
+                        ),
+                    );
+                    return None;
+                }
+                return Some(BackendProc::new(ProcWindows { win_handle: handle }));
+            }
+        }
+
+        ///Open the process that started this one, None if it exited or its id was reused
+        pub fn parent(&self) -> Option<Proc> {
+            let parent = Proc::from_pid(crate::ProcT::parent_pid(self)? as u32)?;
+            let started = crate::ProcT::start_time(self)?;
+            if crate::ProcT::start_time(&parent)? > started {
+                return None;
+            }
+            return Some(parent);
+        }
+
+        ///Open the processes started by this one, skipping the ones that can't be opened
+        pub fn children(&self) -> Vec<Proc> {
+            let pid = crate::ProcT::pid(self) as u32;
+            let started = crate::ProcT::start_time(self);
+            let mut children = Vec::new();
+            unsafe {
+                let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+                if snapshot == INVALID_HANDLE_VALUE {
+                    return children;
+                }
+
+                let mut entry: PROCESSENTRY32W = std::mem::zeroed();
+                entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+
+                let mut found = Process32FirstW(snapshot, &mut entry).as_bool();
+                while found {
+                    if entry.th32ParentProcessID == pid && entry.th32ProcessID != pid {
+                        if let Some(child) = Proc::from_pid(entry.th32ProcessID) {
+                            //Parent ids are never cleared, a process started before us had a previous owner of our id
+                            if crate::ProcT::start_time(&child) >= started {
+                                children.push(child);
+                            }
+                        }
+                    }
+                    found = Process32NextW(snapshot, &mut entry).as_bool();
+                }
+
+                CloseHandle(snapshot);
+            }
+            return children;
+        }
+
+        ///Working set, private bytes and commit charge of the process
+        pub fn memory_info(&self) -> Option<MemoryUsage> {
+            let mut counters = ProcessMemoryCounters {
+                size: std::mem::size_of::<ProcessMemoryCounters>() as u32,
+                ..Default::default()
+            };
+            unsafe {
+                if K32GetProcessMemoryInfo(self.backend().win_handle, &mut counters, counters.size)
+                    == 0
+                {
+                    return None;
+                }
+            }
+            return Some(MemoryUsage {
+                resident: counters.working_set_size,
+                peak_resident: counters.peak_working_set_size,
+                private: counters.private_usage,
+                commit: counters.pagefile_usage,
+            });
+        }
+
+        ///Paths of the files the process has open, found by duplicating the file handles of its handle table
+        pub fn open_files(&self) -> Vec<PathBuf> {
+            const SYSTEM_EXTENDED_HANDLE_INFORMATION: u32 = 64;
+            const STATUS_INFO_LENGTH_MISMATCH: i32 = 0xC000_0004_u32 as i32;
+            const DUPLICATE_SAME_ACCESS: u32 = 2;
+            const FILE_TYPE_DISK: u32 = 1;
+
+            let mut files = Vec::new();
+            unsafe {
+                //NumberOfHandles and Reserved, followed by the entries
+                let mut buffer = vec![0usize; 0x10000];
+                loop {
+                    let mut needed = 0;
+                    let status = NtQuerySystemInformation(
+                        SYSTEM_EXTENDED_HANDLE_INFORMATION,
+                        buffer.as_mut_ptr() as *mut c_void,
+                        (buffer.len() * std::mem::size_of::<usize>()) as u32,
+                        &mut needed,
+                    );
+                    if status == STATUS_INFO_LENGTH_MISMATCH && buffer.len() < 0x1000_0000 {
+                        //Handles keep being opened, leave some room
+                        let len = needed as usize / std::mem::size_of::<usize>() + 0x1000;
+                        buffer.resize(len.max(buffer.len() * 2), 0);
+                        continue;
+                    }
+                    if status < 0 {
+                        return files;
+                    }
+                    break;
+                }
+
+                let capacity = (buffer.len() - 2) * std::mem::size_of::<usize>()
+                    / std::mem::size_of::<SystemHandleEntry>();
+                let entries = std::slice::from_raw_parts(
+                    buffer.as_ptr().add(2) as *const SystemHandleEntry,
+                    buffer[0].min(capacity),
+                );
+
+                let pid = GetProcessId(self.backend().win_handle) as usize;
+                let mut path = [0u16; 1024];
+                for entry in entries.iter().filter(|entry| entry.process_id == pid) {
+                    let mut handle = HANDLE(0);
+                    if DuplicateHandle(
+                        self.backend().win_handle,
+                        HANDLE(entry.handle as isize),
+                        GetCurrentProcess(),
+                        &mut handle,
+                        0,
+                        0,
+                        DUPLICATE_SAME_ACCESS,
+                    ) == 0
+                    {
+                        continue;
+                    }
+                    //Only disk files, querying the name of a pipe can block
+                    if GetFileType(handle) == FILE_TYPE_DISK {
+                        let len = GetFinalPathNameByHandleW(
+                            handle,
+                            path.as_mut_ptr(),
+                            path.len() as u32,
+                            0,
+                        ) as usize;
+                        if len > 0 && len < path.len() {
+                            let name = String::from_utf16_lossy(&path[..len]);
+                            let name = name.strip_prefix(r"\\?\").unwrap_or(&name);
+                            files.push(PathBuf::from(name));
+                        }
+                    }
+                    CloseHandle(handle);
+                }
+            }
+            files.sort();
+            files.dedup();
+            return files;
+        }
+
+        ///Pseudo handle to the calling process
+        pub(crate) fn current() -> Proc {
+            unsafe {
+                return BackendProc::new(ProcWindows {
+                    win_handle: GetCurrentProcess(),
+                });
+            }
+        }
+
+        ///Address range and protection of the committed pages containing 'address'
+        pub(crate) fn region(&self, address: usize) -> Option<(Range<usize>, Protection)> {
+            return self.backend().region(address);
+        }
+
+        ///Address of the Process Environment Block
+        pub fn peb_address(&self) -> Option<usize> {
+            return Some(self.backend().basic_information()?[1]);
+        }
+
+        ///Read the Process Environment Block
+        pub fn peb(&self) -> Option<Peb> {
+            return Peb::read(self, self.peb_address()?);
+        }
+
+        ///NT heaps of the process, read from the PEB heap list. Use Heap::blocks() to walk their allocations
+        pub fn heaps(&self) -> Option<Vec<Heap>> {
+            return self.peb()?.heaps(self);
+        }
+
+        ///Address of the Thread Environment Block of the thread with specified id
+        pub fn teb_address(&self, thread_id: u32) -> Option<usize> {
+            unsafe {
+                let thread = OpenThread(THREAD_QUERY_INFORMATION, None, thread_id);
+                if thread == HANDLE(0) {
+                    return None;
+                }
+
+                //THREAD_BASIC_INFORMATION: exit status, TEB address, client id, affinity, priorities
+                let mut info = [0usize; 6];
+                let status = NtQueryInformationThread(
+                    thread,
+                    0,
+                    info.as_mut_ptr() as *mut c_void,
+                    std::mem::size_of_val(&info) as u32,
+                    std::ptr::null_mut(),
+                );
+                CloseHandle(thread);
+                if status < 0 {
+                    return None;
+                }
+                return Some(info[1]);
+            }
+        }
+
+        ///Read the Thread Environment Block of the thread with specified id
+        pub fn teb(&self, thread_id: u32) -> Option<Teb> {
+            return Teb::read(self, self.teb_address(thread_id)?);
+        }
+    }
+
+    impl crate::MemoryBackend for ProcWindows {
+        fn get(proc_name: &str) -> Option<Self> {
+            let _span = log::span("attach", format_args!("looking up {}", proc_name));
+            unsafe {
+                let mut pid = 0;
+
+                #[cfg(feature = "window-lookup")]
+                {
+                    let mut proc_name_w: Vec<u16> = proc_name.encode_utf16().collect();
+                    proc_name_w.push(0);
+                    let window = FindWindowW(None, PWSTR(proc_name_w.as_mut_ptr()));
+                    if window != windows::Win32::Foundation::HWND(0) {
+                        let _ = GetWindowThreadProcessId(window, &mut pid);
+                    }
+                }
+
+                if pid == 0 {
+                    let arg = format!("IMAGENAME eq {}.exe", proc_name);
+
+                    let plist = match std::process::Command::new("cmd")
+                        .args(["/C", "tasklist", "/FI", &arg, "/FO", "CSV", "/NH"])
+                        .output()
+                    {
+                        Ok(plist) => plist,
+                        Err(_) => return None,
+                    };
+
+                    pid = parse_tlist_output(plist);
+                    if pid == 0 {
+                        return None;
+                    }
+                }
+
+                let handle = OpenProcess(PROCESS_ALL_ACCESS, None, pid);
+                if handle == HANDLE(0) {
+                    return None;
+                }
+
+                return Some(ProcWindows { win_handle: handle });
+            }
+        }
+
+        fn read_raw(&self, address: usize, buffer: &mut [u8]) -> usize {
+            let buffer = unsafe { &mut *(buffer as *mut [u8] as *mut [MaybeUninit<u8>]) };
+            return self.read_at(address, buffer);
+        }
+
+        fn read_uninit(&self, address: usize, buffer: &mut [MaybeUninit<u8>]) -> usize {
+            return self.read_at(address, buffer);
+        }
+
+        fn write_raw(&self, address: usize, data: &[u8]) -> usize {
+            unsafe {
+                let mut write = 0;
+                let result = WriteProcessMemory(
+                    self.win_handle,
+                    address as *const c_void,
+                    data.as_ptr() as *const c_void,
+                    data.len(),
+                    &mut write,
+                );
+                if !result.as_bool() {
+                    log::debug(
+                        "write",
+                        format_args!(
+                            "{} bytes at {:#X} failed: {}",
+                            data.len(),
+                            address,
+                            std::io::Error::last_os_error()
+                        ),
+                    );
+                }
+                return write;
+            }
+        }
+
+        fn modules(&self) -> Vec<Module> {
+            unsafe {
+                let mut modules = Vec::new();
+                let snapshot = CreateToolhelp32Snapshot(
+                    TH32CS_SNAPMODULE | TH32CS_SNAPMODULE32,
+                    GetProcessId(self.win_handle),
+                );
+                if snapshot == INVALID_HANDLE_VALUE {
+                    return modules;
+                }
+
+                let mut entry: MODULEENTRY32W = std::mem::zeroed();
+                entry.dwSize = std::mem::size_of::<MODULEENTRY32W>() as u32;
+
+                let mut found = Module32FirstW(snapshot, &mut entry).as_bool();
+                while found {
+                    modules.push(Module {
+                        name: wide_to_string(&entry.szModule),
+                        path: wide_to_string(&entry.szExePath),
+                        base: entry.modBaseAddr as usize,
+                        size: entry.modBaseSize as usize,
+                    });
+                    found = Module32NextW(snapshot, &mut entry).as_bool();
+                }
+
+                CloseHandle(snapshot);
+                return modules;
+            }
+        }
+
+        fn query(&self, address: usize) -> Option<RegionInfo> {
+            let (range, protection) = self.region(address)?;
+            let sharing = ProcWindows::sharing(&self.query_memory(address)?);
+            return Some(RegionInfo {
+                base: range.start,
+                size: range.end - range.start,
+                protection,
+                file: self.mapped_file(address),
+                sharing,
+            });
+        }
+
+        fn region_infos(&self) -> Vec<RegionInfo> {
+            return self
+                .regions()
+                .into_iter()
+                .map(|region| {
+                    let sharing = self
+                        .query_memory(region.base)
+                        .map(|info| ProcWindows::sharing(&info))
+                        .unwrap_or_default();
+                    RegionInfo {
+                        file: self.mapped_file(region.base),
+                        sharing,
+                        ..RegionInfo::from(region)
+                    }
+                })
+                .collect();
         }
-        let stdout = stdout.unwrap();
-        let args: Vec<&str> = stdout.split(',').collect();
 
-        let pids = args[1].trim_matches('"');
+        fn regions(&self) -> Vec<Region> {
+            const MEM_COMMIT: u32 = 0x1000;
+            const PAGE_GUARD: u32 = 0x100;
 
-        return pids.parse().unwrap();
-    }
+            let mut regions = Vec::new();
+            let mut address = 0usize;
+            loop {
+                let mut info = MemoryBasicInformation::default();
+                let size = unsafe {
+                    VirtualQueryEx(
+                        self.win_handle,
+                        address as *const c_void,
+                        &mut info,
+                        std::mem::size_of::<MemoryBasicInformation>(),
+                    )
+                };
+                if size == 0 || info.region_size == 0 {
+                    break;
+                }
 
-    impl crate::ProcT for ProcWindows {
-        fn get(proc_name: &str) -> Option<ProcWindows> {
-            unsafe {
-                let mut pid = 0;
+                if info.state == MEM_COMMIT {
+                    //Guard pages raise an exception in the target when touched, treat them as inaccessible
+                    let protection = if info.protect & PAGE_GUARD != 0 {
+                        Protection::NONE
+                    } else {
+                        Protection::from_windows(info.protect)
+                    };
+                    regions.push(Region {
+                        base: info.base_address,
+                        size: info.region_size,
+                        protection,
+                    });
+                }
 
-                let mut proc_name_w: Vec<u16> = proc_name.encode_utf16().collect();
-                let window = FindWindowW(None, PWSTR(proc_name_w.as_mut_ptr()));
+                address = match info.base_address.checked_add(info.region_size) {
+                    Some(next) => next,
+                    None => break,
+                };
+            }
+            return regions;
+        }
 
-                if window == HWND(0) {
-                    let arg = format!("IMAGENAME eq {}.exe", proc_name);
+        fn threads(&self) -> Vec<Thread> {
+            unsafe {
+                let mut threads = Vec::new();
+                let pid = GetProcessId(self.win_handle);
+                let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0);
+                if snapshot == INVALID_HANDLE_VALUE {
+                    return threads;
+                }
 
-                    let plist = std::process::Command::new("cmd")
-                        .args(["/C", "tasklist", "/FI", &arg, "/FO", "CSV", "/NH"])
-                        .output()
-                        .expect("Failed to get process name - lib.bs 97");
+                let mut entry: THREADENTRY32 = std::mem::zeroed();
+                entry.dwSize = std::mem::size_of::<THREADENTRY32>() as u32;
 
-                    pid = parse_tlist_output(plist);
-                    if pid == 0 {
-                        return None;
+                let mut found = Thread32First(snapshot, &mut entry).as_bool();
+                while found {
+                    if entry.th32OwnerProcessID == pid {
+                        threads.push(Thread {
+                            id: entry.th32ThreadID,
+                            process_id: pid,
+                            start_address: ProcWindows::thread_start_address(entry.th32ThreadID),
+                            state: ThreadState::Unknown,
+                        });
                     }
+                    found = Thread32Next(snapshot, &mut entry).as_bool();
                 }
 
-                let _ = GetWindowThreadProcessId(window, &mut pid);
+                CloseHandle(snapshot);
+                return threads;
+            }
+        }
 
-                if pid == 0 {}
+        fn is_alive(&self) -> bool {
+            const STILL_ACTIVE: u32 = 259;
+            unsafe {
+                let mut code = 0;
+                let result = GetExitCodeProcess(self.win_handle, &mut code);
+                return result.as_bool() && code == STILL_ACTIVE;
+            }
+        }
 
-                let handle = OpenProcess(PROCESS_ALL_ACCESS, None, pid);
-                if handle == HANDLE(0) {
+        fn protect(
+            &self,
+            address: usize,
+            len: usize,
+            protection: Protection,
+        ) -> Option<Protection> {
+            unsafe {
+                let mut old = 0;
+                let result = VirtualProtectEx(
+                    self.win_handle,
+                    address as *const c_void,
+                    len,
+                    protection.to_windows(),
+                    &mut old,
+                );
+                if result == 0 {
                     return None;
                 }
-
-                return Some(ProcWindows { win_handle: handle });
+                return Some(Protection::from_windows(old));
             }
         }
 
-        fn read<T>(&self, proc_address: usize) -> Option<T> {
+        fn alloc(&self, len: usize, protection: Protection) -> Option<usize> {
+            const MEM_COMMIT_RESERVE: u32 = 0x3000;
             unsafe {
-                let mut t: T = std::mem::zeroed();
-                let mut read_bytes = 0;
-
-                let result = ReadProcessMemory(
+                let address = VirtualAllocEx(
                     self.win_handle,
-                    proc_address as *const c_void,
-                    std::ptr::addr_of_mut!(t) as *mut c_void,
-                    std::mem::size_of::<T>(),
-                    &mut read_bytes,
+                    std::ptr::null(),
+                    len,
+                    MEM_COMMIT_RESERVE,
+                    protection.to_windows(),
                 );
+                if address.is_null() {
+                    return None;
+                }
+                return Some(address as usize);
+            }
+        }
 
-                if !result.as_bool() {
+        fn free(&self, address: usize, _len: usize) -> bool {
+            const MEM_RELEASE: u32 = 0x8000;
+            unsafe {
+                return VirtualFreeEx(self.win_handle, address as *mut c_void, 0, MEM_RELEASE) != 0;
+            }
+        }
+
+        fn create_thread(&self, start: usize, param: usize) -> Option<u32> {
+            unsafe {
+                let (thread, id) = self.start_thread(start, param)?;
+                CloseHandle(thread);
+                return Some(id);
+            }
+        }
+
+        fn create_thread_and_wait(&self, start: usize, param: usize) -> Option<u32> {
+            const INFINITE: u32 = u32::MAX;
+            unsafe {
+                let (thread, _) = self.start_thread(start, param)?;
+                WaitForSingleObject(thread, INFINITE);
+                let mut exit_code = 0;
+                let result = GetExitCodeThread(thread, &mut exit_code);
+                CloseHandle(thread);
+                if result == 0 {
                     return None;
                 }
+                return Some(exit_code);
+            }
+        }
+
+        #[cfg(feature = "minidump")]
+        fn write_minidump(
+            &self,
+            path: &std::path::Path,
+            level: crate::DumpLevel,
+        ) -> std::io::Result<()> {
+            use std::os::windows::io::AsRawHandle;
 
-                return Some(t);
+            let file = std::fs::File::create(path)?;
+            unsafe {
+                let result = MiniDumpWriteDump(
+                    self.win_handle,
+                    GetProcessId(self.win_handle),
+                    HANDLE(file.as_raw_handle() as isize),
+                    level.to_windows(),
+                    std::ptr::null(),
+                    std::ptr::null(),
+                    std::ptr::null(),
+                );
+                if result == 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
             }
+            return Ok(());
         }
 
-        fn read_valid<T>(&self, proc_address: usize, validator: impl Fn(&T) -> bool) -> Option<T> {
+        fn suspend(&self) -> bool {
             unsafe {
-                let mut t: T = std::mem::zeroed();
-                let mut read_bytes = 0;
+                return NtSuspendProcess(self.win_handle) >= 0;
+            }
+        }
 
-                let result = ReadProcessMemory(
+        fn resume(&self) -> bool {
+            unsafe {
+                return NtResumeProcess(self.win_handle) >= 0;
+            }
+        }
+
+        fn command_line(&self) -> Option<String> {
+            let proc = BackendProc::new(*self);
+            return Some(proc.peb()?.parameters(&proc)?.command_line);
+        }
+
+        fn environment(&self) -> Option<Vec<(String, String)>> {
+            let proc = BackendProc::new(*self);
+            return proc.peb()?.parameters(&proc)?.read_environment(&proc);
+        }
+
+        fn name(&self) -> Option<String> {
+            let path = self.exe_path()?;
+            return Some(path.file_name()?.to_string_lossy().into_owned());
+        }
+
+        fn exe_path(&self) -> Option<PathBuf> {
+            unsafe {
+                let mut buffer = [0u16; 1024];
+                let mut size = buffer.len() as u32;
+                let result = QueryFullProcessImageNameW(
                     self.win_handle,
-                    proc_address as *const c_void,
-                    std::ptr::addr_of_mut!(t) as *mut c_void,
-                    std::mem::size_of::<T>(),
-                    &mut read_bytes,
+                    PROCESS_NAME_WIN32,
+                    PWSTR(buffer.as_mut_ptr()),
+                    &mut size,
                 );
                 if !result.as_bool() {
                     return None;
                 }
-                if !validator(&t) {
-                    return None;
-                }
-                return Some(t);
+                return Some(PathBuf::from(String::from_utf16_lossy(
+                    &buffer[..size as usize],
+                )));
             }
         }
 
-        fn read_vec<T>(
-            &self,
-            proc_address: usize,
-            len: usize,
-            default_provider: impl Fn() -> T,
-        ) -> Option<Vec<T>> {
+        fn start_time(&self) -> Option<SystemTime> {
+            //Seconds between the FILETIME epoch (1601) and the UNIX epoch
+            const FILETIME_UNIX_OFFSET: u64 = 11_644_473_600;
             unsafe {
-                let mut vec = Vec::<T>::new();
-                vec.resize_with(len, default_provider);
-                let mut read_bytes = 0;
-
-                let result = ReadProcessMemory(
+                let mut creation = FILETIME::default();
+                let mut exit = FILETIME::default();
+                let mut kernel = FILETIME::default();
+                let mut user = FILETIME::default();
+                let result = GetProcessTimes(
                     self.win_handle,
-                    proc_address as *const c_void,
-                    std::ptr::addr_of_mut!(vec[0]) as *mut c_void,
-                    std::mem::size_of::<T>() * len,
-                    &mut read_bytes,
+                    &mut creation,
+                    &mut exit,
+                    &mut kernel,
+                    &mut user,
                 );
                 if !result.as_bool() {
                     return None;
                 }
-                return Some(vec);
+
+                let intervals =
+                    (creation.dwHighDateTime as u64) << 32 | creation.dwLowDateTime as u64;
+                let since_unix = (intervals / 10_000_000).checked_sub(FILETIME_UNIX_OFFSET)?;
+                return Some(UNIX_EPOCH + Duration::from_secs(since_unix));
             }
         }
 
-        fn pid(&self) -> isize {
-            self.win_handle.0
+        fn parent_pid(&self) -> Option<isize> {
+            return Some(self.basic_information()?[5] as isize);
         }
 
-        fn write<T>(&self, proc_address: usize, data: &T) -> (bool, usize) {
+        fn is_64bit(&self) -> bool {
+            const IMAGE_FILE_MACHINE_UNKNOWN: u16 = 0;
+            const IMAGE_FILE_MACHINE_AMD64: u16 = 0x8664;
+            const IMAGE_FILE_MACHINE_ARM64: u16 = 0xAA64;
             unsafe {
-                let mut write = 0;
-                let result = WriteProcessMemory(
-                    self.win_handle,
-                    proc_address as *const c_void,
-                    std::ptr::addr_of!(*data) as *const c_void,
-                    std::mem::size_of::<T>(),
-                    &mut write,
-                );
-                if !result.as_bool() {
-                    println!("Erro {:?}", GetLastError());
+                let mut process_machine = 0;
+                let mut native_machine = 0;
+                if IsWow64Process2(self.win_handle, &mut process_machine, &mut native_machine) == 0
+                {
+                    return cfg!(target_pointer_width = "64");
+                }
+                //Processes running under WOW64 report their emulated machine
+                if process_machine != IMAGE_FILE_MACHINE_UNKNOWN {
+                    return false;
                 }
-                return (result.as_bool(), write);
+                return native_machine == IMAGE_FILE_MACHINE_AMD64
+                    || native_machine == IMAGE_FILE_MACHINE_ARM64;
             }
         }
+
+        fn pid(&self) -> isize {
+            unsafe { GetProcessId(self.win_handle) as isize }
+        }
     }
 }
 
@@ -227,23 +1737,366 @@ pub mod implementation {
 pub mod implementation {
     use std::{
         ffi::CString,
-        fs::File,
-        io::{Read, Seek, SeekFrom},
+        fs::{File, OpenOptions},
+        io::Read,
+        mem::MaybeUninit,
+        ops::Range,
+        os::unix::io::AsRawFd,
+        path::PathBuf,
+        sync::{Arc, OnceLock},
+        time::{Duration, SystemTime, UNIX_EPOCH},
     };
 
     use libc::pid_t;
 
-    #[derive(Clone, Copy, Debug, Default)]
+    use crate::{
+        log, uninit, BackendProc, MemoryUsage, Module, Proc, Protection, Region, RegionInfo,
+        Sharing, Thread, ThreadState, PAGE_SIZE,
+    };
+
+    ///Error of a pread/pwrite that returned 'count', end of file when nothing could be transferred
+    fn os_error(count: isize) -> std::io::Error {
+        if count < 0 {
+            return std::io::Error::last_os_error();
+        }
+        return std::io::ErrorKind::UnexpectedEof.into();
+    }
+
+    ///Parse a /proc/pid/maps line: start-end perms offset dev inode pathname
+    fn parse_maps_line(line: &str) -> Option<RegionInfo> {
+        let mut fields = line.splitn(6, ' ');
+        let (start, end) = fields.next()?.split_once('-')?;
+        let start = usize::from_str_radix(start, 16).ok()?;
+        let end = usize::from_str_radix(end, 16).ok()?;
+        let perms = fields.next()?;
+        let file = fields
+            .nth(3)
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(str::to_string);
+        //Private file mappings are copied on write, anonymous and pseudo ones like [heap] are plain private memory
+        let sharing = if perms.as_bytes().get(3) == Some(&b's') {
+            Sharing::Shared
+        } else if file.as_ref().is_some_and(|file| !file.starts_with('[')) {
+            Sharing::CopyOnWrite
+        } else {
+            Sharing::Private
+        };
+        return Some(RegionInfo {
+            base: start,
+            size: end - start,
+            protection: Protection::from_maps(perms),
+            file,
+            sharing,
+        });
+    }
+
+    #[derive(Clone, Debug, Default)]
     pub struct ProcLinux {
         handle: libc::pid_t,
+        ///'/proc/<pid>/mem', opened on first access and shared by clones
+        mem: Arc<OnceLock<File>>,
+    }
+
+    impl ProcLinux {
+        fn new(handle: pid_t) -> ProcLinux {
+            return ProcLinux {
+                handle,
+                mem: Arc::new(OnceLock::new()),
+            };
+        }
+
+        ///The mem file of the process, read/write when allowed. Failed opens are retried on the next access.
+        ///The file stays bound to the process it was opened for, so a reused pid can't be read by mistake
+        fn mem(&self) -> Option<&File> {
+            if let Some(mem) = self.mem.get() {
+                return Some(mem);
+            }
+            let path = format!("/proc/{}/mem", self.handle);
+            let mem = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(&path)
+                .or_else(|_| File::open(&path));
+            let mem = match mem {
+                Ok(mem) => mem,
+                Err(error) => {
+                    log::debug("attach", format_args!("opening {} failed: {}", path, error));
+                    return None;
+                }
+            };
+            return Some(self.mem.get_or_init(|| mem));
+        }
+
+        ///Read into 'buffer' from 'address' until it is full or a byte can't be read, returns the read count
+        fn read_at(&self, address: usize, buffer: &mut [MaybeUninit<u8>]) -> usize {
+            let mem = match self.mem() {
+                Some(mem) => mem,
+                None => return 0,
+            };
+            let mut done = 0;
+            while done < buffer.len() {
+                let count = unsafe {
+                    libc::pread(
+                        mem.as_raw_fd(),
+                        buffer.as_mut_ptr().add(done) as *mut libc::c_void,
+                        buffer.len() - done,
+                        (address + done) as libc::off_t,
+                    )
+                };
+                if count <= 0 {
+                    log::debug(
+                        "read",
+                        format_args!(
+                            "{} bytes at {:#X} stopped after {}: {}",
+                            buffer.len(),
+                            address,
+                            done,
+                            os_error(count)
+                        ),
+                    );
+                    break;
+                }
+                done += count as usize;
+            }
+            return done;
+        }
+
+        ///Write 'data' at 'address' until a byte can't be written, returns the written count
+        fn write_at(&self, address: usize, data: &[u8]) -> usize {
+            let mem = match self.mem() {
+                Some(mem) => mem,
+                None => return 0,
+            };
+            let mut done = 0;
+            while done < data.len() {
+                let count = unsafe {
+                    libc::pwrite(
+                        mem.as_raw_fd(),
+                        data.as_ptr().add(done) as *const libc::c_void,
+                        data.len() - done,
+                        (address + done) as libc::off_t,
+                    )
+                };
+                if count <= 0 {
+                    log::debug(
+                        "write",
+                        format_args!(
+                            "{} bytes at {:#X} stopped after {}: {}",
+                            data.len(),
+                            address,
+                            done,
+                            os_error(count)
+                        ),
+                    );
+                    break;
+                }
+                done += count as usize;
+            }
+            return done;
+        }
+
+        ///Every process listed in /proc, which is the host view unless the caller runs in a container itself
+        fn all() -> Vec<ProcLinux> {
+            let entries = match std::fs::read_dir("/proc") {
+                Ok(entries) => entries,
+                Err(_) => return Vec::new(),
+            };
+            return entries
+                .flatten()
+                .filter_map(|entry| entry.file_name().to_str()?.parse::<pid_t>().ok())
+                .map(ProcLinux::new)
+                .collect();
+        }
+
+        ///First process whose command name is 'proc_name', found through /proc when pidof isn't available
+        fn find_by_name(proc_name: &str) -> Option<ProcLinux> {
+            //The kernel truncates command names to 15 bytes
+            let comm = &proc_name.as_bytes()[..proc_name.len().min(15)];
+            return ProcLinux::all().into_iter().find(|proc| {
+                crate::MemoryBackend::name(proc).is_some_and(|name| name.as_bytes() == comm)
+            });
+        }
+
+        ///Field of /proc/<pid>/stat, numbered from 1 like in proc(5)
+        fn stat_field(&self, field: usize) -> Option<String> {
+            let stat = std::fs::read_to_string(format!("/proc/{}/stat", self.handle)).ok()?;
+            //The command name may contain spaces, fields after it start with the state (field 3)
+            let after_name = &stat[stat.rfind(')')? + 1..];
+            return after_name
+                .split_whitespace()
+                .nth(field.checked_sub(3)?)
+                .map(str::to_string);
+        }
+
+        ///Run a system call inside the process, negative results are errno values
+        #[cfg(target_arch = "x86_64")]
+        fn syscall(&self, number: i64, args: [u64; 6]) -> Option<i64> {
+            return crate::syscall::remote_syscall(self.handle, number, args);
+        }
+
+        ///Remote system calls are only implemented for x86_64
+        #[cfg(not(target_arch = "x86_64"))]
+        fn syscall(&self, _number: i64, _args: [u64; 6]) -> Option<i64> {
+            return None;
+        }
+    }
+
+    impl BackendProc<ProcLinux> {
+        ///Open the process with the specified id, None if it doesn't exist
+        pub fn from_pid(pid: u32) -> Option<Proc> {
+            if !std::path::Path::new(&format!("/proc/{}", pid)).exists() {
+                return None;
+            }
+            return Some(BackendProc::new(ProcLinux::new(pid as pid_t)));
+        }
+
+        ///The process that started this one, None if it exited. Orphans are reparented to init or a subreaper
+        pub fn parent(&self) -> Option<Proc> {
+            return Proc::from_pid(crate::ProcT::parent_pid(self)? as u32);
+        }
+
+        ///The processes started by this one and still running
+        pub fn children(&self) -> Vec<Proc> {
+            return ProcLinux::all()
+                .into_iter()
+                .filter(|proc| {
+                    crate::MemoryBackend::parent_pid(proc) == Some(self.backend().handle as isize)
+                })
+                .map(BackendProc::new)
+                .collect();
+        }
+
+        ///Resident, private and committed memory of the process, from /proc/<pid>/status and smaps_rollup
+        pub fn memory_info(&self) -> Option<MemoryUsage> {
+            //Lines like 'VmRSS:     1234 kB'
+            let kilobytes = |text: &str, field: &str| -> Option<usize> {
+                let line = text.lines().find_map(|line| line.strip_prefix(field))?;
+                let value: usize = line
+                    .trim_start_matches(':')
+                    .split_whitespace()
+                    .next()?
+                    .parse()
+                    .ok()?;
+                Some(value * 1024)
+            };
+
+            let status =
+                std::fs::read_to_string(format!("/proc/{}/status", self.backend().handle)).ok()?;
+            let resident = kilobytes(&status, "VmRSS")?;
+            //smaps_rollup needs Linux 4.14, anonymous resident memory is a close approximation
+            let private =
+                std::fs::read_to_string(format!("/proc/{}/smaps_rollup", self.backend().handle))
+                    .ok()
+                    .and_then(|rollup| {
+                        Some(
+                            kilobytes(&rollup, "Private_Clean")?
+                                + kilobytes(&rollup, "Private_Dirty")?,
+                        )
+                    })
+                    .or_else(|| kilobytes(&status, "RssAnon"))
+                    .unwrap_or(0);
+            return Some(MemoryUsage {
+                resident,
+                peak_resident: kilobytes(&status, "VmHWM").unwrap_or(resident),
+                private,
+                commit: kilobytes(&status, "VmData").unwrap_or(0)
+                    + kilobytes(&status, "VmStk").unwrap_or(0),
+            });
+        }
+
+        ///Paths of the files the process has open, from the links in /proc/<pid>/fd.
+        ///Sockets, pipes and other descriptors without a path are skipped
+        pub fn open_files(&self) -> Vec<PathBuf> {
+            let entries = match std::fs::read_dir(format!("/proc/{}/fd", self.backend().handle)) {
+                Ok(entries) => entries,
+                Err(_) => return Vec::new(),
+            };
+            let mut files: Vec<PathBuf> = entries
+                .flatten()
+                .filter_map(|entry| std::fs::read_link(entry.path()).ok())
+                .filter(|path| path.is_absolute())
+                .collect();
+            files.sort();
+            files.dedup();
+            return files;
+        }
+
+        ///Ids of the process in each nested PID namespace from the NSpid line of /proc/<pid>/status,
+        ///the host pid first and the pid seen inside the innermost namespace (e.g. a container) last
+        pub fn namespace_pids(&self) -> Vec<u32> {
+            let status = std::fs::read_to_string(format!("/proc/{}/status", self.backend().handle));
+            let pids = status.ok().and_then(|status| {
+                let line = status
+                    .lines()
+                    .find_map(|line| line.strip_prefix("NSpid:"))?;
+                line.split_whitespace()
+                    .map(|pid| pid.parse().ok())
+                    .collect::<Option<Vec<u32>>>()
+            });
+            //Kernels older than 4.1 don't report NSpid, exited processes can report an empty one
+            return pids
+                .filter(|pids| !pids.is_empty())
+                .unwrap_or_else(|| vec![self.backend().handle as u32]);
+        }
+
+        ///Pid of the process as seen inside its own PID namespace, 1 for the init of a container
+        pub fn namespace_pid(&self) -> u32 {
+            return self
+                .namespace_pids()
+                .last()
+                .copied()
+                .unwrap_or(self.backend().handle as u32);
+        }
+
+        ///Inode identifying the PID namespace of the process
+        pub fn pid_namespace(&self) -> Option<u64> {
+            let link =
+                std::fs::read_link(format!("/proc/{}/ns/pid", self.backend().handle)).ok()?;
+            //pid:[4026531836]
+            let link = link.to_str()?;
+            return link.strip_prefix("pid:[")?.strip_suffix(']')?.parse().ok();
+        }
+
+        ///Returns 'true' if the process lives in a different PID namespace than the caller, like a container
+        pub fn is_in_other_namespace(&self) -> bool {
+            return self.pid_namespace() != Proc::current().pid_namespace();
+        }
+
+        ///Open the process known as 'namespace_pid' inside the PID namespace of 'member', e.g. the pid printed by
+        ///'docker top' or logged by the containerized application, with any process of that container as member
+        pub fn from_namespace_pid(member: &Proc, namespace_pid: u32) -> Option<Proc> {
+            let namespace = member.pid_namespace()?;
+            return ProcLinux::all()
+                .into_iter()
+                .map(BackendProc::new)
+                .find(|proc| {
+                    proc.namespace_pid() == namespace_pid && proc.pid_namespace() == Some(namespace)
+                });
+        }
+
+        ///The calling process
+        pub(crate) fn current() -> Proc {
+            return BackendProc::new(ProcLinux::new(unsafe { libc::getpid() }));
+        }
+
+        ///Address range and protection of the mapping containing 'address'
+        pub(crate) fn region(&self, address: usize) -> Option<(Range<usize>, Protection)> {
+            let info = crate::ProcT::query(self, address)?;
+            return Some((info.range(), info.protection));
+        }
     }
 
-    impl crate::ProcT for ProcLinux {
-        fn get(proc_name: &str) -> Option<Proc> {
+    impl crate::MemoryBackend for ProcLinux {
+        fn get(proc_name: &str) -> Option<Self> {
+            let _span = log::span("attach", format_args!("looking up {}", proc_name));
             unsafe {
-                let pid_cmd = CString::new(format!("pidof -s {}", proc_name)).unwrap();
+                let pid_cmd = CString::new(format!("pidof -s {}", proc_name)).ok()?;
                 let mode_c = CString::new("r").unwrap();
                 let pid_pipe = libc::popen(pid_cmd.as_c_str().as_ptr(), mode_c.as_c_str().as_ptr());
+                if pid_pipe.is_null() {
+                    return ProcLinux::find_by_name(proc_name);
+                }
 
                 let mut buff = [0i8; 512];
                 libc::fgets(&mut buff[0], 512, pid_pipe);
@@ -253,104 +2106,388 @@ pub mod implementation {
                 libc::pclose(pid_pipe);
 
                 if pid == 0 {
-                    return None;
-                } else {
-                    println!("PID: {}", pid);
+                    //Minimal images, like most containers, don't ship pidof
+                    return ProcLinux::find_by_name(proc_name);
                 }
+                log::debug(
+                    "attach",
+                    format_args!("found {} with pid {}", proc_name, pid),
+                );
 
-                Some(ProcLinux {
-                    handle: pid as pid_t,
-                })
+                Some(ProcLinux::new(pid as pid_t))
             }
         }
 
-        fn read<T>(&self, proc_address: usize) -> Option<T> {
-            unsafe {
-                let mut temp: T = std::mem::zeroed();
-                let proc_file = format!("/proc/{}/mem", self.handle);
-                let mem_f = File::open(proc_file);
+        fn read_raw(&self, address: usize, buffer: &mut [u8]) -> usize {
+            let buffer = unsafe { &mut *(buffer as *mut [u8] as *mut [MaybeUninit<u8>]) };
+            return self.read_at(address, buffer);
+        }
+
+        fn read_uninit(&self, address: usize, buffer: &mut [MaybeUninit<u8>]) -> usize {
+            return self.read_at(address, buffer);
+        }
+
+        fn write_raw(&self, address: usize, data: &[u8]) -> usize {
+            return self.write_at(address, data);
+        }
 
-                if let Ok(mut mem) = mem_f {
-                    let _ = mem.seek(SeekFrom::Start(proc_address as u64)).unwrap();
+        fn read_batch(&self, requests: &[(usize, usize)]) -> Vec<Option<Vec<u8>>> {
+            const MAX_IOVECS: usize = 1024;
 
-                    let dst_ptr = &mut temp as *mut T as *mut u8;
-                    let mut buffer =
-                        std::slice::from_raw_parts_mut(dst_ptr, std::mem::size_of::<T>());
+            let mut results: Vec<Option<Vec<u8>>> = requests
+                .iter()
+                .map(|(_, len)| Some(vec![0u8; *len]))
+                .collect();
+            let mut next = 0;
+            while next < requests.len() {
+                let end = (next + MAX_IOVECS).min(requests.len());
+                let local: Vec<libc::iovec> = results[next..end]
+                    .iter_mut()
+                    .flatten()
+                    .map(|buffer| libc::iovec {
+                        iov_base: buffer.as_mut_ptr() as *mut libc::c_void,
+                        iov_len: buffer.len(),
+                    })
+                    .collect();
+                let remote: Vec<libc::iovec> = requests[next..end]
+                    .iter()
+                    .map(|(address, len)| libc::iovec {
+                        iov_base: *address as *mut libc::c_void,
+                        iov_len: *len,
+                    })
+                    .collect();
 
-                    if let Ok(()) = mem.read_exact(&mut buffer) {
-                        return Some(temp);
+                let read = unsafe {
+                    libc::process_vm_readv(
+                        self.handle,
+                        local.as_ptr(),
+                        local.len() as libc::c_ulong,
+                        remote.as_ptr(),
+                        remote.len() as libc::c_ulong,
+                        0,
+                    )
+                };
+                if read < 0 {
+                    if std::io::Error::last_os_error().raw_os_error() == Some(libc::EFAULT) {
+                        results[next] = None;
+                        next += 1;
                     } else {
-                        return None;
+                        //process_vm_readv is unavailable, read through /proc/<pid>/mem instead
+                        for index in next..end {
+                            let (address, len) = requests[index];
+                            results[index] = uninit::read_values(len, |buffer| {
+                                self.read_at(address, buffer) == buffer.len()
+                            });
+                        }
+                        next = end;
                     }
-                };
-                return None;
+                    continue;
+                }
+
+                //The transfer stops at the first range that can't be read completely
+                let mut remaining = read as usize;
+                while next < end && remaining >= requests[next].1 {
+                    remaining -= requests[next].1;
+                    next += 1;
+                }
+                if next < end {
+                    results[next] = None;
+                    next += 1;
+                }
             }
+            return results;
         }
 
-        fn read_valid<T>(&self, proc_address: usize, validator: impl Fn(&T) -> bool) -> Option<T> {
-            unsafe {
-                let mut temp: T = std::mem::zeroed();
-                let proc_file = format!("/proc/{}/mem", self.handle);
-                let mem_f = File::open(proc_file);
-
-                if let Ok(mut mem) = mem_f {
-                    let _ = mem.seek(SeekFrom::Start(proc_address as u64)).unwrap();
-
-                    let dst_ptr = &mut temp as *mut T as *mut u8;
-                    let mut buffer =
-                        std::slice::from_raw_parts_mut(dst_ptr, std::mem::size_of::<T>());
-
-                    if let Ok(()) = mem.read_exact(&mut buffer) {
-                        if validator(&temp) {
-                            return Some(temp);
-                        } else {
-                            return None;
-                        }
-                    } else {
-                        return None;
-                    }
+        fn modules(&self) -> Vec<Module> {
+            let mut modules: Vec<Module> = Vec::new();
+            let maps = match std::fs::read_to_string(format!("/proc/{}/maps", self.handle)) {
+                Ok(maps) => maps,
+                Err(_) => return modules,
+            };
+
+            for line in maps.lines() {
+                //start-end perms offset dev inode pathname
+                let mut fields = line.splitn(6, ' ');
+                let range = fields.next().unwrap_or("");
+                let path = fields.nth(4).unwrap_or("").trim();
+                if !path.starts_with('/') {
+                    continue;
                 }
 
-                return None;
+                let (start, end) = match range.split_once('-') {
+                    Some((start, end)) => (
+                        usize::from_str_radix(start, 16).unwrap_or(0),
+                        usize::from_str_radix(end, 16).unwrap_or(0),
+                    ),
+                    None => continue,
+                };
+
+                if let Some(module) = modules.iter_mut().find(|module| module.path == path) {
+                    let module_end = (module.base + module.size).max(end);
+                    module.base = module.base.min(start);
+                    module.size = module_end - module.base;
+                } else {
+                    modules.push(Module {
+                        name: path.rsplit('/').next().unwrap_or(path).to_string(),
+                        path: path.to_string(),
+                        base: start,
+                        size: end - start,
+                    });
+                }
+            }
+
+            return modules;
+        }
+
+        fn regions(&self) -> Vec<Region> {
+            let maps = match std::fs::read_to_string(format!("/proc/{}/maps", self.handle)) {
+                Ok(maps) => maps,
+                Err(_) => return Vec::new(),
+            };
+
+            return maps
+                .lines()
+                .filter_map(|line| {
+                    let mut fields = line.split(' ');
+                    let (start, end) = fields.next()?.split_once('-')?;
+                    let start = usize::from_str_radix(start, 16).ok()?;
+                    let end = usize::from_str_radix(end, 16).ok()?;
+                    Some(Region {
+                        base: start,
+                        size: end - start,
+                        protection: Protection::from_maps(fields.next()?),
+                    })
+                })
+                .collect();
+        }
+
+        fn query(&self, address: usize) -> Option<RegionInfo> {
+            let maps = std::fs::read_to_string(format!("/proc/{}/maps", self.handle)).ok()?;
+            return maps
+                .lines()
+                .filter_map(parse_maps_line)
+                .find(|info| info.contains(address));
+        }
+
+        fn region_infos(&self) -> Vec<RegionInfo> {
+            let maps = match std::fs::read_to_string(format!("/proc/{}/maps", self.handle)) {
+                Ok(maps) => maps,
+                Err(_) => return Vec::new(),
+            };
+            return maps.lines().filter_map(parse_maps_line).collect();
+        }
+
+        fn threads(&self) -> Vec<Thread> {
+            let mut threads = Vec::new();
+            let tasks = match std::fs::read_dir(format!("/proc/{}/task", self.handle)) {
+                Ok(tasks) => tasks,
+                Err(_) => return threads,
+            };
+
+            for task in tasks.flatten() {
+                let id: u32 = match task.file_name().to_string_lossy().parse() {
+                    Ok(id) => id,
+                    Err(_) => continue,
+                };
+                let stat = std::fs::read_to_string(task.path().join("stat")).unwrap_or_default();
+                //State is the first field after the command name
+                let state = match stat
+                    .rsplit(')')
+                    .next()
+                    .and_then(|rest| rest.trim().chars().next())
+                {
+                    Some('R') => ThreadState::Running,
+                    Some('S') | Some('I') => ThreadState::Sleeping,
+                    Some('D') => ThreadState::DiskSleep,
+                    Some('T') | Some('t') => ThreadState::Stopped,
+                    Some('Z') => ThreadState::Zombie,
+                    Some('X') => ThreadState::Terminated,
+                    _ => ThreadState::Unknown,
+                };
+
+                threads.push(Thread {
+                    id,
+                    process_id: self.handle as u32,
+                    start_address: None,
+                    state,
+                });
             }
+
+            threads.sort_by_key(|thread| thread.id);
+            return threads;
         }
 
-        fn read_vec<T>(
+        fn is_alive(&self) -> bool {
+            return std::path::Path::new(&format!("/proc/{}", self.handle)).exists();
+        }
+
+        fn protect(
             &self,
-            proc_address: usize,
+            address: usize,
             len: usize,
-            default_provider: impl Fn() -> T,
-        ) -> Option<Vec<T>> {
-            unsafe {
-                let mut temp = Vec::<T>::new();
-                temp.resize_with(len, default_provider);
+            protection: Protection,
+        ) -> Option<Protection> {
+            let old = crate::MemoryBackend::query(self, address)?.protection;
+            let start = address & !(PAGE_SIZE - 1);
+            let len = address + len - start;
+            let result = self.syscall(
+                libc::SYS_mprotect,
+                [
+                    start as u64,
+                    len as u64,
+                    protection.to_prot() as u64,
+                    0,
+                    0,
+                    0,
+                ],
+            )?;
+            if result != 0 {
+                return None;
+            }
+            return Some(old);
+        }
+
+        fn alloc(&self, len: usize, protection: Protection) -> Option<usize> {
+            let result = self.syscall(
+                libc::SYS_mmap,
+                [
+                    0,
+                    len as u64,
+                    protection.to_prot() as u64,
+                    (libc::MAP_PRIVATE | libc::MAP_ANONYMOUS) as u64,
+                    u64::MAX,
+                    0,
+                ],
+            )?;
+            //Results between -4095 and -1 are errno values
+            if (-4095..0).contains(&result) {
+                return None;
+            }
+            return Some(result as usize);
+        }
+
+        fn free(&self, address: usize, len: usize) -> bool {
+            let result = self.syscall(libc::SYS_munmap, [address as u64, len as u64, 0, 0, 0, 0]);
+            return result == Some(0);
+        }
+
+        #[cfg(target_arch = "x86_64")]
+        fn create_thread(&self, start: usize, param: usize) -> Option<u32> {
+            return crate::syscall::remote_thread(self.handle, start as u64, param as u64);
+        }
+
+        #[cfg(not(target_arch = "x86_64"))]
+        fn create_thread(&self, _start: usize, _param: usize) -> Option<u32> {
+            return None;
+        }
 
-                let proc_file = format!("/proc/{}/mem", self.handle);
-                let mem_f = File::open(proc_file);
+        #[cfg(target_arch = "x86_64")]
+        fn create_thread_and_wait(&self, start: usize, param: usize) -> Option<u32> {
+            let result = crate::syscall::remote_call(self.handle, start as u64, &[param as u64])?;
+            return Some(result as u32);
+        }
+
+        #[cfg(not(target_arch = "x86_64"))]
+        fn create_thread_and_wait(&self, _start: usize, _param: usize) -> Option<u32> {
+            return None;
+        }
+
+        #[cfg(feature = "minidump")]
+        fn write_minidump(
+            &self,
+            path: &std::path::Path,
+            level: crate::DumpLevel,
+        ) -> std::io::Result<()> {
+            return crate::minidump::write_core(&BackendProc::new(self.clone()), path, level);
+        }
 
-                if let Ok(mut mem) = mem_f {
-                    let _ = mem.seek(SeekFrom::Start(proc_address as u64)).unwrap();
+        fn suspend(&self) -> bool {
+            unsafe {
+                return libc::kill(self.handle, libc::SIGSTOP) == 0;
+            }
+        }
 
-                    let dst_ptr = &mut temp[0] as *mut T as *mut u8;
-                    let mut buffer =
-                        std::slice::from_raw_parts_mut(dst_ptr, std::mem::size_of::<T>() * len);
+        fn resume(&self) -> bool {
+            unsafe {
+                return libc::kill(self.handle, libc::SIGCONT) == 0;
+            }
+        }
 
-                    if let Ok(()) = mem.read_exact(&mut buffer) {
-                        return Some(temp);
+        fn command_line(&self) -> Option<String> {
+            let cmdline = std::fs::read(format!("/proc/{}/cmdline", self.handle)).ok()?;
+            //Every argument is NUL terminated, empty ones are kept quoted so they stay visible
+            let cmdline = cmdline.strip_suffix(&[0]).unwrap_or(&cmdline);
+            if cmdline.is_empty() {
+                return Some(String::new());
+            }
+            let args: Vec<String> = cmdline
+                .split(|c| *c == 0)
+                .map(|arg| {
+                    let arg = String::from_utf8_lossy(arg);
+                    if arg.is_empty() || arg.contains(' ') {
+                        format!("\"{}\"", arg)
                     } else {
-                        return None;
+                        arg.into_owned()
                     }
-                };
+                })
+                .collect();
+            return Some(args.join(" "));
+        }
+
+        fn environment(&self) -> Option<Vec<(String, String)>> {
+            let environ = std::fs::read(format!("/proc/{}/environ", self.handle)).ok()?;
+            let environ = String::from_utf8_lossy(&environ);
+            return Some(crate::peb::split_environment(&environ));
+        }
+
+        fn name(&self) -> Option<String> {
+            let comm = std::fs::read_to_string(format!("/proc/{}/comm", self.handle)).ok()?;
+            return Some(comm.trim_end().to_string());
+        }
+
+        fn exe_path(&self) -> Option<PathBuf> {
+            return std::fs::read_link(format!("/proc/{}/exe", self.handle)).ok();
+        }
+
+        fn start_time(&self) -> Option<SystemTime> {
+            //Start time is stored in clock ticks since boot
+            let ticks: u64 = self.stat_field(22)?.parse().ok()?;
+            let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+            if ticks_per_second <= 0 {
                 return None;
             }
+
+            let stat = std::fs::read_to_string("/proc/stat").ok()?;
+            let boot_time: u64 = stat
+                .lines()
+                .find_map(|line| line.strip_prefix("btime "))?
+                .trim()
+                .parse()
+                .ok()?;
+
+            let since_boot = Duration::from_millis(ticks * 1000 / ticks_per_second as u64);
+            return Some(UNIX_EPOCH + Duration::from_secs(boot_time) + since_boot);
         }
 
-        fn pid(&self) -> isize {
-            self.handle as isize
+        fn parent_pid(&self) -> Option<isize> {
+            return self.stat_field(4)?.parse().ok();
         }
 
-        fn write<T>(&self, proc_address: usize, data: &T) -> (bool, usize) {
-            todo!()
+        fn is_64bit(&self) -> bool {
+            //ELF class of the executable: 1 for 32-bit, 2 for 64-bit
+            let mut header = [0u8; 5];
+            let class = File::open(format!("/proc/{}/exe", self.handle))
+                .and_then(|mut exe| exe.read_exact(&mut header))
+                .map(|_| header[4]);
+            return match class {
+                Ok(1) => false,
+                Ok(2) => true,
+                _ => cfg!(target_pointer_width = "64"),
+            };
+        }
+
+        fn pid(&self) -> isize {
+            self.handle as isize
         }
     }
 }
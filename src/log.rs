@@ -0,0 +1,101 @@
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+///Verbosity of a log record, from the most to the least important
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    ///Details of operations, like the OS error of failed reads
+    Debug,
+    ///Start and end of spans: attach, scans
+    Trace,
+}
+
+///Diagnostic emitted by the crate
+#[derive(Clone, Copy, Debug)]
+pub struct LogRecord<'a> {
+    pub level: LogLevel,
+    ///Operation the record belongs to: "attach", "read", "write" or "scan"
+    pub operation: &'static str,
+    pub message: fmt::Arguments<'a>,
+}
+
+type Logger = Arc<dyn Fn(&LogRecord<'_>) + Send + Sync>;
+
+static LOGGER: RwLock<Option<Logger>> = RwLock::new(None);
+///Most verbose level passed to the logger plus one, zero when there is no logger
+static MAX_LEVEL: AtomicUsize = AtomicUsize::new(0);
+
+///Pass the records up to 'level' to 'logger', replacing the previous one. The crate logs nothing by default,
+///forward the records to the log or tracing crates, or anything else, from here
+///
+///```
+///use proc_memory::LogLevel;
+///
+///proc_memory::set_logger(LogLevel::Debug, |record| {
+///    eprintln!("[{:?}] {}: {}", record.level, record.operation, record.message);
+///});
+///```
+pub fn set_logger(level: LogLevel, logger: impl Fn(&LogRecord<'_>) + Send + Sync + 'static) {
+    *LOGGER.write().unwrap() = Some(Arc::new(logger));
+    MAX_LEVEL.store(level as usize + 1, Ordering::Relaxed);
+}
+
+///Stop logging
+pub fn clear_logger() {
+    MAX_LEVEL.store(0, Ordering::Relaxed);
+    *LOGGER.write().unwrap() = None;
+}
+
+///Returns 'true' if records of 'level' reach the logger, to skip building expensive messages
+pub(crate) fn enabled(level: LogLevel) -> bool {
+    (level as usize) < MAX_LEVEL.load(Ordering::Relaxed)
+}
+
+pub(crate) fn log(level: LogLevel, operation: &'static str, message: fmt::Arguments<'_>) {
+    if !enabled(level) {
+        return;
+    }
+    //Clone the logger so it can log or replace itself without deadlocking
+    let logger = LOGGER.read().unwrap().clone();
+    if let Some(logger) = logger {
+        logger(&LogRecord {
+            level,
+            operation,
+            message,
+        });
+    }
+}
+
+pub(crate) fn debug(operation: &'static str, message: fmt::Arguments<'_>) {
+    log(LogLevel::Debug, operation, message);
+}
+
+///Logs the end of an operation and how long it took when dropped
+pub(crate) struct Span {
+    operation: &'static str,
+    started: Instant,
+}
+
+///Log the start of 'operation', the returned Span logs its end
+pub(crate) fn span(operation: &'static str, message: fmt::Arguments<'_>) -> Span {
+    log(LogLevel::Trace, operation, message);
+    Span {
+        operation,
+        started: Instant::now(),
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        log(
+            LogLevel::Trace,
+            self.operation,
+            format_args!("done in {:?}", self.started.elapsed()),
+        );
+    }
+}
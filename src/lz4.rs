@@ -0,0 +1,434 @@
+use std::convert::TryInto;
+use std::io::{self, Error, ErrorKind, Read, Write};
+
+const MAGIC: u32 = 0x184D_2204;
+///Frame descriptor flags: version 01, independent blocks, no checksums or content size
+const FLG: u8 = 0x60;
+///Frame descriptor block size: 4 MB
+const BD: u8 = 0x70;
+const BLOCK_SIZE: usize = 4 << 20;
+///Block size word flag of blocks stored uncompressed
+const UNCOMPRESSED: u32 = 0x8000_0000;
+
+const MIN_MATCH: usize = 4;
+///The last literals of a block, a match can't end closer to the end
+const LAST_LITERALS: usize = 5;
+///A match can't start closer to the end of a block
+const MF_LIMIT: usize = 12;
+const MAX_OFFSET: usize = 0xFFFF;
+const HASH_BITS: u32 = 16;
+
+const PRIME32_1: u32 = 0x9E37_79B1;
+const PRIME32_2: u32 = 0x85EB_CA77;
+const PRIME32_3: u32 = 0xC2B2_AE3D;
+const PRIME32_4: u32 = 0x27D4_EB2F;
+const PRIME32_5: u32 = 0x1656_67B1;
+
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+///xxHash32 of 'data', used for the frame header checksum
+fn xxh32(data: &[u8], seed: u32) -> u32 {
+    let round = |acc: u32, lane: u32| {
+        acc.wrapping_add(lane.wrapping_mul(PRIME32_2))
+            .rotate_left(13)
+            .wrapping_mul(PRIME32_1)
+    };
+
+    let mut offset = 0;
+    let mut hash = if data.len() >= 16 {
+        let mut lanes = [
+            seed.wrapping_add(PRIME32_1).wrapping_add(PRIME32_2),
+            seed.wrapping_add(PRIME32_2),
+            seed,
+            seed.wrapping_sub(PRIME32_1),
+        ];
+        while offset + 16 <= data.len() {
+            for (index, lane) in lanes.iter_mut().enumerate() {
+                *lane = round(*lane, u32_at(data, offset + index * 4));
+            }
+            offset += 16;
+        }
+        lanes[0]
+            .rotate_left(1)
+            .wrapping_add(lanes[1].rotate_left(7))
+            .wrapping_add(lanes[2].rotate_left(12))
+            .wrapping_add(lanes[3].rotate_left(18))
+    } else {
+        seed.wrapping_add(PRIME32_5)
+    };
+    hash = hash.wrapping_add(data.len() as u32);
+
+    while offset + 4 <= data.len() {
+        hash = hash
+            .wrapping_add(u32_at(data, offset).wrapping_mul(PRIME32_3))
+            .rotate_left(17)
+            .wrapping_mul(PRIME32_4);
+        offset += 4;
+    }
+    for byte in &data[offset..] {
+        hash = hash
+            .wrapping_add((*byte as u32).wrapping_mul(PRIME32_5))
+            .rotate_left(11)
+            .wrapping_mul(PRIME32_1);
+    }
+
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(PRIME32_2);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(PRIME32_3);
+    hash ^ (hash >> 16)
+}
+
+///Length of a literal run or match above the 4 bits of the token, as 255 bytes followed by the rest
+fn put_length(out: &mut Vec<u8>, mut len: usize) {
+    while len >= 255 {
+        out.push(255);
+        len -= 255;
+    }
+    out.push(len as u8);
+}
+
+fn put_sequence(out: &mut Vec<u8>, literals: &[u8], offset: usize, match_len: Option<usize>) {
+    let literal_token = literals.len().min(15) as u8;
+    let match_token = match_len.map_or(0, |len| (len - MIN_MATCH).min(15) as u8);
+    out.push(literal_token << 4 | match_token);
+    if literals.len() >= 15 {
+        put_length(out, literals.len() - 15);
+    }
+    out.extend_from_slice(literals);
+    if let Some(len) = match_len {
+        out.extend_from_slice(&(offset as u16).to_le_bytes());
+        if len - MIN_MATCH >= 15 {
+            put_length(out, len - MIN_MATCH - 15);
+        }
+    }
+}
+
+///Compress 'input' as one LZ4 block, greedily taking the last position with the same 4 bytes as match
+fn compress_block(input: &[u8], table: &mut [u32]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len() / 2);
+    let hash = |position: usize| {
+        (u32_at(input, position).wrapping_mul(PRIME32_1) >> (32 - HASH_BITS)) as usize
+    };
+    table.iter_mut().for_each(|entry| *entry = u32::MAX);
+
+    let mut anchor = 0;
+    let mut position = 0;
+    if input.len() > MF_LIMIT {
+        let match_limit = input.len() - LAST_LITERALS;
+        while position < input.len() - MF_LIMIT {
+            let slot = hash(position);
+            let candidate = table[slot] as usize;
+            table[slot] = position as u32;
+            if candidate == u32::MAX as usize
+                || position - candidate > MAX_OFFSET
+                || input[candidate..candidate + 4] != input[position..position + 4]
+            {
+                position += 1;
+                continue;
+            }
+
+            let mut len = MIN_MATCH;
+            while position + len < match_limit && input[candidate + len] == input[position + len] {
+                len += 1;
+            }
+            put_sequence(
+                &mut out,
+                &input[anchor..position],
+                position - candidate,
+                Some(len),
+            );
+            position += len;
+            anchor = position;
+        }
+    }
+    put_sequence(&mut out, &input[anchor..], 0, None);
+    out
+}
+
+fn corrupt() -> Error {
+    Error::new(ErrorKind::InvalidData, "corrupted LZ4 data")
+}
+
+///Byte at 'position' of a block being decompressed, advancing past it
+fn next_byte(input: &[u8], position: &mut usize) -> io::Result<u8> {
+    let byte = *input.get(*position).ok_or_else(corrupt)?;
+    *position += 1;
+    Ok(byte)
+}
+
+///Length of a literal run or match whose token field is 'len', adding the extra bytes when it is 15
+fn read_length(input: &[u8], position: &mut usize, mut len: usize) -> io::Result<usize> {
+    if len == 15 {
+        loop {
+            let byte = next_byte(input, position)?;
+            len += byte as usize;
+            if byte != 255 {
+                break;
+            }
+        }
+    }
+    Ok(len)
+}
+
+///Decompress one LZ4 block, appending it to 'out'. The bytes already in 'out' can be referenced by matches.
+///Blocks decompressing to more than 'max_block' bytes are corrupt
+fn decompress_block(input: &[u8], out: &mut Vec<u8>, max_block: usize) -> io::Result<()> {
+    let limit = out.len() + max_block;
+    let mut position = 0;
+    loop {
+        let token = next_byte(input, &mut position)?;
+        let literals = read_length(input, &mut position, (token >> 4) as usize)?;
+        if literals > limit - out.len() {
+            return Err(corrupt());
+        }
+        let end = position.checked_add(literals).ok_or_else(corrupt)?;
+        out.extend_from_slice(input.get(position..end).ok_or_else(corrupt)?);
+        position = end;
+        if position == input.len() {
+            return Ok(());
+        }
+
+        let offset = next_byte(input, &mut position)? as usize
+            | (next_byte(input, &mut position)? as usize) << 8;
+        let len = read_length(input, &mut position, (token & 0xF) as usize)? + MIN_MATCH;
+        if offset == 0 || offset > out.len() || len > limit - out.len() {
+            return Err(corrupt());
+        }
+        let start = out.len() - offset;
+        for index in 0..len {
+            out.push(out[start + index]);
+        }
+    }
+}
+
+///Writer compressing everything written to it as an LZ4 frame, readable by Lz4Reader and the lz4 command line
+///tool. Call finish() to write the end of the frame, dropping the writer without it leaves a truncated frame
+///
+///```
+///use std::io::{Read, Write};
+///use proc_memory::{Lz4Reader, Lz4Writer};
+///
+///let mut writer = Lz4Writer::new(Vec::new()).unwrap();
+///writer.write_all(&[7u8; 4096]).unwrap();
+///let compressed = writer.finish().unwrap();
+///assert!(compressed.len() < 100);
+///
+///let mut data = Vec::new();
+///Lz4Reader::new(compressed.as_slice()).read_to_end(&mut data).unwrap();
+///assert_eq!(data, vec![7u8; 4096]);
+///```
+pub struct Lz4Writer<W: Write> {
+    inner: W,
+    block: Vec<u8>,
+    table: Vec<u32>,
+}
+
+impl<W: Write> Lz4Writer<W> {
+    ///Start a frame on 'inner'
+    pub fn new(mut inner: W) -> io::Result<Lz4Writer<W>> {
+        let checksum = (xxh32(&[FLG, BD], 0) >> 8) as u8;
+        let mut header = MAGIC.to_le_bytes().to_vec();
+        header.extend_from_slice(&[FLG, BD, checksum]);
+        inner.write_all(&header)?;
+        Ok(Lz4Writer {
+            inner,
+            block: Vec::with_capacity(BLOCK_SIZE),
+            table: vec![0; 1 << HASH_BITS],
+        })
+    }
+
+    fn write_block(&mut self) -> io::Result<()> {
+        if self.block.is_empty() {
+            return Ok(());
+        }
+        let compressed = compress_block(&self.block, &mut self.table);
+        if compressed.len() < self.block.len() {
+            self.inner
+                .write_all(&(compressed.len() as u32).to_le_bytes())?;
+            self.inner.write_all(&compressed)?;
+        } else {
+            let size = self.block.len() as u32 | UNCOMPRESSED;
+            self.inner.write_all(&size.to_le_bytes())?;
+            self.inner.write_all(&self.block)?;
+        }
+        self.block.clear();
+        Ok(())
+    }
+
+    ///Compress the buffered data and end the frame, returns the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_block()?;
+        self.inner.write_all(&0u32.to_le_bytes())?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for Lz4Writer<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let len = data.len().min(BLOCK_SIZE - self.block.len());
+        self.block.extend_from_slice(&data[..len]);
+        if self.block.len() == BLOCK_SIZE {
+            self.write_block()?;
+        }
+        Ok(len)
+    }
+
+    ///Flushes the inner writer, data is only compressed once a whole block is buffered or on finish()
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+///Reader decompressing LZ4 frames, like the ones written by Lz4Writer or the lz4 command line tool.
+///Concatenated frames are read one after the other, checksums are skipped without being verified
+pub struct Lz4Reader<R: Read> {
+    inner: R,
+    ///Decompressed bytes, the ones before 'position' were already returned and are kept as the window of
+    ///linked blocks
+    buffer: Vec<u8>,
+    position: usize,
+    ///Flags of the current frame, None between frames
+    flags: Option<u8>,
+    max_block: usize,
+}
+
+impl<R: Read> Lz4Reader<R> {
+    pub fn new(inner: R) -> Lz4Reader<R> {
+        Lz4Reader {
+            inner,
+            buffer: Vec::new(),
+            position: 0,
+            flags: None,
+            max_block: BLOCK_SIZE,
+        }
+    }
+
+    fn read_u32(&mut self) -> io::Result<u32> {
+        let mut bytes = [0u8; 4];
+        self.inner.read_exact(&mut bytes)?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    ///Read the header of the next frame, returns 'false' at the end of the input
+    fn start_frame(&mut self) -> io::Result<bool> {
+        let mut magic = [0u8; 4];
+        let mut filled = 0;
+        while filled < 4 {
+            match self.inner.read(&mut magic[filled..])? {
+                0 if filled == 0 => return Ok(false),
+                0 => return Err(Error::from(ErrorKind::UnexpectedEof)),
+                count => filled += count,
+            }
+        }
+        let magic = u32::from_le_bytes(magic);
+
+        //Skippable frames carry user data, 0x184D2A50 to 0x184D2A5F
+        if magic & 0xFFFF_FFF0 == 0x184D_2A50 {
+            let len = self.read_u32()? as u64;
+            io::copy(&mut (&mut self.inner).take(len), &mut io::sink())?;
+            return self.start_frame();
+        }
+        if magic != MAGIC {
+            return Err(Error::new(ErrorKind::InvalidData, "not an LZ4 frame"));
+        }
+
+        let mut descriptor = [0u8; 2];
+        self.inner.read_exact(&mut descriptor)?;
+        let [flags, bd] = descriptor;
+        if flags >> 6 != 1 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "unsupported LZ4 frame version",
+            ));
+        }
+        let mut header = descriptor.to_vec();
+        let optional =
+            if flags & 0x08 != 0 { 8 } else { 0 } + if flags & 0x01 != 0 { 4 } else { 0 };
+        let mut rest = vec![0u8; optional + 1];
+        self.inner.read_exact(&mut rest)?;
+        header.extend_from_slice(&rest[..optional]);
+        if (xxh32(&header, 0) >> 8) as u8 != rest[optional] {
+            return Err(corrupt());
+        }
+
+        self.max_block = match (bd >> 4) & 7 {
+            4 => 64 << 10,
+            5 => 256 << 10,
+            6 => 1 << 20,
+            7 => 4 << 20,
+            _ => return Err(corrupt()),
+        };
+        self.flags = Some(flags);
+        Ok(true)
+    }
+
+    ///Decompress the next block into the buffer, returns 'false' at the end of the input
+    fn fill(&mut self) -> io::Result<bool> {
+        loop {
+            let flags = match self.flags {
+                Some(flags) => flags,
+                None => {
+                    if !self.start_frame()? {
+                        return Ok(false);
+                    }
+                    continue;
+                }
+            };
+
+            let size = self.read_u32()?;
+            if size == 0 {
+                if flags & 0x04 != 0 {
+                    self.read_u32()?;
+                }
+                self.flags = None;
+                continue;
+            }
+            let len = (size & !UNCOMPRESSED) as usize;
+            if len > self.max_block {
+                return Err(corrupt());
+            }
+            let mut block = vec![0u8; len];
+            self.inner.read_exact(&mut block)?;
+            if flags & 0x10 != 0 {
+                self.read_u32()?;
+            }
+
+            //Keep the last 64 KB returned, matches of linked blocks can reach back into them
+            let keep = self.position.min(MAX_OFFSET + 1);
+            self.buffer.drain(..self.position - keep);
+            self.position = keep;
+            if flags & 0x20 != 0 {
+                self.buffer.drain(..);
+                self.position = 0;
+            }
+
+            if size & UNCOMPRESSED != 0 {
+                self.buffer.extend_from_slice(&block);
+            } else {
+                decompress_block(&block, &mut self.buffer, self.max_block)?;
+            }
+            if self.buffer.len() > self.position {
+                return Ok(true);
+            }
+        }
+    }
+}
+
+impl<R: Read> Read for Lz4Reader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if out.is_empty() {
+            return Ok(0);
+        }
+        if self.position == self.buffer.len() && !self.fill()? {
+            return Ok(0);
+        }
+        let len = out.len().min(self.buffer.len() - self.position);
+        out[..len].copy_from_slice(&self.buffer[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{Proc, ProcT, Session};
+
+///Process tracked by a Manager
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Tracked {
+    ///Any process with this title, reattached when it restarts
+    Name(String),
+    ///One process, dropped once it exits
+    Pid(u32),
+}
+
+///Change found by Manager::refresh()
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ManagerEvent {
+    ///A process was attached to, 'pid' is its id
+    Attached { tracked: Tracked, pid: isize },
+    ///The attached process exited. Pid entries are removed from the manager
+    Exited { tracked: Tracked, pid: isize },
+}
+
+#[derive(Debug)]
+struct Named {
+    session: Session,
+    ///Process id the session was attached to at the last refresh
+    pid: Option<isize>,
+}
+
+///Set of attached processes for tools monitoring several targets at once, like a fleet of worker processes.
+///Processes tracked by name are reattached when they restart, processes tracked by pid are removed once they
+///exit. Call refresh() periodically to keep up with the targets
+#[derive(Debug, Default)]
+pub struct Manager {
+    names: Vec<Named>,
+    pids: Vec<Proc>,
+}
+
+impl Manager {
+    pub fn new() -> Manager {
+        Manager::default()
+    }
+
+    ///Track the process with specified title, attaching if it is running. Returns 'true' if attached
+    pub fn add_name(&mut self, proc_name: &str) -> bool {
+        if let Some(named) = self
+            .names
+            .iter()
+            .find(|named| named.session.proc_name() == proc_name)
+        {
+            return named.pid.is_some();
+        }
+        let session = Session::new(proc_name);
+        let pid = session.attached().map(|proc| proc.pid());
+        self.names.push(Named { session, pid });
+        pid.is_some()
+    }
+
+    ///Track the process with specified id. Returns 'false' if it can't be opened
+    pub fn add_pid(&mut self, pid: u32) -> bool {
+        if self.pids.iter().any(|proc| proc.pid() == pid as isize) {
+            return true;
+        }
+        match Proc::from_pid(pid) {
+            Some(proc) => {
+                self.pids.push(proc);
+                true
+            }
+            None => false,
+        }
+    }
+
+    ///Stop tracking a process, returns 'true' if it was tracked
+    pub fn remove(&mut self, tracked: &Tracked) -> bool {
+        let count = self.names.len() + self.pids.len();
+        match tracked {
+            Tracked::Name(name) => self.names.retain(|named| named.session.proc_name() != name),
+            Tracked::Pid(pid) => self.pids.retain(|proc| proc.pid() != *pid as isize),
+        }
+        count != self.names.len() + self.pids.len()
+    }
+
+    ///Reattach the names whose process restarted and remove the pids whose process exited, returning the changes
+    pub fn refresh(&mut self) -> Vec<ManagerEvent> {
+        let mut events = Vec::new();
+        for named in &mut self.names {
+            let tracked = Tracked::Name(named.session.proc_name().to_string());
+            named.session.refresh();
+            let pid = named
+                .session
+                .attached()
+                .filter(|proc| proc.is_alive())
+                .map(|proc| proc.pid());
+            if named.pid == pid {
+                continue;
+            }
+            if let Some(pid) = named.pid {
+                events.push(ManagerEvent::Exited {
+                    tracked: tracked.clone(),
+                    pid,
+                });
+            }
+            if let Some(pid) = pid {
+                events.push(ManagerEvent::Attached { tracked, pid });
+            }
+            named.pid = pid;
+        }
+        self.pids.retain(|proc| {
+            let alive = proc.is_alive();
+            if !alive {
+                events.push(ManagerEvent::Exited {
+                    tracked: Tracked::Pid(proc.pid() as u32),
+                    pid: proc.pid(),
+                });
+            }
+            alive
+        });
+        events
+    }
+
+    ///Process of a tracked entry attached at the last refresh
+    pub fn get(&self, tracked: &Tracked) -> Option<&Proc> {
+        match tracked {
+            Tracked::Name(name) => self
+                .names
+                .iter()
+                .find(|named| named.session.proc_name() == name)?
+                .session
+                .attached(),
+            Tracked::Pid(pid) => self.pids.iter().find(|proc| proc.pid() == *pid as isize),
+        }
+    }
+
+    ///Session of a process tracked by name, to resolve RebasedAddress values against it
+    pub fn session(&mut self, proc_name: &str) -> Option<&mut Session> {
+        self.names
+            .iter_mut()
+            .find(|named| named.session.proc_name() == proc_name)
+            .map(|named| &mut named.session)
+    }
+
+    ///Tracked entries, attached or not
+    pub fn tracked(&self) -> Vec<Tracked> {
+        let names = self
+            .names
+            .iter()
+            .map(|named| Tracked::Name(named.session.proc_name().to_string()));
+        let pids = self.pids.iter().map(|proc| Tracked::Pid(proc.pid() as u32));
+        names.chain(pids).collect()
+    }
+
+    ///Attached processes with what tracks them, as of the last refresh
+    pub fn iter(&self) -> impl Iterator<Item = (Tracked, &Proc)> + '_ {
+        let names = self.names.iter().filter_map(|named| {
+            let tracked = Tracked::Name(named.session.proc_name().to_string());
+            Some((tracked, named.session.attached()?))
+        });
+        let pids = self
+            .pids
+            .iter()
+            .map(|proc| (Tracked::Pid(proc.pid() as u32), proc));
+        names.chain(pids)
+    }
+
+    ///Number of tracked entries
+    pub fn len(&self) -> usize {
+        self.names.len() + self.pids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -0,0 +1,49 @@
+///Offset of the first 'needle' in 'haystack'
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn memchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+    use std::arch::x86_64::{
+        __m128i, _mm_cmpeq_epi8, _mm_loadu_si128, _mm_movemask_epi8, _mm_set1_epi8,
+    };
+
+    //SSE2 is part of the x86_64 baseline, compare 16 bytes at once
+    let mut offset = 0;
+    unsafe {
+        let needles = _mm_set1_epi8(needle as i8);
+        while offset + 16 <= haystack.len() {
+            let block = _mm_loadu_si128(haystack.as_ptr().add(offset) as *const __m128i);
+            let mask = _mm_movemask_epi8(_mm_cmpeq_epi8(block, needles));
+            if mask != 0 {
+                return Some(offset + mask.trailing_zeros() as usize);
+            }
+            offset += 16;
+        }
+    }
+    haystack[offset..]
+        .iter()
+        .position(|byte| *byte == needle)
+        .map(|position| offset + position)
+}
+
+///Offset of the first 'needle' in 'haystack'
+#[cfg(not(target_arch = "x86_64"))]
+pub(crate) fn memchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+    const LOW_BITS: u64 = 0x0101_0101_0101_0101;
+    const HIGH_BITS: u64 = 0x8080_8080_8080_8080;
+
+    //Test 8 bytes at once: a zero byte in 'word ^ needles' is a match
+    let needles = LOW_BITS * needle as u64;
+    let mut offset = 0;
+    while offset + 8 <= haystack.len() {
+        let mut word = [0u8; 8];
+        word.copy_from_slice(&haystack[offset..offset + 8]);
+        let word = u64::from_le_bytes(word) ^ needles;
+        if word.wrapping_sub(LOW_BITS) & !word & HIGH_BITS != 0 {
+            break;
+        }
+        offset += 8;
+    }
+    haystack[offset..]
+        .iter()
+        .position(|byte| *byte == needle)
+        .map(|position| offset + position)
+}
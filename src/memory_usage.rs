@@ -0,0 +1,12 @@
+///Memory use of a process, in bytes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    ///Memory resident in RAM, the working set on Windows and RSS on Linux
+    pub resident: usize,
+    ///Highest resident memory since the process started
+    pub peak_resident: usize,
+    ///Memory no other process shares: private bytes on Windows, private resident pages on Linux
+    pub private: usize,
+    ///Memory the system committed to back the process: commit charge on Windows, data and stack segments on Linux
+    pub commit: usize,
+}
@@ -0,0 +1,234 @@
+///How much of the process a dump captures
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DumpLevel {
+    ///Threads, modules and stacks on Windows; only writable memory on Linux
+    Mini,
+    ///Every readable page of the process
+    Full,
+}
+
+impl DumpLevel {
+    ///MINIDUMP_TYPE flags passed to MiniDumpWriteDump
+    #[cfg(target_os = "windows")]
+    pub(crate) fn to_windows(self) -> u32 {
+        const WITH_FULL_MEMORY: u32 = 0x2;
+        const WITH_HANDLE_DATA: u32 = 0x4;
+        const WITH_UNLOADED_MODULES: u32 = 0x20;
+        const WITH_FULL_MEMORY_INFO: u32 = 0x800;
+        const WITH_THREAD_INFO: u32 = 0x1000;
+        match self {
+            DumpLevel::Mini => WITH_UNLOADED_MODULES | WITH_THREAD_INFO,
+            DumpLevel::Full => {
+                WITH_FULL_MEMORY
+                    | WITH_HANDLE_DATA
+                    | WITH_UNLOADED_MODULES
+                    | WITH_FULL_MEMORY_INFO
+                    | WITH_THREAD_INFO
+            }
+        }
+    }
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+mod elf_core {
+    use std::fs::File;
+    use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+    use std::path::Path;
+
+    use super::DumpLevel;
+    use crate::{ProcT, Region, PAGE_SIZE};
+
+    const ELF_HEADER_SIZE: usize = 64;
+    const PROGRAM_HEADER_SIZE: usize = 56;
+    const PRSTATUS_SIZE: usize = 336;
+    const PRSTATUS_REGS_OFFSET: usize = 112;
+    const NT_PRSTATUS: u32 = 1;
+    const NT_FILE: u32 = 0x46494C45;
+    const PT_LOAD: u32 = 1;
+    const PT_NOTE: u32 = 4;
+
+    fn push_note(notes: &mut Vec<u8>, kind: u32, desc: &[u8]) {
+        notes.extend_from_slice(&5u32.to_le_bytes());
+        notes.extend_from_slice(&(desc.len() as u32).to_le_bytes());
+        notes.extend_from_slice(&kind.to_le_bytes());
+        notes.extend_from_slice(b"CORE\0\0\0\0");
+        notes.extend_from_slice(desc);
+        notes.resize(notes.len().next_multiple_of(4), 0);
+    }
+
+    ///NT_PRSTATUS note description holding the registers of one thread, pr_pid is the thread id
+    fn prstatus(tid: u32, parent_pid: u32, regs: &libc::user_regs_struct) -> Vec<u8> {
+        let mut desc = vec![0u8; PRSTATUS_SIZE];
+        desc[32..36].copy_from_slice(&tid.to_le_bytes());
+        desc[36..40].copy_from_slice(&parent_pid.to_le_bytes());
+        let regs = unsafe {
+            std::slice::from_raw_parts(
+                regs as *const libc::user_regs_struct as *const u8,
+                std::mem::size_of::<libc::user_regs_struct>(),
+            )
+        };
+        desc[PRSTATUS_REGS_OFFSET..PRSTATUS_REGS_OFFSET + regs.len()].copy_from_slice(regs);
+        desc
+    }
+
+    ///NT_FILE note description listing the file mapped at each module, so the dump can name its modules
+    fn mapped_files<P: ProcT + ?Sized>(proc: &P) -> Vec<u8> {
+        let modules = proc.modules();
+        let mut desc = Vec::new();
+        desc.extend_from_slice(&(modules.len() as u64).to_le_bytes());
+        desc.extend_from_slice(&(PAGE_SIZE as u64).to_le_bytes());
+        for module in &modules {
+            desc.extend_from_slice(&(module.base as u64).to_le_bytes());
+            desc.extend_from_slice(&((module.base + module.size) as u64).to_le_bytes());
+            desc.extend_from_slice(&0u64.to_le_bytes());
+        }
+        for module in &modules {
+            desc.extend_from_slice(module.path.as_bytes());
+            desc.push(0);
+        }
+        desc
+    }
+
+    fn program_header(
+        kind: u32,
+        flags: u32,
+        offset: usize,
+        region: &Region,
+        file_size: usize,
+    ) -> [u8; PROGRAM_HEADER_SIZE] {
+        let mut header = [0u8; PROGRAM_HEADER_SIZE];
+        header[0..4].copy_from_slice(&kind.to_le_bytes());
+        header[4..8].copy_from_slice(&flags.to_le_bytes());
+        header[8..16].copy_from_slice(&(offset as u64).to_le_bytes());
+        header[16..24].copy_from_slice(&(region.base as u64).to_le_bytes());
+        header[32..40].copy_from_slice(&(file_size as u64).to_le_bytes());
+        header[40..48].copy_from_slice(&(region.size as u64).to_le_bytes());
+        header[48..56]
+            .copy_from_slice(&(if kind == PT_LOAD { PAGE_SIZE } else { 4 } as u64).to_le_bytes());
+        header
+    }
+
+    ///Write an ELF core file of the process, every thread is stopped while its memory is copied
+    pub(crate) fn write_core<P: ProcT + ?Sized>(
+        proc: &P,
+        path: &Path,
+        level: DumpLevel,
+    ) -> io::Result<()> {
+        let threads = proc.threads();
+        let mut stopped: Vec<_> = threads.iter().filter(|thread| thread.suspend()).collect();
+        //Readers take the process id from the first NT_PRSTATUS note
+        stopped.sort_by_key(|thread| thread.id as isize != proc.pid());
+
+        let result = (|| {
+            let mut notes = Vec::new();
+            let parent_pid = proc.parent_pid().unwrap_or(0) as u32;
+            for thread in &stopped {
+                if let Some(regs) = thread.user_regs() {
+                    push_note(
+                        &mut notes,
+                        NT_PRSTATUS,
+                        &prstatus(thread.id, parent_pid, &regs),
+                    );
+                }
+            }
+            push_note(&mut notes, NT_FILE, &mapped_files(proc));
+
+            let regions: Vec<Region> = proc
+                .regions()
+                .into_iter()
+                .filter(|region| match level {
+                    DumpLevel::Mini => region.protection.read && region.protection.write,
+                    DumpLevel::Full => true,
+                })
+                .collect();
+
+            let mut file = BufWriter::new(File::create(path)?);
+            let header_count = regions.len() + 1;
+            let notes_offset = ELF_HEADER_SIZE + header_count * PROGRAM_HEADER_SIZE;
+            let mut data_offset = (notes_offset + notes.len()).next_multiple_of(PAGE_SIZE);
+
+            //ELF header
+            let mut header = [0u8; ELF_HEADER_SIZE];
+            header[0..4].copy_from_slice(b"\x7FELF");
+            header[4] = 2; //64-bit
+            header[5] = 1; //little endian
+            header[6] = 1; //version
+            header[16..18].copy_from_slice(&4u16.to_le_bytes()); //ET_CORE
+            header[18..20].copy_from_slice(&62u16.to_le_bytes()); //EM_X86_64
+            header[20..24].copy_from_slice(&1u32.to_le_bytes());
+            header[32..40].copy_from_slice(&(ELF_HEADER_SIZE as u64).to_le_bytes());
+            header[52..54].copy_from_slice(&(ELF_HEADER_SIZE as u16).to_le_bytes());
+            header[54..56].copy_from_slice(&(PROGRAM_HEADER_SIZE as u16).to_le_bytes());
+            header[56..58].copy_from_slice(&(header_count as u16).to_le_bytes());
+            file.write_all(&header)?;
+
+            let notes_region = Region {
+                base: 0,
+                size: 0,
+                protection: Default::default(),
+            };
+            file.write_all(&program_header(
+                PT_NOTE,
+                0,
+                notes_offset,
+                &notes_region,
+                notes.len(),
+            ))?;
+            let mut file_sizes = Vec::with_capacity(regions.len());
+            for region in &regions {
+                let file_size = if region.protection.read {
+                    region.size
+                } else {
+                    0
+                };
+                let flags = (region.protection.read as u32) << 2
+                    | (region.protection.write as u32) << 1
+                    | region.protection.execute as u32;
+                file.write_all(&program_header(
+                    PT_LOAD,
+                    flags,
+                    data_offset,
+                    region,
+                    file_size,
+                ))?;
+                file_sizes.push((data_offset, file_size));
+                data_offset += file_size;
+            }
+            file.write_all(&notes)?;
+
+            for (region, (offset, file_size)) in regions.iter().zip(file_sizes) {
+                if file_size == 0 {
+                    continue;
+                }
+                file.seek(SeekFrom::Start(offset as u64))?;
+                let mut address = region.base;
+                while address < region.end() {
+                    let len = (0x10_0000).min(region.end() - address);
+                    file.write_all(&proc.read_bulk(address, len, 0).data)?;
+                    address += len;
+                }
+            }
+            file.flush()
+        })();
+
+        for thread in stopped {
+            thread.resume();
+        }
+        result
+    }
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+pub(crate) use elf_core::write_core;
+
+#[cfg(all(target_os = "linux", not(target_arch = "x86_64")))]
+pub(crate) fn write_core<P: crate::ProcT + ?Sized>(
+    _proc: &P,
+    _path: &std::path::Path,
+    _level: DumpLevel,
+) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "core dumps are only implemented for x86_64",
+    ))
+}
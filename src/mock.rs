@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+use std::mem::MaybeUninit;
+use std::sync::RwLock;
+
+use crate::{uninit, Module, Pod, ProcT, Protection, Region, Thread, PAGE_SIZE};
+
+///Lowest address returned by alloc()
+const FIRST_ALLOCATION: usize = 0x1000_0000;
+
+#[derive(Clone, Debug)]
+struct Block {
+    bytes: Vec<u8>,
+    protection: Protection,
+}
+
+///In-memory process for unit testing code written against ProcT without a real target.
+///Memory is a set of blocks keyed by address, reads and writes fail outside of them or when the block
+///protection doesn't allow the access, like they would on a live process
+///
+///```
+///use proc_memory::{MockProc, ProcT};
+///
+///let proc = MockProc::new();
+///proc.map_value(0x1000, &0x2000u64);
+///proc.map_value(0x2010, &42i32);
+///let health = proc.read_ptr(0x1000).and_then(|base| proc.read::<i32>(base + 0x10));
+///assert_eq!(health, Some(42));
+///```
+#[derive(Debug)]
+pub struct MockProc {
+    memory: RwLock<HashMap<usize, Block>>,
+    modules: Vec<Module>,
+    threads: Vec<Thread>,
+    name: String,
+    pid: isize,
+    is_64bit: bool,
+}
+
+impl Default for MockProc {
+    fn default() -> Self {
+        MockProc::new()
+    }
+}
+
+///Split the block containing 'address' so that a block starts exactly at it
+fn split_at(memory: &mut HashMap<usize, Block>, address: usize) {
+    let base = memory
+        .iter()
+        .find(|(base, block)| **base < address && address - **base < block.bytes.len())
+        .map(|(base, _)| *base);
+    if let Some(base) = base {
+        let block = memory.get_mut(&base).unwrap();
+        let tail = Block {
+            bytes: block.bytes.split_off(address - base),
+            protection: block.protection,
+        };
+        memory.insert(address, tail);
+    }
+}
+
+impl MockProc {
+    ///Empty process with pid 1, named 'mock' and using the pointer size of the current target
+    pub fn new() -> MockProc {
+        MockProc {
+            memory: RwLock::new(HashMap::new()),
+            modules: Vec::new(),
+            threads: Vec::new(),
+            name: "mock".to_string(),
+            pid: 1,
+            is_64bit: cfg!(target_pointer_width = "64"),
+        }
+    }
+
+    pub fn with_pid(mut self, pid: isize) -> Self {
+        self.pid = pid;
+        self
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    ///Pointer size reported to read_ptr() and friends
+    pub fn with_64bit(mut self, is_64bit: bool) -> Self {
+        self.is_64bit = is_64bit;
+        self
+    }
+
+    ///Add a module returned by modules(), its memory is mapped separately with map()
+    pub fn with_module(mut self, module: Module) -> Self {
+        self.modules.push(module);
+        self
+    }
+
+    pub fn with_thread(mut self, thread: Thread) -> Self {
+        self.threads.push(thread);
+        self
+    }
+
+    ///Map 'bytes' read/write at 'address', replacing whatever was mapped in that range
+    pub fn map(&self, address: usize, bytes: impl Into<Vec<u8>>) {
+        self.map_with(address, bytes, Protection::READ_WRITE);
+    }
+
+    ///Map 'bytes' at 'address' with the specified protection, replacing whatever was mapped in that range
+    pub fn map_with(&self, address: usize, bytes: impl Into<Vec<u8>>, protection: Protection) {
+        let bytes = bytes.into();
+        let mut memory = self.memory.write().unwrap();
+        MockProc::unmap_range(&mut memory, address, bytes.len());
+        if !bytes.is_empty() {
+            memory.insert(address, Block { bytes, protection });
+        }
+    }
+
+    ///Map the bytes of 'value' read/write at 'address'
+    pub fn map_value<T: Pod>(&self, address: usize, value: &T) {
+        let bytes = unsafe {
+            std::slice::from_raw_parts(value as *const T as *const u8, std::mem::size_of::<T>())
+        };
+        self.map(address, bytes);
+    }
+
+    ///Remove 'len' bytes of memory at 'address', returns 'true' if any of them were mapped
+    pub fn unmap(&self, address: usize, len: usize) -> bool {
+        let mut memory = self.memory.write().unwrap();
+        MockProc::unmap_range(&mut memory, address, len)
+    }
+
+    fn unmap_range(memory: &mut HashMap<usize, Block>, address: usize, len: usize) -> bool {
+        let end = address.saturating_add(len);
+        split_at(memory, address);
+        split_at(memory, end);
+        let count = memory.len();
+        memory.retain(|base, _| *base < address || *base >= end);
+        memory.len() != count
+    }
+
+    ///Copy mapped bytes starting at 'address' into 'buffer', through adjacent blocks, returns the copied count
+    fn copy_from(&self, address: usize, buffer: &mut [MaybeUninit<u8>]) -> usize {
+        let memory = self.memory.read().unwrap();
+        let mut done = 0;
+        while done < buffer.len() {
+            let current = address + done;
+            let block = memory.iter().find(|(base, block)| {
+                **base <= current && current - **base < block.bytes.len() && block.protection.read
+            });
+            let (base, block) = match block {
+                Some(block) => block,
+                None => break,
+            };
+            let offset = current - base;
+            let count = (block.bytes.len() - offset).min(buffer.len() - done);
+            uninit::copy_to(&mut buffer[done..], &block.bytes[offset..offset + count]);
+            done += count;
+        }
+        done
+    }
+
+    fn read_into(&self, proc_address: usize, buffer: &mut [MaybeUninit<u8>]) -> bool {
+        self.copy_from(proc_address, buffer) == buffer.len()
+    }
+}
+
+impl ProcT for MockProc {
+    ///Always None, mocks are built with MockProc::new()
+    fn get(_proc_name: &str) -> Option<Self> {
+        None
+    }
+
+    fn read<T: Pod>(&self, proc_address: usize) -> Option<T> {
+        uninit::read_value(|buffer| self.read_into(proc_address, buffer))
+    }
+
+    fn read_valid<T: Pod>(&self, proc_address: usize, validator: impl Fn(&T) -> bool) -> Option<T> {
+        self.read::<T>(proc_address)
+            .filter(|value| validator(value))
+    }
+
+    fn read_vec<T: Pod>(
+        &self,
+        proc_address: usize,
+        len: usize,
+        _default_provider: impl Fn() -> T,
+    ) -> Option<Vec<T>> {
+        uninit::read_values(len, |buffer| self.read_into(proc_address, buffer))
+    }
+
+    fn read_vec_uninit<T: Pod>(&self, proc_address: usize, len: usize) -> Option<Vec<T>> {
+        uninit::read_values(len, |buffer| self.read_into(proc_address, buffer))
+    }
+
+    fn read_partial(&self, proc_address: usize, len: usize) -> (Vec<u8>, usize) {
+        let data = uninit::read_bytes(len, |buffer| self.copy_from(proc_address, buffer));
+        let count = data.len();
+        (data, count)
+    }
+
+    fn write<T>(&self, proc_address: usize, data: &T) -> (bool, usize) {
+        let bytes = unsafe {
+            std::slice::from_raw_parts(data as *const T as *const u8, std::mem::size_of::<T>())
+        };
+        self.write_bytes(proc_address, bytes)
+    }
+
+    fn write_bytes(&self, proc_address: usize, data: &[u8]) -> (bool, usize) {
+        let mut memory = self.memory.write().unwrap();
+        let mut done = 0;
+        while done < data.len() {
+            let current = proc_address + done;
+            let block = memory.iter_mut().find(|(base, block)| {
+                **base <= current && current - **base < block.bytes.len() && block.protection.write
+            });
+            let (base, block) = match block {
+                Some(block) => block,
+                None => break,
+            };
+            let offset = current - base;
+            let count = (block.bytes.len() - offset).min(data.len() - done);
+            block.bytes[offset..offset + count].copy_from_slice(&data[done..done + count]);
+            done += count;
+        }
+        (done == data.len(), done)
+    }
+
+    fn modules(&self) -> Vec<Module> {
+        self.modules.clone()
+    }
+
+    ///Mapped blocks sorted by address, adjacent blocks are not merged
+    fn regions(&self) -> Vec<Region> {
+        let memory = self.memory.read().unwrap();
+        let mut regions: Vec<Region> = memory
+            .iter()
+            .map(|(base, block)| Region {
+                base: *base,
+                size: block.bytes.len(),
+                protection: block.protection,
+            })
+            .collect();
+        regions.sort_by_key(|region| region.base);
+        regions
+    }
+
+    fn threads(&self) -> Vec<Thread> {
+        self.threads.clone()
+    }
+
+    fn is_alive(&self) -> bool {
+        true
+    }
+
+    ///Change the protection of exactly 'len' bytes, which must all be mapped
+    fn protect(&self, address: usize, len: usize, protection: Protection) -> Option<Protection> {
+        let end = address.checked_add(len)?;
+        let mut memory = self.memory.write().unwrap();
+        split_at(&mut memory, address);
+        split_at(&mut memory, end);
+
+        let mut covered = address;
+        let mut bases: Vec<usize> = memory
+            .keys()
+            .copied()
+            .filter(|base| *base >= address && *base < end)
+            .collect();
+        bases.sort_unstable();
+        for base in &bases {
+            if *base != covered {
+                return None;
+            }
+            covered += memory[base].bytes.len();
+        }
+        if covered != end || bases.is_empty() {
+            return None;
+        }
+
+        let previous = memory[&address].protection;
+        for base in bases {
+            memory.get_mut(&base).unwrap().protection = protection;
+        }
+        Some(previous)
+    }
+
+    ///Map zeroed memory after the highest mapped block
+    fn alloc(&self, len: usize, protection: Protection) -> Option<usize> {
+        if len == 0 {
+            return None;
+        }
+        let address = {
+            let memory = self.memory.read().unwrap();
+            let highest = memory
+                .iter()
+                .map(|(base, block)| base + block.bytes.len())
+                .max()
+                .unwrap_or(0);
+            highest.max(FIRST_ALLOCATION).next_multiple_of(PAGE_SIZE)
+        };
+        self.map_with(address, vec![0u8; len], protection);
+        Some(address)
+    }
+
+    fn free(&self, address: usize, len: usize) -> bool {
+        self.unmap(address, len)
+    }
+
+    fn create_thread(&self, _start: usize, _param: usize) -> Option<u32> {
+        None
+    }
+
+    fn create_thread_and_wait(&self, _start: usize, _param: usize) -> Option<u32> {
+        None
+    }
+
+    fn suspend(&self) -> bool {
+        true
+    }
+
+    fn resume(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> Option<String> {
+        Some(self.name.clone())
+    }
+
+    fn is_64bit(&self) -> bool {
+        self.is_64bit
+    }
+
+    fn pid(&self) -> isize {
+        self.pid
+    }
+}
@@ -0,0 +1,92 @@
+use crate::cave::{self, CodeCave};
+use crate::elf::{self, Symbol};
+use crate::pe::{self, Export, Import, PeHeaders, Section};
+use crate::ProcT;
+
+///A module (executable or shared library) loaded in another process
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Module {
+    ///File name of the module, e.g. 'game.exe' or 'libc.so.6'
+    pub name: String,
+    ///Full path of the module file
+    pub path: String,
+    ///Address the module is loaded at
+    pub base: usize,
+    ///Size in bytes of the mapped module image
+    pub size: usize,
+}
+
+impl Module {
+    ///Returns 'true' if the address lies inside the module image
+    pub fn contains(&self, address: usize) -> bool {
+        address >= self.base && address < self.base + self.size
+    }
+
+    ///Returns 'true' if 'name' matches the module file name, ignoring ASCII case
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name)
+    }
+
+    ///Parse the export table of a PE module from the process memory, returns None if it isn't a PE image
+    pub fn exports<P: ProcT + ?Sized>(&self, proc: &P) -> Option<Vec<Export>> {
+        pe::read_exports(proc, self)
+    }
+
+    ///Parse the PE headers of the module from the process memory, returns None if it isn't a PE image
+    pub fn pe_headers<P: ProcT + ?Sized>(&self, proc: &P) -> Option<PeHeaders> {
+        pe::read_headers(proc, self)
+    }
+
+    ///Find a PE section of the module by name, e.g. '.text'
+    pub fn section<P: ProcT + ?Sized>(&self, proc: &P, name: &str) -> Option<Section> {
+        self.pe_headers(proc)?.section(name).cloned()
+    }
+
+    ///Parse the import table of a PE module from the process memory, returns None if it isn't a PE image
+    pub fn imports<P: ProcT + ?Sized>(&self, proc: &P) -> Option<Vec<Import>> {
+        pe::read_imports(proc, self)
+    }
+
+    ///Address of the export named 'name', None if it doesn't exist or is forwarded to another module
+    pub fn export<P: ProcT + ?Sized>(&self, proc: &P, name: &str) -> Option<usize> {
+        self.exports(proc)?
+            .into_iter()
+            .find(|export| export.name.as_deref() == Some(name) && export.forwarder.is_none())
+            .map(|export| export.address)
+    }
+
+    ///Read the ELF symbol tables of the module file, returns None if it isn't an ELF file
+    pub fn symbols<P: ProcT + ?Sized>(&self, proc: &P) -> Option<Vec<Symbol>> {
+        elf::read_symbols(proc, self)
+    }
+
+    ///Live address of the ELF symbol named 'name'
+    pub fn symbol<P: ProcT + ?Sized>(&self, proc: &P, name: &str) -> Option<usize> {
+        self.symbols(proc)?
+            .into_iter()
+            .find(|symbol| symbol.name == name)
+            .map(|symbol| symbol.address)
+    }
+
+    ///Find runs of at least 'min_size' 0x00 or 0xCC padding bytes inside the executable sections of the module
+    pub fn code_caves<P: ProcT + ?Sized>(&self, proc: &P, min_size: usize) -> Vec<CodeCave> {
+        cave::find_code_caves(proc, self, min_size)
+    }
+
+    ///Read the CodeView record identifying the PDB of a PE module
+    #[cfg(feature = "pdb")]
+    pub fn pdb_info<P: ProcT + ?Sized>(&self, proc: &P) -> Option<crate::PdbInfo> {
+        crate::pdb::pdb_info(proc, self)
+    }
+
+    ///Locate the PDB of the module in 'search_paths' and load its public symbols
+    #[cfg(feature = "pdb")]
+    pub fn load_pdb<P: ProcT + ?Sized>(
+        &self,
+        proc: &P,
+        search_paths: &[std::path::PathBuf],
+    ) -> Option<crate::PdbSymbols> {
+        let path = self.pdb_info(proc)?.locate(search_paths)?;
+        crate::PdbSymbols::load(proc, self, path).ok()
+    }
+}
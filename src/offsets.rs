@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::{invalid_data, AddressExpr, ProcT};
+
+///Address described as a base (absolute or module relative) followed by a chain of pointer offsets.
+///
+///Written as `game.exe+0x1A2B30 -> 0x10 -> 0x4`: the pointer stored at `game.exe+0x1A2B30` is read and 0x10
+///is added, then the pointer stored there is read and 0x4 is added. Numbers are hexadecimal, with or without '0x'.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AddressChain {
+    ///Module the base offset is relative to, None for absolute addresses
+    pub module: Option<String>,
+    ///Offset from the module base, or the absolute address
+    pub base: usize,
+    ///Offsets added after each pointer dereference
+    pub offsets: Vec<isize>,
+}
+
+impl AddressChain {
+    ///Parse a chain written as `module+offset -> offset -> ...`
+    pub fn parse(text: &str) -> Option<AddressChain> {
+        let mut parts = text.split("->").map(str::trim);
+
+        let base = parts.next()?;
+        let (module, base) = match base.rfind('+') {
+            Some(index) => (
+                Some(base[..index].trim()),
+                parse_hex(base[index + 1..].trim())?,
+            ),
+            None => match parse_hex(base) {
+                Some(address) => (None, address),
+                None => (Some(base), 0),
+            },
+        };
+        if module == Some("") {
+            return None;
+        }
+
+        let offsets = parts.map(parse_offset).collect::<Option<Vec<isize>>>()?;
+        Some(AddressChain {
+            module: module.map(str::to_string),
+            base,
+            offsets,
+        })
+    }
+
+    ///Resolve the chain against the live process, returns None if a module or pointer can't be read
+    pub fn resolve<P: ProcT + ?Sized>(&self, proc: &P) -> Option<usize> {
+        let mut address = match &self.module {
+            Some(name) => proc.module(name)?.base.wrapping_add(self.base),
+            None => self.base,
+        };
+        let pointer_size = proc.pointer_size();
+        for offset in &self.offsets {
+            address = proc
+                .read_ptr_sized(address, pointer_size)?
+                .wrapping_add(*offset as usize);
+        }
+        Some(address)
+    }
+}
+
+///Table of named addresses loaded from a TOML or JSON file.
+///
+///TOML files contain `name = "address"` lines, JSON files a single object of `"name": "address"` pairs.
+///Names under a TOML table header like `[player]` are prefixed with it, `health` becomes `player.health`.
+///Addresses are pointer chains or any expression accepted by AddressExpr.
+#[derive(Clone, Debug, Default)]
+pub struct OffsetTable {
+    entries: HashMap<String, AddressExpr>,
+}
+
+impl OffsetTable {
+    ///Create an empty table
+    pub fn new() -> OffsetTable {
+        OffsetTable::default()
+    }
+
+    ///Load a table from a '.json' or '.toml' file
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<OffsetTable> {
+        let text = std::fs::read_to_string(path)?;
+        OffsetTable::parse(&text)
+    }
+
+    ///Parse a table from TOML or JSON text, detected by a leading '{'
+    pub fn parse(text: &str) -> std::io::Result<OffsetTable> {
+        let pairs = if text.trim_start().starts_with('{') {
+            parse_json(text)?
+        } else {
+            parse_toml(text)?
+        };
+
+        let mut table = OffsetTable::new();
+        for (name, value) in pairs {
+            if table.entries.contains_key(&name) {
+                return Err(invalid_data(format!("'{}' is defined twice", name)));
+            }
+            let expr = AddressExpr::parse(&value).ok_or_else(|| {
+                invalid_data(format!("invalid address '{}' for '{}'", value, name))
+            })?;
+            table.insert(&name, expr);
+        }
+        Ok(table)
+    }
+
+    ///Load the entries of a Cheat Engine '.CT' file, see parse_cheat_table()
+    pub fn load_cheat_table(path: impl AsRef<Path>) -> std::io::Result<OffsetTable> {
+        let text = std::fs::read_to_string(path)?;
+        OffsetTable::parse_cheat_table(&text)
+    }
+
+    ///Parse a Cheat Engine table. User defined symbols keep their name, cheat entries are named by their
+    ///description, prefixed with the descriptions of their groups like 'Player/Health'. Entries without an address,
+    ///like scripts, and addresses that use Lua or unsupported syntax are skipped
+    ///
+    ///```
+    ///use proc_memory::{MockProc, Module, OffsetTable, ProcT};
+    ///
+    ///let table = OffsetTable::parse_cheat_table(r#"<?xml version="1.0" encoding="utf-8"?>
+    ///<CheatTable CheatEngineTableVersion="45">
+    ///  <CheatEntries>
+    ///    <CheatEntry>
+    ///      <ID>1</ID>
+    ///      <Description>"Player"</Description>
+    ///      <GroupHeader>1</GroupHeader>
+    ///      <CheatEntries>
+    ///        <CheatEntry>
+    ///          <ID>2</ID>
+    ///          <Description>"Health"</Description>
+    ///          <VariableType>4 Bytes</VariableType>
+    ///          <Address>"game.exe"+100</Address>
+    ///          <Offsets>
+    ///            <Offset>4</Offset>
+    ///            <Offset>10</Offset>
+    ///          </Offsets>
+    ///        </CheatEntry>
+    ///      </CheatEntries>
+    ///    </CheatEntry>
+    ///    <CheatEntry>
+    ///      <ID>3</ID>
+    ///      <Description>"Ammo"</Description>
+    ///      <Address>ammo_base+8</Address>
+    ///    </CheatEntry>
+    ///  </CheatEntries>
+    ///  <UserdefinedSymbols>
+    ///    <SymbolEntry>
+    ///      <Name>ammo_base</Name>
+    ///      <Address>game.exe+200</Address>
+    ///    </SymbolEntry>
+    ///  </UserdefinedSymbols>
+    ///</CheatTable>"#).unwrap();
+    ///
+    ///let game = Module { name: "game.exe".to_string(), base: 0x40_0000, size: 0x1000, ..Module::default() };
+    ///let proc = MockProc::new().with_module(game);
+    ///proc.map_value(0x40_0100, &0x1000u64);
+    ///proc.map_value(0x1010, &0x2000u64);
+    ///assert_eq!(table.resolve(&proc, "Player/Health"), Some(0x2004));
+    ///assert_eq!(table.resolve(&proc, "Ammo"), Some(0x40_0208));
+    ///```
+    pub fn parse_cheat_table(text: &str) -> std::io::Result<OffsetTable> {
+        crate::cheat_table::parse(text)
+    }
+
+    ///Add or replace a named address
+    pub fn insert(&mut self, name: &str, address: impl Into<AddressExpr>) {
+        self.entries.insert(name.to_string(), address.into());
+    }
+
+    ///Get the address registered as 'name'
+    pub fn get(&self, name: &str) -> Option<&AddressExpr> {
+        self.entries.get(name)
+    }
+
+    ///Iterate over every name and address
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &AddressExpr)> {
+        self.entries.iter()
+    }
+
+    ///Resolve the address registered as 'name' against the live process
+    pub fn resolve<P: ProcT + ?Sized>(&self, proc: &P, name: &str) -> Option<usize> {
+        self.get(name)?.eval(proc)
+    }
+}
+
+pub(crate) fn parse_hex(text: &str) -> Option<usize> {
+    let digits = text
+        .strip_prefix("0x")
+        .or_else(|| text.strip_prefix("0X"))
+        .unwrap_or(text);
+    usize::from_str_radix(digits, 16).ok()
+}
+
+fn parse_offset(text: &str) -> Option<isize> {
+    match text.strip_prefix('-') {
+        Some(text) => parse_hex(text.trim()).map(|offset| -(offset as isize)),
+        None => parse_hex(text.trim_start_matches('+').trim()).map(|offset| offset as isize),
+    }
+}
+
+fn parse_toml(text: &str) -> std::io::Result<Vec<(String, String)>> {
+    let mut pairs = Vec::new();
+    let mut section = String::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with("[[") {
+            return Err(invalid_data(format!(
+                "line {}: arrays of tables are not supported",
+                number + 1
+            )));
+        }
+        if let Some(header) = line.strip_prefix('[') {
+            let name = header
+                .split_once(']')
+                .map(|(name, _)| name.trim())
+                .filter(|name| !name.is_empty())
+                .ok_or_else(|| {
+                    invalid_data(format!("line {}: invalid table header", number + 1))
+                })?;
+            section = name
+                .split('.')
+                .map(|part| part.trim().trim_matches('"'))
+                .collect::<Vec<&str>>()
+                .join(".");
+            continue;
+        }
+
+        let (key, value) = line.split_once('=').ok_or_else(|| {
+            invalid_data(format!(
+                "line {}: expected 'name = \"address\"'",
+                number + 1
+            ))
+        })?;
+        let key = key.trim().trim_matches('"');
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|value| value.split_once('"'))
+            .map(|(value, _)| value)
+            .ok_or_else(|| {
+                invalid_data(format!(
+                    "line {}: address must be a quoted string",
+                    number + 1
+                ))
+            })?;
+
+        let key = if section.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", section, key)
+        };
+        pairs.push((key, value.to_string()));
+    }
+    Ok(pairs)
+}
+
+fn parse_json(text: &str) -> std::io::Result<Vec<(String, String)>> {
+    let body = text
+        .trim()
+        .strip_prefix('{')
+        .and_then(|body| body.strip_suffix('}'))
+        .ok_or_else(|| invalid_data("expected a JSON object"))?;
+
+    let mut pairs = Vec::new();
+    let mut rest = body.trim();
+    while !rest.is_empty() {
+        let (key, after) = json_string(rest)?;
+        let after = after
+            .trim_start()
+            .strip_prefix(':')
+            .ok_or_else(|| invalid_data(format!("expected ':' after \"{}\"", key)))?;
+        let (value, after) = json_string(after.trim_start())?;
+        pairs.push((key, value));
+
+        rest = after.trim_start();
+        rest = rest.strip_prefix(',').unwrap_or(rest).trim_start();
+    }
+    Ok(pairs)
+}
+
+///Code unit of a \u escape, from the 4 hex digits after the 'u'
+fn json_hex(chars: &mut std::str::CharIndices) -> std::io::Result<u32> {
+    let digits: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+    if digits.len() == 4 && digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        if let Ok(code) = u32::from_str_radix(&digits, 16) {
+            return Ok(code);
+        }
+    }
+    Err(invalid_data(format!(
+        "invalid escape '\\u{}' in JSON string",
+        digits
+    )))
+}
+
+fn json_string(text: &str) -> std::io::Result<(String, &str)> {
+    let text = text
+        .strip_prefix('"')
+        .ok_or_else(|| invalid_data("expected a JSON string"))?;
+
+    let mut value = String::new();
+    let mut chars = text.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, &text[index + 1..])),
+            '\\' => {
+                let escaped = match chars.next() {
+                    Some((_, escaped)) => escaped,
+                    None => break,
+                };
+                match escaped {
+                    '"' | '\\' | '/' => value.push(escaped),
+                    'b' => value.push('\u{8}'),
+                    'f' => value.push('\u{C}'),
+                    'n' => value.push('\n'),
+                    'r' => value.push('\r'),
+                    't' => value.push('\t'),
+                    'u' => {
+                        let mut code = json_hex(&mut chars)?;
+                        //Characters outside the BMP are escaped as a UTF-16 surrogate pair
+                        if (0xD800..0xDC00).contains(&code) {
+                            let low = match (chars.next(), chars.next()) {
+                                (Some((_, '\\')), Some((_, 'u'))) => json_hex(&mut chars)?,
+                                _ => 0,
+                            };
+                            if !(0xDC00..0xE000).contains(&low) {
+                                return Err(invalid_data("unpaired surrogate in JSON string"));
+                            }
+                            code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                        }
+                        let c = char::from_u32(code)
+                            .ok_or_else(|| invalid_data("unpaired surrogate in JSON string"))?;
+                        value.push(c);
+                    }
+                    _ => {
+                        return Err(invalid_data(format!(
+                            "invalid escape '\\{}' in JSON string",
+                            escaped
+                        )))
+                    }
+                }
+            }
+            _ => value.push(c),
+        }
+    }
+    Err(invalid_data("unterminated JSON string"))
+}
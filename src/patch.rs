@@ -0,0 +1,252 @@
+use crate::{Proc, ProcT, Protection};
+
+const NOP: u8 = 0x90;
+
+///Write to possibly read-only code, making the pages writable for the duration of the write
+pub(crate) fn write_code<P: ProcT + ?Sized>(proc: &P, address: usize, bytes: &[u8]) -> bool {
+    let (ok, written) = proc.write_bytes(address, bytes);
+    if ok && written == bytes.len() {
+        return true;
+    }
+
+    let old = match proc.protect(address, bytes.len(), Protection::READ_WRITE_EXECUTE) {
+        Some(old) => old,
+        None => return false,
+    };
+    let (ok, written) = proc.write_bytes(address, bytes);
+    proc.protect(address, bytes.len(), old);
+    ok && written == bytes.len()
+}
+
+///Modification of the process memory that remembers the original bytes and restores them when dropped
+pub struct Patch<'a, P: ProcT = Proc> {
+    proc: &'a P,
+    address: usize,
+    original: Vec<u8>,
+    patched: Vec<u8>,
+    applied: bool,
+}
+
+impl<'a, P: ProcT> Patch<'a, P> {
+    ///Replace 'len' bytes at 'address' with x86 NOP instructions
+    pub fn nop(proc: &'a P, address: usize, len: usize) -> Option<Patch<'a, P>> {
+        Patch::bytes(proc, address, &vec![NOP; len])
+    }
+
+    ///Overwrite the memory at 'address' with 'bytes', None if the original bytes can't be read or the write fails
+    pub fn bytes(proc: &'a P, address: usize, bytes: &[u8]) -> Option<Patch<'a, P>> {
+        let mut patch = Patch::new(proc, address, bytes)?;
+        if !patch.apply() {
+            return None;
+        }
+        Some(patch)
+    }
+
+    ///Save the original bytes at 'address' without writing anything yet, apply() writes 'bytes'
+    pub fn new(proc: &'a P, address: usize, bytes: &[u8]) -> Option<Patch<'a, P>> {
+        let original = proc.read_vec_uninit::<u8>(address, bytes.len())?;
+        Some(Patch {
+            proc,
+            address,
+            original,
+            patched: bytes.to_vec(),
+            applied: false,
+        })
+    }
+
+    ///Address of the first patched byte
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    ///Number of patched bytes
+    pub fn len(&self) -> usize {
+        self.patched.len()
+    }
+
+    ///Returns 'true' if the patch covers no bytes
+    pub fn is_empty(&self) -> bool {
+        self.patched.is_empty()
+    }
+
+    ///Bytes found at the address before patching
+    pub fn original_bytes(&self) -> &[u8] {
+        &self.original
+    }
+
+    ///Bytes written by the patch
+    pub fn patched_bytes(&self) -> &[u8] {
+        &self.patched
+    }
+
+    ///Returns 'true' while the patched bytes are in place
+    pub fn is_applied(&self) -> bool {
+        self.applied
+    }
+
+    ///Update the applied state from the live memory.
+    ///Returns None if the memory matches neither the original nor the patched bytes
+    pub fn sync(&mut self) -> Option<bool> {
+        let current = self
+            .proc
+            .read_vec_uninit::<u8>(self.address, self.patched.len())?;
+        if current == self.patched {
+            self.applied = true;
+        } else if current == self.original {
+            self.applied = false;
+        } else {
+            return None;
+        }
+        Some(self.applied)
+    }
+
+    ///Write the patched bytes, after new() or a revert()
+    pub fn apply(&mut self) -> bool {
+        if !self.applied {
+            self.applied = write_code(self.proc, self.address, &self.patched);
+        }
+        self.applied
+    }
+
+    ///Restore the original bytes
+    pub fn revert(&mut self) -> bool {
+        if self.applied {
+            self.applied = !write_code(self.proc, self.address, &self.original);
+        }
+        !self.applied
+    }
+}
+
+impl<'a, P: ProcT> Drop for Patch<'a, P> {
+    fn drop(&mut self) {
+        self.revert();
+    }
+}
+
+///Named group of patches that can be toggled one at a time or all together
+pub struct PatchSet<'a, P: ProcT = Proc> {
+    patches: Vec<(String, Patch<'a, P>)>,
+}
+
+impl<'a, P: ProcT> Default for PatchSet<'a, P> {
+    fn default() -> Self {
+        PatchSet {
+            patches: Vec::new(),
+        }
+    }
+}
+
+impl<'a, P: ProcT> PatchSet<'a, P> {
+    pub fn new() -> PatchSet<'a, P> {
+        PatchSet::default()
+    }
+
+    ///Register 'patch' as 'name', returns the patch previously registered with that name
+    pub fn insert(&mut self, name: &str, patch: Patch<'a, P>) -> Option<Patch<'a, P>> {
+        match self.patches.iter_mut().find(|(key, _)| key == name) {
+            Some((_, existing)) => Some(std::mem::replace(existing, patch)),
+            None => {
+                self.patches.push((name.to_string(), patch));
+                None
+            }
+        }
+    }
+
+    ///Unregister the patch named 'name', it is reverted when the returned value is dropped
+    pub fn remove(&mut self, name: &str) -> Option<Patch<'a, P>> {
+        let index = self.patches.iter().position(|(key, _)| key == name)?;
+        Some(self.patches.remove(index).1)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Patch<'a, P>> {
+        self.patches
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, patch)| patch)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Patch<'a, P>> {
+        self.patches
+            .iter_mut()
+            .find(|(key, _)| key == name)
+            .map(|(_, patch)| patch)
+    }
+
+    ///Names of the registered patches in insertion order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.patches.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.patches.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patches.is_empty()
+    }
+
+    ///Apply the patch named 'name', 'false' if it doesn't exist or the write failed
+    pub fn enable(&mut self, name: &str) -> bool {
+        self.get_mut(name).is_some_and(Patch::apply)
+    }
+
+    ///Revert the patch named 'name', 'false' if it doesn't exist or the write failed
+    pub fn disable(&mut self, name: &str) -> bool {
+        self.get_mut(name).is_some_and(Patch::revert)
+    }
+
+    ///Applied state of the patch named 'name'
+    pub fn is_enabled(&self, name: &str) -> Option<bool> {
+        self.get(name).map(Patch::is_applied)
+    }
+
+    ///Apply every patch, or none of them: patches applied by this call are reverted if one fails
+    pub fn enable_all(&mut self) -> bool {
+        let mut changed: Vec<usize> = Vec::new();
+        for index in 0..self.patches.len() {
+            let patch = &mut self.patches[index].1;
+            if patch.is_applied() {
+                continue;
+            }
+            if !patch.apply() {
+                for index in changed {
+                    self.patches[index].1.revert();
+                }
+                return false;
+            }
+            changed.push(index);
+        }
+        true
+    }
+
+    ///Revert every patch, or none of them: patches reverted by this call are applied again if one fails
+    pub fn disable_all(&mut self) -> bool {
+        let mut changed: Vec<usize> = Vec::new();
+        for index in 0..self.patches.len() {
+            let patch = &mut self.patches[index].1;
+            if !patch.is_applied() {
+                continue;
+            }
+            if !patch.revert() {
+                for index in changed {
+                    self.patches[index].1.apply();
+                }
+                return false;
+            }
+            changed.push(index);
+        }
+        true
+    }
+
+    ///Re-read the applied state of every patch from the process.
+    ///Returns the names of patches whose memory matches neither their original nor patched bytes
+    pub fn sync(&mut self) -> Vec<String> {
+        self.patches
+            .iter_mut()
+            .filter_map(|(name, patch)| match patch.sync() {
+                Some(_) => None,
+                None => Some(name.clone()),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,268 @@
+use std::path::{Path, PathBuf};
+
+use crate::pe::{data_directory, u16_at, u32_at};
+use crate::{invalid_data, Module, ProcT};
+
+///Debug information record (CodeView RSDS) that identifies the PDB matching a module
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PdbInfo {
+    ///PDB path recorded by the linker
+    pub path: String,
+    ///PDB signature GUID, in the byte order stored in the image
+    pub guid: [u8; 16],
+    ///PDB age
+    pub age: u32,
+}
+
+impl PdbInfo {
+    ///File name of the PDB, without the directories recorded by the linker
+    pub fn file_name(&self) -> &str {
+        self.path.rsplit(['\\', '/']).next().unwrap_or(&self.path)
+    }
+
+    ///Identifier used by symbol servers to store this PDB: GUID followed by the age, in uppercase hex
+    pub fn symbol_server_id(&self) -> String {
+        let g = &self.guid;
+        let mut id = format!(
+            "{:08X}{:04X}{:04X}",
+            u32::from_le_bytes([g[0], g[1], g[2], g[3]]),
+            u16::from_le_bytes([g[4], g[5]]),
+            u16::from_le_bytes([g[6], g[7]])
+        );
+        for byte in &g[8..] {
+            id.push_str(&format!("{:02X}", byte));
+        }
+        id.push_str(&format!("{:X}", self.age));
+        id
+    }
+
+    ///URL of the PDB on a symbol server, e.g. 'https://msdl.microsoft.com/download/symbols'
+    pub fn symbol_server_url(&self, server: &str) -> String {
+        format!(
+            "{}/{}/{}/{}",
+            server.trim_end_matches('/'),
+            self.file_name(),
+            self.symbol_server_id(),
+            self.file_name()
+        )
+    }
+
+    ///Find the PDB on disk: the path recorded by the linker, then every search directory either flat or with the
+    ///symbol server cache layout, then the directories of the '_NT_SYMBOL_PATH' environment variable
+    pub fn locate(&self, search_paths: &[PathBuf]) -> Option<PathBuf> {
+        let mut directories = search_paths.to_vec();
+        if let Ok(symbol_path) = std::env::var("_NT_SYMBOL_PATH") {
+            for entry in symbol_path.split(';') {
+                //'srv*cache*server' entries store downloads in the cache directory
+                let directory = match entry
+                    .strip_prefix("srv*")
+                    .or_else(|| entry.strip_prefix("SRV*"))
+                {
+                    Some(rest) => rest.split('*').next().unwrap_or(""),
+                    None => entry,
+                };
+                if !directory.is_empty() {
+                    directories.push(PathBuf::from(directory));
+                }
+            }
+        }
+
+        let mut candidates = vec![PathBuf::from(&self.path)];
+        for directory in directories {
+            candidates.push(directory.join(self.file_name()));
+            candidates.push(
+                directory
+                    .join(self.file_name())
+                    .join(self.symbol_server_id())
+                    .join(self.file_name()),
+            );
+        }
+        candidates.into_iter().find(|candidate| candidate.is_file())
+    }
+}
+
+///Public symbols of a PDB, relocated to the address a module is loaded at
+#[derive(Clone, Debug, Default)]
+pub struct PdbSymbols {
+    ///Symbol names and absolute addresses, sorted by address
+    symbols: Vec<(usize, String)>,
+}
+
+impl PdbSymbols {
+    ///Load the public symbols of the PDB at 'path' for 'module', which must match its GUID
+    pub fn load<P: ProcT + ?Sized>(
+        proc: &P,
+        module: &Module,
+        path: impl AsRef<Path>,
+    ) -> std::io::Result<PdbSymbols> {
+        let info =
+            pdb_info(proc, module).ok_or_else(|| invalid_data("module has no PDB debug record"))?;
+        let sections = module
+            .pe_headers(proc)
+            .ok_or_else(|| invalid_data("module is not a PE image"))?
+            .sections;
+
+        let file = std::fs::read(path)?;
+        let msf = Msf::parse(&file).ok_or_else(|| invalid_data("not a PDB (MSF 7.00) file"))?;
+
+        //PDB info stream: version, signature, age, GUID
+        let info_stream = msf
+            .stream(1)
+            .ok_or_else(|| invalid_data("missing PDB info stream"))?;
+        if info_stream.get(12..28) != Some(&info.guid[..]) {
+            return Err(invalid_data("PDB GUID does not match the module"));
+        }
+
+        let dbi = msf
+            .stream(3)
+            .ok_or_else(|| invalid_data("missing DBI stream"))?;
+        let record_stream = u16_at(&dbi, 20).ok_or_else(|| invalid_data("truncated DBI stream"))?;
+        let records = msf
+            .stream(record_stream as usize)
+            .ok_or_else(|| invalid_data("missing symbol record stream"))?;
+
+        let mut symbols = Vec::new();
+        let mut offset = 0;
+        while let (Some(len), Some(kind)) = (u16_at(&records, offset), u16_at(&records, offset + 2))
+        {
+            let record = records
+                .get(offset + 4..offset + 2 + len as usize)
+                .unwrap_or(&[]);
+            //S_PUB32: flags, offset, segment, name
+            if kind == 0x110E && record.len() > 10 {
+                let symbol_offset = u32_at(record, 4).unwrap_or(0) as usize;
+                let segment = u16_at(record, 8).unwrap_or(0) as usize;
+                if let Some(section) = segment.checked_sub(1).and_then(|index| sections.get(index))
+                {
+                    let name = &record[10..];
+                    let name_len = name.iter().position(|c| *c == 0).unwrap_or(name.len());
+                    symbols.push((
+                        section.address + symbol_offset,
+                        String::from_utf8_lossy(&name[..name_len]).into_owned(),
+                    ));
+                }
+            }
+            offset += 2 + len as usize;
+        }
+
+        symbols.sort();
+        Ok(PdbSymbols { symbols })
+    }
+
+    ///Absolute address of the symbol named 'name'
+    pub fn address_of(&self, name: &str) -> Option<usize> {
+        self.symbols
+            .iter()
+            .find(|(_, symbol)| symbol == name)
+            .map(|(address, _)| *address)
+    }
+
+    ///Closest symbol at or before 'address' and the offset from it
+    pub fn symbolize(&self, address: usize) -> Option<(&str, usize)> {
+        let index = match self
+            .symbols
+            .binary_search_by(|(symbol, _)| symbol.cmp(&address))
+        {
+            Ok(index) => index,
+            Err(0) => return None,
+            Err(index) => index - 1,
+        };
+        let (symbol_address, name) = &self.symbols[index];
+        Some((name, address - symbol_address))
+    }
+
+    ///Iterate over every symbol address and name, sorted by address
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.symbols
+            .iter()
+            .map(|(address, name)| (*address, name.as_str()))
+    }
+}
+
+pub(crate) fn pdb_info<P: ProcT + ?Sized>(proc: &P, module: &Module) -> Option<PdbInfo> {
+    let (debug_rva, debug_size) = data_directory(proc, module.base, 6)?;
+    for index in 0..debug_size / 28 {
+        //IMAGE_DEBUG_DIRECTORY: type at 12, size of data at 16, address of raw data at 20
+        let entry = proc.read_vec_uninit::<u8>(module.base + debug_rva + index * 28, 28)?;
+        if u32_at(&entry, 12)? != 2 {
+            continue;
+        }
+        let record = module.base + u32_at(&entry, 20)? as usize;
+        if proc.read::<[u8; 4]>(record)? != *b"RSDS" {
+            continue;
+        }
+        return Some(PdbInfo {
+            guid: proc.read::<[u8; 16]>(record + 4)?,
+            age: proc.read::<u32>(record + 20)?,
+            path: proc.read_cstring_lossy(record + 24, 1024)?,
+        });
+    }
+    None
+}
+
+///Multi-stream file container used by PDB files
+struct Msf<'a> {
+    file: &'a [u8],
+    block_size: usize,
+    streams: Vec<(usize, Vec<usize>)>,
+}
+
+impl<'a> Msf<'a> {
+    fn parse(file: &'a [u8]) -> Option<Msf<'a>> {
+        if file.get(..32)? != b"Microsoft C/C++ MSF 7.00\r\n\x1ADS\0\0\0" {
+            return None;
+        }
+        let block_size = u32_at(file, 32)? as usize;
+        let directory_size = u32_at(file, 44)? as usize;
+        let block_map = u32_at(file, 52)? as usize;
+        if block_size == 0 {
+            return None;
+        }
+
+        let directory_blocks = (0..directory_size.div_ceil(block_size))
+            .map(|index| {
+                u32_at(file, block_map * block_size + index * 4).map(|block| block as usize)
+            })
+            .collect::<Option<Vec<usize>>>()?;
+        let directory = read_blocks(file, block_size, &directory_blocks, directory_size)?;
+
+        //Each stream has at least its size in the directory, bounds the count before anything is allocated
+        let stream_count = u32_at(&directory, 0)? as usize;
+        if stream_count > (directory.len() - 4) / 4 {
+            return None;
+        }
+        let mut position = 4 + stream_count * 4;
+        let mut streams = Vec::new();
+        for index in 0..stream_count {
+            let size = match u32_at(&directory, 4 + index * 4)? {
+                u32::MAX => 0,
+                size => size as usize,
+            };
+            let blocks = (0..size.div_ceil(block_size))
+                .map(|block| u32_at(&directory, position + block * 4).map(|block| block as usize))
+                .collect::<Option<Vec<usize>>>()?;
+            position += blocks.len() * 4;
+            streams.push((size, blocks));
+        }
+
+        Some(Msf {
+            file,
+            block_size,
+            streams,
+        })
+    }
+
+    fn stream(&self, index: usize) -> Option<Vec<u8>> {
+        let (size, blocks) = self.streams.get(index)?;
+        read_blocks(self.file, self.block_size, blocks, *size)
+    }
+}
+
+fn read_blocks(file: &[u8], block_size: usize, blocks: &[usize], size: usize) -> Option<Vec<u8>> {
+    let mut data = Vec::with_capacity(size.min(file.len()));
+    for block in blocks {
+        let start = block.checked_mul(block_size)?;
+        data.extend_from_slice(file.get(start..start + block_size.min(size - data.len()))?);
+    }
+    Some(data)
+}
@@ -0,0 +1,322 @@
+use crate::{Module, ProcT};
+
+///Upper bound for the number of exports read from a module, larger tables are treated as corrupted
+const MAX_EXPORTS: usize = 0x10000;
+
+///Function or variable exported by a PE module
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Export {
+    ///Exported name, None for exports only available by ordinal
+    pub name: Option<String>,
+    ///Export ordinal
+    pub ordinal: u32,
+    ///Absolute address of the export in the process, zero for forwarded exports
+    pub address: usize,
+    ///Target of a forwarded export, e.g. 'NTDLL.RtlAllocateHeap'
+    pub forwarder: Option<String>,
+}
+
+pub(crate) fn u32_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+///Location of the NT headers of a module loaded at 'base' and whether its optional header is PE32+
+pub(crate) fn nt_headers<P: ProcT + ?Sized>(proc: &P, base: usize) -> Option<(usize, bool)> {
+    if proc.read::<[u8; 2]>(base)? != *b"MZ" {
+        return None;
+    }
+    let nt = base + proc.read::<u32>(base + 0x3C)? as usize;
+    if proc.read::<[u8; 4]>(nt)? != *b"PE\0\0" {
+        return None;
+    }
+    //Optional header magic, right after the 20 byte file header
+    match proc.read::<u16>(nt + 24)? {
+        0x10B => Some((nt, false)),
+        0x20B => Some((nt, true)),
+        _ => None,
+    }
+}
+
+///Relative address and size of data directory 'index' of the module loaded at 'base'
+pub(crate) fn data_directory<P: ProcT + ?Sized>(
+    proc: &P,
+    base: usize,
+    index: usize,
+) -> Option<(usize, usize)> {
+    let (nt, is_64) = nt_headers(proc, base)?;
+    let directories = nt + 24 + if is_64 { 112 } else { 96 };
+    let count = proc.read::<u32>(directories - 4)? as usize;
+    if index >= count {
+        return None;
+    }
+    let [rva, size] = proc.read::<[u32; 2]>(directories + index * 8)?;
+    if rva == 0 {
+        return None;
+    }
+    Some((rva as usize, size as usize))
+}
+
+pub(crate) fn read_exports<P: ProcT + ?Sized>(proc: &P, module: &Module) -> Option<Vec<Export>> {
+    let base = module.base;
+    let (export_rva, export_size) = match data_directory(proc, base, 0) {
+        Some(directory) => directory,
+        None if nt_headers(proc, base).is_some() => return Some(Vec::new()),
+        None => return None,
+    };
+
+    let directory = proc.read_vec_uninit::<u8>(base + export_rva, 40)?;
+    let ordinal_base = u32_at(&directory, 16)?;
+    let function_count = u32_at(&directory, 20)? as usize;
+    let name_count = u32_at(&directory, 24)? as usize;
+    if function_count > MAX_EXPORTS || name_count > MAX_EXPORTS {
+        return None;
+    }
+
+    let functions = read_u32_table(
+        proc,
+        base + u32_at(&directory, 28)? as usize,
+        function_count,
+    )?;
+    let names = read_u32_table(proc, base + u32_at(&directory, 32)? as usize, name_count)?;
+    let name_ordinals = read_u16_table(proc, base + u32_at(&directory, 36)? as usize, name_count)?;
+
+    let mut exports: Vec<Export> = functions
+        .iter()
+        .enumerate()
+        .filter(|(_, rva)| **rva != 0)
+        .map(|(index, rva)| {
+            let rva = *rva as usize;
+            let forwarded = rva >= export_rva && rva < export_rva + export_size;
+            Export {
+                name: None,
+                ordinal: ordinal_base + index as u32,
+                address: if forwarded { 0 } else { base + rva },
+                forwarder: if forwarded {
+                    proc.read_cstring_lossy(base + rva, 256)
+                } else {
+                    None
+                },
+            }
+        })
+        .collect();
+
+    for (name_rva, index) in names.iter().zip(&name_ordinals) {
+        let ordinal = ordinal_base + *index as u32;
+        if let Some(export) = exports.iter_mut().find(|export| export.ordinal == ordinal) {
+            export.name = proc.read_cstring_lossy(base + *name_rva as usize, 512);
+        }
+    }
+
+    Some(exports)
+}
+
+fn read_u32_table<P: ProcT + ?Sized>(proc: &P, address: usize, len: usize) -> Option<Vec<u32>> {
+    if len == 0 {
+        return Some(Vec::new());
+    }
+    proc.read_vec_uninit::<u32>(address, len)
+}
+
+fn read_u16_table<P: ProcT + ?Sized>(proc: &P, address: usize, len: usize) -> Option<Vec<u16>> {
+    if len == 0 {
+        return Some(Vec::new());
+    }
+    proc.read_vec_uninit::<u16>(address, len)
+}
+
+///Upper bound for the number of imported functions read from a module
+const MAX_IMPORTS: usize = 0x10000;
+
+///Section of a PE module
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Section {
+    ///Section name, e.g. '.text'
+    pub name: String,
+    ///Absolute address of the section in the process
+    pub address: usize,
+    ///Size of the section once loaded
+    pub size: usize,
+    ///IMAGE_SCN_* characteristics flags
+    pub characteristics: u32,
+}
+
+impl Section {
+    ///Returns 'true' if the address lies inside the section
+    pub fn contains(&self, address: usize) -> bool {
+        address >= self.address && address < self.address + self.size
+    }
+
+    ///Returns 'true' if the section contains executable code
+    pub fn is_executable(&self) -> bool {
+        self.characteristics & 0x2000_0000 != 0
+    }
+
+    ///Returns 'true' if the section is readable
+    pub fn is_readable(&self) -> bool {
+        self.characteristics & 0x4000_0000 != 0
+    }
+
+    ///Returns 'true' if the section is writable
+    pub fn is_writable(&self) -> bool {
+        self.characteristics & 0x8000_0000 != 0
+    }
+}
+
+///Parsed PE headers of a module loaded in another process
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PeHeaders {
+    ///IMAGE_FILE_MACHINE_* value of the file header
+    pub machine: u16,
+    ///Link time stamp, seconds since the UNIX epoch
+    pub timestamp: u32,
+    ///'true' for PE32+ (64-bit) images
+    pub is_64bit: bool,
+    ///Preferred load address from the optional header
+    pub image_base: usize,
+    ///Size of the loaded image
+    pub image_size: usize,
+    ///Absolute address of the entry point, zero if the image has none
+    pub entry_point: usize,
+    ///Sections of the image
+    pub sections: Vec<Section>,
+}
+
+impl PeHeaders {
+    ///Find a section by name
+    pub fn section(&self, name: &str) -> Option<&Section> {
+        self.sections.iter().find(|section| section.name == name)
+    }
+}
+
+///Function imported by a PE module
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Import {
+    ///Name of the module the function is imported from
+    pub module: String,
+    ///Imported name, None for imports by ordinal
+    pub name: Option<String>,
+    ///Ordinal of imports by ordinal
+    pub ordinal: Option<u16>,
+    ///Address of the import address table slot holding the resolved function
+    pub iat_address: usize,
+    ///Resolved function address currently stored in the import address table
+    pub address: usize,
+}
+
+pub(crate) fn u16_at(bytes: &[u8], offset: usize) -> Option<u16> {
+    let bytes = bytes.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+pub(crate) fn u64_at(bytes: &[u8], offset: usize) -> Option<u64> {
+    Some(u32_at(bytes, offset)? as u64 | (u32_at(bytes, offset + 4)? as u64) << 32)
+}
+
+pub(crate) fn read_headers<P: ProcT + ?Sized>(proc: &P, module: &Module) -> Option<PeHeaders> {
+    let base = module.base;
+    let (nt, is_64bit) = nt_headers(proc, base)?;
+
+    let file_header = proc.read_vec_uninit::<u8>(nt + 4, 20)?;
+    let section_count = u16_at(&file_header, 2)? as usize;
+    let optional_size = u16_at(&file_header, 16)? as usize;
+
+    let optional = nt + 24;
+    let optional_header = proc.read_vec_uninit::<u8>(optional, optional_size.max(64))?;
+    let entry_rva = u32_at(&optional_header, 16)? as usize;
+    let image_base = if is_64bit {
+        u64_at(&optional_header, 24)? as usize
+    } else {
+        u32_at(&optional_header, 28)? as usize
+    };
+
+    let table = if section_count == 0 {
+        Vec::new()
+    } else {
+        proc.read_vec_uninit::<u8>(optional + optional_size, section_count * 40)?
+    };
+    let sections = table
+        .chunks_exact(40)
+        .map(|section| {
+            let name_len = section[..8].iter().position(|c| *c == 0).unwrap_or(8);
+            Some(Section {
+                name: String::from_utf8_lossy(&section[..name_len]).into_owned(),
+                address: base + u32_at(section, 12)? as usize,
+                size: u32_at(section, 8)? as usize,
+                characteristics: u32_at(section, 36)?,
+            })
+        })
+        .collect::<Option<Vec<Section>>>()?;
+
+    Some(PeHeaders {
+        machine: u16_at(&file_header, 0)?,
+        timestamp: u32_at(&file_header, 4)?,
+        is_64bit,
+        image_base,
+        image_size: u32_at(&optional_header, 56)? as usize,
+        entry_point: if entry_rva == 0 { 0 } else { base + entry_rva },
+        sections,
+    })
+}
+
+pub(crate) fn read_imports<P: ProcT + ?Sized>(proc: &P, module: &Module) -> Option<Vec<Import>> {
+    let base = module.base;
+    let (_, is_64bit) = nt_headers(proc, base)?;
+    let (import_rva, _) = match data_directory(proc, base, 1) {
+        Some(directory) => directory,
+        None => return Some(Vec::new()),
+    };
+
+    let thunk_size = if is_64bit { 8 } else { 4 };
+    let ordinal_flag = if is_64bit { 1u64 << 63 } else { 1u64 << 31 };
+    let read_thunk = |address: usize| -> Option<u64> {
+        if is_64bit {
+            proc.read::<u64>(address)
+        } else {
+            proc.read::<u32>(address).map(u64::from)
+        }
+    };
+
+    let mut imports = Vec::new();
+    let mut descriptor = base + import_rva;
+    loop {
+        //OriginalFirstThunk, TimeDateStamp, ForwarderChain, Name, FirstThunk
+        let [lookup_rva, _, _, name_rva, iat_rva] = proc.read::<[u32; 5]>(descriptor)?;
+        if name_rva == 0 && iat_rva == 0 {
+            break;
+        }
+        let dll = proc.read_cstring_lossy(base + name_rva as usize, 256)?;
+        let lookup_rva = if lookup_rva == 0 { iat_rva } else { lookup_rva };
+
+        for index in 0.. {
+            if imports.len() >= MAX_IMPORTS {
+                return None;
+            }
+            let thunk = read_thunk(base + lookup_rva as usize + index * thunk_size)?;
+            if thunk == 0 {
+                break;
+            }
+            let iat_address = base + iat_rva as usize + index * thunk_size;
+
+            let (name, ordinal) = if thunk & ordinal_flag != 0 {
+                (None, Some(thunk as u16))
+            } else {
+                //IMAGE_IMPORT_BY_NAME: hint followed by the name
+                let name = proc.read_cstring_lossy(base + (thunk as u32) as usize + 2, 512);
+                (name, None)
+            };
+
+            imports.push(Import {
+                module: dll.clone(),
+                name,
+                ordinal,
+                iat_address,
+                address: read_thunk(iat_address)? as usize,
+            });
+        }
+
+        descriptor += 20;
+    }
+
+    Some(imports)
+}
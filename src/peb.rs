@@ -0,0 +1,186 @@
+use crate::{Heap, Module, ProcT};
+
+///Reads a UNICODE_STRING (length, maximum length, buffer) stored at 'address'
+pub(crate) fn read_unicode_string<P: ProcT + ?Sized>(proc: &P, address: usize) -> Option<String> {
+    let length = proc.read::<u16>(address)? as usize;
+    let buffer = proc.read::<usize>(address + 8)?;
+    if length == 0 || buffer == 0 {
+        return Some(String::new());
+    }
+    let units = proc.read_vec_uninit::<u16>(buffer, length / 2)?;
+    Some(String::from_utf16_lossy(&units))
+}
+
+///Splits a block of NUL separated 'NAME=value' entries, names may start with '=' (e.g. '=C:=C:\\')
+pub(crate) fn split_environment(block: &str) -> Vec<(String, String)> {
+    block
+        .split('\0')
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let start = entry.char_indices().nth(1)?.0;
+            let separator = entry[start..].find('=')? + start;
+            Some((
+                entry[..separator].to_string(),
+                entry[separator + 1..].to_string(),
+            ))
+        })
+        .collect()
+}
+
+///Process Environment Block of a 64-bit Windows process
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Peb {
+    ///Address of the PEB in the process
+    pub address: usize,
+    ///'true' if a debugger is attached
+    pub being_debugged: bool,
+    ///Address the main executable is loaded at
+    pub image_base: usize,
+    ///Address of the loader data (PEB_LDR_DATA)
+    pub ldr: usize,
+    ///Address of the RTL_USER_PROCESS_PARAMETERS
+    pub process_parameters: usize,
+    ///Address of the default process heap
+    pub process_heap: usize,
+    ///Number of heaps created by the process
+    pub number_of_heaps: u32,
+    ///Address of the array of heap addresses
+    pub process_heaps: usize,
+}
+
+impl Peb {
+    ///Read the PEB stored at 'address'
+    pub fn read<P: ProcT + ?Sized>(proc: &P, address: usize) -> Option<Peb> {
+        Some(Peb {
+            address,
+            being_debugged: proc.read::<u8>(address + 0x02)? != 0,
+            image_base: proc.read::<usize>(address + 0x10)?,
+            ldr: proc.read::<usize>(address + 0x18)?,
+            process_parameters: proc.read::<usize>(address + 0x20)?,
+            process_heap: proc.read::<usize>(address + 0x30)?,
+            number_of_heaps: proc.read::<u32>(address + 0xE8)?,
+            process_heaps: proc.read::<usize>(address + 0xF0)?,
+        })
+    }
+
+    ///Read the process parameters (command line, image path, environment)
+    pub fn parameters<P: ProcT + ?Sized>(&self, proc: &P) -> Option<ProcessParameters> {
+        ProcessParameters::read(proc, self.process_parameters)
+    }
+
+    ///Walk the loader's in-load-order module list
+    pub fn modules<P: ProcT + ?Sized>(&self, proc: &P) -> Option<Vec<Module>> {
+        //InLoadOrderModuleList head inside PEB_LDR_DATA
+        let head = self.ldr + 0x10;
+        let mut entry = proc.read::<usize>(head)?;
+        let mut modules = Vec::new();
+
+        while entry != head && entry != 0 {
+            //LDR_DATA_TABLE_ENTRY, InLoadOrderLinks is its first field
+            modules.push(Module {
+                base: proc.read::<usize>(entry + 0x30)?,
+                size: proc.read::<u32>(entry + 0x40)? as usize,
+                path: read_unicode_string(proc, entry + 0x48)?,
+                name: read_unicode_string(proc, entry + 0x58)?,
+            });
+            entry = proc.read::<usize>(entry)?;
+            if modules.len() > 0x4000 {
+                return None;
+            }
+        }
+
+        Some(modules)
+    }
+
+    ///Read the heaps listed in the ProcessHeaps array, the process heap first
+    pub fn heaps<P: ProcT + ?Sized>(&self, proc: &P) -> Option<Vec<Heap>> {
+        if self.number_of_heaps > 0x1000 {
+            return None;
+        }
+        let addresses =
+            proc.read_vec_uninit::<usize>(self.process_heaps, self.number_of_heaps as usize)?;
+        Some(
+            addresses
+                .into_iter()
+                .filter_map(|address| Heap::read(proc, address))
+                .collect(),
+        )
+    }
+}
+
+///RTL_USER_PROCESS_PARAMETERS of a 64-bit Windows process
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProcessParameters {
+    ///Current directory
+    pub current_directory: String,
+    ///Full path of the executable
+    pub image_path: String,
+    ///Command line the process was started with
+    pub command_line: String,
+    ///Address of the environment block
+    pub environment: usize,
+    ///Size in bytes of the environment block
+    pub environment_size: usize,
+}
+
+impl ProcessParameters {
+    ///Read the process parameters stored at 'address'
+    pub fn read<P: ProcT + ?Sized>(proc: &P, address: usize) -> Option<ProcessParameters> {
+        Some(ProcessParameters {
+            current_directory: read_unicode_string(proc, address + 0x38)?,
+            image_path: read_unicode_string(proc, address + 0x60)?,
+            command_line: read_unicode_string(proc, address + 0x70)?,
+            environment: proc.read::<usize>(address + 0x80)?,
+            environment_size: proc.read::<usize>(address + 0x3F0)?,
+        })
+    }
+
+    ///Read the environment block as name/value pairs
+    pub fn read_environment<P: ProcT + ?Sized>(&self, proc: &P) -> Option<Vec<(String, String)>> {
+        if self.environment == 0 || self.environment_size < 2 {
+            return Some(Vec::new());
+        }
+        let units = proc.read_vec_uninit::<u16>(self.environment, self.environment_size / 2)?;
+        Some(split_environment(&String::from_utf16_lossy(&units)))
+    }
+}
+
+///Thread Environment Block of a thread in a 64-bit Windows process
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Teb {
+    ///Address of the TEB in the process
+    pub address: usize,
+    ///Highest address of the thread stack
+    pub stack_base: usize,
+    ///Lowest committed address of the thread stack
+    pub stack_limit: usize,
+    ///Id of the process owning the thread
+    pub process_id: usize,
+    ///Id of the thread
+    pub thread_id: usize,
+    ///Address of the array of thread local storage pointers of the modules
+    pub tls_pointer: usize,
+    ///Address of the process PEB
+    pub peb: usize,
+    ///Last error value set by the thread
+    pub last_error: u32,
+    ///TlsAlloc slots
+    pub tls_slots: [usize; 64],
+}
+
+impl Teb {
+    ///Read the TEB stored at 'address'
+    pub fn read<P: ProcT + ?Sized>(proc: &P, address: usize) -> Option<Teb> {
+        Some(Teb {
+            address,
+            stack_base: proc.read::<usize>(address + 0x08)?,
+            stack_limit: proc.read::<usize>(address + 0x10)?,
+            process_id: proc.read::<usize>(address + 0x40)?,
+            thread_id: proc.read::<usize>(address + 0x48)?,
+            tls_pointer: proc.read::<usize>(address + 0x58)?,
+            peb: proc.read::<usize>(address + 0x60)?,
+            last_error: proc.read::<u32>(address + 0x68)?,
+            tls_slots: proc.read::<[usize; 64]>(address + 0x1480)?,
+        })
+    }
+}
@@ -0,0 +1,17 @@
+///Marker for plain old data types that can be safely built from any bit pattern read from another process.
+///
+///# Safety
+///
+///Implementors must be `Copy`, contain no references or pointers into the current process, have no padding
+///bytes that matter and be valid for every possible bit pattern.
+pub unsafe trait Pod: Copy + 'static {}
+
+macro_rules! impl_pod {
+    ($($t:ty),*) => {
+        $(unsafe impl Pod for $t {})*
+    };
+}
+
+impl_pod!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
@@ -0,0 +1,290 @@
+use std::convert::TryInto;
+use std::io::{Error, ErrorKind, Read, Write};
+use std::path::Path;
+
+use crate::{AddressChain, Module, ProcT, ScanOptions};
+
+///Bytes read from the process per step
+const CHUNK_SIZE: usize = 0x10_0000;
+
+///First bytes of saved maps
+const MAGIC: &[u8; 8] = b"PMPTRMAP";
+
+///Paths find_paths() stops at, pointer scans grow exponentially with the depth
+const MAX_PATHS: usize = 100_000;
+
+///Every aligned pointer-sized value of a process that points into its mapped memory, with the module bases of the
+///run it was taken from. Maps are searched offline for pointer paths to an address, and comparing the paths found in
+///maps of two runs quickly narrows them down to the ones that survive a restart of the target
+///
+///```
+///use proc_memory::{MockProc, Module, PointerMap, ProcT, ScanOptions};
+///
+///let game = Module { name: "game.exe".to_string(), base: 0x40_0000, size: 0x1000, ..Module::default() };
+///let proc = MockProc::new().with_module(game);
+///proc.map(0x40_0000, vec![0u8; 0x1000]);
+///proc.map(0x1000, vec![0u8; 0x100]);
+///proc.write(0x40_0100, &0x1000u64);
+///
+///let map = PointerMap::generate(&proc, &ScanOptions::new());
+///let paths = map.find_paths(0x1010, 2, 0x100);
+///assert_eq!(paths.len(), 1);
+///assert_eq!(paths[0].module.as_deref(), Some("game.exe"));
+///assert_eq!((paths[0].base, paths[0].offsets.clone()), (0x100, vec![0x10]));
+///assert_eq!(map.resolve(&paths[0]), Some(0x1010));
+///```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PointerMap {
+    pointer_size: usize,
+    modules: Vec<Module>,
+    ///Address of each pointer and the value stored there, sorted by address
+    pointers: Vec<(usize, usize)>,
+    ///Index into 'pointers' sorted by value
+    by_value: Vec<u32>,
+}
+
+impl PointerMap {
+    ///Map the pointers of the regions selected by 'options'. Pointers are only looked for at addresses aligned to
+    ///the pointer size, the alignment of 'options' is ignored
+    pub fn generate<P: ProcT + ?Sized>(proc: &P, options: &ScanOptions) -> PointerMap {
+        let pointer_size = proc.pointer_size();
+        let mut regions = options.regions(proc);
+        regions.sort_by_key(|region| region.base);
+        let is_mapped = |value: usize| {
+            let index = regions.partition_point(|region| region.base <= value);
+            index > 0 && regions[index - 1].contains(value)
+        };
+
+        let mut pointers = Vec::new();
+        for range in regions.iter().filter_map(|region| options.filter(region)) {
+            let mut chunk_start = range.start;
+            while chunk_start < range.end {
+                let len = CHUNK_SIZE.min(range.end - chunk_start);
+                let chunk = proc.read_bulk(chunk_start, len, 0);
+                let first = (pointer_size - chunk_start % pointer_size) % pointer_size;
+                for offset in (first..len.saturating_sub(pointer_size - 1)).step_by(pointer_size) {
+                    let address = chunk_start + offset;
+                    let value = read_pointer(&chunk.data[offset..], pointer_size);
+                    if is_mapped(value)
+                        && chunk.is_readable(address)
+                        && chunk.is_readable(address + pointer_size - 1)
+                    {
+                        pointers.push((address, value));
+                    }
+                }
+                chunk_start += len;
+            }
+        }
+        PointerMap::from_parts(pointer_size, proc.modules(), pointers)
+    }
+
+    fn from_parts(
+        pointer_size: usize,
+        modules: Vec<Module>,
+        mut pointers: Vec<(usize, usize)>,
+    ) -> PointerMap {
+        pointers.sort_unstable();
+        pointers.dedup_by_key(|(address, _)| *address);
+        let mut by_value: Vec<u32> = (0..pointers.len() as u32).collect();
+        by_value.sort_unstable_by_key(|index| pointers[*index as usize].1);
+        PointerMap {
+            pointer_size,
+            modules,
+            pointers,
+            by_value,
+        }
+    }
+
+    ///Number of pointers in the map
+    pub fn len(&self) -> usize {
+        self.pointers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pointers.is_empty()
+    }
+
+    ///Modules loaded in the run the map was taken from
+    pub fn modules(&self) -> &[Module] {
+        &self.modules
+    }
+
+    ///Address of every pointer and the value stored there, in ascending address order
+    pub fn pointers(&self) -> &[(usize, usize)] {
+        &self.pointers
+    }
+
+    ///Pointer stored at 'address' when the map was taken
+    pub fn pointer_at(&self, address: usize) -> Option<usize> {
+        self.pointers
+            .binary_search_by_key(&address, |(address, _)| *address)
+            .ok()
+            .map(|index| self.pointers[index].1)
+    }
+
+    ///Addresses of the pointers whose value lies in 'start..=end'
+    pub fn pointers_to(&self, start: usize, end: usize) -> Vec<usize> {
+        let value = |index: &u32| self.pointers[*index as usize].1;
+        let first = self.by_value.partition_point(|index| value(index) < start);
+        self.by_value[first..]
+            .iter()
+            .take_while(|index| value(index) <= end)
+            .map(|index| self.pointers[*index as usize].0)
+            .collect()
+    }
+
+    ///Pointer paths from a module to 'target' with at most 'max_depth' dereferences, each one adding at most
+    ///'max_offset' to the pointer read
+    pub fn find_paths(
+        &self,
+        target: usize,
+        max_depth: usize,
+        max_offset: usize,
+    ) -> Vec<AddressChain> {
+        let mut paths = Vec::new();
+        let mut offsets = Vec::new();
+        self.find_paths_into(target, max_depth, max_offset, &mut offsets, &mut paths);
+        paths
+    }
+
+    ///Walk back from 'target', 'offsets' holds the offsets already applied after it in reverse order
+    fn find_paths_into(
+        &self,
+        target: usize,
+        depth: usize,
+        max_offset: usize,
+        offsets: &mut Vec<isize>,
+        paths: &mut Vec<AddressChain>,
+    ) {
+        if depth == 0 {
+            return;
+        }
+        for address in self.pointers_to(target.saturating_sub(max_offset), target) {
+            if paths.len() >= MAX_PATHS {
+                return;
+            }
+            let value = self.pointer_at(address).unwrap();
+            offsets.push((target - value) as isize);
+            match self.modules.iter().find(|module| module.contains(address)) {
+                Some(module) => paths.push(AddressChain {
+                    module: Some(module.name.clone()),
+                    base: address - module.base,
+                    offsets: offsets.iter().rev().copied().collect(),
+                }),
+                None => self.find_paths_into(address, depth - 1, max_offset, offsets, paths),
+            }
+            offsets.pop();
+        }
+    }
+
+    ///Resolve 'chain' with the module bases and pointers of the map instead of a live process
+    pub fn resolve(&self, chain: &AddressChain) -> Option<usize> {
+        let mut address = match &chain.module {
+            Some(name) => self
+                .modules
+                .iter()
+                .find(|module| module.is_named(name))?
+                .base
+                .wrapping_add(chain.base),
+            None => chain.base,
+        };
+        for offset in &chain.offsets {
+            address = self.pointer_at(address)?.wrapping_add(*offset as usize);
+        }
+        Some(address)
+    }
+
+    ///Pointer paths to 'target' in this map that also lead to 'other_target' in 'other', a map of another run of
+    ///the target where the same value was found at 'other_target'
+    pub fn common_paths(
+        &self,
+        target: usize,
+        other: &PointerMap,
+        other_target: usize,
+        max_depth: usize,
+        max_offset: usize,
+    ) -> Vec<AddressChain> {
+        let mut paths = self.find_paths(target, max_depth, max_offset);
+        paths.retain(|path| other.resolve(path) == Some(other_target));
+        paths
+    }
+
+    ///Write the map to 'path'
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+        out.write_all(MAGIC)?;
+        write_u64(&mut out, self.pointer_size)?;
+        write_u64(&mut out, self.modules.len())?;
+        for module in &self.modules {
+            write_string(&mut out, &module.name)?;
+            write_string(&mut out, &module.path)?;
+            write_u64(&mut out, module.base)?;
+            write_u64(&mut out, module.size)?;
+        }
+        write_u64(&mut out, self.pointers.len())?;
+        for (address, value) in &self.pointers {
+            write_u64(&mut out, *address)?;
+            write_u64(&mut out, *value)?;
+        }
+        out.flush()
+    }
+
+    ///Load a map written by save()
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<PointerMap> {
+        let mut input = std::io::BufReader::new(std::fs::File::open(path)?);
+        let mut magic = [0u8; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(Error::new(ErrorKind::InvalidData, "not a pointer map"));
+        }
+
+        let pointer_size = read_u64(&mut input)?;
+        let mut modules = Vec::new();
+        for _ in 0..read_u64(&mut input)? {
+            modules.push(Module {
+                name: read_string(&mut input)?,
+                path: read_string(&mut input)?,
+                base: read_u64(&mut input)?,
+                size: read_u64(&mut input)?,
+            });
+        }
+        let mut pointers = Vec::new();
+        for _ in 0..read_u64(&mut input)? {
+            pointers.push((read_u64(&mut input)?, read_u64(&mut input)?));
+        }
+        Ok(PointerMap::from_parts(pointer_size, modules, pointers))
+    }
+}
+
+fn write_u64(out: &mut impl Write, value: usize) -> std::io::Result<()> {
+    out.write_all(&(value as u64).to_le_bytes())
+}
+
+fn write_string(out: &mut impl Write, text: &str) -> std::io::Result<()> {
+    write_u64(out, text.len())?;
+    out.write_all(text.as_bytes())
+}
+
+fn read_u64(input: &mut impl Read) -> std::io::Result<usize> {
+    let mut bytes = [0u8; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes) as usize)
+}
+
+fn read_string(input: &mut impl Read) -> std::io::Result<String> {
+    let mut bytes = Vec::new();
+    let len = read_u64(input)? as u64;
+    input.take(len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != len {
+        return Err(Error::from(ErrorKind::UnexpectedEof));
+    }
+    String::from_utf8(bytes).map_err(|error| Error::new(ErrorKind::InvalidData, error))
+}
+
+fn read_pointer(bytes: &[u8], pointer_size: usize) -> usize {
+    if pointer_size == 4 {
+        u32::from_ne_bytes(bytes[..4].try_into().unwrap()) as usize
+    } else {
+        u64::from_ne_bytes(bytes[..8].try_into().unwrap()) as usize
+    }
+}
@@ -0,0 +1,185 @@
+use std::mem::MaybeUninit;
+
+use crate::{uninit, Module, Pod, ProcT, Protection, Region, Thread};
+
+///Object-safe part of ProcT: raw byte access and the layout of the target.
+///Every ProcT implements it, so live processes, mocks and dump files can be held as a Box<dyn ProcRead> and
+///swapped at runtime. The trait object implements ProcT in turn, operations it can't express (allocation,
+///protection, remote threads, suspending) fail
+///
+///```
+///use proc_memory::{MockProc, ProcRead, ProcT};
+///
+///let mock = MockProc::new();
+///mock.map_value(0x1000, &42u32);
+///let proc: Box<dyn ProcRead> = Box::new(mock);
+///assert_eq!(proc.read::<u32>(0x1000), Some(42));
+///assert_eq!(proc.regions().len(), 1);
+///```
+pub trait ProcRead {
+    ///Read into 'buffer' from 'address', stopping at the first unreadable byte. Returns the number of bytes read
+    fn read_raw(&self, address: usize, buffer: &mut [u8]) -> usize;
+
+    ///Write 'data' at 'address', stopping at the first unwritable byte. Returns the number of bytes written
+    fn write_raw(&self, address: usize, data: &[u8]) -> usize;
+
+    ///Mapped memory regions, sorted by address
+    fn regions(&self) -> Vec<Region>;
+
+    ///Loaded modules
+    fn modules(&self) -> Vec<Module>;
+
+    ///Threads of the target
+    fn threads(&self) -> Vec<Thread>;
+
+    ///Executable name of the target
+    fn name(&self) -> Option<String>;
+
+    ///Process id of the target
+    fn pid(&self) -> isize;
+
+    ///Returns 'true' if the target uses 64-bit pointers
+    fn is_64bit(&self) -> bool;
+
+    ///Returns 'true' while the target can still be read
+    fn is_alive(&self) -> bool;
+}
+
+impl<P: ProcT> ProcRead for P {
+    fn read_raw(&self, address: usize, buffer: &mut [u8]) -> usize {
+        let (data, count) = self.read_partial(address, buffer.len());
+        let count = count.min(data.len()).min(buffer.len());
+        buffer[..count].copy_from_slice(&data[..count]);
+        count
+    }
+
+    fn write_raw(&self, address: usize, data: &[u8]) -> usize {
+        self.write_bytes(address, data).1
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        ProcT::regions(self)
+    }
+
+    fn modules(&self) -> Vec<Module> {
+        ProcT::modules(self)
+    }
+
+    fn threads(&self) -> Vec<Thread> {
+        ProcT::threads(self)
+    }
+
+    fn name(&self) -> Option<String> {
+        ProcT::name(self)
+    }
+
+    fn pid(&self) -> isize {
+        ProcT::pid(self)
+    }
+
+    fn is_64bit(&self) -> bool {
+        ProcT::is_64bit(self)
+    }
+
+    fn is_alive(&self) -> bool {
+        ProcT::is_alive(self)
+    }
+}
+
+///Fill 'buffer' completely or fail. Trait objects take initialized buffers, so it is zeroed first
+fn read_into(proc: &dyn ProcRead, proc_address: usize, buffer: &mut [MaybeUninit<u8>]) -> bool {
+    buffer.fill(MaybeUninit::new(0));
+    let buffer = unsafe { &mut *(buffer as *mut [MaybeUninit<u8>] as *mut [u8]) };
+    proc.read_raw(proc_address, buffer) == buffer.len()
+}
+
+impl ProcT for dyn ProcRead + '_ {
+    fn read<T: Pod>(&self, proc_address: usize) -> Option<T> {
+        uninit::read_value(|buffer| read_into(self, proc_address, buffer))
+    }
+
+    fn read_valid<T: Pod>(&self, proc_address: usize, validator: impl Fn(&T) -> bool) -> Option<T> {
+        self.read::<T>(proc_address)
+            .filter(|value| validator(value))
+    }
+
+    fn read_vec<T: Pod>(
+        &self,
+        proc_address: usize,
+        len: usize,
+        _default_provider: impl Fn() -> T,
+    ) -> Option<Vec<T>> {
+        uninit::read_values(len, |buffer| read_into(self, proc_address, buffer))
+    }
+
+    fn read_vec_uninit<T: Pod>(&self, proc_address: usize, len: usize) -> Option<Vec<T>> {
+        uninit::read_values(len, |buffer| read_into(self, proc_address, buffer))
+    }
+
+    fn read_partial(&self, proc_address: usize, len: usize) -> (Vec<u8>, usize) {
+        let mut data = vec![0u8; len];
+        let count = self.read_raw(proc_address, &mut data).min(len);
+        data.truncate(count);
+        (data, count)
+    }
+
+    fn write<T>(&self, proc_address: usize, data: &T) -> (bool, usize) {
+        let bytes = unsafe {
+            std::slice::from_raw_parts(data as *const T as *const u8, std::mem::size_of::<T>())
+        };
+        self.write_bytes(proc_address, bytes)
+    }
+
+    fn write_bytes(&self, proc_address: usize, data: &[u8]) -> (bool, usize) {
+        let count = self.write_raw(proc_address, data).min(data.len());
+        (count == data.len(), count)
+    }
+
+    fn modules(&self) -> Vec<Module> {
+        ProcRead::modules(self)
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        ProcRead::regions(self)
+    }
+
+    fn threads(&self) -> Vec<Thread> {
+        ProcRead::threads(self)
+    }
+
+    fn is_alive(&self) -> bool {
+        ProcRead::is_alive(self)
+    }
+
+    fn protect(&self, _address: usize, _len: usize, _protection: Protection) -> Option<Protection> {
+        None
+    }
+
+    fn alloc(&self, _len: usize, _protection: Protection) -> Option<usize> {
+        None
+    }
+
+    fn free(&self, _address: usize, _len: usize) -> bool {
+        false
+    }
+
+    fn create_thread(&self, _start: usize, _param: usize) -> Option<u32> {
+        None
+    }
+
+    fn create_thread_and_wait(&self, _start: usize, _param: usize) -> Option<u32> {
+        None
+    }
+
+    fn name(&self) -> Option<String> {
+        ProcRead::name(self)
+    }
+
+    fn is_64bit(&self) -> bool {
+        ProcRead::is_64bit(self)
+    }
+
+    fn pid(&self) -> isize {
+        ProcRead::pid(self)
+    }
+}
@@ -0,0 +1,270 @@
+use std::mem::MaybeUninit;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::{uninit, Module, Pod, Proc, ProcT, Protection, Region, Thread};
+
+///The current process, read and written through direct pointer access.
+///Every access first checks that the pages are mapped with the needed rights, so bad addresses fail instead of
+///crashing. Pages unmapped by another thread between the check and the access can still fault.
+///Use Proc::current() to go through the OS instead
+///
+///```
+///use proc_memory::{ProcSelf, ProcT};
+///
+///let value = 42u32;
+///let proc = unsafe { ProcSelf::new() };
+///assert_eq!(proc.read::<u32>(&value as *const u32 as usize), Some(42));
+///```
+#[derive(Clone, Debug)]
+pub struct ProcSelf {
+    proc: Proc,
+}
+
+impl ProcSelf {
+    ///# Safety
+    ///Reads and writes go straight to the memory of the current process. The caller must make sure that no
+    ///address passed to the handle is unmapped concurrently, that writes never hit memory owned by live Rust
+    ///values, and that reads of memory other threads write are synchronized with them
+    pub unsafe fn new() -> ProcSelf {
+        ProcSelf {
+            proc: Proc::current(),
+        }
+    }
+
+    ///Number of bytes from 'address' that are mapped readable, or writable if 'write' is set, up to 'len'
+    fn accessible_len(&self, address: usize, len: usize, write: bool) -> usize {
+        let end = address.saturating_add(len);
+        let mut current = address;
+        while current < end {
+            match self.proc.region(current) {
+                Some((range, protection)) if protection.read && (!write || protection.write) => {
+                    current = range.end.min(end);
+                }
+                _ => break,
+            }
+        }
+        current - address
+    }
+
+    fn read_into(&self, proc_address: usize, buffer: &mut [MaybeUninit<u8>]) -> bool {
+        if self.accessible_len(proc_address, buffer.len(), false) != buffer.len() {
+            return false;
+        }
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                proc_address as *const u8,
+                buffer.as_mut_ptr() as *mut u8,
+                buffer.len(),
+            );
+        }
+        true
+    }
+}
+
+impl ProcT for ProcSelf {
+    ///Always None, the current process is opened with ProcSelf::new()
+    fn get(_proc_name: &str) -> Option<Self> {
+        None
+    }
+
+    fn read<T: Pod>(&self, proc_address: usize) -> Option<T> {
+        if self.accessible_len(proc_address, std::mem::size_of::<T>(), false)
+            != std::mem::size_of::<T>()
+        {
+            return None;
+        }
+        Some(unsafe { std::ptr::read_unaligned(proc_address as *const T) })
+    }
+
+    fn read_valid<T: Pod>(&self, proc_address: usize, validator: impl Fn(&T) -> bool) -> Option<T> {
+        self.read::<T>(proc_address)
+            .filter(|value| validator(value))
+    }
+
+    fn read_vec<T: Pod>(
+        &self,
+        proc_address: usize,
+        len: usize,
+        _default_provider: impl Fn() -> T,
+    ) -> Option<Vec<T>> {
+        uninit::read_values(len, |buffer| self.read_into(proc_address, buffer))
+    }
+
+    fn read_vec_uninit<T: Pod>(&self, proc_address: usize, len: usize) -> Option<Vec<T>> {
+        uninit::read_values(len, |buffer| self.read_into(proc_address, buffer))
+    }
+
+    fn read_partial(&self, proc_address: usize, len: usize) -> (Vec<u8>, usize) {
+        let count = self.accessible_len(proc_address, len, false);
+        let mut data = vec![0u8; count];
+        unsafe {
+            std::ptr::copy_nonoverlapping(proc_address as *const u8, data.as_mut_ptr(), count);
+        }
+        (data, count)
+    }
+
+    fn write<T>(&self, proc_address: usize, data: &T) -> (bool, usize) {
+        let bytes = unsafe {
+            std::slice::from_raw_parts(data as *const T as *const u8, std::mem::size_of::<T>())
+        };
+        self.write_bytes(proc_address, bytes)
+    }
+
+    ///Write into writable pages only, the protection of read-only pages is not changed
+    fn write_bytes(&self, proc_address: usize, data: &[u8]) -> (bool, usize) {
+        let count = self.accessible_len(proc_address, data.len(), true);
+        unsafe {
+            std::ptr::copy(data.as_ptr(), proc_address as *mut u8, count);
+        }
+        (count == data.len(), count)
+    }
+
+    fn modules(&self) -> Vec<Module> {
+        self.proc.modules()
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        self.proc.regions()
+    }
+
+    fn threads(&self) -> Vec<Thread> {
+        self.proc.threads()
+    }
+
+    fn is_alive(&self) -> bool {
+        true
+    }
+
+    #[cfg(target_os = "windows")]
+    fn protect(&self, address: usize, len: usize, protection: Protection) -> Option<Protection> {
+        self.proc.protect(address, len, protection)
+    }
+
+    ///mprotect() on the calling process, the Linux backend can't inject system calls into itself
+    #[cfg(target_os = "linux")]
+    fn protect(&self, address: usize, len: usize, protection: Protection) -> Option<Protection> {
+        let (_, previous) = self.proc.region(address)?;
+        let start = address & !(crate::PAGE_SIZE - 1);
+        let result = unsafe {
+            libc::mprotect(
+                start as *mut libc::c_void,
+                address + len - start,
+                protection.to_prot(),
+            )
+        };
+        if result != 0 {
+            return None;
+        }
+        Some(previous)
+    }
+
+    #[cfg(target_os = "windows")]
+    fn alloc(&self, len: usize, protection: Protection) -> Option<usize> {
+        self.proc.alloc(len, protection)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn alloc(&self, len: usize, protection: Protection) -> Option<usize> {
+        let address = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                protection.to_prot(),
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        if address == libc::MAP_FAILED {
+            return None;
+        }
+        Some(address as usize)
+    }
+
+    #[cfg(target_os = "windows")]
+    fn free(&self, address: usize, len: usize) -> bool {
+        self.proc.free(address, len)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn free(&self, address: usize, len: usize) -> bool {
+        unsafe { libc::munmap(address as *mut libc::c_void, len) == 0 }
+    }
+
+    #[cfg(target_os = "windows")]
+    fn create_thread(&self, start: usize, param: usize) -> Option<u32> {
+        self.proc.create_thread(start, param)
+    }
+
+    ///Run 'start(param)' on a new std thread, 'start' must be an extern "C" fn(usize) -> u32
+    #[cfg(target_os = "linux")]
+    fn create_thread(&self, start: usize, param: usize) -> Option<u32> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::Builder::new()
+            .spawn(move || {
+                let _ = sender.send(unsafe { libc::syscall(libc::SYS_gettid) } as u32);
+                let start: extern "C" fn(usize) -> u32 = unsafe { std::mem::transmute(start) };
+                start(param)
+            })
+            .ok()?;
+        receiver.recv().ok()
+    }
+
+    #[cfg(target_os = "windows")]
+    fn create_thread_and_wait(&self, start: usize, param: usize) -> Option<u32> {
+        self.proc.create_thread_and_wait(start, param)
+    }
+
+    ///Run 'start(param)' on a new std thread and return its result
+    #[cfg(target_os = "linux")]
+    fn create_thread_and_wait(&self, start: usize, param: usize) -> Option<u32> {
+        let start: extern "C" fn(usize) -> u32 = unsafe { std::mem::transmute(start) };
+        std::thread::Builder::new()
+            .spawn(move || start(param))
+            .ok()?
+            .join()
+            .ok()
+    }
+
+    #[cfg(feature = "minidump")]
+    fn write_minidump(
+        &self,
+        path: &std::path::Path,
+        level: crate::DumpLevel,
+    ) -> std::io::Result<()> {
+        self.proc.write_minidump(path, level)
+    }
+
+    fn command_line(&self) -> Option<String> {
+        self.proc.command_line()
+    }
+
+    fn environment(&self) -> Option<Vec<(String, String)>> {
+        self.proc.environment()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.proc.name()
+    }
+
+    fn exe_path(&self) -> Option<PathBuf> {
+        self.proc.exe_path()
+    }
+
+    fn start_time(&self) -> Option<SystemTime> {
+        self.proc.start_time()
+    }
+
+    fn parent_pid(&self) -> Option<isize> {
+        self.proc.parent_pid()
+    }
+
+    fn is_64bit(&self) -> bool {
+        cfg!(target_pointer_width = "64")
+    }
+
+    fn pid(&self) -> isize {
+        self.proc.pid()
+    }
+}
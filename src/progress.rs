@@ -0,0 +1,45 @@
+use std::fmt;
+use std::sync::Arc;
+
+///Progress of a long running operation over process memory
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    ///Bytes processed so far
+    pub bytes_done: usize,
+    ///Bytes the operation will process in total
+    pub bytes_total: usize,
+    ///Regions fully processed so far
+    pub regions_done: usize,
+    ///Regions the operation will process in total
+    pub regions_total: usize,
+}
+
+impl Progress {
+    ///Processed fraction between 0.0 and 1.0
+    pub fn fraction(&self) -> f64 {
+        if self.bytes_total == 0 {
+            return 1.0;
+        }
+        self.bytes_done as f64 / self.bytes_total as f64
+    }
+}
+
+///Callback receiving progress updates, shareable with the threads doing the work
+#[derive(Clone)]
+pub(crate) struct ProgressCallback(Arc<dyn Fn(&Progress) + Send + Sync>);
+
+impl ProgressCallback {
+    pub(crate) fn new(callback: impl Fn(&Progress) + Send + Sync + 'static) -> ProgressCallback {
+        ProgressCallback(Arc::new(callback))
+    }
+
+    pub(crate) fn report(&self, progress: &Progress) {
+        (self.0)(progress)
+    }
+}
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
@@ -0,0 +1,102 @@
+use std::fmt;
+
+///Access rights of a range of memory pages
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Protection {
+    pub read: bool,
+    pub write: bool,
+    pub execute: bool,
+}
+
+impl Protection {
+    pub const NONE: Protection = Protection::new(false, false, false);
+    pub const READ: Protection = Protection::new(true, false, false);
+    pub const READ_WRITE: Protection = Protection::new(true, true, false);
+    pub const READ_EXECUTE: Protection = Protection::new(true, false, true);
+    pub const READ_WRITE_EXECUTE: Protection = Protection::new(true, true, true);
+
+    pub const fn new(read: bool, write: bool, execute: bool) -> Protection {
+        Protection {
+            read,
+            write,
+            execute,
+        }
+    }
+
+    ///Returns 'true' if every right granted by 'other' is also granted by this protection
+    pub fn contains(self, other: Protection) -> bool {
+        (self.read || !other.read)
+            && (self.write || !other.write)
+            && (self.execute || !other.execute)
+    }
+
+    ///Returns 'true' if any right granted by 'other' is also granted by this protection
+    pub fn intersects(self, other: Protection) -> bool {
+        (self.read && other.read) || (self.write && other.write) || (self.execute && other.execute)
+    }
+
+    ///PAGE_* constant with the same rights, writable pages are always readable on Windows
+    #[cfg(target_os = "windows")]
+    pub(crate) fn to_windows(self) -> u32 {
+        match (self.read || self.write, self.write, self.execute) {
+            (false, _, false) => 0x01,
+            (true, false, false) => 0x02,
+            (true, true, false) => 0x04,
+            (false, _, true) => 0x10,
+            (true, false, true) => 0x20,
+            (true, true, true) => 0x40,
+        }
+    }
+
+    ///Rights of a PAGE_* value, modifiers like PAGE_GUARD are ignored.
+    ///Also used for the memory info of minidumps on every platform
+    pub(crate) fn from_windows(flags: u32) -> Protection {
+        match flags & 0xFF {
+            0x02 => Protection::READ,
+            0x04 | 0x08 => Protection::READ_WRITE,
+            0x10 => Protection::new(false, false, true),
+            0x20 => Protection::READ_EXECUTE,
+            0x40 | 0x80 => Protection::READ_WRITE_EXECUTE,
+            _ => Protection::NONE,
+        }
+    }
+
+    ///PROT_* flags for mprotect/mmap
+    #[cfg(target_os = "linux")]
+    pub(crate) fn to_prot(self) -> i32 {
+        let mut prot = libc::PROT_NONE;
+        if self.read {
+            prot |= libc::PROT_READ;
+        }
+        if self.write {
+            prot |= libc::PROT_WRITE;
+        }
+        if self.execute {
+            prot |= libc::PROT_EXEC;
+        }
+        prot
+    }
+
+    ///Rights from the permission column of /proc/<pid>/maps, like "r-xp"
+    #[cfg(target_os = "linux")]
+    pub(crate) fn from_maps(perms: &str) -> Protection {
+        let perms = perms.as_bytes();
+        Protection::new(
+            perms.first() == Some(&b'r'),
+            perms.get(1) == Some(&b'w'),
+            perms.get(2) == Some(&b'x'),
+        )
+    }
+}
+
+impl fmt::Display for Protection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            if self.read { 'r' } else { '-' },
+            if self.write { 'w' } else { '-' },
+            if self.execute { 'x' } else { '-' }
+        )
+    }
+}
@@ -0,0 +1,127 @@
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::{ProcT, Protection};
+
+///Range of committed memory pages sharing the same protection
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Region {
+    ///Address of the first byte of the region
+    pub base: usize,
+    ///Size of the region in bytes
+    pub size: usize,
+    ///Current protection of the pages
+    pub protection: Protection,
+}
+
+impl Region {
+    ///Address one past the last byte of the region
+    pub fn end(&self) -> usize {
+        self.base + self.size
+    }
+
+    ///Addresses covered by the region
+    pub fn range(&self) -> Range<usize> {
+        self.base..self.end()
+    }
+
+    ///Returns 'true' if the address lies inside the region
+    pub fn contains(&self, address: usize) -> bool {
+        address >= self.base && address < self.end()
+    }
+
+    ///FNV-1a hash of the region contents, None if any of it can't be read. Compare hashes to detect changes
+    pub fn hash<P: ProcT + ?Sized>(&self, proc: &P) -> Option<u64> {
+        crate::integrity::hash_range(proc, self.range())
+    }
+
+    ///Write the contents of the region to the file at 'path', returns the address ranges that could not be read
+    pub fn dump<P: ProcT + ?Sized>(&self, proc: &P, path: &Path) -> io::Result<Vec<Range<usize>>> {
+        proc.dump_range(self.base, self.size, path)
+    }
+}
+
+///How the pages of a region relate to other mappings of the same memory
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Sharing {
+    ///Owned by the process alone, like heaps and stacks
+    #[default]
+    Private,
+    ///Shared with other processes, writes are visible to all of them
+    Shared,
+    ///Shared until written, the first write makes a private copy of the page. Module images and private file
+    ///mappings, patching them doesn't change the file or other processes
+    CopyOnWrite,
+}
+
+///Region containing a queried address, with the file mapped in it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegionInfo {
+    ///Address of the first byte of the region
+    pub base: usize,
+    ///Size of the region in bytes
+    pub size: usize,
+    ///Current protection of the pages
+    pub protection: Protection,
+    ///File mapped in the region, or a pseudo name like '[heap]' on Linux. None for anonymous memory
+    pub file: Option<String>,
+    ///Private, shared or copy-on-write mapping, Private when the backend can't tell
+    pub sharing: Sharing,
+}
+
+impl RegionInfo {
+    ///Address one past the last byte of the region
+    pub fn end(&self) -> usize {
+        self.base + self.size
+    }
+
+    ///Addresses covered by the region
+    pub fn range(&self) -> Range<usize> {
+        self.base..self.end()
+    }
+
+    ///Returns 'true' if the address lies inside the region
+    pub fn contains(&self, address: usize) -> bool {
+        address >= self.base && address < self.end()
+    }
+
+    ///Returns 'true' for memory not backed by a file, including Linux pseudo names like '[heap]' or '[stack]'
+    pub fn is_anonymous(&self) -> bool {
+        match &self.file {
+            Some(file) => file.starts_with('['),
+            None => true,
+        }
+    }
+
+    ///Bytes that can be accessed from 'address' to the end of the region, zero if it lies outside
+    pub fn remaining(&self, address: usize) -> usize {
+        if self.contains(address) {
+            self.end() - address
+        } else {
+            0
+        }
+    }
+}
+
+impl From<Region> for RegionInfo {
+    fn from(region: Region) -> Self {
+        RegionInfo {
+            base: region.base,
+            size: region.size,
+            protection: region.protection,
+            file: None,
+            sharing: Sharing::Private,
+        }
+    }
+}
+
+impl From<RegionInfo> for Region {
+    fn from(info: RegionInfo) -> Self {
+        Region {
+            base: info.base,
+            size: info.size,
+            protection: info.protection,
+        }
+    }
+}
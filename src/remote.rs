@@ -0,0 +1,355 @@
+use std::convert::TryInto;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+
+use crate::{BackendProc, MemoryBackend, Module, ProcT, Protection, Region};
+
+///Largest frame accepted from the other side
+const MAX_FRAME: usize = 64 << 20;
+///Largest read or write sent in one request, bigger ones are split
+const MAX_READ: usize = 16 << 20;
+
+const OP_INFO: u8 = 0;
+const OP_READ: u8 = 1;
+const OP_WRITE: u8 = 2;
+const OP_REGIONS: u8 = 3;
+const OP_MODULES: u8 = 4;
+const OP_ALIVE: u8 = 5;
+
+///Send 'payload' prefixed by its length as a little endian u32
+fn write_frame(stream: &mut TcpStream, payload: &[u8]) -> io::Result<()> {
+    let mut frame = Vec::with_capacity(4 + payload.len());
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.extend_from_slice(payload);
+    stream.write_all(&frame)
+}
+
+///Receive a frame, None when the other side closed the connection between frames
+fn read_frame(stream: &mut TcpStream) -> io::Result<Option<Vec<u8>>> {
+    let mut len = [0u8; 4];
+    match stream.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(error) => return Err(error),
+    }
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_FRAME {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "frame too large",
+        ));
+    }
+    let mut payload = vec![0u8; len];
+    stream.read_exact(&mut payload)?;
+    Ok(Some(payload))
+}
+
+fn put_u64(buffer: &mut Vec<u8>, value: u64) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_str(buffer: &mut Vec<u8>, value: &str) {
+    buffer.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(value.as_bytes());
+}
+
+///Reader over a received payload, every getter fails past its end
+struct Fields<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Fields<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if len > self.data.len() {
+            return None;
+        }
+        let (field, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(field)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    fn str(&mut self) -> Option<String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).ok()
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        self.take(self.data.len()).unwrap_or_default()
+    }
+}
+
+fn protection_bits(protection: Protection) -> u8 {
+    protection.read as u8 | (protection.write as u8) << 1 | (protection.execute as u8) << 2
+}
+
+///Answer one request, None if it is malformed
+fn answer<P: ProcT + ?Sized>(proc: &P, request: &[u8]) -> Option<Vec<u8>> {
+    let mut fields = Fields { data: request };
+    let mut response = Vec::new();
+    match fields.u8()? {
+        OP_INFO => {
+            put_u64(&mut response, proc.pid() as i64 as u64);
+            response.push(proc.is_64bit() as u8);
+            match proc.name() {
+                Some(name) => {
+                    response.push(1);
+                    put_str(&mut response, &name);
+                }
+                None => response.push(0),
+            }
+        }
+        OP_READ => {
+            let address = fields.u64()? as usize;
+            let len = (fields.u64()? as usize).min(MAX_READ);
+            let (data, count) = proc.read_partial(address, len);
+            response.extend_from_slice(&data[..count.min(data.len())]);
+        }
+        OP_WRITE => {
+            let address = fields.u64()? as usize;
+            let (_, count) = proc.write_bytes(address, fields.rest());
+            put_u64(&mut response, count as u64);
+        }
+        OP_REGIONS => {
+            let regions = proc.regions();
+            response.extend_from_slice(&(regions.len() as u32).to_le_bytes());
+            for region in regions {
+                put_u64(&mut response, region.base as u64);
+                put_u64(&mut response, region.size as u64);
+                response.push(protection_bits(region.protection));
+            }
+        }
+        OP_MODULES => {
+            let modules = proc.modules();
+            response.extend_from_slice(&(modules.len() as u32).to_le_bytes());
+            for module in modules {
+                put_str(&mut response, &module.name);
+                put_str(&mut response, &module.path);
+                put_u64(&mut response, module.base as u64);
+                put_u64(&mut response, module.size as u64);
+            }
+        }
+        OP_ALIVE => response.push(proc.is_alive() as u8),
+        _ => return None,
+    }
+    Some(response)
+}
+
+///Serve the requests of one client until it disconnects
+pub fn serve_agent_connection<P: ProcT + ?Sized>(
+    proc: &P,
+    mut stream: TcpStream,
+) -> io::Result<()> {
+    stream.set_nodelay(true)?;
+    while let Some(request) = read_frame(&mut stream)? {
+        let response = answer(proc, &request)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed request"))?;
+        write_frame(&mut stream, &response)?;
+    }
+    Ok(())
+}
+
+///Run an agent giving the clients connecting to 'listener' access to the memory of 'proc', each on its own
+///thread. Returns when accepting fails.
+///There is no authentication: anyone reaching the listener can read and write the process, bind it to loopback
+///or a trusted network
+pub fn serve_agent<P: ProcT + Sync + ?Sized>(proc: &P, listener: &TcpListener) -> io::Result<()> {
+    std::thread::scope(|scope| loop {
+        let (stream, _) = listener.accept()?;
+        scope.spawn(move || serve_agent_connection(proc, stream));
+    })
+}
+
+///Connection to an agent started with serve_agent(), the MemoryBackend of RemoteProc
+#[derive(Debug)]
+pub struct RemoteBackend {
+    ///None once a request failed, the connection can't be trusted to be at a frame boundary anymore
+    stream: Mutex<Option<TcpStream>>,
+    pid: isize,
+    is_64bit: bool,
+    name: Option<String>,
+}
+
+impl RemoteBackend {
+    pub fn connect(address: impl ToSocketAddrs) -> io::Result<RemoteBackend> {
+        let stream = TcpStream::connect(address)?;
+        stream.set_nodelay(true)?;
+        let mut backend = RemoteBackend {
+            stream: Mutex::new(Some(stream)),
+            pid: 0,
+            is_64bit: cfg!(target_pointer_width = "64"),
+            name: None,
+        };
+        let info = backend.request(&[OP_INFO])?;
+        let mut fields = Fields { data: &info };
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed agent info");
+        backend.pid = fields.u64().ok_or_else(invalid)? as i64 as isize;
+        backend.is_64bit = fields.u8().ok_or_else(invalid)? != 0;
+        if fields.u8().ok_or_else(invalid)? != 0 {
+            backend.name = Some(fields.str().ok_or_else(invalid)?);
+        }
+        Ok(backend)
+    }
+
+    ///Send a request and wait for its response. Any error closes the connection and fails every later request
+    fn request(&self, request: &[u8]) -> io::Result<Vec<u8>> {
+        let mut guard = self.stream.lock().unwrap();
+        let stream = guard.as_mut().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotConnected,
+                "connection to the agent was lost",
+            )
+        })?;
+        let response = write_frame(stream, request).and_then(|()| {
+            read_frame(stream)?
+                .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "agent disconnected"))
+        });
+        if response.is_err() {
+            *guard = None;
+        }
+        response
+    }
+}
+
+impl MemoryBackend for RemoteBackend {
+    fn read_raw(&self, address: usize, buffer: &mut [u8]) -> usize {
+        let mut done = 0;
+        while done < buffer.len() {
+            let len = (buffer.len() - done).min(MAX_READ);
+            let mut request = vec![OP_READ];
+            put_u64(&mut request, (address + done) as u64);
+            put_u64(&mut request, len as u64);
+            let data = match self.request(&request) {
+                Ok(data) => data,
+                Err(_) => break,
+            };
+            let count = data.len().min(len);
+            buffer[done..done + count].copy_from_slice(&data[..count]);
+            done += count;
+            if count < len {
+                break;
+            }
+        }
+        done
+    }
+
+    fn write_raw(&self, address: usize, data: &[u8]) -> usize {
+        let mut done = 0;
+        for chunk in data.chunks(MAX_READ) {
+            let mut request = vec![OP_WRITE];
+            put_u64(&mut request, (address + done) as u64);
+            request.extend_from_slice(chunk);
+            let count = self
+                .request(&request)
+                .ok()
+                .and_then(|response| Fields { data: &response }.u64())
+                .map_or(0, |count| (count as usize).min(chunk.len()));
+            done += count;
+            if count < chunk.len() {
+                break;
+            }
+        }
+        done
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        let response = match self.request(&[OP_REGIONS]) {
+            Ok(response) => response,
+            Err(_) => return Vec::new(),
+        };
+        let mut fields = Fields { data: &response };
+        let count = fields.u32().unwrap_or(0);
+        let mut regions = Vec::new();
+        for _ in 0..count {
+            let region = (|| {
+                let base = fields.u64()? as usize;
+                let size = fields.u64()? as usize;
+                let bits = fields.u8()?;
+                let protection = Protection::new(bits & 1 != 0, bits & 2 != 0, bits & 4 != 0);
+                Some(Region {
+                    base,
+                    size,
+                    protection,
+                })
+            })();
+            match region {
+                Some(region) => regions.push(region),
+                None => break,
+            }
+        }
+        regions
+    }
+
+    fn modules(&self) -> Vec<Module> {
+        let response = match self.request(&[OP_MODULES]) {
+            Ok(response) => response,
+            Err(_) => return Vec::new(),
+        };
+        let mut fields = Fields { data: &response };
+        let count = fields.u32().unwrap_or(0);
+        let mut modules = Vec::new();
+        for _ in 0..count {
+            let module = (|| {
+                Some(Module {
+                    name: fields.str()?,
+                    path: fields.str()?,
+                    base: fields.u64()? as usize,
+                    size: fields.u64()? as usize,
+                })
+            })();
+            match module {
+                Some(module) => modules.push(module),
+                None => break,
+            }
+        }
+        modules
+    }
+
+    fn name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    fn pid(&self) -> isize {
+        self.pid
+    }
+
+    fn is_64bit(&self) -> bool {
+        self.is_64bit
+    }
+
+    ///'false' once the agent or its target is gone, or a request to it failed
+    fn is_alive(&self) -> bool {
+        self.request(&[OP_ALIVE])
+            .is_ok_and(|response| response.first() == Some(&1))
+    }
+}
+
+///Process on another machine, or inside a VM, accessed through an agent started with serve_agent().
+///Reads, writes, scans and pointer chains work like on a local process, other operations fail
+///
+///```no_run
+///use proc_memory::{ProcT, RemoteProc};
+///
+///let proc = RemoteProc::connect("192.168.1.20:7878").unwrap();
+///let value = proc.read::<u32>(0x7FF6_1234_0000);
+///```
+pub type RemoteProc = BackendProc<RemoteBackend>;
+
+impl BackendProc<RemoteBackend> {
+    ///Connect to the agent listening at 'address'
+    pub fn connect(address: impl ToSocketAddrs) -> io::Result<RemoteProc> {
+        Ok(BackendProc::new(RemoteBackend::connect(address)?))
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{Pod, Proc, ProcT, RemotePtr};
+
+///Number of elements read at once when iterating a RemoteArray
+const DEFAULT_CHUNK_LEN: usize = 256;
+
+///Fixed length array of T inside another process
+pub struct RemoteArray<'a, T: Pod, P: ProcT = Proc> {
+    ptr: RemotePtr<'a, T, P>,
+    len: usize,
+}
+
+impl<'a, T: Pod, P: ProcT> RemoteArray<'a, T, P> {
+    ///Create an array of 'len' elements starting at the specified address of 'proc'
+    pub fn new(proc: &'a P, address: usize, len: usize) -> Self {
+        RemoteArray {
+            ptr: RemotePtr::new(proc, address),
+            len,
+        }
+    }
+
+    ///Address of the first element
+    pub fn address(&self) -> usize {
+        self.ptr.address()
+    }
+
+    ///Number of elements
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    ///Returns 'true' if the array has no elements
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    ///Pointer to the first element
+    pub fn as_ptr(&self) -> RemotePtr<'a, T, P> {
+        self.ptr
+    }
+
+    ///Read the element at 'index', returns None if out of bounds or unreadable
+    pub fn get(&self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+        self.ptr.offset(index as isize).read()
+    }
+
+    ///Write 'value' to the element at 'index'
+    pub fn set(&self, index: usize, value: &T) -> (bool, usize) {
+        if index >= self.len {
+            return (false, 0);
+        }
+        self.ptr.offset(index as isize).write(value)
+    }
+
+    ///Sub array of the elements in 'start..end', returns None if out of bounds
+    pub fn slice(&self, start: usize, end: usize) -> Option<RemoteArray<'a, T, P>> {
+        if start > end || end > self.len {
+            return None;
+        }
+        Some(RemoteArray {
+            ptr: self.ptr.offset(start as isize),
+            len: end - start,
+        })
+    }
+
+    ///Read every element with a single bulk read
+    pub fn download(&self) -> Option<Vec<T>> {
+        if self.len == 0 {
+            return Some(Vec::new());
+        }
+        self.ptr
+            .proc()
+            .read_vec_uninit::<T>(self.address(), self.len)
+    }
+
+    ///Iterate over the array reading 'chunk_len' elements at a time
+    pub fn chunks(&self, chunk_len: usize) -> RemoteChunks<'a, T, P> {
+        RemoteChunks {
+            array: RemoteArray {
+                ptr: self.ptr,
+                len: self.len,
+            },
+            chunk_len: chunk_len.max(1),
+            position: 0,
+        }
+    }
+
+    ///Iterate over the elements, stops at the first unreadable chunk
+    pub fn iter(&self) -> impl Iterator<Item = T> + 'a {
+        self.chunks(DEFAULT_CHUNK_LEN)
+            .map_while(|chunk| chunk)
+            .flatten()
+    }
+}
+
+///Iterator over the chunks of a RemoteArray, yields None for chunks that could not be read
+pub struct RemoteChunks<'a, T: Pod, P: ProcT = Proc> {
+    array: RemoteArray<'a, T, P>,
+    chunk_len: usize,
+    position: usize,
+}
+
+impl<'a, T: Pod, P: ProcT> Iterator for RemoteChunks<'a, T, P> {
+    type Item = Option<Vec<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position >= self.array.len {
+            return None;
+        }
+        let end = (self.position + self.chunk_len).min(self.array.len);
+        let chunk = self.array.slice(self.position, end)?;
+        self.position = end;
+        Some(chunk.download())
+    }
+}
@@ -0,0 +1,97 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::{Pod, Proc, ProcT};
+
+///Typed pointer to a value of type T inside another process
+pub struct RemotePtr<'a, T, P: ProcT = Proc> {
+    proc: &'a P,
+    address: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T, P: ProcT> RemotePtr<'a, T, P> {
+    ///Create a pointer to the specified address of 'proc'
+    pub fn new(proc: &'a P, address: usize) -> Self {
+        RemotePtr {
+            proc,
+            address,
+            _marker: PhantomData,
+        }
+    }
+
+    ///Address pointed to
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    ///Process the pointer belongs to
+    pub fn proc(&self) -> &'a P {
+        self.proc
+    }
+
+    ///Returns 'true' if the pointer address is zero
+    pub fn is_null(&self) -> bool {
+        self.address == 0
+    }
+
+    ///Read the value pointed to
+    pub fn read(&self) -> Option<T>
+    where
+        T: Pod,
+    {
+        self.proc.read::<T>(self.address)
+    }
+
+    ///Write 'value' to the pointed address
+    pub fn write(&self, value: &T) -> (bool, usize) {
+        self.proc.write(self.address, value)
+    }
+
+    ///Pointer to the 'count'-th element of type T after this one, like pointer arithmetic
+    pub fn offset(&self, count: isize) -> Self {
+        let bytes = count.wrapping_mul(std::mem::size_of::<T>() as isize);
+        RemotePtr::new(self.proc, self.address.wrapping_add(bytes as usize))
+    }
+
+    ///Pointer moved by 'bytes' bytes, keeping the pointed type
+    pub fn byte_offset(&self, bytes: isize) -> Self {
+        RemotePtr::new(self.proc, self.address.wrapping_add(bytes as usize))
+    }
+
+    ///Reinterpret the pointer as pointing to a value of type U
+    pub fn cast<U>(&self) -> RemotePtr<'a, U, P> {
+        RemotePtr::new(self.proc, self.address)
+    }
+
+    ///Read the pointer stored at this address, using the process pointer width, and return it as a pointer to U
+    pub fn deref<U>(&self) -> Option<RemotePtr<'a, U, P>> {
+        let address = self.proc.read_ptr(self.address)?;
+        Some(RemotePtr::new(self.proc, address))
+    }
+}
+
+impl<'a, T, P: ProcT> Clone for RemotePtr<'a, T, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T, P: ProcT> Copy for RemotePtr<'a, T, P> {}
+
+impl<'a, T, P: ProcT> PartialEq for RemotePtr<'a, T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.address == other.address
+    }
+}
+
+impl<'a, T, P: ProcT> fmt::Debug for RemotePtr<'a, T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "RemotePtr<{}>({:#X})",
+            std::any::type_name::<T>(),
+            self.address
+        )
+    }
+}
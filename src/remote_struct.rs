@@ -0,0 +1,14 @@
+use crate::ProcT;
+
+///A struct whose fields are read one by one from their offsets inside another process.
+///
+///Enable the 'derive' feature to generate implementations with `#[derive(RemoteStruct)]`, annotating fields with
+///`#[offset(0x30)]`. Fields without an offset are read right after the previous field, rounded up to their alignment
+///like in a `#[repr(C)]` struct, so padding never has to be written by hand.
+pub trait RemoteStruct: Sized {
+    ///Read every field of the struct starting at 'base'
+    fn read_from<P: ProcT + ?Sized>(proc: &P, base: usize) -> Option<Self>;
+
+    ///Name and offset from the struct base of every field
+    fn field_offsets() -> &'static [(&'static str, usize)];
+}
@@ -0,0 +1,307 @@
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use crate::{Module, Pod, ProcT, Protection, Region, RegionInfo, Thread};
+
+///How often and how fast RetryingProc retries failed transfers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    ///Retries after the first attempt
+    pub retries: u32,
+    ///Wait before the first retry
+    pub delay: Duration,
+    ///Factor applied to the wait after every retry
+    pub backoff: u32,
+    ///Longest wait between two attempts
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    ///3 retries waiting 1, 2 and 4 milliseconds
+    fn default() -> Self {
+        RetryPolicy {
+            retries: 3,
+            delay: Duration::from_millis(1),
+            backoff: 2,
+            max_delay: Duration::from_millis(100),
+        }
+    }
+}
+
+impl RetryPolicy {
+    ///Retry 'retries' times with the default delays
+    pub fn new(retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            retries,
+            ..RetryPolicy::default()
+        }
+    }
+
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn with_backoff(mut self, backoff: u32, max_delay: Duration) -> Self {
+        self.backoff = backoff;
+        self.max_delay = max_delay;
+        self
+    }
+}
+
+///Process whose failed reads and writes are retried with backoff when the failure looks transient.
+///A transfer failing while every byte it covers is mapped with the needed protection is retried, e.g. a copy
+///racing with the target reallocating or reprotecting the memory. Failures on unmapped or protected memory are
+///permanent and returned right away
+///
+///```
+///use proc_memory::{MockProc, ProcT, RetryPolicy, RetryingProc};
+///
+///let proc = MockProc::new();
+///proc.map_value(0x1000, &1u32);
+///let retrying = RetryingProc::new(&proc, RetryPolicy::new(5));
+///assert_eq!(retrying.read::<u32>(0x1000), Some(1));
+///assert_eq!(retrying.read::<u32>(0x8000), None);
+///```
+#[derive(Clone, Debug)]
+pub struct RetryingProc<'a, P: ProcT + ?Sized> {
+    proc: &'a P,
+    policy: RetryPolicy,
+}
+
+impl<'a, P: ProcT + ?Sized> RetryingProc<'a, P> {
+    pub fn new(proc: &'a P, policy: RetryPolicy) -> RetryingProc<'a, P> {
+        RetryingProc { proc, policy }
+    }
+
+    ///The process the calls are forwarded to
+    pub fn proc(&self) -> &'a P {
+        self.proc
+    }
+
+    pub fn policy(&self) -> RetryPolicy {
+        self.policy
+    }
+
+    ///Returns 'true' if every byte of the 'len' bytes at 'address' is mapped readable, or writable if 'write'
+    fn is_transient(&self, address: usize, len: usize, write: bool) -> bool {
+        let end = address.saturating_add(len);
+        let mut current = address;
+        while current < end {
+            let info = match self.proc.query(current) {
+                Some(info) => info,
+                None => return false,
+            };
+            let allowed = if write {
+                info.protection.write
+            } else {
+                info.protection.read
+            };
+            if !allowed || info.remaining(current) == 0 {
+                return false;
+            }
+            current = current.saturating_add(info.remaining(current));
+        }
+        true
+    }
+
+    ///Call 'attempt' until it succeeds, the policy runs out of retries or the failure is permanent
+    fn retry<R>(
+        &self,
+        address: usize,
+        len: usize,
+        write: bool,
+        mut attempt: impl FnMut() -> Option<R>,
+    ) -> Option<R> {
+        let mut delay = self.policy.delay;
+        for retry in 0..=self.policy.retries {
+            if let Some(result) = attempt() {
+                return Some(result);
+            }
+            if retry == self.policy.retries || !self.is_transient(address, len, write) {
+                break;
+            }
+            std::thread::sleep(delay);
+            delay = (delay * self.policy.backoff).min(self.policy.max_delay);
+        }
+        None
+    }
+
+    fn retry_write(
+        &self,
+        address: usize,
+        len: usize,
+        attempt: impl Fn() -> (bool, usize),
+    ) -> (bool, usize) {
+        let mut last = (false, 0);
+        let written = self.retry(address, len, true, || {
+            last = attempt();
+            Some(last).filter(|(ok, _)| *ok)
+        });
+        written.unwrap_or(last)
+    }
+}
+
+impl<'a, P: ProcT + ?Sized> ProcT for RetryingProc<'a, P> {
+    ///Always None, retrying processes are built with RetryingProc::new()
+    fn get(_proc_name: &str) -> Option<Self> {
+        None
+    }
+
+    fn read<T: Pod>(&self, proc_address: usize) -> Option<T> {
+        self.retry(proc_address, std::mem::size_of::<T>(), false, || {
+            self.proc.read(proc_address)
+        })
+    }
+
+    fn read_valid<T: Pod>(&self, proc_address: usize, validator: impl Fn(&T) -> bool) -> Option<T> {
+        self.read::<T>(proc_address)
+            .filter(|value| validator(value))
+    }
+
+    fn read_vec<T: Pod>(
+        &self,
+        proc_address: usize,
+        len: usize,
+        default_provider: impl Fn() -> T,
+    ) -> Option<Vec<T>> {
+        let size = len.saturating_mul(std::mem::size_of::<T>());
+        self.retry(proc_address, size, false, || {
+            self.proc.read_vec(proc_address, len, &default_provider)
+        })
+    }
+
+    fn read_vec_uninit<T: Pod>(&self, proc_address: usize, len: usize) -> Option<Vec<T>> {
+        let size = len.saturating_mul(std::mem::size_of::<T>());
+        self.retry(proc_address, size, false, || {
+            self.proc.read_vec_uninit(proc_address, len)
+        })
+    }
+
+    ///Retried while the bytes after the partial read are mapped readable
+    fn read_partial(&self, proc_address: usize, len: usize) -> (Vec<u8>, usize) {
+        let mut last = (Vec::new(), 0);
+        let mut delay = self.policy.delay;
+        for retry in 0..=self.policy.retries {
+            let (data, count) = self.proc.read_partial(proc_address, len);
+            if count >= len {
+                return (data, count);
+            }
+            if count >= last.1 {
+                last = (data, count);
+            }
+            let rest = proc_address.saturating_add(last.1);
+            if retry == self.policy.retries || !self.is_transient(rest, len - last.1, false) {
+                break;
+            }
+            std::thread::sleep(delay);
+            delay = (delay * self.policy.backoff).min(self.policy.max_delay);
+        }
+        last
+    }
+
+    fn write<T>(&self, proc_address: usize, data: &T) -> (bool, usize) {
+        self.retry_write(proc_address, std::mem::size_of::<T>(), || {
+            self.proc.write(proc_address, data)
+        })
+    }
+
+    fn write_bytes(&self, proc_address: usize, data: &[u8]) -> (bool, usize) {
+        self.retry_write(proc_address, data.len(), || {
+            self.proc.write_bytes(proc_address, data)
+        })
+    }
+
+    fn modules(&self) -> Vec<Module> {
+        self.proc.modules()
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        self.proc.regions()
+    }
+
+    fn query(&self, address: usize) -> Option<RegionInfo> {
+        self.proc.query(address)
+    }
+
+    fn region_infos(&self) -> Vec<RegionInfo> {
+        self.proc.region_infos()
+    }
+
+    fn threads(&self) -> Vec<Thread> {
+        self.proc.threads()
+    }
+
+    fn is_alive(&self) -> bool {
+        self.proc.is_alive()
+    }
+
+    fn protect(&self, address: usize, len: usize, protection: Protection) -> Option<Protection> {
+        self.proc.protect(address, len, protection)
+    }
+
+    fn alloc(&self, len: usize, protection: Protection) -> Option<usize> {
+        self.proc.alloc(len, protection)
+    }
+
+    fn free(&self, address: usize, len: usize) -> bool {
+        self.proc.free(address, len)
+    }
+
+    fn create_thread(&self, start: usize, param: usize) -> Option<u32> {
+        self.proc.create_thread(start, param)
+    }
+
+    fn create_thread_and_wait(&self, start: usize, param: usize) -> Option<u32> {
+        self.proc.create_thread_and_wait(start, param)
+    }
+
+    #[cfg(feature = "minidump")]
+    fn write_minidump(
+        &self,
+        path: &std::path::Path,
+        level: crate::DumpLevel,
+    ) -> std::io::Result<()> {
+        self.proc.write_minidump(path, level)
+    }
+
+    fn suspend(&self) -> bool {
+        self.proc.suspend()
+    }
+
+    fn resume(&self) -> bool {
+        self.proc.resume()
+    }
+
+    fn command_line(&self) -> Option<String> {
+        self.proc.command_line()
+    }
+
+    fn environment(&self) -> Option<Vec<(String, String)>> {
+        self.proc.environment()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.proc.name()
+    }
+
+    fn exe_path(&self) -> Option<PathBuf> {
+        self.proc.exe_path()
+    }
+
+    fn start_time(&self) -> Option<SystemTime> {
+        self.proc.start_time()
+    }
+
+    fn parent_pid(&self) -> Option<isize> {
+        self.proc.parent_pid()
+    }
+
+    fn is_64bit(&self) -> bool {
+        self.proc.is_64bit()
+    }
+
+    fn pid(&self) -> isize {
+        self.proc.pid()
+    }
+}
@@ -0,0 +1,402 @@
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::memchr::memchr;
+use crate::progress::ProgressCallback;
+use crate::{Module, Pod, ProcT, Progress, Protection, Region, Sharing};
+
+///Bytes read from the process per step of a scan
+const CHUNK_SIZE: usize = 0x10_0000;
+
+///Byte signature to search for, bytes can be wildcards that match anything
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pattern {
+    bytes: Vec<u8>,
+    ///'true' for bytes that must match, 'false' for wildcards
+    mask: Vec<bool>,
+}
+
+impl Pattern {
+    ///Parse a signature of hex bytes separated by spaces, '?' or '??' are wildcards, e.g. "48 8B 05 ?? ?? ?? ??"
+    pub fn parse(signature: &str) -> Option<Pattern> {
+        let mut bytes = Vec::new();
+        let mut mask = Vec::new();
+        for token in signature.split_whitespace() {
+            if token == "?" || token == "??" {
+                bytes.push(0);
+                mask.push(false);
+            } else if token.len() == 2 {
+                bytes.push(u8::from_str_radix(token, 16).ok()?);
+                mask.push(true);
+            } else {
+                return None;
+            }
+        }
+        Some(Pattern { bytes, mask })
+    }
+
+    ///Pattern matching exactly 'bytes'
+    pub fn from_bytes(bytes: &[u8]) -> Pattern {
+        Pattern {
+            bytes: bytes.to_vec(),
+            mask: vec![true; bytes.len()],
+        }
+    }
+
+    ///Pattern matching the in-memory representation of 'value'
+    pub fn from_value<T: Pod>(value: &T) -> Pattern {
+        let bytes = unsafe {
+            std::slice::from_raw_parts(value as *const T as *const u8, std::mem::size_of::<T>())
+        };
+        Pattern::from_bytes(bytes)
+    }
+
+    ///Number of bytes covered by the pattern, wildcards included
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    ///Returns 'true' if 'data' starts with bytes matching the pattern
+    pub fn matches(&self, data: &[u8]) -> bool {
+        data.len() >= self.bytes.len()
+            && self
+                .bytes
+                .iter()
+                .zip(&self.mask)
+                .zip(data)
+                .all(|((byte, must_match), value)| !must_match || byte == value)
+    }
+
+    ///Index and value of the concrete byte candidates are searched for, preferring bytes that are rarely
+    ///filler like 0x00 or 0xFF. None if every byte is a wildcard
+    fn anchor(&self) -> Option<(usize, u8)> {
+        let concrete = || {
+            self.bytes
+                .iter()
+                .zip(&self.mask)
+                .enumerate()
+                .filter(|(_, (_, must_match))| **must_match)
+                .map(|(index, (byte, _))| (index, *byte))
+        };
+        concrete()
+            .find(|(_, byte)| !matches!(byte, 0x00 | 0xFF | 0xCC | 0x90))
+            .or_else(|| concrete().next())
+    }
+
+    ///Offsets of every match in 'data' that starts before 'limit' and whose address, with 'data' starting at
+    ///'address', is a multiple of 'alignment'
+    pub(crate) fn find_all(
+        &self,
+        data: &[u8],
+        limit: usize,
+        address: usize,
+        alignment: usize,
+    ) -> Vec<usize> {
+        if self.is_empty() || data.len() < self.len() || limit == 0 {
+            return Vec::new();
+        }
+        let last_start = (data.len() - self.len()).min(limit - 1);
+        let (index, byte) = match self.anchor() {
+            Some(anchor) => anchor,
+            None => {
+                return (0..=last_start)
+                    .filter(|offset| (address + offset).is_multiple_of(alignment))
+                    .collect()
+            }
+        };
+
+        //Jump between occurrences of the anchor byte and only compare the whole pattern there
+        let mut matches = Vec::new();
+        let mut start = 0;
+        while start <= last_start {
+            let offset = match memchr(byte, &data[start + index..=last_start + index]) {
+                Some(position) => start + position,
+                None => break,
+            };
+            if (address + offset).is_multiple_of(alignment) && self.matches(&data[offset..]) {
+                matches.push(offset);
+            }
+            start = offset + 1;
+        }
+        matches
+    }
+}
+
+///Something scans search for, matches are found within the bytes read from each chunk of memory
+pub(crate) trait Matcher {
+    ///Number of bytes a match covers
+    fn len(&self) -> usize;
+
+    ///Offsets of every match in 'data' that starts before 'limit' and whose address, with 'data' starting at
+    ///'address', is a multiple of 'alignment'
+    fn find_all(&self, data: &[u8], limit: usize, address: usize, alignment: usize) -> Vec<usize>;
+}
+
+impl Matcher for Pattern {
+    fn len(&self) -> usize {
+        Pattern::len(self)
+    }
+
+    fn find_all(&self, data: &[u8], limit: usize, address: usize, alignment: usize) -> Vec<usize> {
+        Pattern::find_all(self, data, limit, address, alignment)
+    }
+}
+
+///Shared flag used to stop a running scan from another thread
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    ///Ask scans using this token to stop, they return the matches found so far
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl From<Arc<AtomicBool>> for CancelToken {
+    fn from(flag: Arc<AtomicBool>) -> CancelToken {
+        CancelToken(flag)
+    }
+}
+
+///Settings for a scan
+#[derive(Clone, Debug, Default)]
+pub struct ScanOptions {
+    cancel: Option<CancelToken>,
+    progress: Option<ProgressCallback>,
+    required: Protection,
+    excluded: Protection,
+    range: Option<Range<usize>>,
+    skip_shared: bool,
+    alignment: usize,
+}
+
+impl ScanOptions {
+    pub fn new() -> ScanOptions {
+        ScanOptions::default()
+    }
+
+    ///Stop the scan once 'token' is cancelled
+    pub fn cancel_token(mut self, token: CancelToken) -> ScanOptions {
+        self.cancel = Some(token);
+        self
+    }
+
+    ///Call 'callback' after each chunk of memory is scanned
+    pub fn on_progress(
+        mut self,
+        callback: impl Fn(&Progress) + Send + Sync + 'static,
+    ) -> ScanOptions {
+        self.progress = Some(ProgressCallback::new(callback));
+        self
+    }
+
+    ///Only scan regions granting every right of 'protection', e.g. Protection::READ_WRITE for writable data
+    pub fn require(mut self, protection: Protection) -> ScanOptions {
+        self.required = protection;
+        self
+    }
+
+    ///Skip regions granting any right of 'protection', e.g. Protection::new(false, false, true) to skip code
+    pub fn exclude(mut self, protection: Protection) -> ScanOptions {
+        self.excluded = protection;
+        self
+    }
+
+    ///Only scan the part of the regions inside 'range'
+    pub fn within(mut self, range: Range<usize>) -> ScanOptions {
+        self.range = Some(range);
+        self
+    }
+
+    ///Only scan the memory of 'module'
+    pub fn in_module(self, module: &Module) -> ScanOptions {
+        self.within(module.base..module.base + module.size)
+    }
+
+    ///Skip memory shared with other processes, like system pages and shared file mappings
+    pub fn skip_shared(mut self) -> ScanOptions {
+        self.skip_shared = true;
+        self
+    }
+
+    ///Only report matches at addresses that are a multiple of 'alignment', like 4 for i32 values or 8 for
+    ///pointers. Compilers align most values, so this drops misaligned false positives
+    pub fn align(mut self, alignment: usize) -> ScanOptions {
+        self.alignment = alignment;
+        self
+    }
+
+    ///Alignment of the matches, 1 when unset
+    pub(crate) fn alignment(&self) -> usize {
+        self.alignment.max(1)
+    }
+
+    ///Regions of 'proc' the scan starts from, before the protection and range filters
+    pub(crate) fn regions<P: ProcT + ?Sized>(&self, proc: &P) -> Vec<Region> {
+        if !self.skip_shared {
+            return proc.regions();
+        }
+        proc.region_infos()
+            .into_iter()
+            .filter(|info| info.sharing != Sharing::Shared)
+            .map(Region::from)
+            .collect()
+    }
+
+    ///Part of 'region' to scan, None if the filters skip it
+    pub(crate) fn filter(&self, region: &Region) -> Option<Range<usize>> {
+        let protection = region.protection;
+        if !protection.read
+            || !protection.contains(self.required)
+            || protection.intersects(self.excluded)
+        {
+            return None;
+        }
+        let range = match &self.range {
+            Some(range) => region.base.max(range.start)..region.end().min(range.end),
+            None => region.range(),
+        };
+        if range.is_empty() {
+            return None;
+        }
+        Some(range)
+    }
+
+    fn report(&self, progress: &Progress) {
+        if let Some(callback) = &self.progress {
+            callback.report(progress);
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(CancelToken::is_cancelled)
+    }
+}
+
+///Matches found by a scan
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScanResult {
+    ///Addresses of the matches in ascending order
+    pub matches: Vec<usize>,
+    ///'true' if the scan was cancelled before covering every address, 'matches' holds what was found until then
+    pub cancelled: bool,
+}
+
+///Append the addresses of every match of 'pattern' inside 'range' to 'matches', unreadable pages never match.
+///Returns false if the scan was cancelled
+fn scan_range_into<P: ProcT + ?Sized, M: Matcher + ?Sized>(
+    proc: &P,
+    range: Range<usize>,
+    pattern: &M,
+    options: &ScanOptions,
+    progress: &mut Progress,
+    matches: &mut Vec<usize>,
+) -> bool {
+    if pattern.len() == 0 {
+        return true;
+    }
+
+    let mut chunk_start = range.start;
+    while chunk_start < range.end {
+        if options.is_cancelled() {
+            return false;
+        }
+
+        //Overlap chunks so matches crossing a chunk boundary are found once
+        let step = CHUNK_SIZE.min(range.end - chunk_start);
+        let len = (step + pattern.len() - 1).min(range.end - chunk_start);
+        let chunk = proc.read_bulk(chunk_start, len, 0);
+
+        for offset in pattern.find_all(&chunk.data, step, chunk_start, options.alignment()) {
+            let address = chunk_start + offset;
+            let end = address + pattern.len();
+            if !chunk
+                .gaps
+                .iter()
+                .any(|gap| gap.start < end && address < gap.end)
+            {
+                matches.push(address);
+            }
+        }
+        chunk_start += step;
+        progress.bytes_done += step;
+        options.report(progress);
+    }
+    true
+}
+
+pub(crate) fn scan_range<P: ProcT + ?Sized, M: Matcher + ?Sized>(
+    proc: &P,
+    range: Range<usize>,
+    pattern: &M,
+    options: &ScanOptions,
+) -> ScanResult {
+    let mut progress = Progress {
+        bytes_total: range.len(),
+        regions_total: 1,
+        ..Progress::default()
+    };
+    let mut result = ScanResult::default();
+    result.cancelled = !scan_range_into(
+        proc,
+        range,
+        pattern,
+        options,
+        &mut progress,
+        &mut result.matches,
+    );
+    if !result.cancelled {
+        progress.regions_done = 1;
+        options.report(&progress);
+    }
+    result
+}
+
+pub(crate) fn scan_regions<P: ProcT + ?Sized, M: Matcher + ?Sized>(
+    proc: &P,
+    regions: &[Region],
+    pattern: &M,
+    options: &ScanOptions,
+) -> ScanResult {
+    let ranges: Vec<Range<usize>> = regions
+        .iter()
+        .filter_map(|region| options.filter(region))
+        .collect();
+    let mut progress = Progress {
+        bytes_total: ranges.iter().map(|range| range.len()).sum(),
+        regions_total: ranges.len(),
+        ..Progress::default()
+    };
+
+    let mut result = ScanResult::default();
+    for range in ranges {
+        if !scan_range_into(
+            proc,
+            range,
+            pattern,
+            options,
+            &mut progress,
+            &mut result.matches,
+        ) {
+            result.cancelled = true;
+            break;
+        }
+        progress.regions_done += 1;
+        options.report(&progress);
+    }
+    result
+}
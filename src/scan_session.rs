@@ -0,0 +1,300 @@
+use std::cmp::Ordering;
+use std::convert::TryInto;
+use std::path::Path;
+
+use crate::{invalid_data, ProcT};
+
+///First line of saved sessions
+const HEADER: &str = "proc_memory scan session 1";
+
+///Type of a value tracked by a scan session
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ValueType {
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    F32,
+    F64,
+}
+
+impl ValueType {
+    const ALL: [ValueType; 10] = [
+        ValueType::U8,
+        ValueType::I8,
+        ValueType::U16,
+        ValueType::I16,
+        ValueType::U32,
+        ValueType::I32,
+        ValueType::U64,
+        ValueType::I64,
+        ValueType::F32,
+        ValueType::F64,
+    ];
+
+    ///Size of the value in bytes
+    pub fn size(self) -> usize {
+        match self {
+            ValueType::U8 | ValueType::I8 => 1,
+            ValueType::U16 | ValueType::I16 => 2,
+            ValueType::U32 | ValueType::I32 | ValueType::F32 => 4,
+            ValueType::U64 | ValueType::I64 | ValueType::F64 => 8,
+        }
+    }
+
+    ///Rust name of the type, like "i32"
+    pub fn name(self) -> &'static str {
+        match self {
+            ValueType::U8 => "u8",
+            ValueType::I8 => "i8",
+            ValueType::U16 => "u16",
+            ValueType::I16 => "i16",
+            ValueType::U32 => "u32",
+            ValueType::I32 => "i32",
+            ValueType::U64 => "u64",
+            ValueType::I64 => "i64",
+            ValueType::F32 => "f32",
+            ValueType::F64 => "f64",
+        }
+    }
+
+    ///Type named 'name', as returned by name()
+    pub fn from_name(name: &str) -> Option<ValueType> {
+        ValueType::ALL
+            .iter()
+            .copied()
+            .find(|value_type| value_type.name() == name)
+    }
+
+    ///Value stored in 'bytes' written in decimal, None if there are fewer than size() bytes
+    pub fn format(self, bytes: &[u8]) -> Option<String> {
+        let bytes = bytes.get(..self.size())?;
+        Some(match self {
+            ValueType::U8 => bytes[0].to_string(),
+            ValueType::I8 => (bytes[0] as i8).to_string(),
+            ValueType::U16 => u16::from_ne_bytes(bytes.try_into().ok()?).to_string(),
+            ValueType::I16 => i16::from_ne_bytes(bytes.try_into().ok()?).to_string(),
+            ValueType::U32 => u32::from_ne_bytes(bytes.try_into().ok()?).to_string(),
+            ValueType::I32 => i32::from_ne_bytes(bytes.try_into().ok()?).to_string(),
+            ValueType::U64 => u64::from_ne_bytes(bytes.try_into().ok()?).to_string(),
+            ValueType::I64 => i64::from_ne_bytes(bytes.try_into().ok()?).to_string(),
+            ValueType::F32 => f32::from_ne_bytes(bytes.try_into().ok()?).to_string(),
+            ValueType::F64 => f64::from_ne_bytes(bytes.try_into().ok()?).to_string(),
+        })
+    }
+
+    ///Order of the values stored in 'a' and 'b', None if either is too short or a float is NaN
+    pub fn compare(self, a: &[u8], b: &[u8]) -> Option<Ordering> {
+        let a = a.get(..self.size())?;
+        let b = b.get(..self.size())?;
+        macro_rules! compare_as {
+            ($type:ty) => {
+                <$type>::from_ne_bytes(a.try_into().ok()?)
+                    .partial_cmp(&<$type>::from_ne_bytes(b.try_into().ok()?))
+            };
+        }
+        match self {
+            ValueType::U8 => compare_as!(u8),
+            ValueType::I8 => compare_as!(i8),
+            ValueType::U16 => compare_as!(u16),
+            ValueType::I16 => compare_as!(i16),
+            ValueType::U32 => compare_as!(u32),
+            ValueType::I32 => compare_as!(i32),
+            ValueType::U64 => compare_as!(u64),
+            ValueType::I64 => compare_as!(i64),
+            ValueType::F32 => compare_as!(f32),
+            ValueType::F64 => compare_as!(f64),
+        }
+    }
+}
+
+///Address tracked by a scan session and the value it held at the last scan
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScanEntry {
+    pub address: usize,
+    pub value_type: ValueType,
+    ///Bytes of the value at the last scan
+    pub previous: Vec<u8>,
+}
+
+///How the current value of an entry must relate to its previous one to stay in the session
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Refine {
+    Changed,
+    Unchanged,
+    Increased,
+    Decreased,
+}
+
+///Candidates of an investigation narrowed down over several scans, e.g. the addresses that could hold the health
+///of a player. Sessions can be saved to a text file and loaded again after the tool or the target restarts
+///
+///```
+///use proc_memory::{MockProc, ProcT, Refine, ScanSession, ValueType};
+///
+///let proc = MockProc::new();
+///proc.map_value(0x1000, &100i32);
+///proc.map_value(0x2000, &100i32);
+///let mut session = ScanSession::from_matches(&proc, &[0x1000, 0x2000], ValueType::I32);
+///
+///proc.write(0x2000, &90i32);
+///session.refine(&proc, Refine::Decreased);
+///assert_eq!(session.addresses(), vec![0x2000]);
+///
+///let restored = ScanSession::parse(&session.to_text()).unwrap();
+///assert_eq!(restored, session);
+///```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScanSession {
+    entries: Vec<ScanEntry>,
+}
+
+impl ScanSession {
+    pub fn new() -> ScanSession {
+        ScanSession::default()
+    }
+
+    ///Start a session from the addresses found by a scan, remembering their current values. Addresses that can't
+    ///be read are left out
+    pub fn from_matches<P: ProcT + ?Sized>(
+        proc: &P,
+        addresses: &[usize],
+        value_type: ValueType,
+    ) -> ScanSession {
+        let mut session = ScanSession::new();
+        for address in addresses {
+            if let Some(previous) = proc.read_vec_uninit::<u8>(*address, value_type.size()) {
+                session.add(*address, value_type, previous);
+            }
+        }
+        session
+    }
+
+    ///Track 'address', holding 'previous' at the last scan
+    pub fn add(&mut self, address: usize, value_type: ValueType, previous: Vec<u8>) {
+        self.entries.push(ScanEntry {
+            address,
+            value_type,
+            previous,
+        });
+    }
+
+    pub fn entries(&self) -> &[ScanEntry] {
+        &self.entries
+    }
+
+    ///Addresses still tracked
+    pub fn addresses(&self) -> Vec<usize> {
+        self.entries.iter().map(|entry| entry.address).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    ///Keep the entries whose current value relates to the previous one as 'refine' asks, and remember the current
+    ///values for the next scan. Entries that can't be read anymore are dropped
+    pub fn refine<P: ProcT + ?Sized>(&mut self, proc: &P, refine: Refine) {
+        self.refine_with(proc, |entry, current| {
+            let ordering = entry.value_type.compare(current, &entry.previous);
+            match refine {
+                Refine::Changed => current != entry.previous.as_slice(),
+                Refine::Unchanged => current == entry.previous.as_slice(),
+                Refine::Increased => ordering == Some(Ordering::Greater),
+                Refine::Decreased => ordering == Some(Ordering::Less),
+            }
+        });
+    }
+
+    ///Keep the entries for which 'keep' returns 'true' when given the entry and its current bytes, and remember the
+    ///current values for the next scan. Entries that can't be read anymore are dropped
+    pub fn refine_with<P: ProcT + ?Sized>(
+        &mut self,
+        proc: &P,
+        mut keep: impl FnMut(&ScanEntry, &[u8]) -> bool,
+    ) {
+        self.entries.retain_mut(|entry| {
+            match proc.read_vec_uninit::<u8>(entry.address, entry.value_type.size()) {
+                Some(current) if keep(entry, &current) => {
+                    entry.previous = current;
+                    true
+                }
+                _ => false,
+            }
+        });
+    }
+
+    ///Write the session to 'path'
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_text())
+    }
+
+    ///Load a session written by save()
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<ScanSession> {
+        let text = std::fs::read_to_string(path)?;
+        ScanSession::parse(&text)
+    }
+
+    ///Session as text: a header line, then one 'address type previous-bytes' line per entry, all in hexadecimal
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}\n", HEADER);
+        for entry in &self.entries {
+            let bytes: String = entry
+                .previous
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect();
+            text += &format!(
+                "{:#x} {} {}\n",
+                entry.address,
+                entry.value_type.name(),
+                bytes
+            );
+        }
+        text
+    }
+
+    ///Parse a session written by to_text()
+    pub fn parse(text: &str) -> std::io::Result<ScanSession> {
+        let mut lines = text.lines().enumerate();
+        if lines.next().map(|(_, line)| line.trim()) != Some(HEADER) {
+            return Err(invalid_data("not a scan session"));
+        }
+
+        let mut session = ScanSession::new();
+        for (number, line) in lines {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || invalid_data(format!("line {}: invalid entry '{}'", number + 1, line));
+
+            let mut fields = line.split_whitespace();
+            let address = fields
+                .next()
+                .and_then(crate::offsets::parse_hex)
+                .ok_or_else(invalid)?;
+            let value_type = fields
+                .next()
+                .and_then(ValueType::from_name)
+                .ok_or_else(invalid)?;
+            let previous = fields
+                .next()
+                .and_then(crate::parse_hex_bytes)
+                .ok_or_else(invalid)?;
+            if previous.len() != value_type.size() || fields.next().is_some() {
+                return Err(invalid());
+            }
+            session.add(address, value_type, previous);
+        }
+        Ok(session)
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::{AddressExpr, Module, Proc, ProcT};
+
+///Address stored as an offset from the base of a module, so it stays valid when the module is loaded elsewhere
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RebasedAddress {
+    ///File name of the module the address belongs to
+    pub module: String,
+    ///Offset from the module base (relative virtual address)
+    pub rva: usize,
+}
+
+impl RebasedAddress {
+    ///Create an address relative to the base of 'module'
+    pub fn new(module: &str, rva: usize) -> RebasedAddress {
+        RebasedAddress {
+            module: module.to_string(),
+            rva,
+        }
+    }
+
+    ///Convert an absolute address of the process into a module relative one, None if no module contains it
+    pub fn from_absolute<P: ProcT + ?Sized>(proc: &P, address: usize) -> Option<RebasedAddress> {
+        let module = proc
+            .modules()
+            .into_iter()
+            .find(|module| module.contains(address))?;
+        Some(RebasedAddress {
+            rva: address - module.base,
+            module: module.name,
+        })
+    }
+
+    ///Absolute address in the process, looking up the current module base
+    pub fn resolve<P: ProcT + ?Sized>(&self, proc: &P) -> Option<usize> {
+        proc.module(&self.module)
+            .map(|module| module.base + self.rva)
+    }
+}
+
+///Callback for the descendants of the session target whose name matches a pattern
+struct ChildFollower {
+    pattern: String,
+    callback: Box<dyn FnMut(Proc)>,
+    ///Pids already passed to the callback
+    attached: HashSet<isize>,
+}
+
+impl fmt::Debug for ChildFollower {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChildFollower")
+            .field("pattern", &self.pattern)
+            .field("attached", &self.attached)
+            .finish()
+    }
+}
+
+///Attachment to a process by name that reattaches when the target restarts.
+///
+///Module bases are cached, reloaded on every reattach and checked against the mapping at the base before use, so
+///RebasedAddress values resolve to the right absolute address across restarts and module reloads.
+#[derive(Debug)]
+pub struct Session {
+    proc_name: String,
+    proc: Option<Proc>,
+    ///Modules by lowercase name
+    modules: HashMap<String, Module>,
+    followers: Vec<ChildFollower>,
+}
+
+impl Session {
+    ///Create a session for the process with specified title, attaching if it is already running
+    pub fn new(proc_name: &str) -> Session {
+        let mut session = Session {
+            proc_name: proc_name.to_string(),
+            proc: None,
+            modules: HashMap::new(),
+            followers: Vec::new(),
+        };
+        session.refresh();
+        session
+    }
+
+    ///Name the session attaches to
+    pub fn proc_name(&self) -> &str {
+        &self.proc_name
+    }
+
+    ///Returns 'true' if the session is attached to a running process
+    pub fn is_attached(&self) -> bool {
+        self.proc.as_ref().is_some_and(|proc| proc.is_alive())
+    }
+
+    ///Reattach if the target exited and reload the module bases.
+    ///Returns 'true' if the session is attached after the refresh
+    pub fn refresh(&mut self) -> bool {
+        if !self.is_attached() {
+            self.proc = Proc::get(&self.proc_name);
+        }
+        self.reload_modules();
+        self.poll_children();
+        self.proc.is_some()
+    }
+
+    ///Call 'callback' with every process started by the target, directly or through other children, whose name
+    ///contains 'pattern' ignoring ASCII case. Like launchers starting the actual game.
+    ///Children are detected by refresh() and poll_children(), call either periodically
+    pub fn follow_children(&mut self, pattern: &str, callback: impl FnMut(Proc) + 'static) {
+        self.followers.push(ChildFollower {
+            pattern: pattern.to_ascii_lowercase(),
+            callback: Box::new(callback),
+            attached: HashSet::new(),
+        });
+        self.poll_children();
+    }
+
+    ///Attach to the new matching descendants of the target, returns how many were passed to the callbacks
+    pub fn poll_children(&mut self) -> usize {
+        if self.followers.is_empty() {
+            return 0;
+        }
+        let proc = match &self.proc {
+            Some(proc) if proc.is_alive() => proc,
+            _ => return 0,
+        };
+
+        let mut descendants = Vec::new();
+        let mut pending = proc.children();
+        while let Some(child) = pending.pop() {
+            if descendants.len() > 0x1000 {
+                break;
+            }
+            pending.extend(child.children());
+            descendants.push(child);
+        }
+
+        let mut count = 0;
+        for follower in &mut self.followers {
+            //Forget exited children so a reused pid is reported again
+            follower
+                .attached
+                .retain(|pid| descendants.iter().any(|child| child.pid() == *pid));
+            for child in &descendants {
+                let name = child.name().unwrap_or_default().to_ascii_lowercase();
+                if name.contains(&follower.pattern) && follower.attached.insert(child.pid()) {
+                    (follower.callback)(child.clone());
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    ///Currently attached process, reattaching first if the target restarted
+    pub fn proc(&mut self) -> Option<&Proc> {
+        if !self.is_attached() {
+            self.refresh();
+        }
+        self.proc.as_ref()
+    }
+
+    ///Process attached at the last refresh, without reattaching
+    pub(crate) fn attached(&self) -> Option<&Proc> {
+        self.proc.as_ref()
+    }
+
+    ///Absolute address of 'address' in the attached process
+    pub fn address(&mut self, address: &RebasedAddress) -> Option<usize> {
+        self.proc()?;
+        let key = address.module.to_ascii_lowercase();
+        let current = match (&self.proc, self.modules.get(&key)) {
+            (Some(proc), Some(module)) => is_mapped_at_base(proc, module),
+            _ => false,
+        };
+        if !current {
+            //The module may have been loaded, unloaded or moved since the last refresh
+            self.reload_modules();
+        }
+        self.modules
+            .get(&key)
+            .map(|module| module.base + address.rva)
+    }
+
+    ///Evaluate an address expression against the attached process
+    pub fn eval(&mut self, expr: &AddressExpr) -> Option<usize> {
+        expr.eval(self.proc()?)
+    }
+
+    fn reload_modules(&mut self) {
+        self.modules.clear();
+        if let Some(proc) = &self.proc {
+            for module in proc.modules() {
+                self.modules
+                    .insert(module.name.to_ascii_lowercase(), module);
+            }
+        }
+    }
+}
+
+///Returns 'true' if the file of 'module' is still mapped at its base, otherwise it was unloaded or moved
+fn is_mapped_at_base(proc: &Proc, module: &Module) -> bool {
+    let name = module.name.to_ascii_lowercase();
+    proc.query(module.base).is_some_and(|info| {
+        info.file
+            .is_some_and(|file| file.to_ascii_lowercase().ends_with(&name))
+    })
+}
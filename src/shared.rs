@@ -0,0 +1,222 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::{Module, Pod, Proc, ProcT, Protection, Region, RegionInfo, Thread};
+
+#[derive(Debug)]
+struct Shared<P> {
+    proc: P,
+    ///Number of suspend() calls not matched by a resume() yet
+    suspends: Mutex<usize>,
+}
+
+///Process handle that can be cloned and sent to other threads, all clones sharing one attachment.
+///Reads and writes go straight to the process, they don't share any position and run concurrently.
+///suspend() and resume() are counted across the clones: the process is frozen by the first suspend() and only
+///continued once every one of them was matched by a resume(), so a freezer thread doesn't wake up the process
+///while a scanner thread still needs it stopped
+///
+///```
+///use proc_memory::{MockProc, ProcT, SharedProc};
+///
+///let proc = MockProc::new();
+///proc.map_value(0x1000, &7u32);
+///let shared = SharedProc::new(proc);
+///let scanner = shared.clone();
+///let value = std::thread::spawn(move || scanner.read::<u32>(0x1000)).join().unwrap();
+///assert_eq!(value, Some(7));
+///```
+#[derive(Debug)]
+pub struct SharedProc<P: ProcT + Send + Sync = Proc> {
+    shared: Arc<Shared<P>>,
+}
+
+impl<P: ProcT + Send + Sync> Clone for SharedProc<P> {
+    fn clone(&self) -> Self {
+        SharedProc {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<P: ProcT + Send + Sync> SharedProc<P> {
+    pub fn new(proc: P) -> SharedProc<P> {
+        SharedProc {
+            shared: Arc::new(Shared {
+                proc,
+                suspends: Mutex::new(0),
+            }),
+        }
+    }
+
+    ///The shared process
+    pub fn proc(&self) -> &P {
+        &self.shared.proc
+    }
+
+    ///Number of handles sharing the process
+    pub fn handle_count(&self) -> usize {
+        Arc::strong_count(&self.shared)
+    }
+}
+
+impl<P: ProcT + Send + Sync> From<P> for SharedProc<P> {
+    fn from(proc: P) -> Self {
+        SharedProc::new(proc)
+    }
+}
+
+impl<P: ProcT + Send + Sync> ProcT for SharedProc<P> {
+    ///Opens the process with P::get() and shares it
+    fn get(proc_name: &str) -> Option<Self> {
+        P::get(proc_name).map(SharedProc::new)
+    }
+
+    fn read<T: Pod>(&self, proc_address: usize) -> Option<T> {
+        self.proc().read(proc_address)
+    }
+
+    fn read_valid<T: Pod>(&self, proc_address: usize, validator: impl Fn(&T) -> bool) -> Option<T> {
+        self.proc().read_valid(proc_address, validator)
+    }
+
+    fn read_vec<T: Pod>(
+        &self,
+        proc_address: usize,
+        len: usize,
+        default_provider: impl Fn() -> T,
+    ) -> Option<Vec<T>> {
+        self.proc().read_vec(proc_address, len, default_provider)
+    }
+
+    fn read_vec_uninit<T: Pod>(&self, proc_address: usize, len: usize) -> Option<Vec<T>> {
+        self.proc().read_vec_uninit(proc_address, len)
+    }
+
+    fn read_partial(&self, proc_address: usize, len: usize) -> (Vec<u8>, usize) {
+        self.proc().read_partial(proc_address, len)
+    }
+
+    fn write<T>(&self, proc_address: usize, data: &T) -> (bool, usize) {
+        self.proc().write(proc_address, data)
+    }
+
+    fn write_bytes(&self, proc_address: usize, data: &[u8]) -> (bool, usize) {
+        self.proc().write_bytes(proc_address, data)
+    }
+
+    fn modules(&self) -> Vec<Module> {
+        self.proc().modules()
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        self.proc().regions()
+    }
+
+    fn query(&self, address: usize) -> Option<RegionInfo> {
+        self.proc().query(address)
+    }
+
+    fn region_infos(&self) -> Vec<RegionInfo> {
+        self.proc().region_infos()
+    }
+
+    fn threads(&self) -> Vec<Thread> {
+        self.proc().threads()
+    }
+
+    fn is_alive(&self) -> bool {
+        self.proc().is_alive()
+    }
+
+    fn protect(&self, address: usize, len: usize, protection: Protection) -> Option<Protection> {
+        self.proc().protect(address, len, protection)
+    }
+
+    fn alloc(&self, len: usize, protection: Protection) -> Option<usize> {
+        self.proc().alloc(len, protection)
+    }
+
+    fn free(&self, address: usize, len: usize) -> bool {
+        self.proc().free(address, len)
+    }
+
+    fn create_thread(&self, start: usize, param: usize) -> Option<u32> {
+        self.proc().create_thread(start, param)
+    }
+
+    fn create_thread_and_wait(&self, start: usize, param: usize) -> Option<u32> {
+        self.proc().create_thread_and_wait(start, param)
+    }
+
+    #[cfg(feature = "minidump")]
+    fn write_minidump(
+        &self,
+        path: &std::path::Path,
+        level: crate::DumpLevel,
+    ) -> std::io::Result<()> {
+        self.proc().write_minidump(path, level)
+    }
+
+    ///Freeze the process unless another handle already did
+    fn suspend(&self) -> bool {
+        let mut suspends = self.shared.suspends.lock().unwrap();
+        if *suspends == 0 && !self.proc().suspend() {
+            return false;
+        }
+        *suspends += 1;
+        true
+    }
+
+    ///Continue the process once every suspend() was matched. False if it wasn't suspended
+    fn resume(&self) -> bool {
+        let mut suspends = self.shared.suspends.lock().unwrap();
+        match *suspends {
+            0 => false,
+            1 => {
+                let resumed = self.proc().resume();
+                if resumed {
+                    *suspends = 0;
+                }
+                resumed
+            }
+            _ => {
+                *suspends -= 1;
+                true
+            }
+        }
+    }
+
+    fn command_line(&self) -> Option<String> {
+        self.proc().command_line()
+    }
+
+    fn environment(&self) -> Option<Vec<(String, String)>> {
+        self.proc().environment()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.proc().name()
+    }
+
+    fn exe_path(&self) -> Option<PathBuf> {
+        self.proc().exe_path()
+    }
+
+    fn start_time(&self) -> Option<SystemTime> {
+        self.proc().start_time()
+    }
+
+    fn parent_pid(&self) -> Option<isize> {
+        self.proc().parent_pid()
+    }
+
+    fn is_64bit(&self) -> bool {
+        self.proc().is_64bit()
+    }
+
+    fn pid(&self) -> isize {
+        self.proc().pid()
+    }
+}
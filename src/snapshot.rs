@@ -0,0 +1,356 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{MemoryBackend, Module, ProcT, Region, ScanOptions};
+
+///Bytes read from the process per write to the file
+const CHUNK_SIZE: usize = 0x10_0000;
+
+///Snapshots created by this process, keeps the temporary file names unique
+static SNAPSHOTS: AtomicUsize = AtomicUsize::new(0);
+
+///Range of captured memory that was readable, stored contiguously in the file
+#[derive(Clone, Debug)]
+struct Segment {
+    address: usize,
+    size: usize,
+    offset: usize,
+}
+
+///Read-only view of a whole file mapped in the current process
+#[derive(Debug)]
+struct Mapping {
+    address: *const u8,
+    len: usize,
+}
+
+//The mapping is read-only and owned by the snapshot
+unsafe impl Send for Mapping {}
+unsafe impl Sync for Mapping {}
+
+impl Mapping {
+    #[cfg(target_os = "linux")]
+    fn new(file: &File, len: usize) -> io::Result<Mapping> {
+        use std::os::unix::io::AsRawFd;
+
+        if len == 0 {
+            return Ok(Mapping {
+                address: std::ptr::NonNull::dangling().as_ptr(),
+                len,
+            });
+        }
+        let address = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if address == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Mapping {
+            address: address as *const u8,
+            len,
+        })
+    }
+
+    #[cfg(target_os = "windows")]
+    fn new(file: &File, len: usize) -> io::Result<Mapping> {
+        use std::os::windows::io::AsRawHandle;
+
+        if len == 0 {
+            return Ok(Mapping {
+                address: std::ptr::NonNull::dangling().as_ptr(),
+                len,
+            });
+        }
+        unsafe {
+            let mapping = kernel32::CreateFileMappingW(
+                file.as_raw_handle() as isize,
+                std::ptr::null(),
+                kernel32::PAGE_READONLY,
+                0,
+                0,
+                std::ptr::null(),
+            );
+            if mapping == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            //The view keeps the mapping object alive
+            let address = kernel32::MapViewOfFile(mapping, kernel32::FILE_MAP_READ, 0, 0, len);
+            let error = io::Error::last_os_error();
+            kernel32::CloseHandle(mapping);
+            if address.is_null() {
+                return Err(error);
+            }
+            Ok(Mapping {
+                address: address as *const u8,
+                len,
+            })
+        }
+    }
+
+    fn data(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.address, self.len) }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        if self.len == 0 {
+            return;
+        }
+        #[cfg(target_os = "linux")]
+        unsafe {
+            libc::munmap(self.address as *mut libc::c_void, self.len);
+        }
+        #[cfg(target_os = "windows")]
+        unsafe {
+            kernel32::UnmapViewOfFile(self.address as *const std::ffi::c_void);
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod kernel32 {
+    use std::ffi::c_void;
+
+    pub const PAGE_READONLY: u32 = 0x02;
+    pub const FILE_MAP_READ: u32 = 0x04;
+    pub const FILE_FLAG_DELETE_ON_CLOSE: u32 = 0x0400_0000;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        pub fn CreateFileMappingW(
+            file: isize,
+            attributes: *const c_void,
+            protect: u32,
+            maximum_size_high: u32,
+            maximum_size_low: u32,
+            name: *const u16,
+        ) -> isize;
+        pub fn MapViewOfFile(
+            mapping: isize,
+            access: u32,
+            offset_high: u32,
+            offset_low: u32,
+            len: usize,
+        ) -> *mut c_void;
+        pub fn UnmapViewOfFile(address: *const c_void) -> i32;
+        pub fn CloseHandle(handle: isize) -> i32;
+    }
+}
+
+///Create a file only this snapshot uses, removed once it is closed
+fn temporary_file(dir: &Path) -> io::Result<File> {
+    let path: PathBuf = dir.join(format!(
+        "proc_memory_snapshot_{}_{}",
+        std::process::id(),
+        SNAPSHOTS.fetch_add(1, Ordering::Relaxed)
+    ));
+    let mut options = OpenOptions::new();
+    options.read(true).write(true).create_new(true);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::fs::OpenOptionsExt;
+        options.custom_flags(kernel32::FILE_FLAG_DELETE_ON_CLOSE);
+    }
+    let file = options.open(&path)?;
+    //Unlinked right away, the open file and its mapping keep the data
+    #[cfg(target_os = "linux")]
+    std::fs::remove_file(&path)?;
+    Ok(file)
+}
+
+///Memory of a process captured at one point in time. The bytes are stored in a temporary file mapped in the current
+///process instead of being held in RAM, so snapshots of processes larger than the available memory can be kept,
+///diffed and pointer scanned. Wrap it in a BackendProc to use it like a read-only process
+///
+///```
+///use proc_memory::{compare_ranges, BackendProc, MockProc, ProcT, ScanOptions, Snapshot};
+///
+///let proc = MockProc::new();
+///proc.map_value(0x1000, &100i32);
+///let snapshot = BackendProc::new(Snapshot::capture(&proc, &ScanOptions::new()).unwrap());
+///proc.write(0x1000, &50i32);
+///
+///assert_eq!(snapshot.read::<i32>(0x1000), Some(100));
+///let differences = compare_ranges(&snapshot, 0x1000, &proc, 0x1000, 4);
+///assert_eq!(differences[0].a, vec![100]);
+///```
+#[derive(Debug)]
+pub struct Snapshot {
+    mapping: Mapping,
+    ///Sorted by address
+    segments: Vec<Segment>,
+    regions: Vec<Region>,
+    modules: Vec<Module>,
+    name: Option<String>,
+    pid: isize,
+    is_64bit: bool,
+    _file: File,
+}
+
+impl Snapshot {
+    ///Capture the regions of 'proc' selected by 'options' into a file in the temporary directory of the system
+    pub fn capture<P: ProcT + ?Sized>(proc: &P, options: &ScanOptions) -> io::Result<Snapshot> {
+        Snapshot::capture_in(proc, options, &std::env::temp_dir())
+    }
+
+    ///capture() with the file in 'dir'. Use it when the temporary directory is a RAM backed tmpfs or too small
+    pub fn capture_in<P: ProcT + ?Sized>(
+        proc: &P,
+        options: &ScanOptions,
+        dir: &Path,
+    ) -> io::Result<Snapshot> {
+        let file = temporary_file(dir)?;
+        let mut out = BufWriter::new(&file);
+        let mut segments: Vec<Segment> = Vec::new();
+        let mut regions = Vec::new();
+        let mut offset = 0;
+
+        let mut all = options.regions(proc);
+        all.sort_by_key(|region| region.base);
+        for region in &all {
+            let range = match options.filter(region) {
+                Some(range) => range,
+                None => continue,
+            };
+            regions.push(Region {
+                base: range.start,
+                size: range.len(),
+                protection: region.protection,
+            });
+
+            let mut chunk_start = range.start;
+            while chunk_start < range.end {
+                let len = CHUNK_SIZE.min(range.end - chunk_start);
+                let chunk = proc.read_bulk(chunk_start, len, 0);
+                let chunk_end = chunk_start + len;
+
+                //Only the readable runs between the gaps are stored
+                let mut start = chunk_start;
+                let end_gap = chunk_end..chunk_end;
+                for gap in chunk.gaps.iter().chain(std::iter::once(&end_gap)) {
+                    let end = gap.start.clamp(start, chunk_end);
+                    if end > start {
+                        out.write_all(&chunk.data[start - chunk_start..end - chunk_start])?;
+                        match segments.last_mut() {
+                            Some(last)
+                                if last.address + last.size == start
+                                    && last.offset + last.size == offset =>
+                            {
+                                last.size += end - start
+                            }
+                            _ => segments.push(Segment {
+                                address: start,
+                                size: end - start,
+                                offset,
+                            }),
+                        }
+                        offset += end - start;
+                    }
+                    start = start.max(gap.end.min(chunk_end));
+                }
+                chunk_start = chunk_end;
+            }
+        }
+        out.flush()?;
+        drop(out);
+
+        Ok(Snapshot {
+            mapping: Mapping::new(&file, offset)?,
+            segments,
+            regions,
+            modules: proc.modules(),
+            name: proc.name(),
+            pid: proc.pid(),
+            is_64bit: proc.is_64bit(),
+            _file: file,
+        })
+    }
+
+    ///Captured bytes, the size of the file
+    pub fn len(&self) -> usize {
+        self.mapping.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mapping.len == 0
+    }
+
+    ///Captured bytes at 'address' borrowed from the mapping without copying, None unless all 'len' of them were
+    ///readable
+    pub fn bytes(&self, address: usize, len: usize) -> Option<&[u8]> {
+        let segment = self.segment(address)?;
+        let start = segment.offset + (address - segment.address);
+        if len > segment.address + segment.size - address {
+            return None;
+        }
+        Some(&self.mapping.data()[start..start + len])
+    }
+
+    fn segment(&self, address: usize) -> Option<&Segment> {
+        let index = self
+            .segments
+            .partition_point(|segment| segment.address <= address);
+        let segment = &self.segments[index.checked_sub(1)?];
+        if address - segment.address < segment.size {
+            Some(segment)
+        } else {
+            None
+        }
+    }
+}
+
+impl MemoryBackend for Snapshot {
+    fn read_raw(&self, address: usize, buffer: &mut [u8]) -> usize {
+        let mut done = 0;
+        while done < buffer.len() {
+            let current = match address.checked_add(done) {
+                Some(current) => current,
+                None => break,
+            };
+            let segment = match self.segment(current) {
+                Some(segment) => segment,
+                None => break,
+            };
+            let count = (segment.address + segment.size - current).min(buffer.len() - done);
+            let start = segment.offset + (current - segment.address);
+            buffer[done..done + count].copy_from_slice(&self.mapping.data()[start..start + count]);
+            done += count;
+        }
+        done
+    }
+
+    ///Snapshots are read-only, always 0
+    fn write_raw(&self, _address: usize, _data: &[u8]) -> usize {
+        0
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        self.regions.clone()
+    }
+
+    fn modules(&self) -> Vec<Module> {
+        self.modules.clone()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    fn pid(&self) -> isize {
+        self.pid
+    }
+
+    fn is_64bit(&self) -> bool {
+        self.is_64bit
+    }
+}
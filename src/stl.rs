@@ -0,0 +1,113 @@
+use crate::{Pod, ProcT};
+
+///Upper bound for the byte size of remote containers, larger values are treated as corrupted
+pub const MAX_CONTAINER_BYTES: usize = 64 * 1024 * 1024;
+
+///Memory layout of the C++ standard library used by the target process (64-bit release builds)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StdLayout {
+    ///Microsoft STL: buffer/pointer union, size, capacity
+    Msvc,
+    ///GNU libstdc++ (C++11 ABI): pointer, size, buffer/capacity union
+    LibStdCpp,
+}
+
+impl StdLayout {
+    ///Layout used by the default compiler of the current platform
+    pub fn native() -> StdLayout {
+        if cfg!(target_os = "windows") {
+            StdLayout::Msvc
+        } else {
+            StdLayout::LibStdCpp
+        }
+    }
+
+    ///Size in bytes of a 'wchar_t' for this layout
+    pub fn wchar_size(self) -> usize {
+        match self {
+            StdLayout::Msvc => 2,
+            StdLayout::LibStdCpp => 4,
+        }
+    }
+}
+
+///Size of the small string buffer, in bytes, for both supported layouts
+const SSO_BYTES: usize = 16;
+
+///Reads the raw character bytes of a remote std::basic_string with characters of 'char_size' bytes
+pub(crate) fn read_string_bytes<P: ProcT + ?Sized>(
+    proc: &P,
+    proc_address: usize,
+    layout: StdLayout,
+    char_size: usize,
+) -> Option<Vec<u8>> {
+    let raw = proc.read::<[usize; 4]>(proc_address)?;
+
+    let (data_address, size) = match layout {
+        StdLayout::Msvc => {
+            let (size, capacity) = (raw[2], raw[3]);
+            if size > capacity {
+                return None;
+            }
+            if capacity < SSO_BYTES / char_size {
+                (proc_address, size)
+            } else {
+                (raw[0], size)
+            }
+        }
+        StdLayout::LibStdCpp => {
+            let (pointer, size) = (raw[0], raw[1]);
+            if pointer == proc_address + SSO_BYTES {
+                if size >= SSO_BYTES / char_size {
+                    return None;
+                }
+            } else if size > raw[2] {
+                return None;
+            }
+            (pointer, size)
+        }
+    };
+
+    let len = size.checked_mul(char_size)?;
+    if len > MAX_CONTAINER_BYTES {
+        return None;
+    }
+    proc.read_vec_uninit::<u8>(data_address, len)
+}
+
+pub(crate) fn decode_wide(bytes: &[u8], char_size: usize) -> String {
+    if char_size == 2 {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|unit| u16::from_ne_bytes([unit[0], unit[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        bytes
+            .chunks_exact(4)
+            .map(|unit| u32::from_ne_bytes([unit[0], unit[1], unit[2], unit[3]]))
+            .map(|unit| std::char::from_u32(unit).unwrap_or(std::char::REPLACEMENT_CHARACTER))
+            .collect()
+    }
+}
+
+///Reads the elements of a remote std::vector, MSVC and libstdc++ share the begin/end/capacity pointer layout
+pub(crate) fn read_vector<P: ProcT + ?Sized, T: Pod>(
+    proc: &P,
+    proc_address: usize,
+) -> Option<Vec<T>> {
+    let [begin, end, capacity_end] = proc.read::<[usize; 3]>(proc_address)?;
+    if begin > end || end > capacity_end {
+        return None;
+    }
+
+    let bytes = end - begin;
+    let element_size = std::mem::size_of::<T>();
+    if bytes > MAX_CONTAINER_BYTES || element_size == 0 || bytes % element_size != 0 {
+        return None;
+    }
+    if bytes == 0 {
+        return Some(Vec::new());
+    }
+    proc.read_vec_uninit::<T>(begin, bytes / element_size)
+}
@@ -0,0 +1,155 @@
+use crate::scan::{self, Matcher};
+use crate::{ProcT, ScanOptions};
+
+///Encoding of a string found in memory
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StringEncoding {
+    Utf8,
+    ///Little-endian UTF-16, the wide strings of Windows
+    Utf16,
+}
+
+impl StringEncoding {
+    ///Bytes of 'c' in this encoding
+    fn encode(self, c: char) -> Vec<u8> {
+        match self {
+            StringEncoding::Utf8 => c.to_string().into_bytes(),
+            StringEncoding::Utf16 => c
+                .encode_utf16(&mut [0; 2])
+                .iter()
+                .flat_map(|unit| unit.to_le_bytes())
+                .collect(),
+        }
+    }
+}
+
+///String found by a scan
+///
+///```
+///use proc_memory::{MockProc, ProcT, ScanOptions, StringEncoding, StringMatch};
+///
+///let proc = MockProc::new();
+///proc.map(0x1000, b"Player\0P\0l\0a\0y\0e\0r\0".to_vec());
+///let found = proc.scan_string("PLAYER", true, &ScanOptions::new());
+///assert_eq!(
+///    found.matches,
+///    vec![
+///        StringMatch { address: 0x1000, encoding: StringEncoding::Utf8 },
+///        StringMatch { address: 0x1007, encoding: StringEncoding::Utf16 },
+///    ]
+///);
+///assert!(proc.scan_string("PLAYER", false, &ScanOptions::new()).matches.is_empty());
+///```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StringMatch {
+    pub address: usize,
+    pub encoding: StringEncoding,
+}
+
+///Strings found by a scan
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StringScanResult {
+    ///Matches of every encoding in ascending address order
+    pub matches: Vec<StringMatch>,
+    ///'true' if the scan was cancelled before covering every address, 'matches' holds what was found until then
+    pub cancelled: bool,
+}
+
+///Matches a string in one encoding, each character accepting a few spellings of the same length
+pub(crate) struct StringMatcher {
+    chars: Vec<Vec<Vec<u8>>>,
+    len: usize,
+}
+
+impl StringMatcher {
+    ///Case variants of a character that encode to a different length, like the Kelvin sign for 'k', don't match
+    pub(crate) fn new(text: &str, encoding: StringEncoding, ignore_case: bool) -> StringMatcher {
+        let chars: Vec<Vec<Vec<u8>>> = text
+            .chars()
+            .map(|c| {
+                let bytes = encoding.encode(c);
+                let mut spellings = vec![bytes.clone()];
+                if ignore_case {
+                    let variants = [single(c.to_lowercase()), single(c.to_uppercase())];
+                    for variant in variants.iter().flatten() {
+                        let variant = encoding.encode(*variant);
+                        if variant.len() == bytes.len() && !spellings.contains(&variant) {
+                            spellings.push(variant);
+                        }
+                    }
+                }
+                spellings
+            })
+            .collect();
+        let len = chars.iter().map(|spellings| spellings[0].len()).sum();
+        StringMatcher { chars, len }
+    }
+
+    ///Returns 'true' if 'data' starts with the string
+    fn matches(&self, data: &[u8]) -> bool {
+        let mut offset = 0;
+        for spellings in &self.chars {
+            match spellings
+                .iter()
+                .find(|spelling| data[offset..].starts_with(spelling))
+            {
+                Some(spelling) => offset += spelling.len(),
+                None => return false,
+            }
+        }
+        true
+    }
+}
+
+///The only character of a case mapping, None if it maps to several
+fn single(mut chars: impl Iterator<Item = char>) -> Option<char> {
+    let c = chars.next()?;
+    match chars.next() {
+        Some(_) => None,
+        None => Some(c),
+    }
+}
+
+impl Matcher for StringMatcher {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn find_all(&self, data: &[u8], limit: usize, address: usize, alignment: usize) -> Vec<usize> {
+        if self.len == 0 || data.len() < self.len || limit == 0 {
+            return Vec::new();
+        }
+        let last_start = (data.len() - self.len).min(limit - 1);
+        let first = (alignment - address % alignment) % alignment;
+        (first..=last_start)
+            .step_by(alignment)
+            .filter(|offset| self.matches(&data[*offset..]))
+            .collect()
+    }
+}
+
+pub(crate) fn scan_string<P: ProcT + ?Sized>(
+    proc: &P,
+    text: &str,
+    ignore_case: bool,
+    options: &ScanOptions,
+) -> StringScanResult {
+    let regions = options.regions(proc);
+    let mut result = StringScanResult::default();
+    for encoding in [StringEncoding::Utf8, StringEncoding::Utf16] {
+        let matcher = StringMatcher::new(text, encoding, ignore_case);
+        let found = scan::scan_regions(proc, &regions, &matcher, options);
+        result.matches.extend(
+            found
+                .matches
+                .into_iter()
+                .map(|address| StringMatch { address, encoding }),
+        );
+        if found.cancelled {
+            result.cancelled = true;
+            break;
+        }
+    }
+    result.matches.sort_by_key(|found| found.address);
+    result
+}
@@ -0,0 +1,77 @@
+use crate::{ProcT, PAGE_SIZE};
+
+///Reads units of 'unit_size' bytes until a zeroed unit is found or 'max_units' were read.
+///Returns the bytes before the terminator, or None if memory became unreadable first
+pub(crate) fn read_until_nul<P: ProcT + ?Sized>(
+    proc: &P,
+    proc_address: usize,
+    max_units: usize,
+    unit_size: usize,
+) -> Option<Vec<u8>> {
+    let max_len = max_units * unit_size;
+    let mut data = Vec::new();
+
+    while data.len() < max_len {
+        let current = proc_address + data.len();
+        let page_end = ((current / PAGE_SIZE) + 1) * PAGE_SIZE;
+        //Keep whole units in every chunk so a terminator is never split between two reads
+        let chunk = (page_end - current)
+            .min(max_len - data.len())
+            .max(unit_size);
+        let chunk = chunk - chunk % unit_size;
+
+        let (bytes, count) = proc.read_partial(current, chunk);
+        let units = count / unit_size;
+
+        for unit in bytes[..units * unit_size].chunks_exact(unit_size) {
+            if unit.iter().all(|b| *b == 0) {
+                return Some(data);
+            }
+            data.extend_from_slice(unit);
+        }
+
+        if units * unit_size < chunk {
+            return None;
+        }
+    }
+
+    Some(data)
+}
+
+///Encodes 's' as UTF-8 plus a NUL terminator, truncated on a character boundary to fit 'max_len' bytes
+pub(crate) fn encode_cstring(s: &str, max_len: Option<usize>) -> Vec<u8> {
+    let mut end = s.len();
+    if let Some(max_len) = max_len {
+        end = end.min(max_len.saturating_sub(1));
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+    }
+
+    let mut bytes = s.as_bytes()[..end].to_vec();
+    if max_len != Some(0) {
+        bytes.push(0);
+    }
+    bytes
+}
+
+///Encodes 's' as UTF-16 plus a NUL terminator, truncated without splitting surrogate pairs to fit 'max_len' units
+pub(crate) fn encode_wstring(s: &str, max_len: Option<usize>) -> Vec<u8> {
+    let mut units: Vec<u16> = Vec::new();
+    let limit = max_len.map(|max_len| max_len.saturating_sub(1));
+    let mut buffer = [0u16; 2];
+    for c in s.chars() {
+        let encoded = c.encode_utf16(&mut buffer);
+        if let Some(limit) = limit {
+            if units.len() + encoded.len() > limit {
+                break;
+            }
+        }
+        units.extend_from_slice(encoded);
+    }
+    if max_len != Some(0) {
+        units.push(0);
+    }
+
+    units.iter().flat_map(|unit| unit.to_ne_bytes()).collect()
+}
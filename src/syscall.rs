@@ -0,0 +1,347 @@
+use std::fs::{File, OpenOptions};
+use std::os::unix::fs::FileExt;
+
+use libc::pid_t;
+
+const SYSCALL_INSTRUCTION: [u8; 2] = [0x0F, 0x05];
+
+///call r13; int3
+const CALL_TRAMPOLINE: [u8; 4] = [0x41, 0xFF, 0xD5, 0xCC];
+
+///Clone a thread that runs r13(r12) and exits with its result, the calling thread stops on int3
+const THREAD_TRAMPOLINE: [u8; 28] = [
+    0xB8, 0x38, 0x00, 0x00, 0x00, //mov eax, SYS_clone
+    0x0F, 0x05, //syscall
+    0x48, 0x85, 0xC0, //test rax, rax
+    0x75, 0x0F, //jnz parent
+    0x4C, 0x89, 0xE7, //mov rdi, r12
+    0x41, 0xFF, 0xD5, //call r13
+    0x89, 0xC7, //mov edi, eax
+    0xB8, 0x3C, 0x00, 0x00, 0x00, //mov eax, SYS_exit
+    0x0F, 0x05, //syscall
+    0xCC, //parent: int3
+];
+
+const THREAD_STACK_SIZE: u64 = 0x40000;
+const PTRACE_EVENT_STOP: i32 = 128;
+
+///Keeps a thread seized and stopped, detaching when dropped
+struct Seized {
+    tid: pid_t,
+}
+
+impl Seized {
+    fn new(tid: pid_t) -> Option<Seized> {
+        let null = std::ptr::null_mut::<libc::c_void>();
+        unsafe {
+            if libc::ptrace(libc::PTRACE_SEIZE, tid, null, null) != 0 {
+                return None;
+            }
+            let seized = Seized { tid };
+            if libc::ptrace(libc::PTRACE_INTERRUPT, tid, null, null) != 0 {
+                return None;
+            }
+            let mut status = 0;
+            if libc::waitpid(tid, &mut status, libc::__WALL) != tid {
+                return None;
+            }
+            Some(seized)
+        }
+    }
+
+    fn registers(&self) -> Option<libc::user_regs_struct> {
+        unsafe {
+            let mut regs: libc::user_regs_struct = std::mem::zeroed();
+            let result = libc::ptrace(
+                libc::PTRACE_GETREGS,
+                self.tid,
+                std::ptr::null_mut::<libc::c_void>(),
+                &mut regs as *mut libc::user_regs_struct,
+            );
+            if result != 0 {
+                return None;
+            }
+            Some(regs)
+        }
+    }
+
+    fn set_registers(&self, regs: &libc::user_regs_struct) -> bool {
+        unsafe {
+            libc::ptrace(
+                libc::PTRACE_SETREGS,
+                self.tid,
+                std::ptr::null_mut::<libc::c_void>(),
+                regs as *const libc::user_regs_struct,
+            ) == 0
+        }
+    }
+
+    fn single_step(&self) -> bool {
+        let null = std::ptr::null_mut::<libc::c_void>();
+        unsafe {
+            if libc::ptrace(libc::PTRACE_SINGLESTEP, self.tid, null, null) != 0 {
+                return false;
+            }
+            let mut status = 0;
+            libc::waitpid(self.tid, &mut status, libc::__WALL) == self.tid
+                && libc::WIFSTOPPED(status)
+        }
+    }
+
+    ///Let the thread run until it hits a breakpoint, passing other signals on to it.
+    ///Returns false if it faults or exits first
+    fn run_until_trap(&self) -> bool {
+        let mut signal = 0;
+        loop {
+            unsafe {
+                let data = signal as usize as *mut libc::c_void;
+                if libc::ptrace(
+                    libc::PTRACE_CONT,
+                    self.tid,
+                    std::ptr::null_mut::<libc::c_void>(),
+                    data,
+                ) != 0
+                {
+                    return false;
+                }
+                let mut status = 0;
+                if libc::waitpid(self.tid, &mut status, libc::__WALL) != self.tid
+                    || !libc::WIFSTOPPED(status)
+                {
+                    return false;
+                }
+                signal = match libc::WSTOPSIG(status) {
+                    _ if status >> 16 == PTRACE_EVENT_STOP => 0,
+                    libc::SIGTRAP => return true,
+                    libc::SIGSEGV | libc::SIGBUS | libc::SIGILL | libc::SIGFPE => return false,
+                    other => other,
+                };
+            }
+        }
+    }
+}
+
+impl Drop for Seized {
+    fn drop(&mut self) {
+        let null = std::ptr::null_mut::<libc::c_void>();
+        unsafe {
+            libc::ptrace(libc::PTRACE_DETACH, self.tid, null, null);
+        }
+    }
+}
+
+///Address of a syscall instruction inside the [vdso] mapping
+fn find_vdso_syscall(pid: pid_t, mem: &File) -> Option<u64> {
+    let maps = std::fs::read_to_string(format!("/proc/{}/maps", pid)).ok()?;
+    let line = maps.lines().find(|line| line.ends_with("[vdso]"))?;
+    let (start, end) = line.split(' ').next()?.split_once('-')?;
+    let start = u64::from_str_radix(start, 16).ok()?;
+    let end = u64::from_str_radix(end, 16).ok()?;
+
+    let mut vdso = vec![0u8; (end - start) as usize];
+    mem.read_exact_at(&mut vdso, start).ok()?;
+    let offset = vdso
+        .windows(SYSCALL_INSTRUCTION.len())
+        .position(|window| window == SYSCALL_INSTRUCTION)?;
+    Some(start + offset as u64)
+}
+
+///Execute system call 'number' inside process 'pid' by borrowing its main thread.
+///Returns the raw result, negative errno values mean the call failed in the target
+pub(crate) fn remote_syscall(pid: pid_t, number: i64, args: [u64; 6]) -> Option<i64> {
+    let thread = Seized::new(pid)?;
+    syscall_on(pid, &thread, number, args)
+}
+
+///Execute system call 'number' on thread 'tid' of process 'pid', which the calling thread already traces and
+///stopped. The thread is left stopped
+pub(crate) fn stopped_thread_syscall(
+    pid: pid_t,
+    tid: pid_t,
+    number: i64,
+    args: [u64; 6],
+) -> Option<i64> {
+    //Seized by the caller, it must not be detached here
+    let thread = std::mem::ManuallyDrop::new(Seized { tid });
+    syscall_on(pid, &thread, number, args)
+}
+
+fn syscall_on(pid: pid_t, thread: &Seized, number: i64, args: [u64; 6]) -> Option<i64> {
+    let mem = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(format!("/proc/{}/mem", pid))
+        .ok()?;
+    let saved = thread.registers()?;
+
+    //Reuse an existing syscall instruction so no code has to be patched while other threads run
+    let mut previous = [0u8; 2];
+    let existing = if mem.read_exact_at(&mut previous, saved.rip - 2).is_ok()
+        && previous == SYSCALL_INSTRUCTION
+    {
+        Some(saved.rip - 2)
+    } else {
+        find_vdso_syscall(pid, &mem)
+    };
+    let (location, original_code) = match existing {
+        Some(location) => (location, None),
+        None => {
+            let mut original = [0u8; 2];
+            mem.read_exact_at(&mut original, saved.rip).ok()?;
+            mem.write_all_at(&SYSCALL_INSTRUCTION, saved.rip).ok()?;
+            (saved.rip, Some(original))
+        }
+    };
+
+    let mut regs = saved;
+    regs.rip = location;
+    regs.rax = number as u64;
+    regs.rdi = args[0];
+    regs.rsi = args[1];
+    regs.rdx = args[2];
+    regs.r10 = args[3];
+    regs.r8 = args[4];
+    regs.r9 = args[5];
+    //Keep the kernel from restarting the syscall the thread was interrupted in
+    regs.orig_rax = u64::MAX;
+
+    let result = if thread.set_registers(&regs) && thread.single_step() {
+        thread.registers().map(|regs| regs.rax as i64)
+    } else {
+        None
+    };
+
+    if let Some(original) = original_code {
+        let _ = mem.write_all_at(&original, saved.rip);
+    }
+    thread.set_registers(&saved);
+    result
+}
+
+fn map_anonymous(pid: pid_t, len: u64, prot: i32) -> Option<u64> {
+    let flags = (libc::MAP_PRIVATE | libc::MAP_ANONYMOUS) as u64;
+    let result = remote_syscall(
+        pid,
+        libc::SYS_mmap,
+        [0, len, prot as u64, flags, u64::MAX, 0],
+    )?;
+    if (-4095..0).contains(&result) {
+        return None;
+    }
+    Some(result as u64)
+}
+
+fn unmap(pid: pid_t, address: u64, len: u64) {
+    let _ = remote_syscall(pid, libc::SYS_munmap, [address, len, 0, 0, 0, 0]);
+}
+
+///Map a page holding 'code', writes through /proc/<pid>/mem ignore the missing write permission
+fn map_code(pid: pid_t, mem: &File, code: &[u8]) -> Option<u64> {
+    let page = map_anonymous(
+        pid,
+        crate::PAGE_SIZE as u64,
+        libc::PROT_READ | libc::PROT_EXEC,
+    )?;
+    if mem.write_all_at(code, page).is_err() {
+        unmap(pid, page, crate::PAGE_SIZE as u64);
+        return None;
+    }
+    Some(page)
+}
+
+///Point the main thread at 'code' below its current stack, run it until it traps and restore the thread
+fn run_hijacked(
+    pid: pid_t,
+    code: u64,
+    setup: impl FnOnce(&mut libc::user_regs_struct),
+) -> Option<libc::user_regs_struct> {
+    let thread = Seized::new(pid)?;
+    let saved = thread.registers()?;
+
+    let mut regs = saved;
+    regs.rip = code;
+    //Skip the red zone and align the stack for the call
+    regs.rsp = (saved.rsp - 512) & !0xF;
+    regs.orig_rax = u64::MAX;
+    setup(&mut regs);
+
+    let result = if thread.set_registers(&regs) && thread.run_until_trap() {
+        thread.registers()
+    } else {
+        None
+    };
+    thread.set_registers(&saved);
+    result
+}
+
+///Call 'function' with up to six integer arguments on the main thread of 'pid' and return its result
+pub(crate) fn remote_call(pid: pid_t, function: u64, args: &[u64]) -> Option<u64> {
+    if args.len() > 6 {
+        return None;
+    }
+    let mem = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(format!("/proc/{}/mem", pid))
+        .ok()?;
+    let code = map_code(pid, &mem, &CALL_TRAMPOLINE)?;
+    let result = run_hijacked(pid, code, |regs| {
+        let mut registers = [
+            &mut regs.rdi,
+            &mut regs.rsi,
+            &mut regs.rdx,
+            &mut regs.rcx,
+            &mut regs.r8,
+            &mut regs.r9,
+        ];
+        for (register, arg) in registers.iter_mut().zip(args) {
+            **register = *arg;
+        }
+        regs.r13 = function;
+    });
+    unmap(pid, code, crate::PAGE_SIZE as u64);
+    result.map(|regs| regs.rax)
+}
+
+///Start a new thread in 'pid' running 'function(param)', returns its thread id.
+///The thread shares the TLS of the main thread, its stack and code page are never freed
+pub(crate) fn remote_thread(pid: pid_t, function: u64, param: u64) -> Option<u32> {
+    let mem = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(format!("/proc/{}/mem", pid))
+        .ok()?;
+    let code = map_code(pid, &mem, &THREAD_TRAMPOLINE)?;
+    let stack = match map_anonymous(pid, THREAD_STACK_SIZE, libc::PROT_READ | libc::PROT_WRITE) {
+        Some(stack) => stack,
+        None => {
+            unmap(pid, code, crate::PAGE_SIZE as u64);
+            return None;
+        }
+    };
+
+    let flags = libc::CLONE_VM
+        | libc::CLONE_FS
+        | libc::CLONE_FILES
+        | libc::CLONE_SIGHAND
+        | libc::CLONE_THREAD
+        | libc::CLONE_SYSVSEM;
+    let result = run_hijacked(pid, code, |regs| {
+        regs.rdi = flags as u64;
+        regs.rsi = stack + THREAD_STACK_SIZE;
+        regs.rdx = 0;
+        regs.r10 = 0;
+        regs.r8 = 0;
+        regs.r12 = param;
+        regs.r13 = function;
+    });
+
+    match result.map(|regs| regs.rax as i64) {
+        Some(tid) if tid > 0 => Some(tid as u32),
+        _ => {
+            unmap(pid, stack, THREAD_STACK_SIZE);
+            unmap(pid, code, crate::PAGE_SIZE as u64);
+            None
+        }
+    }
+}
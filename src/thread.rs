@@ -0,0 +1,733 @@
+use std::fmt;
+
+use crate::{ProcT, RebasedAddress};
+
+///Scheduling state of a thread
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThreadState {
+    ///Running or ready to run
+    Running,
+    ///Waiting for an event
+    Sleeping,
+    ///Waiting on uninterruptible IO
+    DiskSleep,
+    ///Stopped by a signal or a debugger
+    Stopped,
+    ///Exited but not yet reaped
+    Zombie,
+    ///Exited
+    Terminated,
+    ///The platform doesn't report the state
+    Unknown,
+}
+
+///A thread of another process
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Thread {
+    ///Thread id
+    pub id: u32,
+    ///Id of the process owning the thread
+    pub process_id: u32,
+    ///Address the thread started executing at, when the platform reports it
+    pub start_address: Option<usize>,
+    ///Scheduling state when the thread list was taken
+    pub state: ThreadState,
+}
+
+///General purpose registers of a x86_64 thread
+#[cfg(target_arch = "x86_64")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Registers {
+    pub rax: u64,
+    pub rbx: u64,
+    pub rcx: u64,
+    pub rdx: u64,
+    pub rsi: u64,
+    pub rdi: u64,
+    pub rbp: u64,
+    pub rsp: u64,
+    pub r8: u64,
+    pub r9: u64,
+    pub r10: u64,
+    pub r11: u64,
+    pub r12: u64,
+    pub r13: u64,
+    pub r14: u64,
+    pub r15: u64,
+    ///Instruction pointer
+    pub rip: u64,
+    pub rflags: u64,
+}
+
+#[cfg(target_arch = "x86_64")]
+impl Registers {
+    ///Registers and their values, named like 'rax'
+    pub fn named(&self) -> [(&'static str, u64); 18] {
+        [
+            ("rax", self.rax),
+            ("rbx", self.rbx),
+            ("rcx", self.rcx),
+            ("rdx", self.rdx),
+            ("rsi", self.rsi),
+            ("rdi", self.rdi),
+            ("rbp", self.rbp),
+            ("rsp", self.rsp),
+            ("r8", self.r8),
+            ("r9", self.r9),
+            ("r10", self.r10),
+            ("r11", self.r11),
+            ("r12", self.r12),
+            ("r13", self.r13),
+            ("r14", self.r14),
+            ("r15", self.r15),
+            ("rip", self.rip),
+            ("rflags", self.rflags),
+        ]
+    }
+
+    ///Registers whose value differs in 'other', with their value there
+    pub fn changes(&self, other: &Registers) -> Vec<(&'static str, u64)> {
+        self.named()
+            .iter()
+            .zip(other.named().iter())
+            .filter(|(old, new)| old.1 != new.1)
+            .map(|(_, new)| *new)
+            .collect()
+    }
+}
+
+///Floating point and vector registers of a x86_64 thread
+#[cfg(target_arch = "x86_64")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FloatRegisters {
+    ///x87 control word
+    pub fcw: u16,
+    ///x87 status word
+    pub fsw: u16,
+    ///Abridged x87 tag word, a bit set for each register holding a value
+    pub ftw: u8,
+    ///SSE control and status register
+    pub mxcsr: u32,
+    ///x87 registers st0 to st7, or mm0 to mm7, 80 bit values in the low bytes
+    pub st: [u128; 8],
+    pub xmm: [u128; 16],
+    ///Upper halves of ymm0 to ymm15, None when the AVX state of the thread can't be accessed
+    pub ymm_high: Option<[u128; 16]>,
+}
+
+///Offsets in the FXSAVE area, the legacy part of the XSAVE area
+#[cfg(target_arch = "x86_64")]
+const FXSAVE_ST_OFFSET: usize = 32;
+#[cfg(target_arch = "x86_64")]
+const FXSAVE_XMM_OFFSET: usize = 160;
+
+#[cfg(target_arch = "x86_64")]
+impl FloatRegisters {
+    ///ymm register 'index' as its lower and upper half, None without the AVX state
+    pub fn ymm(&self, index: usize) -> Option<[u128; 2]> {
+        Some([self.xmm[index], self.ymm_high?[index]])
+    }
+
+    fn from_fxsave(area: &[u8]) -> FloatRegisters {
+        let u128_at = |offset: usize| {
+            let mut bytes = [0u8; 16];
+            bytes.copy_from_slice(&area[offset..offset + 16]);
+            u128::from_le_bytes(bytes)
+        };
+        let mut registers = FloatRegisters {
+            fcw: u16::from_le_bytes([area[0], area[1]]),
+            fsw: u16::from_le_bytes([area[2], area[3]]),
+            ftw: area[4],
+            mxcsr: u32::from_le_bytes([area[24], area[25], area[26], area[27]]),
+            ..FloatRegisters::default()
+        };
+        for (index, st) in registers.st.iter_mut().enumerate() {
+            *st = u128_at(FXSAVE_ST_OFFSET + index * 16);
+        }
+        for (index, xmm) in registers.xmm.iter_mut().enumerate() {
+            *xmm = u128_at(FXSAVE_XMM_OFFSET + index * 16);
+        }
+        registers
+    }
+
+    ///Store the registers in a FXSAVE area, leaving the fields they don't have as they are
+    fn write_fxsave(&self, area: &mut [u8]) {
+        area[0..2].copy_from_slice(&self.fcw.to_le_bytes());
+        area[2..4].copy_from_slice(&self.fsw.to_le_bytes());
+        area[4] = self.ftw;
+        area[24..28].copy_from_slice(&self.mxcsr.to_le_bytes());
+        for (index, st) in self.st.iter().enumerate() {
+            let offset = FXSAVE_ST_OFFSET + index * 16;
+            area[offset..offset + 16].copy_from_slice(&st.to_le_bytes());
+        }
+        for (index, xmm) in self.xmm.iter().enumerate() {
+            let offset = FXSAVE_XMM_OFFSET + index * 16;
+            area[offset..offset + 16].copy_from_slice(&xmm.to_le_bytes());
+        }
+    }
+}
+
+///Return address found while walking the stack of a thread
+#[cfg(target_arch = "x86_64")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StackFrame {
+    ///Absolute address of the instruction
+    pub address: usize,
+    ///Module relative address, None when no loaded module contains it
+    pub location: Option<RebasedAddress>,
+}
+
+#[cfg(target_arch = "x86_64")]
+impl fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "{}+{:#X}", location.module, location.rva),
+            None => write!(f, "{:#X}", self.address),
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Thread {
+    ///Suspend the thread, suspensions are counted and need the same number of resume() calls
+    pub fn suspend(&self) -> bool {
+        use windows::Win32::Foundation::{CloseHandle, HANDLE};
+        use windows::Win32::System::Threading::{OpenThread, SuspendThread, THREAD_SUSPEND_RESUME};
+
+        unsafe {
+            let thread = OpenThread(THREAD_SUSPEND_RESUME, None, self.id);
+            if thread == HANDLE(0) {
+                return false;
+            }
+            let previous_count = SuspendThread(thread);
+            CloseHandle(thread);
+            previous_count != u32::MAX
+        }
+    }
+
+    ///Resume a suspended thread
+    pub fn resume(&self) -> bool {
+        use windows::Win32::Foundation::{CloseHandle, HANDLE};
+        use windows::Win32::System::Threading::{OpenThread, ResumeThread, THREAD_SUSPEND_RESUME};
+
+        unsafe {
+            let thread = OpenThread(THREAD_SUSPEND_RESUME, None, self.id);
+            if thread == HANDLE(0) {
+                return false;
+            }
+            let previous_count = ResumeThread(thread);
+            CloseHandle(thread);
+            previous_count != u32::MAX
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Thread {
+    ///Stop the thread without stopping the rest of the process.
+    ///The thread stays traced by the calling thread, resume() must be called from the same thread
+    pub fn suspend(&self) -> bool {
+        let tid = self.id as libc::pid_t;
+        let null = std::ptr::null_mut::<libc::c_void>();
+        unsafe {
+            if libc::ptrace(libc::PTRACE_SEIZE, tid, null, null) != 0 {
+                return false;
+            }
+            if libc::ptrace(libc::PTRACE_INTERRUPT, tid, null, null) != 0 {
+                libc::ptrace(libc::PTRACE_DETACH, tid, null, null);
+                return false;
+            }
+            let mut status = 0;
+            libc::waitpid(tid, &mut status, libc::__WALL) == tid
+        }
+    }
+
+    ///Resume a thread stopped with suspend()
+    pub fn resume(&self) -> bool {
+        let null = std::ptr::null_mut::<libc::c_void>();
+        unsafe { libc::ptrace(libc::PTRACE_DETACH, self.id as libc::pid_t, null, null) == 0 }
+    }
+}
+
+#[cfg(all(target_os = "windows", target_arch = "x86_64"))]
+impl Thread {
+    ///Read the general purpose registers, the thread should be suspended first
+    pub fn context(&self) -> Option<Registers> {
+        use windows::Win32::Foundation::{CloseHandle, HANDLE};
+        use windows::Win32::System::Diagnostics::Debug::{GetThreadContext, CONTEXT};
+        use windows::Win32::System::Threading::{OpenThread, THREAD_GET_CONTEXT};
+
+        unsafe {
+            let thread = OpenThread(THREAD_GET_CONTEXT, None, self.id);
+            if thread == HANDLE(0) {
+                return None;
+            }
+            let mut context: CONTEXT = std::mem::zeroed();
+            context.ContextFlags = CONTEXT_AMD64_FULL;
+            let ok = GetThreadContext(thread, &mut context).as_bool();
+            CloseHandle(thread);
+            if !ok {
+                return None;
+            }
+            Some(Registers {
+                rax: context.Rax,
+                rbx: context.Rbx,
+                rcx: context.Rcx,
+                rdx: context.Rdx,
+                rsi: context.Rsi,
+                rdi: context.Rdi,
+                rbp: context.Rbp,
+                rsp: context.Rsp,
+                r8: context.R8,
+                r9: context.R9,
+                r10: context.R10,
+                r11: context.R11,
+                r12: context.R12,
+                r13: context.R13,
+                r14: context.R14,
+                r15: context.R15,
+                rip: context.Rip,
+                rflags: context.EFlags as u64,
+            })
+        }
+    }
+
+    ///Overwrite the general purpose registers, the thread should be suspended first
+    pub fn set_context(&self, registers: &Registers) -> bool {
+        use windows::Win32::Foundation::{CloseHandle, HANDLE};
+        use windows::Win32::System::Diagnostics::Debug::{
+            GetThreadContext, SetThreadContext, CONTEXT,
+        };
+        use windows::Win32::System::Threading::{
+            OpenThread, THREAD_GET_CONTEXT, THREAD_SET_CONTEXT,
+        };
+
+        unsafe {
+            let thread = OpenThread(THREAD_GET_CONTEXT | THREAD_SET_CONTEXT, None, self.id);
+            if thread == HANDLE(0) {
+                return false;
+            }
+            //Start from the current context so segment registers stay valid
+            let mut context: CONTEXT = std::mem::zeroed();
+            context.ContextFlags = CONTEXT_AMD64_FULL;
+            let mut ok = GetThreadContext(thread, &mut context).as_bool();
+            if ok {
+                context.Rax = registers.rax;
+                context.Rbx = registers.rbx;
+                context.Rcx = registers.rcx;
+                context.Rdx = registers.rdx;
+                context.Rsi = registers.rsi;
+                context.Rdi = registers.rdi;
+                context.Rbp = registers.rbp;
+                context.Rsp = registers.rsp;
+                context.R8 = registers.r8;
+                context.R9 = registers.r9;
+                context.R10 = registers.r10;
+                context.R11 = registers.r11;
+                context.R12 = registers.r12;
+                context.R13 = registers.r13;
+                context.R14 = registers.r14;
+                context.R15 = registers.r15;
+                context.Rip = registers.rip;
+                context.EFlags = registers.rflags as u32;
+                ok = SetThreadContext(thread, &context).as_bool();
+            }
+            CloseHandle(thread);
+            ok
+        }
+    }
+}
+
+#[cfg(all(target_os = "windows", target_arch = "x86_64"))]
+impl Thread {
+    ///Read the floating point and vector registers, the thread should be suspended first
+    pub fn float_context(&self) -> Option<FloatRegisters> {
+        let mut registers = None;
+        self.with_float_context(false, false, |fxsave, ymm_high| {
+            let mut read = FloatRegisters::from_fxsave(fxsave);
+            read.ymm_high = ymm_high.map(|area| {
+                let mut high = [0u128; 16];
+                for (index, register) in high.iter_mut().enumerate() {
+                    let mut bytes = [0u8; 16];
+                    bytes.copy_from_slice(&area[index * 16..index * 16 + 16]);
+                    *register = u128::from_le_bytes(bytes);
+                }
+                high
+            });
+            registers = Some(read);
+        });
+        registers
+    }
+
+    ///Overwrite the floating point and vector registers, the upper halves of the ymm registers only if
+    ///'registers' has them. The thread should be suspended first
+    pub fn set_float_context(&self, registers: &FloatRegisters) -> bool {
+        let mut written = false;
+        let updated =
+            self.with_float_context(true, registers.ymm_high.is_some(), |fxsave, ymm_high| {
+                registers.write_fxsave(fxsave);
+                written = match (registers.ymm_high, ymm_high) {
+                    (Some(high), Some(area)) => {
+                        for (index, register) in high.iter().enumerate() {
+                            area[index * 16..index * 16 + 16]
+                                .copy_from_slice(&register.to_le_bytes());
+                        }
+                        true
+                    }
+                    (Some(_), None) => false,
+                    (None, _) => true,
+                };
+            });
+        updated && written
+    }
+
+    ///Call 'f' with the FXSAVE area of the thread context and, if the system supports AVX, the upper halves of
+    ///its ymm registers. The context is written back afterwards if 'write' is set, the AVX state only with 'write_avx'
+    fn with_float_context(
+        &self,
+        write: bool,
+        write_avx: bool,
+        f: impl FnOnce(&mut [u8], Option<&mut [u8]>),
+    ) -> bool {
+        use windows::Win32::Foundation::{CloseHandle, HANDLE};
+        use windows::Win32::System::Diagnostics::Debug::{
+            GetThreadContext, SetThreadContext, CONTEXT,
+        };
+        use windows::Win32::System::Threading::{
+            OpenThread, THREAD_GET_CONTEXT, THREAD_SET_CONTEXT,
+        };
+
+        #[link(name = "kernel32")]
+        extern "system" {
+            fn InitializeContext(
+                buffer: *mut u8,
+                flags: u32,
+                context: *mut *mut CONTEXT,
+                len: *mut u32,
+            ) -> i32;
+            fn SetXStateFeaturesMask(context: *mut CONTEXT, mask: u64) -> i32;
+            fn GetXStateFeaturesMask(context: *mut CONTEXT, mask: *mut u64) -> i32;
+            fn LocateXStateFeature(context: *mut CONTEXT, feature: u32, len: *mut u32) -> *mut u8;
+        }
+
+        unsafe {
+            let thread = OpenThread(THREAD_GET_CONTEXT | THREAD_SET_CONTEXT, None, self.id);
+            if thread == HANDLE(0) {
+                return false;
+            }
+            //CONTEXT_XSTATE needs a buffer sized and aligned by InitializeContext()
+            let mut context: *mut CONTEXT = std::ptr::null_mut();
+            let mut len = 0;
+            InitializeContext(
+                std::ptr::null_mut(),
+                CONTEXT_AMD64_XSTATE,
+                &mut context,
+                &mut len,
+            );
+            let mut buffer = vec![0u8; len as usize];
+            let xstate = len != 0
+                && InitializeContext(
+                    buffer.as_mut_ptr(),
+                    CONTEXT_AMD64_XSTATE,
+                    &mut context,
+                    &mut len,
+                ) != 0
+                && SetXStateFeaturesMask(context, XSTATE_MASK_AVX) != 0;
+            let mut plain: CONTEXT = std::mem::zeroed();
+            if !xstate {
+                plain.ContextFlags = CONTEXT_AMD64_FLOATING_POINT;
+                context = &mut plain;
+            }
+
+            let mut ok = GetThreadContext(thread, &mut *context).as_bool();
+            if ok {
+                let fxsave = std::slice::from_raw_parts_mut(
+                    (context as *mut u8).add(CONTEXT_FLTSAVE_OFFSET),
+                    512,
+                );
+                let mut ymm_high = None;
+                if xstate {
+                    let mut feature_len = 0;
+                    let area = LocateXStateFeature(context, XSTATE_AVX, &mut feature_len);
+                    let mut mask = 0;
+                    GetXStateFeaturesMask(context, &mut mask);
+                    if !area.is_null() && feature_len >= 256 {
+                        let area = std::slice::from_raw_parts_mut(area, 256);
+                        //AVX state in its initial configuration isn't stored
+                        if mask & XSTATE_MASK_AVX == 0 {
+                            area.fill(0);
+                        }
+                        ymm_high = Some(area);
+                    }
+                }
+                f(fxsave, ymm_high);
+                if write {
+                    if xstate && write_avx {
+                        SetXStateFeaturesMask(context, XSTATE_MASK_AVX);
+                    } else {
+                        //Leaves the upper halves of the ymm registers as they are
+                        (*context).ContextFlags = CONTEXT_AMD64_FLOATING_POINT;
+                    }
+                    ok = SetThreadContext(thread, &*context).as_bool();
+                }
+            }
+            CloseHandle(thread);
+            ok
+        }
+    }
+}
+
+///CONTEXT_AMD64 | CONTEXT_FLOATING_POINT
+#[cfg(all(target_os = "windows", target_arch = "x86_64"))]
+const CONTEXT_AMD64_FLOATING_POINT: u32 = 0x0010_0008;
+///CONTEXT_AMD64_FLOATING_POINT | CONTEXT_XSTATE
+#[cfg(all(target_os = "windows", target_arch = "x86_64"))]
+const CONTEXT_AMD64_XSTATE: u32 = 0x0010_0048;
+#[cfg(all(target_os = "windows", target_arch = "x86_64"))]
+const XSTATE_AVX: u32 = 2;
+#[cfg(all(target_os = "windows", target_arch = "x86_64"))]
+const XSTATE_MASK_AVX: u64 = 1 << XSTATE_AVX;
+///Offset of FltSave, the FXSAVE area, in CONTEXT
+#[cfg(all(target_os = "windows", target_arch = "x86_64"))]
+const CONTEXT_FLTSAVE_OFFSET: usize = 0x100;
+
+///CONTEXT_AMD64 | CONTEXT_CONTROL | CONTEXT_INTEGER
+#[cfg(all(target_os = "windows", target_arch = "x86_64"))]
+const CONTEXT_AMD64_FULL: u32 = 0x0010_0003;
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+impl Thread {
+    ///Read the general purpose registers, the thread must be stopped with suspend() first
+    pub fn context(&self) -> Option<Registers> {
+        let regs = self.user_regs()?;
+        Some(Registers {
+            rax: regs.rax,
+            rbx: regs.rbx,
+            rcx: regs.rcx,
+            rdx: regs.rdx,
+            rsi: regs.rsi,
+            rdi: regs.rdi,
+            rbp: regs.rbp,
+            rsp: regs.rsp,
+            r8: regs.r8,
+            r9: regs.r9,
+            r10: regs.r10,
+            r11: regs.r11,
+            r12: regs.r12,
+            r13: regs.r13,
+            r14: regs.r14,
+            r15: regs.r15,
+            rip: regs.rip,
+            rflags: regs.eflags,
+        })
+    }
+
+    ///Overwrite the general purpose registers, the thread must be stopped with suspend() first
+    pub fn set_context(&self, registers: &Registers) -> bool {
+        //Start from the current registers so segment and orig_rax values stay valid
+        let mut regs = match self.user_regs() {
+            Some(regs) => regs,
+            None => return false,
+        };
+        regs.rax = registers.rax;
+        regs.rbx = registers.rbx;
+        regs.rcx = registers.rcx;
+        regs.rdx = registers.rdx;
+        regs.rsi = registers.rsi;
+        regs.rdi = registers.rdi;
+        regs.rbp = registers.rbp;
+        regs.rsp = registers.rsp;
+        regs.r8 = registers.r8;
+        regs.r9 = registers.r9;
+        regs.r10 = registers.r10;
+        regs.r11 = registers.r11;
+        regs.r12 = registers.r12;
+        regs.r13 = registers.r13;
+        regs.r14 = registers.r14;
+        regs.r15 = registers.r15;
+        regs.rip = registers.rip;
+        regs.eflags = registers.rflags;
+        unsafe {
+            libc::ptrace(
+                libc::PTRACE_SETREGS,
+                self.id as libc::pid_t,
+                std::ptr::null_mut::<libc::c_void>(),
+                &regs as *const libc::user_regs_struct,
+            ) == 0
+        }
+    }
+
+    pub(crate) fn user_regs(&self) -> Option<libc::user_regs_struct> {
+        unsafe {
+            let mut regs: libc::user_regs_struct = std::mem::zeroed();
+            let result = libc::ptrace(
+                libc::PTRACE_GETREGS,
+                self.id as libc::pid_t,
+                std::ptr::null_mut::<libc::c_void>(),
+                &mut regs as *mut libc::user_regs_struct,
+            );
+            if result != 0 {
+                return None;
+            }
+            Some(regs)
+        }
+    }
+
+    ///Read the floating point and vector registers, the thread must be stopped with suspend() first
+    pub fn float_context(&self) -> Option<FloatRegisters> {
+        let mut fxsave = [0u8; FXSAVE_SIZE];
+        unsafe {
+            let result = libc::ptrace(
+                libc::PTRACE_GETFPREGS,
+                self.id as libc::pid_t,
+                std::ptr::null_mut::<libc::c_void>(),
+                fxsave.as_mut_ptr(),
+            );
+            if result != 0 {
+                return None;
+            }
+        }
+        let mut registers = FloatRegisters::from_fxsave(&fxsave);
+        registers.ymm_high = self.xsave().and_then(|area| {
+            let ymm = area.get(XSAVE_YMM_HIGH_OFFSET..XSAVE_YMM_HIGH_OFFSET + 256)?;
+            let mut high = [0u128; 16];
+            //AVX state in its initial configuration isn't stored
+            if area[XSAVE_XSTATE_BV_OFFSET] & XSTATE_AVX != 0 {
+                for (index, register) in high.iter_mut().enumerate() {
+                    let mut bytes = [0u8; 16];
+                    bytes.copy_from_slice(&ymm[index * 16..index * 16 + 16]);
+                    *register = u128::from_le_bytes(bytes);
+                }
+            }
+            Some(high)
+        });
+        Some(registers)
+    }
+
+    ///Overwrite the floating point and vector registers, the upper halves of the ymm registers only if
+    ///'registers' has them. The thread must be stopped with suspend() first
+    pub fn set_float_context(&self, registers: &FloatRegisters) -> bool {
+        let tid = self.id as libc::pid_t;
+        let null = std::ptr::null_mut::<libc::c_void>();
+        let high = match registers.ymm_high {
+            Some(high) => high,
+            None => unsafe {
+                let mut fxsave = [0u8; FXSAVE_SIZE];
+                if libc::ptrace(libc::PTRACE_GETFPREGS, tid, null, fxsave.as_mut_ptr()) != 0 {
+                    return false;
+                }
+                registers.write_fxsave(&mut fxsave);
+                return libc::ptrace(libc::PTRACE_SETFPREGS, tid, null, fxsave.as_ptr()) == 0;
+            },
+        };
+
+        //The kernel only takes whole XSAVE areas
+        let mut area = match self.xsave() {
+            Some(area) if area.len() >= XSAVE_YMM_HIGH_OFFSET + 256 => area,
+            _ => return false,
+        };
+        registers.write_fxsave(&mut area[..FXSAVE_SIZE]);
+        for (index, register) in high.iter().enumerate() {
+            let offset = XSAVE_YMM_HIGH_OFFSET + index * 16;
+            area[offset..offset + 16].copy_from_slice(&register.to_le_bytes());
+        }
+        area[XSAVE_XSTATE_BV_OFFSET] |= XSTATE_X87 | XSTATE_SSE | XSTATE_AVX;
+        let mut iov = libc::iovec {
+            iov_base: area.as_mut_ptr() as *mut libc::c_void,
+            iov_len: area.len(),
+        };
+        unsafe {
+            libc::ptrace(
+                libc::PTRACE_SETREGSET,
+                tid,
+                NT_X86_XSTATE as *mut libc::c_void,
+                &mut iov as *mut libc::iovec,
+            ) == 0
+        }
+    }
+
+    ///XSAVE area of the thread in the standard format, None if the system doesn't have one
+    fn xsave(&self) -> Option<Vec<u8>> {
+        let mut area = vec![0u8; XSAVE_MAX_SIZE];
+        let mut iov = libc::iovec {
+            iov_base: area.as_mut_ptr() as *mut libc::c_void,
+            iov_len: area.len(),
+        };
+        unsafe {
+            let result = libc::ptrace(
+                libc::PTRACE_GETREGSET,
+                self.id as libc::pid_t,
+                NT_X86_XSTATE as *mut libc::c_void,
+                &mut iov as *mut libc::iovec,
+            );
+            if result != 0 {
+                return None;
+            }
+        }
+        area.truncate(iov.iov_len);
+        Some(area)
+    }
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+const FXSAVE_SIZE: usize = 512;
+///Regset of the XSAVE area
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+const NT_X86_XSTATE: usize = 0x202;
+///Read size of XSAVE areas, large enough for AVX-512 and AMX states
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+const XSAVE_MAX_SIZE: usize = 0x4000;
+///Offsets in the XSAVE area of the XSTATE_BV field, which tells the stored states, and of the upper halves of the
+///ymm registers
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+const XSAVE_XSTATE_BV_OFFSET: usize = 512;
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+const XSAVE_YMM_HIGH_OFFSET: usize = 576;
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+const XSTATE_X87: u8 = 1;
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+const XSTATE_SSE: u8 = 2;
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+const XSTATE_AVX: u8 = 4;
+
+#[cfg(target_arch = "x86_64")]
+impl Thread {
+    ///Capture up to 'max_frames' return addresses by walking the frame pointer chain, starting at the current instruction.
+    ///The thread should be suspended first, code built without frame pointers ends the walk early
+    pub fn backtrace<P: ProcT + ?Sized>(&self, proc: &P, max_frames: usize) -> Vec<StackFrame> {
+        let registers = match self.context() {
+            Some(registers) => registers,
+            None => return Vec::new(),
+        };
+
+        let mut addresses = vec![registers.rip as usize];
+        let mut frame = registers.rbp as usize;
+        let mut stack_low = registers.rsp as usize;
+        while addresses.len() < max_frames && frame >= stack_low && frame.is_multiple_of(8) {
+            //Each frame starts with the caller's frame pointer followed by the return address
+            let [next_frame, return_address] = match proc.read::<[u64; 2]>(frame) {
+                Some(pair) => pair,
+                None => break,
+            };
+            if return_address == 0 {
+                break;
+            }
+            addresses.push(return_address as usize);
+            stack_low = frame + 16;
+            frame = next_frame as usize;
+        }
+        addresses.truncate(max_frames);
+
+        let modules = proc.modules();
+        addresses
+            .into_iter()
+            .map(|address| StackFrame {
+                address,
+                location: modules
+                    .iter()
+                    .find(|module| module.contains(address))
+                    .map(|module| RebasedAddress::new(&module.name, address - module.base)),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,241 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::{Module, Pod, ProcT, Protection, Region, RegionInfo, Thread};
+
+///Calls and bytes that can be spent right away, refilled at the configured rates up to one second worth
+#[derive(Debug)]
+struct Budget {
+    calls: f64,
+    bytes: f64,
+    refilled: Instant,
+}
+
+///Process whose reads and writes are rate limited, so a background monitor has no measurable impact on the
+///target. Calls block until the budget allows them: up to one second worth of calls and bytes can be spent at
+///once, then they are spread at the configured rates. A single transfer larger than the byte budget still goes
+///through, later calls wait until it is paid back
+///
+///```
+///use proc_memory::{MockProc, ProcT, ThrottledProc};
+///use std::time::{Duration, Instant};
+///
+///let proc = MockProc::new();
+///proc.map_value(0x1000, &1u32);
+///let throttled = ThrottledProc::new(&proc).with_call_rate(20);
+///let started = Instant::now();
+///for _ in 0..30 {
+///    assert_eq!(throttled.read::<u32>(0x1000), Some(1));
+///}
+///assert!(started.elapsed() >= Duration::from_millis(400));
+///```
+#[derive(Debug)]
+pub struct ThrottledProc<'a, P: ProcT + ?Sized> {
+    proc: &'a P,
+    calls_per_second: Option<u32>,
+    bytes_per_second: Option<usize>,
+    budget: Mutex<Budget>,
+}
+
+impl<'a, P: ProcT + ?Sized> ThrottledProc<'a, P> {
+    ///Wrap 'proc' without any limit, set them with with_call_rate() and with_byte_rate()
+    pub fn new(proc: &'a P) -> ThrottledProc<'a, P> {
+        ThrottledProc {
+            proc,
+            calls_per_second: None,
+            bytes_per_second: None,
+            budget: Mutex::new(Budget {
+                calls: 0.0,
+                bytes: 0.0,
+                refilled: Instant::now(),
+            }),
+        }
+    }
+
+    ///Allow at most 'calls' reads and writes per second
+    pub fn with_call_rate(mut self, calls: u32) -> Self {
+        self.calls_per_second = Some(calls.max(1));
+        self.budget.get_mut().unwrap().calls = calls.max(1) as f64;
+        self
+    }
+
+    ///Transfer at most 'bytes' per second
+    pub fn with_byte_rate(mut self, bytes: usize) -> Self {
+        self.bytes_per_second = Some(bytes.max(1));
+        self.budget.get_mut().unwrap().bytes = bytes.max(1) as f64;
+        self
+    }
+
+    ///The process the calls are forwarded to
+    pub fn proc(&self) -> &'a P {
+        self.proc
+    }
+
+    ///Block until a call transferring 'len' bytes fits in the budget, then spend it
+    fn acquire(&self, len: usize) {
+        loop {
+            let mut budget = self.budget.lock().unwrap();
+            let elapsed = budget.refilled.elapsed().as_secs_f64();
+            budget.refilled = Instant::now();
+            let mut wait = 0f64;
+            if let Some(rate) = self.calls_per_second {
+                let rate = rate as f64;
+                budget.calls = (budget.calls + elapsed * rate).min(rate);
+                wait = wait.max((1.0 - budget.calls) / rate);
+            }
+            if let Some(rate) = self.bytes_per_second {
+                let rate = rate as f64;
+                budget.bytes = (budget.bytes + elapsed * rate).min(rate);
+                wait = wait.max(-budget.bytes / rate);
+            }
+            if wait <= 0.0 {
+                budget.calls -= 1.0;
+                budget.bytes -= len as f64;
+                return;
+            }
+            drop(budget);
+            std::thread::sleep(Duration::from_secs_f64(wait));
+        }
+    }
+}
+
+impl<'a, P: ProcT + ?Sized> ProcT for ThrottledProc<'a, P> {
+    ///Always None, throttled processes are built with ThrottledProc::new()
+    fn get(_proc_name: &str) -> Option<Self> {
+        None
+    }
+
+    fn read<T: Pod>(&self, proc_address: usize) -> Option<T> {
+        self.acquire(std::mem::size_of::<T>());
+        self.proc.read(proc_address)
+    }
+
+    fn read_valid<T: Pod>(&self, proc_address: usize, validator: impl Fn(&T) -> bool) -> Option<T> {
+        self.acquire(std::mem::size_of::<T>());
+        self.proc.read_valid(proc_address, validator)
+    }
+
+    fn read_vec<T: Pod>(
+        &self,
+        proc_address: usize,
+        len: usize,
+        default_provider: impl Fn() -> T,
+    ) -> Option<Vec<T>> {
+        self.acquire(len.saturating_mul(std::mem::size_of::<T>()));
+        self.proc.read_vec(proc_address, len, default_provider)
+    }
+
+    fn read_vec_uninit<T: Pod>(&self, proc_address: usize, len: usize) -> Option<Vec<T>> {
+        self.acquire(len.saturating_mul(std::mem::size_of::<T>()));
+        self.proc.read_vec_uninit(proc_address, len)
+    }
+
+    fn read_partial(&self, proc_address: usize, len: usize) -> (Vec<u8>, usize) {
+        self.acquire(len);
+        self.proc.read_partial(proc_address, len)
+    }
+
+    fn write<T>(&self, proc_address: usize, data: &T) -> (bool, usize) {
+        self.acquire(std::mem::size_of::<T>());
+        self.proc.write(proc_address, data)
+    }
+
+    fn write_bytes(&self, proc_address: usize, data: &[u8]) -> (bool, usize) {
+        self.acquire(data.len());
+        self.proc.write_bytes(proc_address, data)
+    }
+
+    fn modules(&self) -> Vec<Module> {
+        self.proc.modules()
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        self.proc.regions()
+    }
+
+    fn query(&self, address: usize) -> Option<RegionInfo> {
+        self.proc.query(address)
+    }
+
+    fn region_infos(&self) -> Vec<RegionInfo> {
+        self.proc.region_infos()
+    }
+
+    fn threads(&self) -> Vec<Thread> {
+        self.proc.threads()
+    }
+
+    fn is_alive(&self) -> bool {
+        self.proc.is_alive()
+    }
+
+    fn protect(&self, address: usize, len: usize, protection: Protection) -> Option<Protection> {
+        self.proc.protect(address, len, protection)
+    }
+
+    fn alloc(&self, len: usize, protection: Protection) -> Option<usize> {
+        self.proc.alloc(len, protection)
+    }
+
+    fn free(&self, address: usize, len: usize) -> bool {
+        self.proc.free(address, len)
+    }
+
+    fn create_thread(&self, start: usize, param: usize) -> Option<u32> {
+        self.proc.create_thread(start, param)
+    }
+
+    fn create_thread_and_wait(&self, start: usize, param: usize) -> Option<u32> {
+        self.proc.create_thread_and_wait(start, param)
+    }
+
+    #[cfg(feature = "minidump")]
+    fn write_minidump(
+        &self,
+        path: &std::path::Path,
+        level: crate::DumpLevel,
+    ) -> std::io::Result<()> {
+        self.proc.write_minidump(path, level)
+    }
+
+    fn suspend(&self) -> bool {
+        self.proc.suspend()
+    }
+
+    fn resume(&self) -> bool {
+        self.proc.resume()
+    }
+
+    fn command_line(&self) -> Option<String> {
+        self.proc.command_line()
+    }
+
+    fn environment(&self) -> Option<Vec<(String, String)>> {
+        self.proc.environment()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.proc.name()
+    }
+
+    fn exe_path(&self) -> Option<PathBuf> {
+        self.proc.exe_path()
+    }
+
+    fn start_time(&self) -> Option<SystemTime> {
+        self.proc.start_time()
+    }
+
+    fn parent_pid(&self) -> Option<isize> {
+        self.proc.parent_pid()
+    }
+
+    fn is_64bit(&self) -> bool {
+        self.proc.is_64bit()
+    }
+
+    fn pid(&self) -> isize {
+        self.proc.pid()
+    }
+}
@@ -0,0 +1,109 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::{DebugEvent, Debugger, ProcT, Thread, ThreadState};
+
+///Time a thread gets to report itself once a debugger attached
+const ATTACH_TIMEOUT: Duration = Duration::from_secs(2);
+
+///Instruction executed by a traced thread
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceStep {
+    ///Address of the instruction
+    pub address: usize,
+    ///Registers other than rip the instruction changed, with their new value. Empty unless registers are recorded
+    pub changes: Vec<(&'static str, u64)>,
+}
+
+///Instructions executed by a thread, in order
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Trace {
+    pub thread: u32,
+    pub steps: Vec<TraceStep>,
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for step in &self.steps {
+            write!(f, "{:#X}", step.address)?;
+            for (name, value) in &step.changes {
+                write!(f, "  {}={:#X}", name, value)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<P: ProcT + ?Sized> Debugger<'_, P> {
+    ///Single step the thread stopped by the last event for up to 'count' instructions, recording their addresses
+    ///and, if 'registers' is set, the registers each one changed. The event is continued as handled, and so is the
+    ///last step. Stops early if the thread exits or raises another exception, which is left for the caller to
+    ///continue. None if no thread is stopped.
+    ///Blocks while the thread waits in a system call. The events of other threads are continued meanwhile,
+    ///passing their exceptions on to the process
+    pub fn trace(&mut self, count: usize, registers: bool) -> Option<Trace> {
+        let id = self.stopped_thread()?;
+        let thread = Thread {
+            id,
+            process_id: self.proc().pid() as u32,
+            start_address: None,
+            state: ThreadState::Stopped,
+        };
+        let mut before = thread.context()?;
+        let mut trace = Trace {
+            thread: id,
+            steps: Vec::new(),
+        };
+        while trace.steps.len() < count {
+            self.step_event(true);
+            let stepped = loop {
+                match self.wait(Duration::from_millis(100)) {
+                    Some(DebugEvent::Exception(exception)) if exception.thread == id => {
+                        break exception.is_single_step();
+                    }
+                    Some(DebugEvent::ThreadExited { thread, .. }) if thread == id => break false,
+                    Some(_) => {}
+                    None if self.threads().contains(&id) => {}
+                    None => break false,
+                }
+            };
+            if !stepped {
+                return Some(trace);
+            }
+            let after = match thread.context() {
+                Some(after) => after,
+                None => break,
+            };
+            let mut changes = Vec::new();
+            if registers {
+                changes = before.changes(&after);
+                changes.retain(|(name, _)| *name != "rip");
+            }
+            trace.steps.push(TraceStep {
+                address: before.rip as usize,
+                changes,
+            });
+            before = after;
+        }
+        self.continue_event(true);
+        Some(trace)
+    }
+}
+
+pub(crate) fn trace_thread<P: ProcT + ?Sized>(
+    proc: &P,
+    thread: u32,
+    count: usize,
+    registers: bool,
+) -> Option<Trace> {
+    let mut debugger = Debugger::attach(proc)?;
+    let deadline = Instant::now() + ATTACH_TIMEOUT;
+    //Every thread reports itself when the debugger attaches, stopped
+    while let Some(event) = debugger.wait(deadline.saturating_duration_since(Instant::now())) {
+        if event == (DebugEvent::ThreadCreated { thread }) {
+            return debugger.trace(count, registers);
+        }
+    }
+    None
+}
@@ -0,0 +1,51 @@
+use std::mem::MaybeUninit;
+
+use crate::Pod;
+
+///Bytes of 'len' values of type T starting at 'ptr', none of them need to be initialized
+unsafe fn bytes_of<'a, T>(ptr: *mut T, len: usize) -> &'a mut [MaybeUninit<u8>] {
+    std::slice::from_raw_parts_mut(ptr as *mut MaybeUninit<u8>, std::mem::size_of::<T>() * len)
+}
+
+///Read a T without initializing it first. 'fill' must initialize every byte of the buffer or return 'false'
+pub(crate) fn read_value<T: Pod>(fill: impl FnOnce(&mut [MaybeUninit<u8>]) -> bool) -> Option<T> {
+    let mut value = MaybeUninit::<T>::uninit();
+    if !fill(unsafe { bytes_of(value.as_mut_ptr(), 1) }) {
+        return None;
+    }
+    Some(unsafe { value.assume_init() })
+}
+
+///Read 'len' values of type T without initializing them first. 'fill' must initialize every byte of the buffer or
+///return 'false'
+pub(crate) fn read_values<T: Pod>(
+    len: usize,
+    fill: impl FnOnce(&mut [MaybeUninit<u8>]) -> bool,
+) -> Option<Vec<T>> {
+    let mut values = Vec::<T>::with_capacity(len);
+    if !fill(unsafe { bytes_of(values.as_mut_ptr(), len) }) {
+        return None;
+    }
+    unsafe { values.set_len(len) };
+    Some(values)
+}
+
+///Read up to 'len' bytes without zeroing the buffer first. 'fill' initializes the start of the buffer and returns
+///how many bytes it wrote
+pub(crate) fn read_bytes(
+    len: usize,
+    fill: impl FnOnce(&mut [MaybeUninit<u8>]) -> usize,
+) -> Vec<u8> {
+    let mut data = Vec::<u8>::with_capacity(len);
+    let count = fill(&mut data.spare_capacity_mut()[..len]).min(len);
+    unsafe { data.set_len(count) };
+    data
+}
+
+///Copy 'src' to the start of 'dst', which must be at least as long
+pub(crate) fn copy_to(dst: &mut [MaybeUninit<u8>], src: &[u8]) {
+    assert!(src.len() <= dst.len());
+    unsafe {
+        std::ptr::copy_nonoverlapping(src.as_ptr(), dst.as_mut_ptr() as *mut u8, src.len());
+    }
+}
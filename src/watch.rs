@@ -0,0 +1,524 @@
+use std::time::{Duration, Instant};
+
+use crate::{DebugEvent, DebugException, Debugger, ProcT, Protection, RebasedAddress, PAGE_SIZE};
+
+///Debug registers available for watchpoints, DR0 to DR3
+pub const MAX_WATCHPOINTS: usize = 4;
+
+///Accesses a watchpoint triggers on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WatchAccess {
+    Write,
+    ///Reads and writes, the CPU can't watch reads alone
+    ReadWrite,
+    ///Execution of the instruction starting at the watched address
+    Execute,
+}
+
+///How accesses to watched addresses are caught
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WatchMethod {
+    ///Debug registers of every thread, at most MAX_WATCHPOINTS of 1, 2, 4 or 8 bytes aligned to their size
+    DebugRegisters,
+    ///Remove access to the pages holding the watched bytes and catch the faults, for any number and size of
+    ///watchpoints. Every access to the pages stops the target, accesses by other threads while one is stepped past
+    ///its fault are missed, and on Linux system calls touching the pages fail instead of being reported
+    GuardPages,
+}
+
+///Watched range of memory
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub address: usize,
+    ///Bytes watched, 1, 2, 4 or 8 aligned to their size for WatchMethod::DebugRegisters
+    pub len: usize,
+    pub access: WatchAccess,
+}
+
+impl Watchpoint {
+    ///Watch writes to 'len' bytes at 'address'. None if 'len' isn't 1, 2, 4 or 8 or 'address' isn't aligned to it
+    pub fn write(address: usize, len: usize) -> Option<Watchpoint> {
+        Watchpoint::data(address, len, WatchAccess::Write)
+    }
+
+    ///Watch reads and writes of 'len' bytes at 'address'. None if 'len' isn't 1, 2, 4 or 8 or 'address' isn't
+    ///aligned to it
+    pub fn read_write(address: usize, len: usize) -> Option<Watchpoint> {
+        Watchpoint::data(address, len, WatchAccess::ReadWrite)
+    }
+
+    ///Watch the execution of the instruction at 'address'
+    pub fn execute(address: usize) -> Watchpoint {
+        Watchpoint {
+            address,
+            len: 1,
+            access: WatchAccess::Execute,
+        }
+    }
+
+    fn data(address: usize, len: usize, access: WatchAccess) -> Option<Watchpoint> {
+        let watchpoint = Watchpoint {
+            address,
+            len,
+            access,
+        };
+        if !watchpoint.fits_debug_register() {
+            return None;
+        }
+        Some(watchpoint)
+    }
+
+    fn fits_debug_register(&self) -> bool {
+        match self.access {
+            WatchAccess::Execute => self.len == 1,
+            _ => matches!(self.len, 1 | 2 | 4 | 8) && self.address.is_multiple_of(self.len),
+        }
+    }
+
+    fn contains(&self, address: usize) -> bool {
+        address >= self.address && address - self.address < self.len.max(1)
+    }
+
+    ///Bits of DR7 enabling the watchpoint in debug register 'index'
+    fn dr7_bits(&self, index: usize) -> u64 {
+        let access = match self.access {
+            WatchAccess::Execute => 0,
+            WatchAccess::Write => 1,
+            WatchAccess::ReadWrite => 3,
+        };
+        let len = match self.len {
+            1 => 0,
+            2 => 1,
+            8 => 2,
+            _ => 3,
+        };
+        (1 << (index * 2)) | ((access | len << 2) << (16 + index * 4))
+    }
+
+    ///'protection' without the rights whose use the watchpoint has to catch
+    fn guard(&self, protection: Protection) -> Protection {
+        match self.access {
+            WatchAccess::Write => Protection {
+                write: false,
+                ..protection
+            },
+            WatchAccess::ReadWrite => Protection::NONE,
+            WatchAccess::Execute => Protection {
+                execute: false,
+                ..protection
+            },
+        }
+    }
+}
+
+///Value of DR7 enabling 'watchpoints' in DR0 onwards
+fn dr7(watchpoints: &[Watchpoint]) -> u64 {
+    watchpoints
+        .iter()
+        .enumerate()
+        .fold(0, |dr7, (index, watchpoint)| {
+            dr7 | watchpoint.dr7_bits(index)
+        })
+}
+
+///Page holding watched bytes with WatchMethod::GuardPages
+#[derive(Clone, Copy, Debug)]
+struct GuardedPage {
+    address: usize,
+    original: Protection,
+    guarded: Protection,
+}
+
+impl GuardedPage {
+    fn contains(&self, address: usize) -> bool {
+        address >= self.address && address - self.address < PAGE_SIZE
+    }
+}
+
+fn guarded_pages<P: ProcT + ?Sized>(
+    proc: &P,
+    watchpoints: &[Watchpoint],
+) -> Option<Vec<GuardedPage>> {
+    let mut pages: Vec<GuardedPage> = Vec::new();
+    for watchpoint in watchpoints {
+        let first = watchpoint.address & !(PAGE_SIZE - 1);
+        let end = watchpoint.address + watchpoint.len.max(1);
+        for address in (first..end).step_by(PAGE_SIZE) {
+            match pages.iter_mut().find(|page| page.address == address) {
+                Some(page) => page.guarded = watchpoint.guard(page.guarded),
+                None => {
+                    let original = proc.query(address)?.protection;
+                    pages.push(GuardedPage {
+                        address,
+                        original,
+                        guarded: watchpoint.guard(original),
+                    });
+                }
+            }
+        }
+    }
+    Some(pages)
+}
+
+///Instruction that triggered a watchpoint
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WatchHit {
+    ///Index of the watchpoint
+    pub watchpoint: usize,
+    ///Instruction address. Debug registers trigger once a data access completed, so for them it's the address of
+    ///the instruction following the access. Execute watchpoints and guard pages report the accessing instruction
+    pub address: usize,
+    ///Module relative 'address', None when no loaded module contains it
+    pub location: Option<RebasedAddress>,
+    ///Times the watchpoint triggered at 'address'
+    pub count: usize,
+}
+
+pub(crate) fn find_accesses<P: ProcT + ?Sized>(
+    proc: &P,
+    watchpoints: &[Watchpoint],
+    duration: Duration,
+    method: WatchMethod,
+) -> Option<Vec<WatchHit>> {
+    let mut hits: Vec<WatchHit> = Vec::new();
+    let mut record = |watchpoint: usize, address: usize| match hits
+        .iter_mut()
+        .find(|hit| hit.watchpoint == watchpoint && hit.address == address)
+    {
+        Some(hit) => hit.count += 1,
+        None => hits.push(WatchHit {
+            watchpoint,
+            address,
+            location: None,
+            count: 1,
+        }),
+    };
+    match method {
+        WatchMethod::DebugRegisters => {
+            if watchpoints.is_empty()
+                || watchpoints.len() > MAX_WATCHPOINTS
+                || !watchpoints.iter().all(Watchpoint::fits_debug_register)
+            {
+                return None;
+            }
+            let mut debugger = Debugger::attach(proc)?;
+            watch_debug_registers(&mut debugger, watchpoints, duration, &mut record);
+        }
+        WatchMethod::GuardPages => {
+            if watchpoints.is_empty() {
+                return None;
+            }
+            let pages = guarded_pages(proc, watchpoints)?;
+            let mut debugger = Debugger::attach(proc)?;
+            watch_guard_pages(
+                &mut debugger,
+                proc,
+                watchpoints,
+                &pages,
+                duration,
+                &mut record,
+            )?;
+        }
+    }
+
+    let modules = proc.modules();
+    for hit in &mut hits {
+        hit.location = modules
+            .iter()
+            .find(|module| module.contains(hit.address))
+            .map(|module| RebasedAddress::new(&module.name, hit.address - module.base));
+    }
+    hits.sort_by_key(|hit| (hit.watchpoint, hit.address));
+    Some(hits)
+}
+
+fn watch_debug_registers<P: ProcT + ?Sized>(
+    debugger: &mut Debugger<'_, P>,
+    watchpoints: &[Watchpoint],
+    duration: Duration,
+    hit: &mut dyn FnMut(usize, usize),
+) {
+    debugger.run(duration, |event| match event {
+        DebugEvent::ThreadCreated { thread } => platform::arm(*thread, watchpoints),
+        DebugEvent::Exception(exception) => platform::triggered(exception, hit),
+        _ => false,
+    });
+
+    debugger.stop();
+    for thread in debugger.threads() {
+        platform::arm(thread, &[]);
+    }
+    //Watchpoints triggered before the threads stopped
+    while let Some(event) = debugger.wait(Duration::ZERO) {
+        let handled = match &event {
+            DebugEvent::Exception(exception) => platform::triggered(exception, hit),
+            _ => false,
+        };
+        debugger.continue_event(handled);
+    }
+}
+
+///Returns 'false' for the accesses 'exception' reports that 'watchpoint' doesn't watch. Only Windows tells reads,
+///writes and executions apart, on Linux every access to the watched bytes counts
+fn is_watched_access(watchpoint: &Watchpoint, exception: &DebugException) -> bool {
+    match (watchpoint.access, exception.parameters.first()) {
+        (_, None) => true,
+        (WatchAccess::Write, Some(kind)) => *kind == 1,
+        (WatchAccess::ReadWrite, Some(kind)) => *kind <= 1,
+        (WatchAccess::Execute, Some(kind)) => *kind == 8,
+    }
+}
+
+///Handle an exception of the guard page watch, returns 'false' if the watch didn't cause it. The faulting
+///instruction runs with the pages unguarded while 'guarding', once the watch is over it runs again
+#[allow(clippy::too_many_arguments)]
+fn guard_page_exception<P: ProcT + ?Sized>(
+    debugger: &mut Debugger<'_, P>,
+    proc: &P,
+    exception: &DebugException,
+    watchpoints: &[Watchpoint],
+    pages: &[GuardedPage],
+    stepping: &mut Vec<u32>,
+    guarding: bool,
+    hit: &mut dyn FnMut(usize, usize),
+) -> bool {
+    match exception.fault_address {
+        Some(address) if pages.iter().any(|page| page.contains(address)) => {
+            for (index, watchpoint) in watchpoints.iter().enumerate() {
+                if watchpoint.contains(address) && is_watched_access(watchpoint, exception) {
+                    hit(index, exception.address);
+                }
+            }
+            if guarding {
+                platform::protect(proc, exception.thread, pages, |page| page.original);
+                stepping.push(exception.thread);
+                debugger.step_event(true);
+            }
+            true
+        }
+        _ if exception.is_single_step() && stepping.contains(&exception.thread) => {
+            stepping.retain(|thread| *thread != exception.thread);
+            if guarding {
+                platform::protect(proc, exception.thread, pages, |page| page.guarded);
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+fn watch_guard_pages<P: ProcT + ?Sized>(
+    debugger: &mut Debugger<'_, P>,
+    proc: &P,
+    watchpoints: &[Watchpoint],
+    pages: &[GuardedPage],
+    duration: Duration,
+    hit: &mut dyn FnMut(usize, usize),
+) -> Option<()> {
+    let deadline = Instant::now() + duration;
+    let mut guarded = false;
+    //Threads running the faulting instruction with the pages unguarded
+    let mut stepping = Vec::new();
+    while let Some(event) = debugger.wait(deadline.saturating_duration_since(Instant::now())) {
+        let handled = match &event {
+            //Linux changes the protection through a stopped thread, the first event stops one
+            DebugEvent::ThreadCreated { thread } if !guarded => {
+                guarded = true;
+                if !platform::protect(proc, *thread, pages, |page| page.guarded) {
+                    platform::protect(proc, *thread, pages, |page| page.original);
+                    return None;
+                }
+                false
+            }
+            DebugEvent::Exception(exception) => guard_page_exception(
+                debugger,
+                proc,
+                exception,
+                watchpoints,
+                pages,
+                &mut stepping,
+                true,
+                hit,
+            ),
+            _ => false,
+        };
+        debugger.continue_event(handled);
+    }
+
+    debugger.stop();
+    if let Some(thread) = debugger.threads().first() {
+        platform::protect(proc, *thread, pages, |page| page.original);
+    }
+    //Faults raised before the pages were restored
+    while let Some(event) = debugger.wait(Duration::ZERO) {
+        let handled = match &event {
+            DebugEvent::Exception(exception) => guard_page_exception(
+                debugger,
+                proc,
+                exception,
+                watchpoints,
+                pages,
+                &mut stepping,
+                false,
+                hit,
+            ),
+            _ => false,
+        };
+        debugger.continue_event(handled);
+    }
+    Some(())
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use libc::{c_void, pid_t};
+
+    use super::{GuardedPage, Watchpoint};
+    use crate::{DebugException, ProcT, Protection, PAGE_SIZE};
+
+    fn set_debug_register(tid: pid_t, index: usize, value: u64) -> bool {
+        let offset = std::mem::offset_of!(libc::user, u_debugreg) + index * 8;
+        unsafe {
+            libc::ptrace(
+                libc::PTRACE_POKEUSER,
+                tid,
+                offset as *mut c_void,
+                value as usize as *mut c_void,
+            ) == 0
+        }
+    }
+
+    fn debug_register(tid: pid_t, index: usize) -> u64 {
+        let offset = std::mem::offset_of!(libc::user, u_debugreg) + index * 8;
+        unsafe { libc::ptrace(libc::PTRACE_PEEKUSER, tid, offset as *mut c_void) as u64 }
+    }
+
+    ///Set the watchpoints of a stopped thread
+    pub(super) fn arm(thread: u32, watchpoints: &[Watchpoint]) -> bool {
+        let tid = thread as pid_t;
+        watchpoints
+            .iter()
+            .enumerate()
+            .all(|(index, watchpoint)| set_debug_register(tid, index, watchpoint.address as u64))
+            && set_debug_register(tid, 7, super::dr7(watchpoints))
+    }
+
+    ///Report the watchpoints 'exception' was raised by, returns false if it wasn't one of them
+    pub(super) fn triggered(exception: &DebugException, hit: &mut dyn FnMut(usize, usize)) -> bool {
+        if !exception.is_single_step() {
+            return false;
+        }
+        let tid = exception.thread as pid_t;
+        let dr6 = debug_register(tid, 6);
+        if dr6 & 0xF == 0 {
+            return false;
+        }
+        for index in (0..super::MAX_WATCHPOINTS).filter(|index| dr6 & (1 << index) != 0) {
+            hit(index, exception.address);
+        }
+        set_debug_register(tid, 6, 0);
+        true
+    }
+
+    ///Change the protection of 'pages' with system calls made by 'thread', which the debugger stopped
+    pub(super) fn protect<P: ProcT + ?Sized>(
+        proc: &P,
+        thread: u32,
+        pages: &[GuardedPage],
+        protection: impl Fn(&GuardedPage) -> Protection,
+    ) -> bool {
+        pages.iter().all(|page| {
+            let args = [
+                page.address as u64,
+                PAGE_SIZE as u64,
+                protection(page).to_prot() as u64,
+                0,
+                0,
+                0,
+            ];
+            let (pid, tid) = (proc.pid() as pid_t, thread as pid_t);
+            crate::syscall::stopped_thread_syscall(pid, tid, libc::SYS_mprotect, args) == Some(0)
+        })
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::System::Diagnostics::Debug::{GetThreadContext, SetThreadContext, CONTEXT};
+    use windows::Win32::System::Threading::{OpenThread, THREAD_GET_CONTEXT, THREAD_SET_CONTEXT};
+
+    use super::{GuardedPage, Watchpoint};
+    use crate::{DebugException, ProcT, Protection, PAGE_SIZE};
+
+    ///CONTEXT_AMD64 | CONTEXT_CONTROL | CONTEXT_DEBUG_REGISTERS
+    const CONTEXT_AMD64_DEBUG: u32 = 0x0010_0011;
+    ///Resume flag, keeps an execute watchpoint from triggering again when the thread continues
+    const EFLAGS_RF: u32 = 0x1_0000;
+
+    ///Change the debug registers or flags of a stopped thread
+    fn update_thread(thread_id: u32, update: impl FnOnce(&mut CONTEXT)) -> bool {
+        unsafe {
+            let thread = OpenThread(THREAD_GET_CONTEXT | THREAD_SET_CONTEXT, None, thread_id);
+            if thread == HANDLE(0) {
+                return false;
+            }
+            let mut context: CONTEXT = std::mem::zeroed();
+            context.ContextFlags = CONTEXT_AMD64_DEBUG;
+            let updated = GetThreadContext(thread, &mut context).as_bool() && {
+                update(&mut context);
+                SetThreadContext(thread, &context).as_bool()
+            };
+            CloseHandle(thread);
+            updated
+        }
+    }
+
+    ///Set the watchpoints of a stopped thread
+    pub(super) fn arm(thread: u32, watchpoints: &[Watchpoint]) -> bool {
+        update_thread(thread, |context| {
+            for (index, watchpoint) in watchpoints.iter().enumerate() {
+                let address = watchpoint.address as u64;
+                match index {
+                    0 => context.Dr0 = address,
+                    1 => context.Dr1 = address,
+                    2 => context.Dr2 = address,
+                    _ => context.Dr3 = address,
+                }
+            }
+            context.Dr7 = super::dr7(watchpoints);
+        })
+    }
+
+    ///Report the watchpoints 'exception' was raised by, returns false if it wasn't one of them
+    pub(super) fn triggered(exception: &DebugException, hit: &mut dyn FnMut(usize, usize)) -> bool {
+        if !exception.is_single_step() {
+            return false;
+        }
+        let mut dr6 = 0;
+        update_thread(exception.thread, |context| {
+            dr6 = context.Dr6;
+            if dr6 & 0xF != 0 {
+                context.Dr6 = 0;
+                context.EFlags |= EFLAGS_RF;
+            }
+        });
+        for index in (0..super::MAX_WATCHPOINTS).filter(|index| dr6 & (1 << index) != 0) {
+            hit(index, exception.address);
+        }
+        dr6 & 0xF != 0
+    }
+
+    ///Change the protection of 'pages', any thread can do it on Windows
+    pub(super) fn protect<P: ProcT + ?Sized>(
+        proc: &P,
+        _thread: u32,
+        pages: &[GuardedPage],
+        protection: impl Fn(&GuardedPage) -> Protection,
+    ) -> bool {
+        pages.iter().all(|page| {
+            proc.protect(page.address, PAGE_SIZE, protection(page))
+                .is_some()
+        })
+    }
+}
@@ -0,0 +1,1232 @@
+mod support;
+
+use proc_memory::{
+    copy_between, parse_hex_bytes, AddressChain, AddressExpr, BackendProc, DumpFile, LogLevel,
+    Manager, ManagerEvent, MemoryBackend, MockProc, Module, OffsetTable, Pattern, Proc, ProcT,
+    Protection, RemoteCursor, ScanOptions, Sharing, Snapshot, StringEncoding, StringMatch, Tracked,
+};
+#[cfg(target_arch = "x86_64")]
+use proc_memory::{DebugEvent, Debugger, WatchMethod, Watchpoint};
+use std::sync::{Arc, Mutex};
+use support::{values, Target};
+
+#[test]
+fn reads_published_value() {
+    let target = Target::spawn();
+    assert_eq!(target.proc.read::<u64>(target.value), Some(values::VALUE));
+    assert_eq!(
+        target
+            .proc
+            .read_vec_uninit::<u8>(target.pattern, values::PATTERN.len()),
+        Some(values::PATTERN.to_vec())
+    );
+}
+
+#[test]
+fn write_is_seen_by_target() {
+    let mut target = Target::spawn();
+    assert_eq!(target.proc.write(target.value, &42u64), (true, 8));
+    assert_eq!(target.current_value(), 42);
+}
+
+#[test]
+fn platform_backend_reads_and_writes_raw_bytes() {
+    let mut target = Target::spawn();
+    let backend = target.proc.backend();
+    let mut buffer = [0u8; 8];
+    assert_eq!(backend.read_raw(target.value, &mut buffer), 8);
+    assert_eq!(u64::from_ne_bytes(buffer), values::VALUE);
+    assert_eq!(backend.write_raw(target.value, &7u64.to_ne_bytes()), 8);
+    assert_eq!(backend.read_raw(0, &mut buffer), 0);
+    assert_eq!(target.current_value(), 7);
+}
+
+#[test]
+fn reads_cstring() {
+    let target = Target::spawn();
+    assert_eq!(
+        target.proc.read_cstring(target.text, 64).as_deref(),
+        Some(values::TEXT)
+    );
+}
+
+#[test]
+fn follows_pointer() {
+    let target = Target::spawn();
+    assert_eq!(target.proc.read_ptr(target.pointer), Some(target.value));
+    let value = target
+        .proc
+        .ptr::<usize>(target.pointer)
+        .deref::<u64>()
+        .unwrap();
+    assert_eq!(value.read(), Some(values::VALUE));
+}
+
+#[test]
+fn scan_finds_pattern() {
+    let target = Target::spawn();
+    let matches = target.proc.scan(&Pattern::from_bytes(&values::PATTERN));
+    assert!(matches.contains(&target.pattern), "{:x?}", matches);
+}
+
+#[test]
+fn unmapped_read_fails() {
+    let target = Target::spawn();
+    assert_eq!(target.proc.read::<u64>(0), None);
+    assert_eq!(target.proc.read_partial(0, 16).1, 0);
+}
+
+#[test]
+fn logs_failed_reads() {
+    let target = Target::spawn();
+    let records = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&records);
+    proc_memory::set_logger(LogLevel::Debug, move |record| {
+        sink.lock()
+            .unwrap()
+            .push((record.operation, record.message.to_string()));
+    });
+    assert_eq!(target.proc.read::<u64>(0), None);
+    proc_memory::clear_logger();
+    assert!(records
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|(operation, message)| *operation == "read" && message.contains("at 0x0")));
+}
+
+#[test]
+fn lists_target_module() {
+    let target = Target::spawn();
+    assert_eq!(target.proc.pid(), target.pid() as isize);
+    assert!(target.module().size > 0);
+}
+
+#[test]
+fn reads_arguments_and_environment() {
+    let target = Target::spawn_with(&["a", "", "b c"], &[("ÉX", "1")]);
+    let command_line = target.proc.command_line().unwrap();
+    assert!(
+        command_line.ends_with(" a \"\" \"b c\""),
+        "{}",
+        command_line
+    );
+    let environment = target.proc.environment().unwrap();
+    assert!(environment.contains(&("ÉX".to_string(), "1".to_string())));
+}
+
+#[test]
+fn get_without_match_returns_none() {
+    assert!(Proc::get("proc_memory_no_such_process").is_none());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn lists_unique_symbols() {
+    let target = Target::spawn();
+    let symbols = target.module().symbols(&target.proc).unwrap();
+    let mut names: Vec<&str> = symbols.iter().map(|symbol| symbol.name.as_str()).collect();
+    assert!(names.contains(&"main"));
+    let count = names.len();
+    names.sort_unstable();
+    names.dedup();
+    assert_eq!(names.len(), count);
+}
+
+#[test]
+fn corrupted_elf_header_offsets_fail() {
+    //64-bit ELF header with one program header right after it and two section headers
+    let mut elf = vec![0u8; 64 + 56];
+    elf[..6].copy_from_slice(b"\x7FELF\x02\x01");
+    elf[0x20..0x28].copy_from_slice(&64u64.to_le_bytes());
+    elf[0x36..0x38].copy_from_slice(&56u16.to_le_bytes());
+    elf[0x38..0x3A].copy_from_slice(&1u16.to_le_bytes());
+    elf[0x3A..0x3C].copy_from_slice(&64u16.to_le_bytes());
+    elf[0x3C..0x3E].copy_from_slice(&2u16.to_le_bytes());
+    elf[64..68].copy_from_slice(&1u32.to_le_bytes());
+
+    let path = std::env::temp_dir().join(format!("proc_memory_elf_{}.so", std::process::id()));
+    let module = Module {
+        name: "corrupted.so".to_string(),
+        path: path.to_str().unwrap().to_string(),
+        base: 0x10000,
+        size: 0x1000,
+    };
+    let proc = MockProc::new().with_module(module.clone());
+    //Section and program header tables ending past the address space
+    for (field, offset) in [(0x28, u64::MAX), (0x28, u64::MAX - 3), (0x20, u64::MAX - 1)] {
+        let mut elf = elf.clone();
+        elf[field..field + 8].copy_from_slice(&offset.to_le_bytes());
+        std::fs::write(&path, &elf).unwrap();
+        assert_eq!(module.symbols(&proc), None, "{:#X} at {:#X}", offset, field);
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn query_reports_region() {
+    let target = Target::spawn();
+    let info = target.proc.query(target.value).unwrap();
+    assert!(info.contains(target.value));
+    assert!(info.protection.read && info.protection.write);
+    assert!(info.remaining(target.value) >= 8);
+
+    let image = target.proc.query(target.module().base).unwrap();
+    assert!(image.file.unwrap().contains("proc_memory_test_target"));
+    assert_eq!(target.proc.query(0), None);
+}
+
+#[test]
+fn region_infos_name_mapped_files() {
+    let target = Target::spawn();
+    let infos = target.proc.region_infos();
+    let image = infos
+        .iter()
+        .find(|info| info.contains(target.module().base))
+        .unwrap();
+    assert!(!image.is_anonymous());
+    assert_eq!(image.sharing, Sharing::CopyOnWrite);
+    assert!(image
+        .file
+        .as_ref()
+        .unwrap()
+        .contains("proc_memory_test_target"));
+    let heap = infos
+        .iter()
+        .find(|info| info.contains(target.value))
+        .unwrap();
+    assert!(heap.is_anonymous());
+    assert_eq!(heap.sharing, Sharing::Private);
+}
+
+#[test]
+fn regions_and_modules_round_trip_through_csv() {
+    let target = Target::spawn();
+    let regions = target.proc.region_infos();
+    let mut csv = Vec::new();
+    proc_memory::write_regions_csv(&regions, &mut csv).unwrap();
+    assert_eq!(
+        proc_memory::read_regions_csv(csv.as_slice()).unwrap(),
+        regions
+    );
+
+    let modules = target.proc.modules();
+    let mut csv = Vec::new();
+    proc_memory::write_modules_csv(&modules, &mut csv).unwrap();
+    assert_eq!(
+        proc_memory::read_modules_csv(csv.as_slice()).unwrap(),
+        modules
+    );
+    assert!(proc_memory::read_modules_csv(&b"name,path\n"[..]).is_err());
+}
+
+#[test]
+fn scan_filters_by_protection() {
+    let target = Target::spawn();
+    let pattern = Pattern::from_bytes(&values::PATTERN);
+
+    let writable = ScanOptions::new().require(Protection::READ_WRITE);
+    assert!(target
+        .proc
+        .scan_with(&pattern, &writable)
+        .matches
+        .contains(&target.pattern));
+
+    let read_only = ScanOptions::new().exclude(Protection::new(false, true, false));
+    assert!(!target
+        .proc
+        .scan_with(&pattern, &read_only)
+        .matches
+        .contains(&target.pattern));
+
+    let module = ScanOptions::new().in_module(&target.module());
+    assert!(target
+        .proc
+        .scan_with(&pattern, &module)
+        .matches
+        .iter()
+        .all(|address| target.module().contains(*address)));
+}
+
+#[test]
+fn scan_skips_misaligned_matches() {
+    let target = Target::spawn();
+    let pattern = Pattern::from_bytes(&values::PATTERN);
+    assert_eq!(target.pattern % 8, 4);
+
+    let aligned = ScanOptions::new().align(4);
+    assert!(target
+        .proc
+        .scan_with(&pattern, &aligned)
+        .matches
+        .contains(&target.pattern));
+
+    let misaligned = ScanOptions::new().align(8);
+    assert!(!target
+        .proc
+        .scan_with(&pattern, &misaligned)
+        .matches
+        .contains(&target.pattern));
+}
+
+#[test]
+fn scan_finds_strings_ignoring_case() {
+    let target = Target::spawn();
+    let text = values::TEXT.to_uppercase();
+    let found = target.proc.scan_string(
+        &text,
+        true,
+        &ScanOptions::new().require(Protection::READ_WRITE),
+    );
+    assert!(found.matches.contains(&StringMatch {
+        address: target.text,
+        encoding: StringEncoding::Utf8,
+    }));
+}
+
+#[test]
+fn copies_between_processes() {
+    let source = Target::spawn();
+    let mut destination = Target::spawn();
+    assert_eq!(source.proc.write(source.value, &0x1234u64), (true, 8));
+    assert_eq!(
+        copy_between(
+            &source.proc,
+            source.value,
+            &destination.proc,
+            destination.value,
+            8
+        ),
+        (true, 8)
+    );
+    assert_eq!(destination.current_value(), 0x1234);
+    assert_eq!(
+        copy_between(&source.proc, 0, &destination.proc, destination.value, 8),
+        (false, 0)
+    );
+}
+
+#[test]
+fn navigates_process_tree() {
+    let target = Target::spawn();
+    let parent = target.proc.parent().unwrap();
+    assert_eq!(parent.pid(), std::process::id() as isize);
+    assert!(parent
+        .children()
+        .iter()
+        .any(|child| child.pid() == target.pid() as isize));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn reports_namespace_pids() {
+    let target = Target::spawn();
+    let pids = target.proc.namespace_pids();
+    assert_eq!(pids.first(), Some(&target.pid()));
+    assert_eq!(Some(target.proc.namespace_pid()), pids.last().copied());
+    assert!(!target.proc.is_in_other_namespace());
+    let found = Proc::from_namespace_pid(&target.proc, target.proc.namespace_pid()).unwrap();
+    assert_eq!(found.pid(), target.pid() as isize);
+}
+
+#[test]
+fn lists_open_files() {
+    let path = std::env::current_exe().unwrap();
+    let _file = std::fs::File::open(&path).unwrap();
+    let proc = Proc::from_pid(std::process::id()).unwrap();
+    assert!(proc.open_files().contains(&path.canonicalize().unwrap()));
+}
+
+#[test]
+fn reports_memory_usage() {
+    let target = Target::spawn();
+    let usage = target.proc.memory_info().unwrap();
+    assert!(usage.resident > 0 && usage.private > 0 && usage.commit > 0);
+    assert!(usage.peak_resident >= usage.resident);
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn cli_reads_and_writes() {
+    let mut target = Target::spawn();
+    let procmem = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_procmem"))
+            .arg(args[0])
+            .arg(target.pid().to_string())
+            .args(&args[1..])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let value = format!("{:#x}", target.value);
+    assert!(procmem(&["read", &value, "8"]).contains("88 77 66 55"));
+    procmem(&["write", &value, "2a 00 00 00 00 00 00 00"]);
+    let pattern = values::PATTERN
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<_>>()
+        .join(" ");
+    assert!(procmem(&["scan", &pattern]).contains(&format!("{:#X}", target.pattern)));
+    assert!(procmem(&["modules"]).contains("proc_memory_test_target"));
+    assert!(!procmem(&["regions"]).is_empty());
+    assert_eq!(target.current_value(), 42);
+}
+
+#[test]
+fn snapshot_keeps_captured_memory() {
+    let mut target = Target::spawn();
+    let snapshot = Snapshot::capture(&target.proc, &ScanOptions::new().skip_shared()).unwrap();
+    assert!(!snapshot.is_empty());
+    assert_eq!(
+        snapshot.bytes(target.value, 8),
+        Some(&values::VALUE.to_ne_bytes()[..])
+    );
+    assert_eq!(target.proc.write(target.value, &7u64), (true, 8));
+    assert_eq!(target.current_value(), 7);
+
+    let snapshot = BackendProc::new(snapshot);
+    assert_eq!(snapshot.read::<u64>(target.value), Some(values::VALUE));
+    assert!(snapshot
+        .scan(&Pattern::from_bytes(&values::PATTERN))
+        .contains(&target.pattern));
+    let differences =
+        proc_memory::compare_ranges(&snapshot, target.value, &target.proc, target.value, 8);
+    assert_eq!(
+        differences.first().map(|difference| difference.offset),
+        Some(0)
+    );
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn derived_struct_aligns_implicit_offsets() {
+    use proc_memory::RemoteStruct;
+
+    #[derive(RemoteStruct)]
+    struct Entity {
+        flag: u8,
+        health: u32,
+        #[offset(0x10)]
+        id: u16,
+        position: [f64; 2],
+        team: u8,
+    }
+
+    assert_eq!(
+        Entity::field_offsets(),
+        &[
+            ("flag", 0),
+            ("health", 4),
+            ("id", 0x10),
+            ("position", 0x18),
+            ("team", 0x28)
+        ]
+    );
+
+    let proc = MockProc::new();
+    proc.map(0x1000, vec![0u8; 0x30]);
+    proc.write(0x1000, &1u8);
+    proc.write(0x1004, &100u32);
+    proc.write(0x1010, &7u16);
+    proc.write(0x1018, &[1.5f64, -2.0]);
+    proc.write(0x1028, &3u8);
+    let entity = Entity::read_from(&proc, 0x1000).unwrap();
+    assert_eq!(
+        (entity.flag, entity.health, entity.id, entity.team),
+        (1, 100, 7, 3)
+    );
+    assert_eq!(entity.position, [1.5, -2.0]);
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn lz4_dump_matches_plain_dump() {
+    use std::io::Read;
+
+    let target = Target::spawn();
+    let module = target.module();
+    let dir = std::env::temp_dir();
+    let plain = dir.join(format!("proc_memory_dump_{}.bin", target.pid()));
+    let compressed = dir.join(format!("proc_memory_dump_{}.lz4", target.pid()));
+    let gaps = target
+        .proc
+        .dump_range(module.base, module.size, &plain)
+        .unwrap();
+    assert_eq!(
+        target
+            .proc
+            .dump_range_lz4(module.base, module.size, &compressed)
+            .unwrap(),
+        gaps
+    );
+
+    let mut data = Vec::new();
+    proc_memory::Lz4Reader::new(std::fs::File::open(&compressed).unwrap())
+        .read_to_end(&mut data)
+        .unwrap();
+    assert_eq!(data, std::fs::read(&plain).unwrap());
+    assert!(std::fs::metadata(&compressed).unwrap().len() < data.len() as u64);
+    std::fs::remove_file(plain).unwrap();
+    std::fs::remove_file(compressed).unwrap();
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn lz4_rejects_blocks_larger_than_the_frame_allows() {
+    use proc_memory::{Lz4Reader, Lz4Writer};
+    use std::io::Read;
+
+    //Frame header with 4 MB blocks, taken from an empty frame
+    let mut frame = Lz4Writer::new(Vec::new()).unwrap().finish().unwrap();
+    frame.truncate(7);
+
+    //One literal followed by a match repeating it far past 4 MB, then an empty last sequence
+    let mut block = vec![0x1F, 0, 1, 0];
+    block.resize(block.len() + (4 << 20) / 255 + 1, 255);
+    block.extend_from_slice(&[0, 0]);
+    frame.extend_from_slice(&(block.len() as u32).to_le_bytes());
+    frame.extend_from_slice(&block);
+    frame.extend_from_slice(&0u32.to_le_bytes());
+
+    let error = Lz4Reader::new(frame.as_slice())
+        .read_to_end(&mut Vec::new())
+        .unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[cfg(feature = "minidump")]
+#[test]
+fn dump_round_trips_through_dump_file() {
+    let target = Target::spawn();
+    let path = std::env::temp_dir().join(format!("proc_memory_dump_{}.dmp", target.pid()));
+    target
+        .proc
+        .write_minidump(&path, proc_memory::DumpLevel::Mini)
+        .unwrap();
+
+    let dump = DumpFile::open(&path).unwrap();
+    assert_eq!(dump.read::<u64>(target.value), Some(values::VALUE));
+    assert_eq!(
+        dump.read_vec_uninit::<u8>(target.pattern, values::PATTERN.len()),
+        Some(values::PATTERN.to_vec())
+    );
+    assert_eq!(dump.pid(), target.pid() as isize);
+    assert_eq!(dump.is_64bit(), target.proc.is_64bit());
+    assert!(dump
+        .threads()
+        .iter()
+        .any(|thread| thread.id == target.pid()));
+    assert!(dump
+        .modules()
+        .iter()
+        .any(|module| module.name == target.module().name));
+    assert!(!dump.is_alive());
+    assert_eq!(dump.write(target.value, &0u64), (false, 0));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[cfg(all(feature = "minidump", target_os = "linux"))]
+#[test]
+fn truncated_core_dump_fails_cleanly() {
+    use std::convert::TryInto;
+
+    let target = Target::spawn();
+    let path = std::env::temp_dir().join(format!("proc_memory_core_{}.core", target.pid()));
+    target
+        .proc
+        .write_minidump(&path, proc_memory::DumpLevel::Mini)
+        .unwrap();
+    let core = std::fs::read(&path).unwrap();
+    let u64_at = |offset: usize| u64::from_le_bytes(core[offset..offset + 8].try_into().unwrap());
+    //The note segment comes first, followed by the memory of the first PT_LOAD
+    let program_headers = u64_at(32) as usize;
+    let notes = u64_at(program_headers + 8) as usize;
+    let memory = u64_at(program_headers + 56 + 8) as usize;
+
+    for len in [0, 16, 63, program_headers + 8, notes + 1] {
+        std::fs::write(&path, &core[..len]).unwrap();
+        assert!(DumpFile::open(&path).is_err(), "{} bytes", len);
+    }
+    //Memory is read lazily, missing bytes fail the reads instead of the open
+    std::fs::write(&path, &core[..memory]).unwrap();
+    let dump = DumpFile::open(&path).unwrap();
+    assert_eq!(dump.read::<u64>(target.value), None);
+    assert_eq!(dump.pid(), target.pid() as isize);
+    std::fs::remove_file(path).unwrap();
+}
+
+///Minidump of a 64-bit process 1234 with one thread, one module and 16 bytes of memory at 0x10000
+fn minidump_fixture() -> Vec<u8> {
+    fn push_u32(data: &mut Vec<u8>, value: u32) {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    //Header followed by a directory of 5 streams
+    let mut file = b"MDMP".to_vec();
+    for value in [0xA793, 5, 32, 0, 0, 0, 0] {
+        push_u32(&mut file, value);
+    }
+    file.resize(32 + 5 * 12, 0);
+    let add_stream = |file: &mut Vec<u8>, index: usize, kind: u32, stream: Vec<u8>| {
+        let entry = 32 + index * 12;
+        file[entry..entry + 4].copy_from_slice(&kind.to_le_bytes());
+        file[entry + 4..entry + 8].copy_from_slice(&(stream.len() as u32).to_le_bytes());
+        let rva = file.len() as u32;
+        file[entry + 8..entry + 12].copy_from_slice(&rva.to_le_bytes());
+        file.extend_from_slice(&stream);
+    };
+
+    //SystemInfoStream: PROCESSOR_ARCHITECTURE_AMD64
+    let mut system_info = vec![0u8; 56];
+    system_info[0] = 9;
+    add_stream(&mut file, 0, 7, system_info);
+
+    //MiscInfoStream: MINIDUMP_MISC1_PROCESS_ID
+    let mut misc_info = Vec::new();
+    for value in [24, 1, 1234, 0, 0, 0] {
+        push_u32(&mut misc_info, value);
+    }
+    add_stream(&mut file, 1, 15, misc_info);
+
+    //ThreadListStream
+    let mut threads = Vec::new();
+    push_u32(&mut threads, 1);
+    push_u32(&mut threads, 42);
+    threads.resize(4 + 48, 0);
+    add_stream(&mut file, 2, 3, threads);
+
+    //MemoryListStream, the memory is stored before it
+    let memory = file.len() as u32;
+    file.extend(0..16u8);
+    let mut memory_list = Vec::new();
+    push_u32(&mut memory_list, 1);
+    memory_list.extend_from_slice(&0x10000u64.to_le_bytes());
+    push_u32(&mut memory_list, 16);
+    push_u32(&mut memory_list, memory);
+    add_stream(&mut file, 3, 5, memory_list);
+
+    //ModuleListStream, the MINIDUMP_STRING name is stored before it
+    let name = file.len() as u32;
+    let path: Vec<u8> = "C:\\game\\game.exe"
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect();
+    push_u32(&mut file, path.len() as u32);
+    file.extend_from_slice(&path);
+    let mut modules = Vec::new();
+    push_u32(&mut modules, 1);
+    modules.extend_from_slice(&0x40_0000u64.to_le_bytes());
+    push_u32(&mut modules, 0x2000);
+    modules.resize(4 + 20, 0);
+    push_u32(&mut modules, name);
+    modules.resize(4 + 108, 0);
+    add_stream(&mut file, 4, 4, modules);
+    file
+}
+
+#[test]
+fn opens_minidump() {
+    let path = std::env::temp_dir().join(format!("proc_memory_fixture_{}.dmp", std::process::id()));
+    std::fs::write(&path, minidump_fixture()).unwrap();
+
+    let dump = DumpFile::open(&path).unwrap();
+    assert_eq!(
+        dump.read_vec_uninit::<u8>(0x10000, 16),
+        Some((0..16).collect())
+    );
+    assert_eq!(dump.read_partial(0x1000C, 8), (vec![12, 13, 14, 15], 4));
+    assert_eq!(dump.read::<u8>(0x10010), None);
+    assert_eq!(dump.pid(), 1234);
+    assert!(dump.is_64bit());
+    assert_eq!(
+        dump.threads()
+            .iter()
+            .map(|thread| thread.id)
+            .collect::<Vec<_>>(),
+        [42]
+    );
+    assert_eq!(
+        dump.modules(),
+        [Module {
+            name: "game.exe".to_string(),
+            path: "C:\\game\\game.exe".to_string(),
+            base: 0x40_0000,
+            size: 0x2000,
+        }]
+    );
+    assert_eq!(dump.name().as_deref(), Some("game.exe"));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn truncated_minidump_fails_cleanly() {
+    let path =
+        std::env::temp_dir().join(format!("proc_memory_truncated_{}.dmp", std::process::id()));
+    let fixture = minidump_fixture();
+    //Every stream is read while opening, so any cut fails
+    for len in 0..fixture.len() {
+        std::fs::write(&path, &fixture[..len]).unwrap();
+        assert!(DumpFile::open(&path).is_err(), "{} bytes", len);
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[cfg(feature = "remote")]
+#[test]
+fn remote_proc_goes_through_agent() {
+    let mut target = Target::spawn();
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let proc = target.proc.clone();
+    std::thread::spawn(move || proc_memory::serve_agent(&proc, &listener));
+
+    let remote = proc_memory::RemoteProc::connect(address).unwrap();
+    assert_eq!(remote.pid(), target.pid() as isize);
+    assert_eq!(remote.read::<u64>(target.value), Some(values::VALUE));
+    assert_eq!(remote.read::<u64>(0), None);
+    assert_eq!(remote.write(target.value, &7u64), (true, 8));
+    assert!(remote
+        .scan(&Pattern::from_bytes(&values::PATTERN))
+        .contains(&target.pattern));
+    assert!(remote.modules().contains(&target.module()));
+    assert!(remote.is_alive());
+    assert_eq!(target.current_value(), 7);
+}
+
+#[test]
+fn manager_drops_exited_pids() {
+    let target = Target::spawn();
+    let pid = target.pid();
+    let mut manager = Manager::new();
+    assert!(manager.add_pid(pid));
+    assert!(manager.add_name("proc_memory_test_target"));
+    assert_eq!(manager.iter().count(), 2);
+
+    drop(target);
+    let events = manager.refresh();
+    assert!(events.contains(&ManagerEvent::Exited {
+        tracked: Tracked::Pid(pid),
+        pid: pid as isize,
+    }));
+    assert!(manager.get(&Tracked::Pid(pid)).is_none());
+    assert_eq!(manager.len(), 1);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[test]
+fn finds_what_reads_value() {
+    let mut target = Target::spawn();
+    let proc = Proc::from_pid(target.pid()).unwrap();
+    let watchpoint = Watchpoint::read_write(target.value, 8).unwrap();
+    let watcher = std::thread::spawn(move || {
+        proc.find_accesses(&[watchpoint], std::time::Duration::from_secs(2))
+    });
+    for _ in 0..10 {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        target.current_value();
+    }
+    let hits = watcher.join().unwrap().unwrap();
+    let module = target.module().name;
+    assert!(hits
+        .iter()
+        .any(|hit| hit.location.as_ref().map(|location| &location.module) == Some(&module)));
+}
+
+#[cfg(target_arch = "x86_64")]
+#[test]
+fn finds_what_reads_value_with_guard_pages() {
+    let mut target = Target::spawn();
+    let proc = Proc::from_pid(target.pid()).unwrap();
+    let watchpoint = Watchpoint::read_write(target.value, 8).unwrap();
+    let watcher = std::thread::spawn(move || {
+        let duration = std::time::Duration::from_secs(2);
+        proc.find_accesses_with(&[watchpoint], duration, WatchMethod::GuardPages)
+    });
+    for _ in 0..10 {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        target.current_value();
+    }
+    let hits = watcher.join().unwrap().unwrap();
+    let module = target.module().name;
+    assert!(hits
+        .iter()
+        .any(|hit| hit.location.as_ref().map(|location| &location.module) == Some(&module)));
+    assert_eq!(target.current_value(), values::VALUE);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[test]
+fn debugger_reports_threads_and_modules() {
+    let mut target = Target::spawn();
+    let mut events = Vec::new();
+    {
+        let mut debugger = Debugger::attach(&target.proc).unwrap();
+        debugger.run(std::time::Duration::from_millis(300), |event| {
+            events.push(event.clone());
+            false
+        });
+    }
+    assert!(events.contains(&DebugEvent::ThreadCreated {
+        thread: target.pid()
+    }));
+    assert!(events.contains(&DebugEvent::ModuleLoaded(target.module())));
+    assert_eq!(target.current_value(), values::VALUE);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[test]
+fn traces_thread_instructions() {
+    let mut target = Target::spawn();
+    let proc = Proc::from_pid(target.pid()).unwrap();
+    let thread = target.pid();
+    let tracer = std::thread::spawn(move || proc.trace_thread(thread, 500, true));
+    for _ in 0..10 {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        target.current_value();
+    }
+    let trace = tracer.join().unwrap().unwrap();
+    assert_eq!(trace.steps.len(), 500);
+    let module = target.module();
+    assert!(trace.steps.iter().any(|step| module.contains(step.address)));
+    assert!(trace.steps.iter().any(|step| !step.changes.is_empty()));
+}
+
+#[cfg(target_arch = "x86_64")]
+#[test]
+fn writes_vector_registers() {
+    let mut target = Target::spawn();
+    let thread = target
+        .proc
+        .threads()
+        .into_iter()
+        .find(|thread| thread.id == target.pid())
+        .unwrap();
+    assert!(thread.suspend());
+    let original = thread.float_context().unwrap();
+    let mut changed = original;
+    changed.xmm[15] = 0x0123_4567_89AB_CDEF_FEDC_BA98_7654_3210;
+    if let Some(high) = changed.ymm_high.as_mut() {
+        high[15] = 0x1111_2222_3333_4444_5555_6666_7777_8888;
+    }
+    assert!(thread.set_float_context(&changed));
+    assert_eq!(thread.float_context(), Some(changed));
+
+    //Without upper halves only the xmm registers change
+    let mut xmm_only = changed;
+    xmm_only.ymm_high = None;
+    xmm_only.xmm[14] = 0x0F0E_0D0C_0B0A_0908_0706_0504_0302_0100;
+    assert!(thread.set_float_context(&xmm_only));
+    let read = thread.float_context().unwrap();
+    assert_eq!(read.xmm[14], xmm_only.xmm[14]);
+    assert_eq!(read.ymm_high, changed.ymm_high);
+
+    assert!(thread.set_float_context(&original));
+    assert!(thread.resume());
+    assert_eq!(target.current_value(), values::VALUE);
+}
+
+#[test]
+fn remote_cursor_reads_writes_and_seeks() {
+    use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+
+    let proc = MockProc::new();
+    proc.map(0x1000, (0..32).collect::<Vec<u8>>());
+
+    let mut cursor = RemoteCursor::with_len(&proc, 0x1000, 16);
+    let mut data = Vec::new();
+    cursor.read_to_end(&mut data).unwrap();
+    assert_eq!(data, (0..16).collect::<Vec<u8>>());
+    assert_eq!(cursor.read(&mut [0; 4]).unwrap(), 0);
+
+    let mut buffer = [0; 8];
+    assert_eq!(cursor.seek(SeekFrom::End(-4)).unwrap(), 12);
+    assert_eq!(cursor.read(&mut buffer).unwrap(), 4);
+    assert_eq!(buffer[..4], [12, 13, 14, 15]);
+    assert_eq!(
+        cursor.seek(SeekFrom::Current(-20)).unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+    assert_eq!(cursor.position(), 16);
+
+    cursor.seek(SeekFrom::Start(2)).unwrap();
+    cursor.write_all(&[0xAA, 0xBB]).unwrap();
+    assert_eq!(cursor.address(), 0x1004);
+    assert_eq!(proc.read::<[u8; 4]>(0x1001), Some([1, 0xAA, 0xBB, 4]));
+    cursor.seek(SeekFrom::Start(15)).unwrap();
+    assert_eq!(cursor.write(&[1, 2, 3]).unwrap(), 1);
+    assert_eq!(cursor.write(&[1]).unwrap(), 0);
+    assert_eq!(proc.read::<[u8; 2]>(0x100F), Some([1, 16]));
+
+    //Unbounded cursors stop at unreadable memory and can't seek from the end
+    let mut cursor = proc.reader_at(0x101E);
+    assert_eq!(cursor.read(&mut buffer).unwrap(), 2);
+    assert_eq!(buffer[..2], [30, 31]);
+    assert!(cursor.read(&mut buffer).is_err());
+    assert!(cursor.write(&[0]).is_err());
+    assert_eq!(
+        cursor.seek(SeekFrom::End(0)).unwrap_err().kind(),
+        ErrorKind::Unsupported
+    );
+}
+
+#[test]
+fn parses_hex_bytes() {
+    assert_eq!(parse_hex_bytes("90 90"), Some(vec![0x90, 0x90]));
+    assert_eq!(
+        parse_hex_bytes("48 8b05\t0C"),
+        Some(vec![0x48, 0x8B, 0x05, 0x0C])
+    );
+    assert_eq!(parse_hex_bytes(""), Some(Vec::new()));
+    assert_eq!(parse_hex_bytes("9 0 9"), None);
+    assert_eq!(parse_hex_bytes("zz"), None);
+    assert_eq!(parse_hex_bytes("é0"), None);
+}
+
+#[test]
+fn hexdump_marks_unreadable_bytes() {
+    let proc = MockProc::new();
+    proc.map(0x1FF8, b"Hello,\tw".to_vec());
+    let dump = proc.hexdump(0x1FF8, 20);
+    assert_eq!(dump.memory().gaps.len(), 1);
+    assert_eq!(dump.memory().gaps[0], 0x2000..0x200C);
+    assert_eq!(
+        dump.to_string(),
+        "00001FF8  48 65 6C 6C 6F 2C 09 77 ?? ?? ?? ?? ?? ?? ?? ??  |Hello,.w????????|\n\
+         00002008  ?? ?? ?? ??                                      |????|\n"
+    );
+    assert_eq!(
+        proc.hexdump(0x1FF8, 8).width(4).ascii(false).to_string(),
+        "00001FF8  48 65 6C 6C\n00001FFC  6F 2C 09 77\n"
+    );
+}
+
+#[test]
+fn dump_range_zero_fills_gaps() {
+    let proc = MockProc::new();
+    proc.map(0x1FF8, vec![0xAB; 8]);
+    proc.map(0x3000, vec![0xCD; 8]);
+    let path = std::env::temp_dir().join(format!("proc_memory_mock_{}.bin", std::process::id()));
+    let gaps = proc.dump_range(0x1FF8, 0x1010, &path).unwrap();
+    assert_eq!(gaps.len(), 1);
+    assert_eq!(gaps[0], 0x2000..0x3000);
+
+    let data = std::fs::read(&path).unwrap();
+    assert_eq!(data.len(), 0x1010);
+    assert_eq!(data[..8], [0xAB; 8]);
+    assert!(data[8..0x1008].iter().all(|byte| *byte == 0));
+    assert_eq!(data[0x1008..], [0xCD; 8]);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn reads_batches_of_ranges() {
+    let proc = MockProc::new();
+    proc.map_value(0x1000, &[1u32, 2, 3]);
+    assert_eq!(
+        proc.read_batch(&[
+            (0x1000, 4),
+            (0x5000, 4),
+            (0x1008, 4),
+            (0x100A, 4),
+            (0x1004, 0)
+        ]),
+        [
+            Some(1u32.to_ne_bytes().to_vec()),
+            None,
+            Some(3u32.to_ne_bytes().to_vec()),
+            None,
+            Some(Vec::new()),
+        ]
+    );
+    assert_eq!(
+        proc.read_many::<u32>(&[0x1004, 0x5000, 0x1000, 0x100A]),
+        [Some(2), None, Some(1), None]
+    );
+    assert_eq!(proc.read_many::<u32>(&[]), []);
+}
+
+fn offsets_proc() -> MockProc {
+    let game = Module {
+        name: "game.exe".to_string(),
+        base: 0x40_0000,
+        size: 0x1000,
+        ..Module::default()
+    };
+    let proc = MockProc::new().with_module(game);
+    proc.map_value(0x40_0100, &0x1000u64);
+    proc.map_value(0x1010, &0x2000u64);
+    proc
+}
+
+#[test]
+fn parses_address_chains() {
+    let chain = AddressChain::parse("game.exe+0x100 -> 10 -> -0x4").unwrap();
+    assert_eq!(chain.module.as_deref(), Some("game.exe"));
+    assert_eq!(chain.base, 0x100);
+    assert_eq!(chain.offsets, vec![0x10, -0x4]);
+    assert_eq!(chain.resolve(&offsets_proc()), Some(0x1FFC));
+
+    assert_eq!(AddressChain::parse("0x1000").unwrap().module, None);
+    assert_eq!(AddressChain::parse("+0x100"), None);
+    assert_eq!(AddressChain::parse("game.exe+0x100 -> zz"), None);
+}
+
+#[test]
+fn parses_address_expressions() {
+    let proc = offsets_proc();
+    let eval = |text: &str| AddressExpr::parse(text).and_then(|expr| expr.eval(&proc));
+    assert_eq!(eval("[[game.exe+100]+10]+4"), Some(0x2004));
+    assert_eq!(eval("\"game.exe\"+2*(3+1)"), Some(0x40_0008));
+    assert_eq!(eval("game.exe+0x100 -> 0x10"), Some(0x1010));
+    assert_eq!(eval("missing.dll+4"), None);
+
+    assert_eq!(AddressExpr::parse("[game.exe+100"), None);
+    assert_eq!(AddressExpr::parse("game.exe+"), None);
+    assert_eq!(AddressExpr::parse("1 2"), None);
+}
+
+#[test]
+fn parses_toml_offsets() {
+    let table = OffsetTable::parse(
+        r#"
+# Player data
+base = "game.exe+100"
+
+[player]
+health = "[game.exe+100]+10" # current health
+"ammo" = "game.exe+0x100 -> 0x10 -> 4"
+
+["weapons".primary]
+damage = "1000"
+"#,
+    )
+    .unwrap();
+    let proc = offsets_proc();
+    assert_eq!(table.resolve(&proc, "base"), Some(0x40_0100));
+    assert_eq!(table.resolve(&proc, "player.health"), Some(0x1010));
+    assert_eq!(table.resolve(&proc, "player.ammo"), Some(0x2004));
+    assert_eq!(table.resolve(&proc, "weapons.primary.damage"), Some(0x1000));
+    assert!(table.get("health").is_none());
+    assert_eq!(table.iter().count(), 4);
+
+    assert!(OffsetTable::parse("health = 0x10").is_err());
+    assert!(OffsetTable::parse("health").is_err());
+    assert!(OffsetTable::parse("health = \"[1\"").is_err());
+    assert!(OffsetTable::parse("[]\nhealth = \"1\"").is_err());
+    assert!(OffsetTable::parse("[[players]]\nhealth = \"1\"").is_err());
+    assert!(OffsetTable::parse("a = \"1\"\na = \"2\"").is_err());
+    assert!(OffsetTable::parse("[a]\nb = \"1\"\n[b]\nb = \"2\"").is_ok());
+}
+
+#[test]
+fn loads_cheat_table_fixture() {
+    let table = OffsetTable::load_cheat_table(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/game.CT"
+    ))
+    .unwrap();
+    let proc = offsets_proc();
+    assert_eq!(table.resolve(&proc, "player_base"), Some(0x40_0200));
+    //Offsets are listed from the last one applied
+    assert_eq!(
+        table.resolve(&proc, "Player/Stats/Health & Armor"),
+        Some(0x2004)
+    );
+    assert_eq!(table.resolve(&proc, "Player/Stats/Speed <x>"), Some(0x1008));
+    assert_eq!(table.resolve(&proc, "Player/Gold"), Some(0x40_0208));
+    assert_eq!(table.resolve(&proc, "Player/Gold (6)"), Some(0x40_020C));
+    //Groups and scripts have no address
+    assert_eq!(table.iter().count(), 5);
+
+    assert!(OffsetTable::parse_cheat_table("<CheatTable><CheatEntries></CheatTable>").is_err());
+    assert!(OffsetTable::parse_cheat_table("<CheatTable><CheatEntries>").is_err());
+    assert!(OffsetTable::parse_cheat_table("<CheatTable><![CDATA[x</CheatTable>").is_err());
+    assert!(OffsetTable::parse_cheat_table("<OffsetTable></OffsetTable>").is_err());
+}
+
+#[test]
+fn parses_json_offsets() {
+    let table = OffsetTable::parse(
+        r#"{
+    "health": "[game.exe+100]+10",
+    "quoted \"name\"": "game.exe+0x100 -> 0x10 -> 4"
+}"#,
+    )
+    .unwrap();
+    let proc = offsets_proc();
+    assert_eq!(table.resolve(&proc, "health"), Some(0x1010));
+    assert_eq!(table.resolve(&proc, "quoted \"name\""), Some(0x2004));
+
+    let table = OffsetTable::parse(
+        r#"{"caf\u00E9 \ud83d\ude00 a\/b\r\b\f": "10", "tab\tnewline\n\\": "20"}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        table.resolve(&proc, "caf\u{E9} \u{1F600} a/b\r\u{8}\u{C}"),
+        Some(0x10)
+    );
+    assert_eq!(table.resolve(&proc, "tab\tnewline\n\\"), Some(0x20));
+    for escape in [
+        "\\x41",
+        "\\u12",
+        "\\u12G4",
+        "\\ud83d",
+        "\\ud83d\\u0041",
+        "\\ude00",
+        "\\u+041",
+    ] {
+        let text = format!("{{\"a{}\": \"1\"}}", escape);
+        let error = OffsetTable::parse(&text).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData, "{}", text);
+    }
+
+    assert!(OffsetTable::parse("{\"health\": 10}").is_err());
+    assert!(OffsetTable::parse("{\"health\" \"10\"}").is_err());
+    assert!(OffsetTable::parse("{\"health\": \"10}").is_err());
+    assert!(OffsetTable::parse("{\"health\": \"1\", \"health\": \"2\"}").is_err());
+}
+
+#[cfg(feature = "remote")]
+#[test]
+fn remote_backend_fails_after_broken_response() {
+    use proc_memory::{serve_agent_connection, RemoteProc};
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+
+    fn skip_frame(stream: &mut TcpStream) {
+        let mut len = [0u8; 4];
+        stream.read_exact(&mut len).unwrap();
+        let mut payload = vec![0u8; u32::from_le_bytes(len) as usize];
+        stream.read_exact(&mut payload).unwrap();
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let agent = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        skip_frame(&mut stream);
+        let mut info = vec![10u8, 0, 0, 0];
+        info.extend_from_slice(&7u64.to_le_bytes());
+        info.extend_from_slice(&[1, 0]);
+        stream.write_all(&info).unwrap();
+        //Header of a frame larger than any accepted, then a well behaved agent
+        skip_frame(&mut stream);
+        stream.write_all(&u32::MAX.to_le_bytes()).unwrap();
+        let proc = MockProc::new();
+        proc.map_value(0x1000, &42u32);
+        serve_agent_connection(&proc, stream)
+    });
+
+    let proc = RemoteProc::connect(address).unwrap();
+    assert_eq!(proc.pid(), 7);
+    assert_eq!(proc.read::<u32>(0x1000), None);
+    assert_eq!(proc.read::<u32>(0x1000), None);
+    assert!(!proc.is_alive());
+    drop(proc);
+    assert!(agent.join().unwrap().is_ok());
+}
+
+#[cfg(feature = "async")]
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::task::{Context, Poll, Wake, Waker};
+
+    struct Unpark(std::thread::Thread);
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(Unpark(std::thread::current())));
+    let mut context = Context::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => std::thread::park(),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_proc_reads_and_scans() {
+    use proc_memory::AsyncProc;
+
+    let proc = MockProc::new();
+    proc.map(0x1000, vec![0u8; 0x100]);
+    proc.write(0x1010, &0xDEAD_BEEFu32);
+    let proc = AsyncProc::new(proc);
+
+    assert_eq!(block_on(proc.read::<u32>(0x1010)), Some(0xDEAD_BEEF));
+    assert_eq!(block_on(proc.read::<u32>(0x5000)), None);
+    assert_eq!(block_on(proc.write(0x1020, 7u16)), (true, 2));
+    assert_eq!(block_on(proc.read_bytes(0x1020, 2)), Some(vec![7, 0]));
+    let pattern = Pattern::parse("EF BE AD DE").unwrap();
+    assert_eq!(block_on(proc.scan(pattern)), vec![0x1010]);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_proc_resumes_panics() {
+    use proc_memory::AsyncProc;
+
+    let proc = AsyncProc::new(MockProc::new());
+    //More panics than workers, the pool must survive all of them
+    let workers = std::thread::available_parallelism().map_or(4, |count| count.get());
+    for _ in 0..=workers {
+        let result = std::panic::catch_unwind(|| block_on(proc.run(|_| -> u32 { panic!("job") })));
+        assert!(result.is_err());
+    }
+    assert_eq!(block_on(proc.run(|proc| proc.pid())), 1);
+}
+
+#[cfg(feature = "disasm")]
+#[test]
+fn decodes_known_encodings() {
+    //Bytes, 64-bit mode, text and length as printed by objdump -M intel
+    let cases: &[(&[u8], bool, &str, usize)] = &[
+        (&[0x90], true, "nop", 1),
+        (&[0x48, 0x89, 0xC8], true, "mov rax, rcx", 3),
+        (&[0x41, 0x50], true, "push r8", 2),
+        (&[0x48, 0x83, 0xEC, 0x28], true, "sub rsp, 0x28", 4),
+        (&[0x48, 0x8D, 0x04, 0x88], true, "lea rax, [rax+rcx*4]", 4),
+        (
+            &[0xF0, 0x0F, 0xB1, 0x0A],
+            true,
+            "lock cmpxchg dword ptr [rdx], ecx",
+            4,
+        ),
+        (&[0xE8, 0x10, 0, 0, 0], true, "call 0x1015", 5),
+        (&[0x0F, 0x84, 0x10, 0, 0, 0], true, "je 0x1016", 6),
+        (&[0x0F, 0x05], true, "syscall", 2),
+        (&[0xC5, 0xFD, 0x6F, 0xC1], true, "vmovdqa ymm0, ymm1", 4),
+        (&[0x48, 0x63, 0xC1], true, "movsxd rax, ecx", 3),
+        (&[0x8C, 0xD8], true, "mov eax, ds", 2),
+        (&[0x48, 0x8C, 0xD8], true, "mov rax, ds", 3),
+        (&[0x66, 0x8C, 0xD8], true, "mov ax, ds", 3),
+        (&[0x8C, 0x18], true, "mov word ptr [rax], ds", 2),
+        (&[0x8E, 0xD8], true, "mov ds, eax", 2),
+        (&[0x06, 0x90], true, "(bad)", 1),
+        (&[0x63, 0xC1], false, "arpl cx, ax", 2),
+        (&[0x63, 0x08], false, "arpl word ptr [eax], cx", 2),
+        (&[0x8C, 0xD8], false, "mov eax, ds", 2),
+        (&[0x66, 0x8C, 0xD8], false, "mov ax, ds", 3),
+        (&[0x60], false, "pushad", 1),
+        (
+            &[0x8B, 0x45, 0xFC],
+            false,
+            "mov eax, dword ptr [ebp-0x4]",
+            3,
+        ),
+    ];
+    for (bytes, is_64bit, text, len) in cases {
+        let instruction = proc_memory::decode(bytes, 0x1000, *is_64bit).unwrap();
+        assert_eq!(
+            (instruction.text().as_str(), instruction.len()),
+            (*text, *len),
+            "{:02X?}",
+            bytes
+        );
+    }
+}
@@ -0,0 +1,94 @@
+//! Spawns the helper target process the integration tests read and write.
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use proc_memory::{Module, Proc, ProcT};
+
+pub mod values;
+
+///Running helper process and the addresses it published
+pub struct Target {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    pub proc: Proc,
+    ///u64 holding values::VALUE
+    pub value: usize,
+    ///Pointer to 'value'
+    pub pointer: usize,
+    ///NUL terminated values::TEXT
+    pub text: usize,
+    ///values::PATTERN inside a heap buffer
+    pub pattern: usize,
+}
+
+impl Target {
+    pub fn spawn() -> Target {
+        Target::spawn_with(&[], &[])
+    }
+
+    ///Spawn the target with extra command line arguments and environment variables, it ignores both
+    pub fn spawn_with(args: &[&str], vars: &[(&str, &str)]) -> Target {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_proc_memory_test_target"))
+            .args(args)
+            .envs(vars.iter().copied())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("failed to start the test target");
+        let stdin = child.stdin.take().unwrap();
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        let address = |name: &str| {
+            let field = line
+                .split_whitespace()
+                .find_map(|field| field.strip_prefix(name)?.strip_prefix('='))
+                .unwrap_or_else(|| panic!("target didn't publish '{}': {}", name, line));
+            usize::from_str_radix(field.trim_start_matches("0x"), 16).unwrap()
+        };
+
+        Target {
+            proc: Proc::from_pid(child.id()).expect("failed to open the test target"),
+            value: address("value"),
+            pointer: address("pointer"),
+            text: address("text"),
+            pattern: address("pattern"),
+            child,
+            stdin,
+            stdout,
+        }
+    }
+
+    ///Current content of the u64 at 'value', as seen by the target itself
+    pub fn current_value(&mut self) -> u64 {
+        writeln!(self.stdin, "value").unwrap();
+        self.stdin.flush().unwrap();
+        let mut line = String::new();
+        self.stdout.read_line(&mut line).unwrap();
+        u64::from_str_radix(line.trim().trim_start_matches("0x"), 16).unwrap()
+    }
+
+    ///Executable module of the target, named with or without '.exe'
+    pub fn module(&self) -> Module {
+        self.proc
+            .modules()
+            .into_iter()
+            .find(|module| module.name.starts_with("proc_memory_test_target"))
+            .expect("target executable is not listed in its modules")
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.child.id()
+    }
+}
+
+impl Drop for Target {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,47 @@
+//! Helper process for the integration tests.
+//!
+//! Prints the addresses of a few known values on one line, then answers commands read from stdin until it is
+//! closed: 'value' prints the current content of the u64 value, so tests can check their writes landed.
+
+use std::ffi::CString;
+use std::io::{BufRead, Write};
+
+mod values;
+
+use values::{PATTERN, TEXT, VALUE};
+
+///Value alone in its page, guard pages on it don't catch the accesses to other data
+#[repr(C, align(4096))]
+struct Page {
+    value: u64,
+}
+
+fn main() {
+    let page = Box::new(Page { value: VALUE });
+    let value = &page.value;
+    let pointer = Box::new(value as *const u64 as usize);
+    let text = CString::new(TEXT).unwrap();
+    let mut pattern = vec![0u8; 4096];
+    pattern[100..100 + PATTERN.len()].copy_from_slice(&PATTERN);
+
+    println!(
+        "value={:#x} pointer={:#x} text={:#x} pattern={:#x}",
+        value as *const u64 as usize,
+        &*pointer as *const usize as usize,
+        text.as_ptr() as usize,
+        pattern.as_ptr() as usize + 100,
+    );
+    std::io::stdout().flush().unwrap();
+
+    for line in std::io::stdin().lock().lines() {
+        match line.as_deref() {
+            Ok("value") => {
+                let current = unsafe { std::ptr::read_volatile(value) };
+                println!("{:#x}", current);
+                std::io::stdout().flush().unwrap();
+            }
+            _ => break,
+        }
+    }
+    std::hint::black_box((&page, &pointer, &text, &pattern));
+}
@@ -0,0 +1,6 @@
+//! Values published by the helper target process, shared between it and the tests.
+
+///Bytes searched for by the scan tests, unlikely to appear anywhere else in the process
+pub const PATTERN: [u8; 16] = *b"\xDE\xAD\xBE\xEFpm-test\x13\x37\xC0\xDE\x00";
+pub const VALUE: u64 = 0x1122_3344_5566_7788;
+pub const TEXT: &str = "proc_memory test target";